@@ -0,0 +1,244 @@
+//! Tipos de análisis de la cola y su ejecución
+//!
+//! AIDEV-NOTE: Cada job reutiliza el mismo analizador que el comando manual
+//! (`analyze_beatgrid`, `analyze_loudness`, `analyze_key`, `analyze_phrases`,
+//! `get_waveform`) y guarda el resultado en su tabla; la cola solo decide
+//! cuándo se ejecuta.
+//! En background nunca se escriben tags en los archivos.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::audio::waveform::cache::save_to_cache;
+use crate::audio::waveform::generation::generate_and_stream_peaks;
+use crate::audio::{
+    AudioDecoder, AutoCueDetector, BeatgridDetector, KeyDetector, LoudnessAnalyzer, PhraseAnalysis,
+    PhraseDetector, TempoMap, WaveformMode,
+};
+use crate::db::models::{AnalysisJob, CuePoint};
+use crate::db::{queries, DbPool};
+
+/// Análisis que puede hacer la cola
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnalysisKind {
+    Beatgrid,
+    Loudness,
+    Key,
+    /// Downbeats y frases (necesita beatgrid; lo calcula si falta)
+    Phrases,
+    /// Overview mono (el que pide la vista de biblioteca)
+    Waveform,
+}
+
+impl AnalysisKind {
+    pub const ALL: [AnalysisKind; 5] = [
+        AnalysisKind::Beatgrid,
+        AnalysisKind::Loudness,
+        AnalysisKind::Key,
+        AnalysisKind::Phrases,
+        AnalysisKind::Waveform,
+    ];
+
+    /// Nombre guardado en `analysis_jobs.kind`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnalysisKind::Beatgrid => "beatgrid",
+            AnalysisKind::Loudness => "loudness",
+            AnalysisKind::Key => "key",
+            AnalysisKind::Phrases => "phrases",
+            AnalysisKind::Waveform => "waveform",
+        }
+    }
+
+    /// Tipo a partir de `analysis_jobs.kind`
+    pub fn parse(kind: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.as_str() == kind)
+    }
+}
+
+/// Ejecuta un job y guarda el resultado
+///
+/// Bloqueante: se llama desde los workers de `AnalysisQueue`.
+pub fn run_analysis_job(app: &AppHandle, pool: &DbPool, job: &AnalysisJob) -> Result<(), String> {
+    let kind = AnalysisKind::parse(&job.kind)
+        .ok_or_else(|| format!("Tipo de análisis desconocido: {}", job.kind))?;
+    let path = Path::new(&job.path);
+
+    match kind {
+        AnalysisKind::Beatgrid => {
+            let range = {
+                let conn = pool.get().map_err(|e| e.to_string())?;
+                queries::get_bpm_range(&conn)
+                    .map_err(|e| format!("Error leyendo settings: {}", e))?
+            };
+            let analysis = BeatgridDetector::analyze_in_range(path, range)
+                .map_err(|e| format!("Error de análisis: {}", e))?;
+            let conn = pool.get().map_err(|e| e.to_string())?;
+            queries::upsert_beatgrid(
+                &conn,
+                &job.track_id,
+                analysis.bpm,
+                analysis.offset,
+                Some(analysis.confidence),
+                &analysis.anchors,
+            )
+            .map_err(|e| format!("Error guardando beatgrid: {}", e))?;
+        }
+        AnalysisKind::Loudness => {
+            let analysis =
+                LoudnessAnalyzer::analyze(path).map_err(|e| format!("Error de análisis: {}", e))?;
+            let conn = pool.get().map_err(|e| e.to_string())?;
+            queries::upsert_loudness(
+                &conn,
+                &job.track_id,
+                analysis.integrated_lufs,
+                analysis.true_peak_dbtp,
+                Some(analysis.loudness_range),
+                "analysis",
+            )
+            .map_err(|e| format!("Error guardando loudness: {}", e))?;
+        }
+        AnalysisKind::Key => {
+            let analysis =
+                KeyDetector::analyze(path).map_err(|e| format!("Error de análisis: {}", e))?;
+            let conn = pool.get().map_err(|e| e.to_string())?;
+            queries::upsert_key_analysis(
+                &conn,
+                &job.track_id,
+                &analysis.key.to_string(),
+                analysis.confidence,
+            )
+            .map_err(|e| format!("Error guardando tonalidad: {}", e))?;
+        }
+        AnalysisKind::Phrases => {
+            analyze_phrases(pool, &job.track_id, path)?;
+        }
+        AnalysisKind::Waveform => {
+            // AIDEV-NOTE: La generación es async solo de nombre (no espera nada);
+            // block_on la ejecuta en este worker sin ocupar el runtime de Tokio.
+            tauri::async_runtime::block_on(async {
+                let peaks = generate_and_stream_peaks(
+                    &job.track_id,
+                    &job.path,
+                    job.duration,
+                    WaveformMode::Mono,
+                    app,
+                    &CancellationToken::new(),
+                )
+                .await
+                .map_err(|e| e.to_string())?;
+                save_to_cache(&job.track_id, WaveformMode::Mono, &peaks, pool).await;
+                Ok::<_, String>(())
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Detecta downbeats y frases de una pista y los guarda
+///
+/// Usa el beatgrid guardado; si la pista no tiene, lo detecta sobre el mismo
+/// audio decodificado y lo guarda. Bloqueante.
+///
+/// AIDEV-NOTE: Las frases siempre se refieren al grid guardado. La cola no
+/// reclama el job de frases mientras el de "beatgrid" de la pista siga
+/// pendiente o en curso, y el grid de respaldo se guarda antes de las frases
+/// (un grid posterior las invalida al guardarse).
+pub fn analyze_phrases(
+    pool: &DbPool,
+    track_id: &str,
+    path: &Path,
+) -> Result<PhraseAnalysis, String> {
+    let decoded =
+        AudioDecoder::decode_samples(path).map_err(|e| format!("Error de análisis: {}", e))?;
+    let channels = decoded.channels as usize;
+
+    let (stored, range) = {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        (
+            queries::get_tempo_map(&conn, track_id)
+                .map_err(|e| format!("Error obteniendo beatgrid: {}", e))?,
+            queries::get_bpm_range(&conn).map_err(|e| format!("Error leyendo settings: {}", e))?,
+        )
+    };
+    let tempo = match stored {
+        Some(tempo) => tempo,
+        None => {
+            let beatgrid = BeatgridDetector::analyze_samples_in_range(
+                &decoded.samples,
+                decoded.sample_rate,
+                channels,
+                range,
+            )
+            .map_err(|e| format!("Error de análisis: {}", e))?;
+            let conn = pool.get().map_err(|e| e.to_string())?;
+            queries::upsert_beatgrid(
+                &conn,
+                track_id,
+                beatgrid.bpm,
+                beatgrid.offset,
+                Some(beatgrid.confidence),
+                &beatgrid.anchors,
+            )
+            .map_err(|e| format!("Error guardando beatgrid: {}", e))?;
+            TempoMap::new(&beatgrid.anchors, beatgrid.bpm, beatgrid.offset)
+        }
+    };
+
+    let analysis =
+        PhraseDetector::analyze_samples(&decoded.samples, decoded.sample_rate, channels, &tempo)
+            .map_err(|e| format!("Error de análisis: {}", e))?;
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    queries::save_phrase_analysis(&conn, track_id, &analysis)
+        .map_err(|e| format!("Error guardando frases: {}", e))?;
+
+    Ok(analysis)
+}
+
+/// Detecta los cue points automáticos de una pista y los guarda
+///
+/// Se ajustan al beatgrid (y al compás, si hay frases) cuando la pista ya lo
+/// tiene. Sustituye los cues automáticos anteriores sin tocar los del
+/// usuario. Retorna todos los cues de la pista. Bloqueante.
+pub fn analyze_cues(pool: &DbPool, track_id: &str, path: &Path) -> Result<Vec<CuePoint>, String> {
+    let (tempo, downbeat) = {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        (
+            queries::get_tempo_map(&conn, track_id)
+                .map_err(|e| format!("Error obteniendo beatgrid: {}", e))?,
+            queries::get_downbeat(&conn, track_id)
+                .map_err(|e| format!("Error obteniendo downbeat: {}", e))?,
+        )
+    };
+
+    let cues = AutoCueDetector::analyze(path, tempo.as_ref(), downbeat.map(|d| d.beat))
+        .map_err(|e| format!("Error de análisis: {}", e))?;
+
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    queries::replace_auto_cue_points(&conn, track_id, &cues)
+        .map_err(|e| format!("Error guardando cue points: {}", e))?;
+    queries::get_cue_points(&conn, track_id)
+        .map_err(|e| format!("Error obteniendo cue points: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind_names() {
+        for kind in AnalysisKind::ALL {
+            assert_eq!(AnalysisKind::parse(kind.as_str()), Some(kind));
+        }
+        assert_eq!(AnalysisKind::parse("lyrics"), None);
+        assert_eq!(
+            serde_json::to_string(&AnalysisKind::Beatgrid).unwrap(),
+            "\"beatgrid\""
+        );
+    }
+}
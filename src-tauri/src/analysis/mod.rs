@@ -0,0 +1,15 @@
+//! Análisis de la biblioteca en background
+//!
+//! Cola persistente (tabla `analysis_jobs`) y workers que ejecutan los
+//! análisis de beatgrid, loudness, tonalidad, frases y waveform sin
+//! intervención del usuario. Los cue points automáticos se calculan a
+//! demanda con `analyze_cues`.
+
+pub mod jobs;
+pub mod queue;
+
+pub use jobs::{analyze_cues, analyze_phrases, run_analysis_job, AnalysisKind};
+pub use queue::{
+    AnalysisEvent, AnalysisJobPayload, AnalysisQueue, AnalysisStatus, PRIORITY_NORMAL,
+    PRIORITY_SELECTED,
+};
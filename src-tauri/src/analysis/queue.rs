@@ -0,0 +1,464 @@
+//! Servicio de análisis en background
+//!
+//! AIDEV-NOTE: Un pool de threads (uno por core menos uno, que queda para el
+//! decode thread y la UI) toma jobs de `analysis_jobs` por prioridad. Pausar
+//! no interrumpe los jobs en curso: los workers terminan el actual y esperan.
+//! Encolar, reanudar o parar despierta a los workers; si nadie los despierta
+//! (jobs encolados desde otro sitio) vuelven a mirar la cola cada
+//! `IDLE_POLL_INTERVAL`.
+
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::db::models::AnalysisJob;
+use crate::db::{queries, DbPool};
+
+use super::jobs::{run_analysis_job, AnalysisKind};
+
+/// Prioridad de los jobs encolados desde la biblioteca o la importación
+pub const PRIORITY_NORMAL: i32 = 0;
+
+/// Prioridad del track seleccionado en la UI
+pub const PRIORITY_SELECTED: i32 = 100;
+
+/// Cada cuánto miran la cola los workers ociosos
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Estado de la cola (evento `analysis:progress`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysisStatus {
+    pub pending: i64,
+    pub running: i64,
+    pub failed: i64,
+    /// Jobs terminados con éxito desde que arrancó la app
+    pub completed: u64,
+    pub paused: bool,
+    pub workers: usize,
+}
+
+/// Resultado de un job (evento `analysis:job`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysisJobPayload {
+    pub track_id: String,
+    pub kind: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// Eventos que emite la cola
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnalysisEvent {
+    Progress(AnalysisStatus),
+    Job(AnalysisJobPayload),
+}
+
+type JobRunner = dyn Fn(&AnalysisJob) -> Result<(), String> + Send + Sync;
+type EventSink = dyn Fn(AnalysisEvent) + Send + Sync;
+
+#[derive(Default)]
+struct Control {
+    paused: bool,
+    stopped: bool,
+    /// Cambia cada vez que hay algo nuevo que mirar (encolado, resume, stop)
+    epoch: u64,
+    completed: u64,
+}
+
+struct Shared {
+    pool: DbPool,
+    workers: usize,
+    control: Mutex<Control>,
+    wake: Condvar,
+    runner: Box<JobRunner>,
+    on_event: Box<EventSink>,
+}
+
+/// Cola de análisis con sus workers (estado gestionado por Tauri)
+pub struct AnalysisQueue {
+    shared: Arc<Shared>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl AnalysisQueue {
+    /// Arranca la cola con los analizadores reales y eventos al frontend
+    pub fn start(app: AppHandle, pool: DbPool) -> Result<Self, String> {
+        let workers = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(2)
+            .saturating_sub(1)
+            .max(1);
+
+        let runner_app = app.clone();
+        let runner_pool = pool.clone();
+        Self::with_runner(
+            pool,
+            workers,
+            move |job| run_analysis_job(&runner_app, &runner_pool, job),
+            move |event| {
+                let _ = match event {
+                    AnalysisEvent::Progress(status) => app.emit("analysis:progress", status),
+                    AnalysisEvent::Job(payload) => app.emit("analysis:job", payload),
+                };
+            },
+        )
+    }
+
+    /// Arranca la cola con un ejecutor de jobs cualquiera
+    ///
+    /// Los jobs que quedaron 'running' de una sesión anterior vuelven a la cola.
+    pub fn with_runner<F, E>(
+        pool: DbPool,
+        workers: usize,
+        runner: F,
+        on_event: E,
+    ) -> Result<Self, String>
+    where
+        F: Fn(&AnalysisJob) -> Result<(), String> + Send + Sync + 'static,
+        E: Fn(AnalysisEvent) + Send + Sync + 'static,
+    {
+        {
+            let conn = pool.get().map_err(|e| e.to_string())?;
+            let recovered =
+                queries::reset_running_analysis_jobs(&conn).map_err(|e| e.to_string())?;
+            if recovered > 0 {
+                log::info!("🔁 {} análisis interrumpidos vuelven a la cola", recovered);
+            }
+        }
+
+        let shared = Arc::new(Shared {
+            pool,
+            workers,
+            control: Mutex::new(Control::default()),
+            wake: Condvar::new(),
+            runner: Box::new(runner),
+            on_event: Box::new(on_event),
+        });
+
+        let handles = (0..workers)
+            .map(|i| {
+                let shared = Arc::clone(&shared);
+                thread::Builder::new()
+                    .name(format!("analysis-worker-{}", i))
+                    .spawn(move || shared.worker_loop())
+                    .map_err(|e| format!("Error creando worker de análisis: {}", e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        log::info!("🧵 Cola de análisis iniciada con {} workers", workers);
+        Ok(Self { shared, handles })
+    }
+
+    /// Encola análisis (`track_ids` a `None` = toda la biblioteca)
+    ///
+    /// Sin `force` solo se encolan los análisis que faltan.
+    pub fn enqueue(
+        &self,
+        track_ids: Option<&[String]>,
+        kinds: &[AnalysisKind],
+        priority: i32,
+        force: bool,
+    ) -> Result<usize, String> {
+        let kinds: Vec<&str> = kinds.iter().map(AnalysisKind::as_str).collect();
+        let enqueued = {
+            let conn = self.shared.pool.get().map_err(|e| e.to_string())?;
+            queries::enqueue_analysis_jobs(&conn, track_ids, &kinds, priority, force)
+                .map_err(|e| format!("Error encolando análisis: {}", e))?
+        };
+
+        if enqueued > 0 {
+            log::info!("📥 {} análisis encolados", enqueued);
+            self.shared.notify();
+        }
+        self.shared.emit_progress();
+        Ok(enqueued)
+    }
+
+    /// Pone el track seleccionado el primero de la cola
+    ///
+    /// Encola lo que le falte y devuelve a la prioridad normal el seleccionado antes.
+    pub fn prioritize(&self, track_id: &str) -> Result<usize, String> {
+        let track_ids = [track_id.to_string()];
+        let enqueued = self.enqueue(
+            Some(&track_ids),
+            &AnalysisKind::ALL,
+            PRIORITY_SELECTED,
+            false,
+        )?;
+
+        let conn = self.shared.pool.get().map_err(|e| e.to_string())?;
+        queries::prioritize_analysis_track(&conn, track_id, PRIORITY_SELECTED, PRIORITY_NORMAL)
+            .map_err(|e| format!("Error priorizando análisis: {}", e))?;
+        Ok(enqueued)
+    }
+
+    /// Los workers terminan el job actual y no toman más
+    pub fn pause(&self) {
+        self.shared.control.lock().unwrap().paused = true;
+        log::info!("⏸️ Cola de análisis pausada");
+        self.shared.emit_progress();
+    }
+
+    pub fn resume(&self) {
+        self.shared.control.lock().unwrap().paused = false;
+        log::info!("▶️ Cola de análisis reanudada");
+        self.shared.notify();
+        self.shared.emit_progress();
+    }
+
+    pub fn status(&self) -> Result<AnalysisStatus, String> {
+        self.shared.status()
+    }
+
+    /// Jobs fallidos con su error
+    pub fn failed_jobs(&self) -> Result<Vec<AnalysisJob>, String> {
+        let conn = self.shared.pool.get().map_err(|e| e.to_string())?;
+        queries::get_failed_analysis_jobs(&conn).map_err(|e| e.to_string())
+    }
+
+    /// Vacía la cola (los jobs en curso terminan)
+    pub fn clear(&self) -> Result<usize, String> {
+        let cleared = {
+            let conn = self.shared.pool.get().map_err(|e| e.to_string())?;
+            queries::clear_analysis_jobs(&conn).map_err(|e| e.to_string())?
+        };
+        self.shared.emit_progress();
+        Ok(cleared)
+    }
+
+    /// Detiene los workers (esperan a terminar su job) y espera a sus threads
+    pub fn stop(&mut self) {
+        {
+            let mut control = self.shared.control.lock().unwrap();
+            control.stopped = true;
+            control.epoch += 1;
+        }
+        self.shared.wake.notify_all();
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for AnalysisQueue {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl Shared {
+    fn worker_loop(&self) {
+        loop {
+            let epoch = {
+                let mut control = self.control.lock().unwrap();
+                while control.paused && !control.stopped {
+                    control = self.wake.wait(control).unwrap();
+                }
+                if control.stopped {
+                    return;
+                }
+                control.epoch
+            };
+
+            let claimed = self.pool.get().map_err(|e| e.to_string()).and_then(|conn| {
+                queries::claim_next_analysis_job(&conn).map_err(|e| e.to_string())
+            });
+            match claimed {
+                Ok(Some(job)) => self.run(job),
+                Ok(None) => self.wait_for_work(epoch),
+                Err(e) => {
+                    log::warn!("⚠️ Error leyendo la cola de análisis: {}", e);
+                    self.wait_for_work(epoch);
+                }
+            }
+        }
+    }
+
+    fn run(&self, job: AnalysisJob) {
+        log::info!("🔬 Analizando {} de {}", job.kind, job.path);
+        let result = (self.runner)(&job);
+        match &result {
+            // Antes de borrar el job, para que el estado nunca cuente de menos
+            Ok(()) => self.control.lock().unwrap().completed += 1,
+            Err(e) => log::warn!("⚠️ Análisis {} de {} fallido: {}", job.kind, job.path, e),
+        }
+
+        let saved = self.pool.get().map_err(|e| e.to_string()).and_then(|conn| {
+            match &result {
+                Ok(()) => queries::complete_analysis_job(&conn, &job.id),
+                Err(e) => queries::fail_analysis_job(&conn, &job.id, e),
+            }
+            .map_err(|e| e.to_string())
+        });
+        if let Err(e) = saved {
+            log::warn!("⚠️ Error actualizando job de análisis {}: {}", job.id, e);
+        }
+
+        (self.on_event)(AnalysisEvent::Job(AnalysisJobPayload {
+            track_id: job.track_id,
+            kind: job.kind,
+            success: result.is_ok(),
+            error: result.err(),
+        }));
+        self.emit_progress();
+    }
+
+    /// Espera a que cambie `epoch` (o al sondeo periódico)
+    fn wait_for_work(&self, epoch: u64) {
+        let control = self.control.lock().unwrap();
+        let _ = self
+            .wake
+            .wait_timeout_while(control, IDLE_POLL_INTERVAL, |control| {
+                control.epoch == epoch && !control.stopped
+            })
+            .unwrap();
+    }
+
+    fn notify(&self) {
+        self.control.lock().unwrap().epoch += 1;
+        self.wake.notify_all();
+    }
+
+    fn status(&self) -> Result<AnalysisStatus, String> {
+        let counts = {
+            let conn = self.pool.get().map_err(|e| e.to_string())?;
+            queries::get_analysis_queue_counts(&conn).map_err(|e| e.to_string())?
+        };
+        let control = self.control.lock().unwrap();
+        Ok(AnalysisStatus {
+            pending: counts.pending,
+            running: counts.running,
+            failed: counts.failed,
+            completed: control.completed,
+            paused: control.paused,
+            workers: self.workers,
+        })
+    }
+
+    fn emit_progress(&self) {
+        match self.status() {
+            Ok(status) => (self.on_event)(AnalysisEvent::Progress(status)),
+            Err(e) => log::warn!("⚠️ Error leyendo estado de la cola de análisis: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{migrations, pool::create_test_pool};
+    use std::time::Instant;
+
+    fn setup_pool() -> DbPool {
+        let pool = create_test_pool().unwrap();
+        let conn = pool.get().unwrap();
+        migrations::run_migrations(&conn).unwrap();
+        for id in ["t1", "t2", "t3"] {
+            conn.execute(
+                "INSERT INTO tracks (id, path, title, artist, duration, bitrate, sample_rate, file_size, date_added, date_modified)
+                 VALUES (?1, ?1 || '.mp3', 'Test', 'Artist', 180.0, 320, 44100, 8388608, '2024-01-01', '2024-01-01')",
+                [id],
+            )
+            .unwrap();
+        }
+        pool
+    }
+
+    /// Espera a que la cola se vacíe (pendientes y en curso)
+    fn wait_idle(queue: &AnalysisQueue) -> AnalysisStatus {
+        let start = Instant::now();
+        loop {
+            let status = queue.status().unwrap();
+            if status.pending == 0 && status.running == 0 {
+                return status;
+            }
+            assert!(start.elapsed() < Duration::from_secs(10), "{:?}", status);
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_runs_jobs_and_reports() {
+        let pool = setup_pool();
+        let analyzed = Arc::new(Mutex::new(Vec::new()));
+        let events = Arc::new(Mutex::new(Vec::new()));
+
+        let analyzed_runner = Arc::clone(&analyzed);
+        let events_sink = Arc::clone(&events);
+        let mut queue = AnalysisQueue::with_runner(
+            pool,
+            2,
+            move |job| {
+                analyzed_runner.lock().unwrap().push(job.track_id.clone());
+                match job.track_id.as_str() {
+                    "t2" => Err("Archivo no encontrado".to_string()),
+                    _ => Ok(()),
+                }
+            },
+            move |event| events_sink.lock().unwrap().push(event),
+        )
+        .unwrap();
+
+        assert_eq!(
+            queue
+                .enqueue(None, &[AnalysisKind::Beatgrid], PRIORITY_NORMAL, false)
+                .unwrap(),
+            3
+        );
+        let status = wait_idle(&queue);
+        assert_eq!(status.completed, 2);
+        assert_eq!(status.failed, 1);
+        assert_eq!(analyzed.lock().unwrap().len(), 3);
+
+        let failed = queue.failed_jobs().unwrap();
+        assert_eq!(failed[0].track_id, "t2");
+        // Tras parar, todos los eventos se han emitido
+        queue.stop();
+        let events = events.lock().unwrap();
+        assert!(events.contains(&AnalysisEvent::Job(AnalysisJobPayload {
+            track_id: "t2".to_string(),
+            kind: "beatgrid".to_string(),
+            success: false,
+            error: Some("Archivo no encontrado".to_string()),
+        })));
+    }
+
+    #[test]
+    fn test_pause_and_priority() {
+        let pool = setup_pool();
+        let analyzed = Arc::new(Mutex::new(Vec::new()));
+        let analyzed_runner = Arc::clone(&analyzed);
+        let queue = AnalysisQueue::with_runner(
+            pool,
+            1,
+            move |job| {
+                analyzed_runner.lock().unwrap().push(job.track_id.clone());
+                Ok(())
+            },
+            |_| {},
+        )
+        .unwrap();
+
+        queue.pause();
+        queue
+            .enqueue(None, &[AnalysisKind::Loudness], PRIORITY_NORMAL, false)
+            .unwrap();
+        queue.prioritize("t3").unwrap();
+        thread::sleep(Duration::from_millis(50));
+        assert!(analyzed.lock().unwrap().is_empty());
+        assert!(queue.status().unwrap().paused);
+
+        queue.resume();
+        wait_idle(&queue);
+        // Todos los análisis de t3 primero; su loudness no se repite
+        let analyzed = analyzed.lock().unwrap();
+        let kinds = AnalysisKind::ALL.len();
+        assert_eq!(analyzed.len(), 3 + kinds - 1);
+        assert!(analyzed[..kinds].iter().all(|id| id == "t3"));
+    }
+}
@@ -0,0 +1,161 @@
+//! Beatgrid dinámico: anclas de tempo y conversión tiempo ↔ beat
+//!
+//! Un beatgrid es una lista de anclas ordenadas; cada ancla fija la posición
+//! de un beat y el tempo que sigue hasta la siguiente ancla. Un grid de tempo
+//! constante es una sola ancla (beat 0 en el offset).
+
+use serde::{Deserialize, Serialize};
+
+/// Cambio de tempo dentro del beatgrid
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BeatgridAnchor {
+    /// Índice del beat (0 = primer beat del grid)
+    pub beat: u32,
+    /// Posición del beat en segundos
+    pub position: f64,
+    /// Tempo desde esta ancla hasta la siguiente
+    pub bpm: f64,
+}
+
+/// Mapa de tempo de una pista
+///
+/// AIDEV-NOTE: Antes del primer ancla y después del último se extrapola con el
+/// tempo del ancla más cercana, así los índices de beat son negativos antes
+/// del offset y el grid sigue hasta el final de la pista.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TempoMap {
+    anchors: Vec<BeatgridAnchor>,
+}
+
+impl TempoMap {
+    /// Grid de tempo constante
+    pub fn constant(bpm: f64, offset: f64) -> Self {
+        Self {
+            anchors: vec![BeatgridAnchor {
+                beat: 0,
+                position: offset,
+                bpm,
+            }],
+        }
+    }
+
+    /// Crea el mapa a partir de anclas; sin anclas usa el tempo constante
+    pub fn new(anchors: &[BeatgridAnchor], bpm: f64, offset: f64) -> Self {
+        let mut anchors: Vec<BeatgridAnchor> =
+            anchors.iter().filter(|a| a.bpm > 0.0).copied().collect();
+        anchors.sort_by_key(|a| a.beat);
+        anchors.dedup_by_key(|a| a.beat);
+        if anchors.is_empty() {
+            return Self::constant(bpm, offset);
+        }
+        Self { anchors }
+    }
+
+    pub fn anchors(&self) -> &[BeatgridAnchor] {
+        &self.anchors
+    }
+
+    /// `true` si el tempo cambia a lo largo de la pista
+    pub fn is_dynamic(&self) -> bool {
+        self.anchors.len() > 1
+    }
+
+    /// Beat (fraccionario) en un instante
+    pub fn beat_at(&self, time: f64) -> f64 {
+        let idx = self
+            .anchors
+            .partition_point(|a| a.position <= time)
+            .saturating_sub(1);
+        let anchor = &self.anchors[idx];
+        anchor.beat as f64 + (time - anchor.position) * anchor.bpm / 60.0
+    }
+
+    /// Instante (en segundos) de un beat, admite beats fraccionarios
+    pub fn time_at(&self, beat: f64) -> f64 {
+        let idx = self
+            .anchors
+            .partition_point(|a| a.beat as f64 <= beat)
+            .saturating_sub(1);
+        let anchor = &self.anchors[idx];
+        anchor.position + (beat - anchor.beat as f64) * 60.0 / anchor.bpm
+    }
+
+    /// Tempo en un instante
+    pub fn bpm_at(&self, time: f64) -> f64 {
+        let idx = self
+            .anchors
+            .partition_point(|a| a.position <= time)
+            .saturating_sub(1);
+        self.anchors[idx].bpm
+    }
+
+    /// Posiciones de los beats enteros dentro de `[start, end]`
+    ///
+    /// El rango se recorta a la pista (`[0, duration]`): fuera de ella el grid
+    /// se extrapola sin límite y un `end` enorme generaría millones de beats.
+    pub fn beats_between(&self, start: f64, end: f64, duration: f64) -> Vec<f64> {
+        let start = start.max(0.0);
+        let end = end.min(duration);
+        if end < start {
+            return Vec::new();
+        }
+        let first = self.beat_at(start).ceil() as i64;
+        let last = self.beat_at(end).floor() as i64;
+        (first..=last)
+            .map(|beat| self.time_at(beat as f64))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anchor(beat: u32, position: f64, bpm: f64) -> BeatgridAnchor {
+        BeatgridAnchor {
+            beat,
+            position,
+            bpm,
+        }
+    }
+
+    #[test]
+    fn test_constant_grid() {
+        let map = TempoMap::new(&[], 120.0, 0.25);
+        assert!(!map.is_dynamic());
+        assert!((map.time_at(8.0) - 4.25).abs() < 1e-9);
+        assert!((map.beat_at(4.25) - 8.0).abs() < 1e-9);
+        // Antes del offset los beats son negativos
+        assert!((map.beat_at(0.0) + 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_tempo_change() {
+        // 120 BPM durante 8 beats (4s), luego 150 BPM
+        let map = TempoMap::new(&[anchor(8, 4.0, 150.0), anchor(0, 0.0, 120.0)], 0.0, 0.0);
+        assert!(map.is_dynamic());
+        assert!((map.time_at(4.0) - 2.0).abs() < 1e-9);
+        assert!((map.time_at(18.0) - 8.0).abs() < 1e-9);
+        assert!((map.beat_at(8.0) - 18.0).abs() < 1e-9);
+        assert_eq!(map.bpm_at(3.9), 120.0);
+        assert_eq!(map.bpm_at(4.0), 150.0);
+
+        for beat in [-2.0, 0.0, 3.5, 8.0, 12.25, 40.0] {
+            assert!((map.beat_at(map.time_at(beat)) - beat).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_beats_between() {
+        let map = TempoMap::new(&[anchor(0, 0.0, 120.0), anchor(4, 2.0, 60.0)], 0.0, 0.0);
+        let beats = map.beats_between(1.2, 5.0, 60.0);
+        assert_eq!(beats, vec![1.5, 2.0, 3.0, 4.0, 5.0]);
+        assert!(map.beats_between(5.0, 1.0, 60.0).is_empty());
+
+        // Recortado a la pista: nada antes de 0 ni después de la duración
+        let beats = map.beats_between(-10.0, f64::MAX, 4.5);
+        assert_eq!(beats, vec![0.0, 0.5, 1.0, 1.5, 2.0, 3.0, 4.0]);
+        assert!(map.beats_between(10.0, 20.0, 4.5).is_empty());
+    }
+}
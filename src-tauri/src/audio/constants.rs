@@ -8,6 +8,10 @@
 /// Valor alto = mayor latencia, menor uso de CPU
 pub const BUFFER_SIZE: usize = 2048;
 
+/// Rango admitido para `AudioConfig.buffer_size` (en frames)
+pub const MIN_BUFFER_FRAMES: u32 = 64;
+pub const MAX_BUFFER_FRAMES: u32 = 8192;
+
 /// Tamaño del ring buffer SPSC (en frames estéreo f32)
 /// Debe ser suficientemente grande para absorber variaciones de decode
 /// pero no tanto que cause delay perceptible
@@ -17,6 +21,10 @@ pub const RING_BUFFER_SIZE: usize = 16384;
 /// ~200ms es un buen balance entre precisión y overhead
 pub const TIMESTAMP_INTERVAL_MS: u64 = 200;
 
+/// Intervalo de emisión de niveles y espectro al frontend (en ms)
+/// ~30 fps: suficiente para VU meters y analizador de espectro fluidos
+pub const METER_INTERVAL_MS: u64 = 33;
+
 /// Sample rate por defecto cuando no se puede determinar
 pub const DEFAULT_SAMPLE_RATE: u32 = 44100;
 
@@ -31,6 +39,49 @@ pub const DEFAULT_VOLUME: f64 = 1.0;
 pub const PAUSE_VALUE: u32 = 1;
 pub const PLAY_VALUE: u32 = 0;
 
+/// Tiempo máximo de espera para que el callback vacíe el ring buffer (en ms)
+/// AIDEV-NOTE: Si el stream está parado nadie atiende el flush; no bloquear más que esto
+pub const FLUSH_TIMEOUT_MS: u64 = 100;
+
+/// Tamaño del bloque que consumen las salidas sin tarjeta (null/file) en cada tick (en ms)
+/// AIDEV-NOTE: Imita el periodo de un callback de cpal en tiempo real
+pub const HEADLESS_BLOCK_MS: u64 = 10;
+
+/// Intervalo de sondeo de dispositivos de salida (en ms)
+/// AIDEV-NOTE: cpal no notifica conexiones/desconexiones; se lista periódicamente
+pub const DEVICE_POLL_INTERVAL_MS: u64 = 2000;
+
+/// Duración máxima de la ventana de crossfade (en segundos)
+pub const CROSSFADE_MAX_SECONDS: f64 = 30.0;
+
+/// Duración del crossfade en el punto de vuelta de un loop (en ms)
+/// AIDEV-NOTE: Suficiente para evitar el click sin que se note el solapamiento
+pub const LOOP_SEAM_MS: f64 = 5.0;
+
+/// Longitud mínima de un loop (en ms)
+/// AIDEV-NOTE: Al menos un paquete del decoder (FLAC: 4096 frames ≈ 93 ms a
+/// 44.1 kHz); loops más cortos vuelven al principio en cada paquete.
+pub const LOOP_MIN_MS: f64 = 100.0;
+
+/// Ganancia máxima que aplica la normalización (en dB); la atenuación no se limita
+pub const NORMALIZATION_MAX_BOOST_DB: f64 = 12.0;
+
+/// Techo del limitador de salida (en dBFS)
+pub const LIMITER_CEILING_DB: f64 = -1.0;
+
+/// Tiempo de release del limitador (en ms)
+pub const LIMITER_RELEASE_MS: f64 = 150.0;
+
+/// Ancho de la rodilla del soft limiter (en dB por debajo del techo)
+pub const LIMITER_KNEE_DB: f64 = 6.0;
+
+/// Ganancia máxima por banda del EQ y del preamp (en dB)
+pub const EQ_MAX_GAIN_DB: f64 = 15.0;
+
+/// Duración de la rampa al cambiar el preamp (en ms)
+/// AIDEV-NOTE: Evita clicks al mover el slider en vivo
+pub const PREAMP_RAMP_MS: f64 = 20.0;
+
 // ============================================================================
 // Constantes de Waveform
 // ============================================================================
@@ -46,6 +97,13 @@ pub const WAVEFORM_WINDOW_SIZE: usize = 8192;
 /// Valores más altos = más detalle pero más tiempo de generación
 pub const WAVEFORM_TARGET_PEAKS: usize = 800;
 
+/// Peaks por segundo del nivel de detalle de la pirámide de zoom
+/// AIDEV-NOTE: ~150/s permite colocar cues a ~7 ms de precisión visual
+pub const WAVEFORM_DETAIL_PEAKS_PER_SECOND: usize = 150;
+
+/// Niveles gruesos de la pirámide (peaks por track), de menor a mayor
+pub const WAVEFORM_PYRAMID_LEVELS: [usize; 2] = [WAVEFORM_TARGET_PEAKS, 8000];
+
 /// Número de paquetes entre emisiones de eventos de progreso
 /// Mayor valor = menos overhead IPC, menos actualizaciones de UI
 pub const WAVEFORM_PEAKS_PER_PACKET: usize = 50;
@@ -58,6 +116,12 @@ pub const WAVEFORM_PACKET_SKIP_RATIO: usize = 1;
 pub use crate::audio::dsp::PeakMethod;
 pub const WAVEFORM_PEAK_METHOD: PeakMethod = PeakMethod::Rms;
 
+/// Frecuencias de corte del waveform de tres bandas (Hz)
+/// AIDEV-NOTE: Graves < 200 Hz (bombo, bajo), medios 200 Hz - 2 kHz (voces,
+/// sintes), agudos > 2 kHz (hi-hats, platos)
+pub const WAVEFORM_LOW_CROSSOVER_HZ: f32 = 200.0;
+pub const WAVEFORM_HIGH_CROSSOVER_HZ: f32 = 2000.0;
+
 #[cfg(test)]
 #[allow(clippy::assertions_on_constants)]
 mod tests {
@@ -89,6 +153,11 @@ mod tests {
         assert!(TIMESTAMP_INTERVAL_MS >= 50 && TIMESTAMP_INTERVAL_MS <= 1000);
     }
 
+    #[test]
+    fn test_meter_interval_faster_than_timestamp() {
+        assert!(METER_INTERVAL_MS >= 16 && METER_INTERVAL_MS < TIMESTAMP_INTERVAL_MS);
+    }
+
     #[test]
     fn test_default_sample_rate() {
         assert_eq!(DEFAULT_SAMPLE_RATE, 44100);
@@ -0,0 +1,357 @@
+use crate::audio::beatgrid::TempoMap;
+use crate::audio::decoder::AudioDecoder;
+use crate::audio::error::AudioError;
+use crate::audio::phrase_detector::BEATS_PER_BAR;
+use std::path::Path;
+
+/// Nivel a partir del cual un sample se considera audible (-48 dBFS)
+const AUDIBLE_LEVEL: f32 = 0.004;
+
+/// Duración de cada bloque de energía
+const BLOCK_SECONDS: f64 = 0.25;
+
+/// Ventana (a cada lado) del suavizado de la energía
+const SMOOTH_SECONDS: f64 = 2.0;
+
+/// Energía relativa (a la del momento más fuerte) a partir de la cual empieza
+/// una sección principal
+const HIGH_ENERGY: f64 = 0.6;
+
+/// Energía relativa por debajo de la cual se acaba una sección principal
+///
+/// AIDEV-NOTE: La histéresis entre HIGH_ENERGY y LOW_ENERGY evita cues
+/// repetidos cuando la energía oscila alrededor de un solo umbral.
+const LOW_ENERGY: f64 = 0.4;
+
+/// Duración mínima de una sección para que genere cues
+const MIN_SECTION_SECONDS: f64 = 8.0;
+
+/// Tipo de cue automático
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoCueKind {
+    /// Primer sample audible
+    Intro,
+    /// Entrada de la primera sección principal
+    Main,
+    /// Caída de energía entre dos secciones principales
+    Breakdown,
+    /// Vuelta de la energía tras un breakdown
+    Drop,
+    /// Última caída de energía hasta el final
+    Outro,
+}
+
+impl AutoCueKind {
+    /// Valor de `cue_points.type`
+    pub fn cue_type(&self) -> &'static str {
+        match self {
+            AutoCueKind::Intro => "intro",
+            AutoCueKind::Main => "cue",
+            AutoCueKind::Breakdown => "break",
+            AutoCueKind::Drop => "drop",
+            AutoCueKind::Outro => "outro",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AutoCueKind::Intro => "Intro",
+            AutoCueKind::Main => "Main",
+            AutoCueKind::Breakdown => "Break",
+            AutoCueKind::Drop => "Drop",
+            AutoCueKind::Outro => "Outro",
+        }
+    }
+
+    /// Color del cue (los mismos que usa el editor para cada tipo)
+    pub fn color(&self) -> &'static str {
+        match self {
+            AutoCueKind::Intro => "#10b981",
+            AutoCueKind::Main => "#3b82f6",
+            AutoCueKind::Breakdown => "#06b6d4",
+            AutoCueKind::Drop => "#f59e0b",
+            AutoCueKind::Outro => "#ef4444",
+        }
+    }
+}
+
+/// Cue detectado (posición en segundos)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoCue {
+    pub kind: AutoCueKind,
+    pub position: f64,
+}
+
+/// Detector de cues automáticos por silencio y energía
+pub struct AutoCueDetector;
+
+impl AutoCueDetector {
+    /// Analiza una pista; con beatgrid los cues se ajustan a él
+    ///
+    /// `downbeat` es el índice del primer "1" de compás (análisis de frases):
+    /// si se conoce, las secciones se ajustan al compás y no solo al beat.
+    ///
+    /// # Errors
+    /// Retorna AudioError si el archivo no se puede decodificar o no tiene
+    /// ningún sample audible.
+    pub fn analyze(
+        path: &Path,
+        tempo: Option<&TempoMap>,
+        downbeat: Option<u32>,
+    ) -> Result<Vec<AutoCue>, AudioError> {
+        let decoded = AudioDecoder::decode_samples(path)?;
+        Self::analyze_samples(
+            &decoded.samples,
+            decoded.sample_rate,
+            decoded.channels as usize,
+            tempo,
+            downbeat,
+        )
+    }
+
+    /// Analiza samples interleaved ya decodificados
+    pub fn analyze_samples(
+        samples: &[f32],
+        sample_rate: u32,
+        channels: usize,
+        tempo: Option<&TempoMap>,
+        downbeat: Option<u32>,
+    ) -> Result<Vec<AutoCue>, AudioError> {
+        let channels = channels.max(1);
+        let mono: Vec<f32> = samples
+            .chunks(channels)
+            .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+            .collect();
+
+        let audible = |s: &f32| s.abs() > AUDIBLE_LEVEL;
+        let (Some(first), Some(last)) = (
+            mono.iter().position(audible),
+            mono.iter().rposition(audible),
+        ) else {
+            return Err(AudioError::AnalysisError(
+                "Audio sin contenido audible".into(),
+            ));
+        };
+
+        let block = ((BLOCK_SECONDS * sample_rate as f64) as usize).max(1);
+        let energies: Vec<f64> = mono[first..=last]
+            .chunks(block)
+            .map(|chunk| {
+                (chunk.iter().map(|&s| (s * s) as f64).sum::<f64>() / chunk.len() as f64).sqrt()
+            })
+            .collect();
+        let start = first as f64 / sample_rate as f64;
+
+        let mut cues = vec![AutoCue {
+            kind: AutoCueKind::Intro,
+            position: start,
+        }];
+        for (kind, index) in Self::section_changes(&energies) {
+            cues.push(AutoCue {
+                kind,
+                position: start + index as f64 * BLOCK_SECONDS,
+            });
+        }
+
+        if let Some(tempo) = tempo {
+            for cue in &mut cues {
+                cue.position = Self::snap(cue, tempo, downbeat);
+            }
+            // Dos cues en el mismo beat: se queda el primero
+            cues.dedup_by(|b, a| (b.position - a.position).abs() < 1e-6);
+        }
+
+        Ok(cues)
+    }
+
+    /// Cambios de sección (tipo e índice de bloque) a partir de la energía
+    fn section_changes(energies: &[f64]) -> Vec<(AutoCueKind, usize)> {
+        let radius = (SMOOTH_SECONDS / BLOCK_SECONDS) as usize;
+        let smoothed: Vec<f64> = (0..energies.len())
+            .map(|i| {
+                let window =
+                    &energies[i.saturating_sub(radius)..(i + radius + 1).min(energies.len())];
+                window.iter().sum::<f64>() / window.len() as f64
+            })
+            .collect();
+        let max = smoothed.iter().copied().fold(0.0, f64::max);
+        if max <= f64::EPSILON {
+            return Vec::new();
+        }
+
+        // Tramos (inicio, alto) con histéresis
+        let mut runs: Vec<(usize, bool)> = Vec::new();
+        let mut high = smoothed[0] / max >= HIGH_ENERGY;
+        runs.push((0, high));
+        for (i, &value) in smoothed.iter().enumerate().skip(1) {
+            let relative = value / max;
+            if (!high && relative >= HIGH_ENERGY) || (high && relative < LOW_ENERGY) {
+                high = !high;
+                runs.push((i, high));
+            }
+        }
+
+        // Los tramos cortos se funden con el anterior
+        let min_blocks = (MIN_SECTION_SECONDS / BLOCK_SECONDS) as usize;
+        let mut sections: Vec<(usize, bool)> = Vec::new();
+        for (i, &(start, high)) in runs.iter().enumerate() {
+            let end = runs.get(i + 1).map_or(energies.len(), |next| next.0);
+            match sections.last() {
+                Some(&(_, previous)) if previous == high => {}
+                Some(_) if end - start < min_blocks => {}
+                _ => sections.push((start, high)),
+            }
+        }
+
+        let mut changes = Vec::new();
+        let mut seen_high = false;
+        for (i, &(start, high)) in sections.iter().enumerate() {
+            if i == 0 {
+                seen_high = high;
+                continue;
+            }
+            let position = Self::refine(energies, start, radius, high);
+            let kind = match (high, seen_high) {
+                (true, false) => AutoCueKind::Main,
+                (true, true) => AutoCueKind::Drop,
+                (false, _) if i + 1 == sections.len() => AutoCueKind::Outro,
+                (false, _) => AutoCueKind::Breakdown,
+            };
+            seen_high |= high;
+            changes.push((kind, position));
+        }
+        changes
+    }
+
+    /// Bloque con el mayor salto de energía (subida o bajada) cerca de `index`
+    ///
+    /// La energía suavizada cruza el umbral tarde o pronto según la pendiente;
+    /// el salto en la energía sin suavizar marca dónde cambia la sección.
+    fn refine(energies: &[f64], index: usize, radius: usize, rising: bool) -> usize {
+        let from = index.saturating_sub(radius).max(1);
+        let to = (index + radius).min(energies.len() - 1);
+        (from..=to)
+            .max_by(|&a, &b| {
+                let jump = |i: usize| {
+                    let diff = energies[i] - energies[i - 1];
+                    if rising {
+                        diff
+                    } else {
+                        -diff
+                    }
+                };
+                jump(a).total_cmp(&jump(b))
+            })
+            .unwrap_or(index)
+    }
+
+    /// Ajusta un cue al beat más cercano (al compás si se conoce el downbeat)
+    ///
+    /// AIDEV-NOTE: El intro solo se ajusta al beat: el primer sample audible
+    /// no tiene por qué caer en un "1".
+    fn snap(cue: &AutoCue, tempo: &TempoMap, downbeat: Option<u32>) -> f64 {
+        let beat = tempo.beat_at(cue.position);
+        let snapped = match downbeat {
+            Some(downbeat) if cue.kind != AutoCueKind::Intro => {
+                let bar = BEATS_PER_BAR as f64;
+                downbeat as f64 + ((beat - downbeat as f64) / bar).round() * bar
+            }
+            _ => beat.round(),
+        };
+        tempo.time_at(snapped).max(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 22050;
+
+    /// Pista sintética a 120 BPM (compás de 2s) empezando en `offset`
+    ///
+    /// Cada sección es (compases, nivel); al final hay 2s de silencio.
+    fn render(offset: f64, sections: &[(usize, f32)]) -> Vec<f32> {
+        let bars: usize = sections.iter().map(|&(bars, _)| bars).sum();
+        let length = ((offset + bars as f64 * 2.0 + 2.0) * SAMPLE_RATE as f64) as usize;
+        let mut samples = vec![0.0f32; length];
+
+        let mut start = (offset * SAMPLE_RATE as f64) as usize;
+        for &(bars, level) in sections {
+            let end = start + bars * 2 * SAMPLE_RATE as usize;
+            for (i, sample) in samples[start..end].iter_mut().enumerate() {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                let beat_t = t % 0.5;
+                let kick = (-beat_t * 30.0).exp() * (2.0 * std::f32::consts::PI * 60.0 * t).sin();
+                let pad = (2.0 * std::f32::consts::PI * 220.0 * t).sin();
+                *sample = level * (0.6 * kick + 0.4 * pad);
+            }
+            start = end;
+        }
+        samples
+    }
+
+    const SECTIONS: [(usize, f32); 5] = [(8, 0.15), (16, 0.8), (8, 0.2), (16, 0.8), (8, 0.15)];
+
+    #[test]
+    fn test_detects_sections() {
+        let samples = render(1.3, &SECTIONS);
+        let cues = AutoCueDetector::analyze_samples(&samples, SAMPLE_RATE, 1, None, None).unwrap();
+
+        let kinds: Vec<AutoCueKind> = cues.iter().map(|c| c.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                AutoCueKind::Intro,
+                AutoCueKind::Main,
+                AutoCueKind::Breakdown,
+                AutoCueKind::Drop,
+                AutoCueKind::Outro,
+            ]
+        );
+        assert!((cues[0].position - 1.3).abs() < 0.01);
+        for (cue, expected) in cues.iter().zip([1.3, 17.3, 49.3, 65.3, 97.3]) {
+            assert!(
+                (cue.position - expected).abs() <= BLOCK_SECONDS,
+                "{:?} != {}",
+                cue,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_snaps_to_grid() {
+        let samples = render(1.3, &SECTIONS);
+        // El grid empieza medio beat antes del audio; el primer "1" es el beat 1
+        let tempo = TempoMap::constant(120.0, 0.8);
+        let cues =
+            AutoCueDetector::analyze_samples(&samples, SAMPLE_RATE, 1, Some(&tempo), Some(1))
+                .unwrap();
+
+        let expected = [1.3, 17.3, 49.3, 65.3, 97.3];
+        assert_eq!(cues.len(), expected.len(), "{:?}", cues);
+        for (cue, expected) in cues.iter().zip(expected) {
+            assert!(
+                (cue.position - expected).abs() < 1e-6,
+                "{:?} != {}",
+                cue,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_constant_energy_has_only_intro() {
+        let samples = render(0.5, &[(16, 0.8)]);
+        let cues = AutoCueDetector::analyze_samples(&samples, SAMPLE_RATE, 1, None, None).unwrap();
+        assert_eq!(cues.len(), 1);
+        assert_eq!(cues[0].kind, AutoCueKind::Intro);
+    }
+
+    #[test]
+    fn test_silence_is_rejected() {
+        let samples = vec![0.0; SAMPLE_RATE as usize * 5];
+        assert!(AutoCueDetector::analyze_samples(&samples, SAMPLE_RATE, 1, None, None).is_err());
+    }
+}
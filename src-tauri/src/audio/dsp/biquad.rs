@@ -0,0 +1,239 @@
+//! Filtros biquad (RBJ Audio EQ Cookbook)
+
+use serde::{Deserialize, Serialize};
+
+/// Tipo de filtro de una banda
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum FilterType {
+    /// Campana: realza/atenúa alrededor de la frecuencia
+    #[default]
+    Peaking,
+    /// Shelf de graves: realza/atenúa por debajo de la frecuencia
+    LowShelf,
+    /// Shelf de agudos: realza/atenúa por encima de la frecuencia
+    HighShelf,
+    /// Paso bajo (ignora la ganancia)
+    LowPass,
+    /// Paso alto (ignora la ganancia)
+    HighPass,
+}
+
+/// Coeficientes normalizados (a0 = 1)
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Coefficients {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl Coefficients {
+    const IDENTITY: Self = Self {
+        b0: 1.0,
+        b1: 0.0,
+        b2: 0.0,
+        a1: 0.0,
+        a2: 0.0,
+    };
+
+    fn design(filter: FilterType, sample_rate: u32, frequency: f64, gain_db: f64, q: f64) -> Self {
+        let fs = sample_rate.max(1) as f64;
+        // Por encima de Nyquist el filtro no tiene sentido: dejar pasar la señal
+        let frequency = frequency.clamp(10.0, fs * 0.49);
+        let q = q.max(0.05);
+
+        let a = 10f64.powf(gain_db / 40.0);
+        let w0 = 2.0 * std::f64::consts::PI * frequency / fs;
+        let (sin_w0, cos_w0) = w0.sin_cos();
+        let alpha = sin_w0 / (2.0 * q);
+
+        let (b0, b1, b2, a0, a1, a2) = match filter {
+            FilterType::Peaking => (
+                1.0 + alpha * a,
+                -2.0 * cos_w0,
+                1.0 - alpha * a,
+                1.0 + alpha / a,
+                -2.0 * cos_w0,
+                1.0 - alpha / a,
+            ),
+            FilterType::LowShelf => {
+                let sqrt_a = 2.0 * a.sqrt() * alpha;
+                (
+                    a * ((a + 1.0) - (a - 1.0) * cos_w0 + sqrt_a),
+                    2.0 * a * ((a - 1.0) - (a + 1.0) * cos_w0),
+                    a * ((a + 1.0) - (a - 1.0) * cos_w0 - sqrt_a),
+                    (a + 1.0) + (a - 1.0) * cos_w0 + sqrt_a,
+                    -2.0 * ((a - 1.0) + (a + 1.0) * cos_w0),
+                    (a + 1.0) + (a - 1.0) * cos_w0 - sqrt_a,
+                )
+            }
+            FilterType::HighShelf => {
+                let sqrt_a = 2.0 * a.sqrt() * alpha;
+                (
+                    a * ((a + 1.0) + (a - 1.0) * cos_w0 + sqrt_a),
+                    -2.0 * a * ((a - 1.0) + (a + 1.0) * cos_w0),
+                    a * ((a + 1.0) + (a - 1.0) * cos_w0 - sqrt_a),
+                    (a + 1.0) - (a - 1.0) * cos_w0 + sqrt_a,
+                    2.0 * ((a - 1.0) - (a + 1.0) * cos_w0),
+                    (a + 1.0) - (a - 1.0) * cos_w0 - sqrt_a,
+                )
+            }
+            FilterType::LowPass => (
+                (1.0 - cos_w0) / 2.0,
+                1.0 - cos_w0,
+                (1.0 - cos_w0) / 2.0,
+                1.0 + alpha,
+                -2.0 * cos_w0,
+                1.0 - alpha,
+            ),
+            FilterType::HighPass => (
+                (1.0 + cos_w0) / 2.0,
+                -(1.0 + cos_w0),
+                (1.0 + cos_w0) / 2.0,
+                1.0 + alpha,
+                -2.0 * cos_w0,
+                1.0 - alpha,
+            ),
+        };
+
+        Self {
+            b0: (b0 / a0) as f32,
+            b1: (b1 / a0) as f32,
+            b2: (b2 / a0) as f32,
+            a1: (a1 / a0) as f32,
+            a2: (a2 / a0) as f32,
+        }
+    }
+}
+
+/// Filtro biquad multicanal para samples interleaved
+///
+/// AIDEV-NOTE: Forma directa II transpuesta, con estado por canal. Cambiar los
+/// coeficientes en caliente conserva el estado, así mover una banda no produce
+/// un click por vaciar el filtro.
+#[derive(Debug, Clone)]
+pub struct Biquad {
+    coeffs: Coefficients,
+    /// Estado (z1, z2) por canal
+    state: Vec<(f32, f32)>,
+}
+
+impl Biquad {
+    /// Crea un filtro que deja pasar la señal sin cambios
+    pub fn new(channels: usize) -> Self {
+        Self {
+            coeffs: Coefficients::IDENTITY,
+            state: vec![(0.0, 0.0); channels.max(1)],
+        }
+    }
+
+    /// Recalcula los coeficientes
+    pub fn configure(
+        &mut self,
+        filter: FilterType,
+        sample_rate: u32,
+        frequency: f64,
+        gain_db: f64,
+        q: f64,
+    ) {
+        self.coeffs = Coefficients::design(filter, sample_rate, frequency, gain_db, q);
+    }
+
+    /// Número de canales
+    pub fn channels(&self) -> usize {
+        self.state.len()
+    }
+
+    /// Limpia el estado interno (seek o cambio de track)
+    pub fn reset(&mut self) {
+        self.state.iter_mut().for_each(|s| *s = (0.0, 0.0));
+    }
+
+    /// Filtra en el sitio un bloque interleaved
+    pub fn process(&mut self, samples: &mut [f32]) {
+        let c = self.coeffs;
+        let channels = self.state.len();
+
+        for frame in samples.chunks_mut(channels) {
+            for (sample, (z1, z2)) in frame.iter_mut().zip(self.state.iter_mut()) {
+                let x = *sample;
+                let y = c.b0 * x + *z1;
+                *z1 = c.b1 * x - c.a1 * y + *z2;
+                *z2 = c.b2 * x - c.a2 * y;
+                *sample = y;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Ganancia (dB) en régimen estable para un seno de `freq` Hz
+    fn measure_gain_db(filter: &mut Biquad, sample_rate: u32, freq: f64) -> f64 {
+        let frames = sample_rate as usize;
+        let mut samples: Vec<f32> = (0..frames)
+            .map(|i| {
+                (2.0 * std::f64::consts::PI * freq * i as f64 / sample_rate as f64).sin() as f32
+            })
+            .collect();
+        filter.process(&mut samples);
+
+        // Ignorar el transitorio inicial
+        let tail = &samples[frames / 2..];
+        let peak = tail.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+        20.0 * (peak as f64).log10()
+    }
+
+    #[test]
+    fn test_identity_passes_signal() {
+        let mut filter = Biquad::new(2);
+        let mut samples = vec![0.1, -0.2, 0.3, -0.4];
+        filter.process(&mut samples);
+        assert_eq!(samples, vec![0.1, -0.2, 0.3, -0.4]);
+    }
+
+    #[test]
+    fn test_peaking_boost_at_center() {
+        let mut filter = Biquad::new(1);
+        filter.configure(FilterType::Peaking, 48000, 1000.0, 6.0, 1.0);
+
+        assert!((measure_gain_db(&mut filter, 48000, 1000.0) - 6.0).abs() < 0.2);
+        filter.reset();
+        assert!(measure_gain_db(&mut filter, 48000, 10000.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_shelves() {
+        let mut low = Biquad::new(1);
+        low.configure(FilterType::LowShelf, 44100, 200.0, -9.0, 0.707);
+        assert!((measure_gain_db(&mut low, 44100, 40.0) - -9.0).abs() < 0.5);
+
+        let mut high = Biquad::new(1);
+        high.configure(FilterType::HighShelf, 44100, 5000.0, 4.0, 0.707);
+        assert!((measure_gain_db(&mut high, 44100, 15000.0) - 4.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_lowpass_attenuates_highs() {
+        let mut filter = Biquad::new(1);
+        filter.configure(FilterType::LowPass, 44100, 500.0, 0.0, 0.707);
+        assert!(measure_gain_db(&mut filter, 44100, 8000.0) < -40.0);
+    }
+
+    #[test]
+    fn test_channels_are_independent() {
+        let mut filter = Biquad::new(2);
+        filter.configure(FilterType::Peaking, 44100, 1000.0, 12.0, 1.0);
+
+        // Canal derecho en silencio: debe seguir en silencio
+        let mut samples: Vec<f32> = (0..1000)
+            .flat_map(|i| [(i as f32 * 0.1).sin(), 0.0])
+            .collect();
+        filter.process(&mut samples);
+        assert!(samples.iter().skip(1).step_by(2).all(|s| *s == 0.0));
+    }
+}
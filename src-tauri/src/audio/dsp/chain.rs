@@ -0,0 +1,355 @@
+//! Cadena DSP en tiempo real: preamp → EQ → limitador
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+
+use super::eq::{EqBand, ParametricEq};
+use crate::audio::constants::{EQ_MAX_GAIN_DB, PREAMP_RAMP_MS};
+use crate::audio::player::normalization::Limiter;
+
+/// Etapa de procesado por sample
+///
+/// AIDEV-NOTE: Todas las etapas trabajan en el sitio sobre bloques interleaved y
+/// se ejecutan en el decode thread: nada de allocations ni locks en `process`.
+pub trait DspStage: Send {
+    /// Procesa en el sitio un bloque interleaved
+    fn process(&mut self, samples: &mut [f32], sample_rate: u32, channels: u16);
+
+    /// Limpia el estado interno (seek o cambio de track)
+    fn reset(&mut self);
+
+    /// `false` si la etapa no altera la señal y se puede saltar
+    fn is_active(&self) -> bool;
+}
+
+impl DspStage for ParametricEq {
+    fn process(&mut self, samples: &mut [f32], sample_rate: u32, channels: u16) {
+        ParametricEq::process(self, samples, sample_rate, channels);
+    }
+
+    fn reset(&mut self) {
+        ParametricEq::reset(self);
+    }
+
+    fn is_active(&self) -> bool {
+        !self.is_flat()
+    }
+}
+
+impl DspStage for Limiter {
+    fn process(&mut self, samples: &mut [f32], sample_rate: u32, channels: u16) {
+        Limiter::process(self, samples, sample_rate, channels);
+    }
+
+    fn reset(&mut self) {
+        Limiter::reset(self);
+    }
+
+    fn is_active(&self) -> bool {
+        self.is_enabled()
+    }
+}
+
+/// Configuración de la cadena DSP (persistida en settings)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DspSettings {
+    /// Activa el preamp y el EQ
+    #[serde(default)]
+    pub enabled: bool,
+    /// Ganancia previa al EQ (dB), para dejar headroom a los realces
+    #[serde(default)]
+    pub preamp_db: f64,
+    #[serde(default = "EqBand::default_bands")]
+    pub bands: Vec<EqBand>,
+    /// Limitador de salida (siempre activo si la normalización lo requiere)
+    #[serde(default = "default_limiter_enabled")]
+    pub limiter_enabled: bool,
+    /// Nombre del último preset aplicado, si no se ha modificado después
+    #[serde(default)]
+    pub preset: Option<String>,
+}
+
+impl Default for DspSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            preamp_db: 0.0,
+            bands: EqBand::default_bands(),
+            limiter_enabled: default_limiter_enabled(),
+            preset: None,
+        }
+    }
+}
+
+fn default_limiter_enabled() -> bool {
+    true
+}
+
+impl DspSettings {
+    /// Devuelve una copia con valores dentro de rango
+    pub fn clamped(self) -> Self {
+        Self {
+            preamp_db: self.preamp_db.clamp(-EQ_MAX_GAIN_DB, EQ_MAX_GAIN_DB),
+            bands: self.bands.into_iter().map(EqBand::clamped).collect(),
+            ..self
+        }
+    }
+}
+
+/// Preamp con rampa lineal para cambios en vivo
+struct Preamp {
+    current: f32,
+    target: f32,
+}
+
+impl Preamp {
+    fn new(gain: f32) -> Self {
+        Self {
+            current: gain,
+            target: gain,
+        }
+    }
+
+    fn set_gain(&mut self, gain: f32) {
+        self.target = gain;
+    }
+}
+
+impl DspStage for Preamp {
+    fn process(&mut self, samples: &mut [f32], sample_rate: u32, channels: u16) {
+        let channels = channels.max(1) as usize;
+        if self.current != self.target {
+            let ramp_frames = (PREAMP_RAMP_MS / 1000.0 * sample_rate.max(1) as f64).max(1.0);
+            let step = (self.target - self.current) / ramp_frames as f32;
+            for frame in samples.chunks_mut(channels) {
+                self.current = if (self.target - self.current).abs() <= step.abs() {
+                    self.target
+                } else {
+                    self.current + step
+                };
+                frame.iter_mut().for_each(|s| *s *= self.current);
+            }
+        } else if self.current != 1.0 {
+            samples.iter_mut().for_each(|s| *s *= self.current);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.current = self.target;
+    }
+
+    fn is_active(&self) -> bool {
+        self.current != 1.0 || self.target != 1.0
+    }
+}
+
+/// Cadena DSP aplicada antes de escribir al ring buffer
+///
+/// AIDEV-NOTE: Orden fijo preamp → EQ → limitador. El limitador va el último para
+/// que ni los realces del EQ ni la ganancia de normalización recorten. Si ninguna
+/// etapa está activa, `process_cow` devuelve el bloque prestado sin copiarlo.
+pub struct DspChain {
+    settings: DspSettings,
+    /// La normalización necesita el limitador aunque el usuario lo desactive
+    limiter_required: bool,
+    preamp: Preamp,
+    eq: ParametricEq,
+    limiter: Limiter,
+}
+
+impl DspChain {
+    pub fn new(settings: DspSettings) -> Self {
+        let mut chain = Self {
+            settings: DspSettings::default(),
+            limiter_required: false,
+            preamp: Preamp::new(1.0),
+            eq: ParametricEq::new(Vec::new()),
+            limiter: Limiter::new(false),
+        };
+        chain.set_settings(settings);
+        chain.preamp.reset();
+        chain
+    }
+
+    /// Configuración actual
+    pub fn settings(&self) -> &DspSettings {
+        &self.settings
+    }
+
+    /// Aplica una nueva configuración en vivo
+    pub fn set_settings(&mut self, settings: DspSettings) {
+        let settings = settings.clamped();
+
+        if settings.enabled {
+            self.preamp
+                .set_gain(db_to_linear(settings.preamp_db) as f32);
+            self.eq.set_bands(settings.bands.clone());
+        } else {
+            self.preamp.set_gain(1.0);
+            self.eq.set_bands(Vec::new());
+        }
+
+        self.settings = settings;
+        self.update_limiter();
+    }
+
+    /// Fuerza el limitador (normalización activa)
+    pub fn set_limiter_required(&mut self, required: bool) {
+        self.limiter_required = required;
+        self.update_limiter();
+    }
+
+    /// AIDEV-NOTE: El limitador del usuario usa rodilla suave; el que fuerza la
+    /// normalización mantiene el techo duro (no toca nada por debajo del techo).
+    fn update_limiter(&mut self) {
+        let user_limiter = self.settings.enabled && self.settings.limiter_enabled;
+        self.limiter
+            .set_enabled(user_limiter || self.limiter_required);
+        self.limiter
+            .set_soft_knee(user_limiter && !self.limiter_required);
+    }
+
+    /// `true` si alguna etapa altera la señal
+    pub fn is_active(&self) -> bool {
+        self.stages().iter().any(|stage| stage.is_active())
+    }
+
+    /// Limpia el estado de todas las etapas (seek o cambio de track)
+    pub fn reset(&mut self) {
+        self.stages_mut()
+            .into_iter()
+            .for_each(|stage| stage.reset());
+    }
+
+    /// Procesa en el sitio un bloque interleaved
+    pub fn process(&mut self, samples: &mut [f32], sample_rate: u32, channels: u16) {
+        for stage in self.stages_mut() {
+            if stage.is_active() {
+                stage.process(samples, sample_rate, channels);
+            }
+        }
+    }
+
+    /// Versión para bloques prestados: copia solo si alguna etapa está activa
+    pub fn process_cow<'a>(
+        &mut self,
+        samples: Cow<'a, [f32]>,
+        sample_rate: u32,
+        channels: u16,
+    ) -> Cow<'a, [f32]> {
+        if !self.is_active() {
+            return samples;
+        }
+        let mut owned = samples.into_owned();
+        self.process(&mut owned, sample_rate, channels);
+        Cow::Owned(owned)
+    }
+
+    fn stages(&self) -> [&dyn DspStage; 3] {
+        [&self.preamp, &self.eq, &self.limiter]
+    }
+
+    fn stages_mut(&mut self) -> [&mut dyn DspStage; 3] {
+        [&mut self.preamp, &mut self.eq, &mut self.limiter]
+    }
+}
+
+/// dB → ganancia lineal
+fn db_to_linear(db: f64) -> f64 {
+    10f64.powf(db / 20.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled_settings() -> DspSettings {
+        DspSettings {
+            enabled: true,
+            limiter_enabled: false,
+            ..DspSettings::default()
+        }
+    }
+
+    #[test]
+    fn test_default_chain_is_bypass() {
+        let mut chain = DspChain::new(DspSettings::default());
+        assert!(!chain.is_active());
+
+        let input = [0.5f32, -0.5];
+        let out = chain.process_cow(Cow::Borrowed(&input), 44100, 2);
+        assert!(matches!(out, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_preamp_applies_gain() {
+        let mut chain = DspChain::new(DspSettings {
+            preamp_db: -6.0,
+            ..enabled_settings()
+        });
+
+        let mut samples = vec![1.0f32; 8];
+        chain.process(&mut samples, 44100, 2);
+        assert!(samples.iter().all(|s| (s - 0.501).abs() < 0.001));
+    }
+
+    #[test]
+    fn test_preamp_ramps_live_changes() {
+        let mut chain = DspChain::new(enabled_settings());
+        chain.set_settings(DspSettings {
+            preamp_db: -12.0,
+            ..enabled_settings()
+        });
+
+        // 20 ms a 44.1 kHz: la rampa empieza cerca de 1.0 y termina en el objetivo
+        let mut samples = vec![1.0f32; 44100 / 10];
+        chain.process(&mut samples, 44100, 1);
+        assert!(samples[0] > 0.99);
+        assert!((samples.last().unwrap() - 0.251).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_limiter_required_by_normalization() {
+        let mut chain = DspChain::new(DspSettings::default());
+        chain.set_limiter_required(true);
+        assert!(chain.is_active());
+
+        let mut samples = vec![1.5f32; 4];
+        chain.process(&mut samples, 44100, 2);
+        assert!(samples.iter().all(|s| *s < 1.0));
+
+        chain.set_limiter_required(false);
+        assert!(!chain.is_active());
+    }
+
+    #[test]
+    fn test_user_limiter_soft_knee_only_without_normalization() {
+        // 0.8 ≈ -1.9 dBFS: dentro de la rodilla, por debajo del techo (-1 dBFS)
+        let mut chain = DspChain::new(DspSettings {
+            limiter_enabled: true,
+            ..enabled_settings()
+        });
+        let mut samples = vec![0.8f32; 4];
+        chain.process(&mut samples, 44100, 2);
+        assert!(samples.iter().all(|s| *s < 0.8));
+
+        // Con la normalización el techo es duro: por debajo no se toca
+        chain.set_limiter_required(true);
+        chain.reset();
+        let mut samples = vec![0.8f32; 4];
+        chain.process(&mut samples, 44100, 2);
+        assert_eq!(samples, vec![0.8f32; 4]);
+    }
+
+    #[test]
+    fn test_disabled_settings_keep_bands() {
+        let mut settings = DspSettings::default();
+        settings.bands[0].gain_db = 6.0;
+        let chain = DspChain::new(settings);
+
+        assert!(!chain.is_active());
+        assert_eq!(chain.settings().bands[0].gain_db, 6.0);
+    }
+}
@@ -0,0 +1,271 @@
+//! Ecualizador paramétrico de 10 bandas
+
+use serde::{Deserialize, Serialize};
+
+use super::biquad::{Biquad, FilterType};
+use crate::audio::constants::EQ_MAX_GAIN_DB;
+
+/// Número de bandas del ecualizador
+pub const EQ_BAND_COUNT: usize = 10;
+
+/// Frecuencias centrales por defecto (octavas ISO)
+const DEFAULT_FREQUENCIES: [f64; EQ_BAND_COUNT] = [
+    31.0, 62.0, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0,
+];
+
+/// Q por defecto: una octava de ancho de banda
+const DEFAULT_Q: f64 = 1.41;
+
+/// Banda del ecualizador
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EqBand {
+    /// Frecuencia central/de corte (Hz)
+    pub frequency: f64,
+    /// Ganancia (dB), ignorada en paso bajo/alto
+    pub gain_db: f64,
+    pub q: f64,
+    #[serde(default)]
+    pub kind: FilterType,
+}
+
+impl EqBand {
+    /// Bandas planas en las frecuencias por defecto
+    pub fn default_bands() -> Vec<EqBand> {
+        DEFAULT_FREQUENCIES
+            .iter()
+            .map(|&frequency| EqBand {
+                frequency,
+                gain_db: 0.0,
+                q: DEFAULT_Q,
+                kind: FilterType::Peaking,
+            })
+            .collect()
+    }
+
+    /// Devuelve una copia con valores dentro de rango
+    pub fn clamped(self) -> Self {
+        Self {
+            frequency: self.frequency.clamp(20.0, 20000.0),
+            gain_db: self.gain_db.clamp(-EQ_MAX_GAIN_DB, EQ_MAX_GAIN_DB),
+            q: self.q.clamp(0.1, 18.0),
+            kind: self.kind,
+        }
+    }
+
+    /// Una banda que no altera la señal se puede saltar
+    fn is_neutral(&self) -> bool {
+        match self.kind {
+            FilterType::Peaking | FilterType::LowShelf | FilterType::HighShelf => {
+                self.gain_db.abs() < 0.01
+            }
+            FilterType::LowPass | FilterType::HighPass => false,
+        }
+    }
+}
+
+/// Preset de ecualización guardado en settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EqPreset {
+    pub name: String,
+    #[serde(default)]
+    pub preamp_db: f64,
+    pub bands: Vec<EqBand>,
+    /// Los presets de fábrica no se pueden borrar ni sobrescribir
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+impl EqPreset {
+    /// Presets de fábrica
+    pub fn builtin_presets() -> Vec<EqPreset> {
+        let preset = |name: &str, preamp_db: f64, gains: [f64; EQ_BAND_COUNT]| {
+            let mut bands = EqBand::default_bands();
+            for (band, gain_db) in bands.iter_mut().zip(gains) {
+                band.gain_db = gain_db;
+            }
+            EqPreset {
+                name: name.to_string(),
+                preamp_db,
+                bands,
+                builtin: true,
+            }
+        };
+
+        vec![
+            preset("Flat", 0.0, [0.0; EQ_BAND_COUNT]),
+            preset(
+                "Bass Boost",
+                -4.0,
+                [6.0, 5.0, 4.0, 2.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+            ),
+            preset(
+                "Treble Boost",
+                -4.0,
+                [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 2.0, 4.0, 5.0, 6.0],
+            ),
+            preset(
+                "Vocal",
+                -2.0,
+                [-2.0, -2.0, -1.0, 0.0, 2.0, 3.0, 3.0, 2.0, 0.0, -1.0],
+            ),
+            preset(
+                "Loudness",
+                -4.0,
+                [5.0, 4.0, 2.0, 0.0, -1.0, 0.0, 0.0, 1.0, 3.0, 4.0],
+            ),
+        ]
+    }
+}
+
+/// Ecualizador paramétrico: una cascada de biquads
+///
+/// AIDEV-NOTE: Los filtros se recalculan solo cuando cambian las bandas, el
+/// sample rate o el número de canales. Las bandas neutras no se procesan.
+pub struct ParametricEq {
+    bands: Vec<EqBand>,
+    filters: Vec<Biquad>,
+    sample_rate: u32,
+    channels: u16,
+}
+
+impl ParametricEq {
+    pub fn new(bands: Vec<EqBand>) -> Self {
+        let mut eq = Self {
+            bands: Vec::new(),
+            filters: Vec::new(),
+            sample_rate: 0,
+            channels: 0,
+        };
+        eq.set_bands(bands);
+        eq
+    }
+
+    /// Cambia las bandas conservando el estado de los filtros existentes
+    pub fn set_bands(&mut self, bands: Vec<EqBand>) {
+        self.bands = bands
+            .into_iter()
+            .take(EQ_BAND_COUNT)
+            .map(EqBand::clamped)
+            .collect();
+        self.configure();
+    }
+
+    /// `true` si ninguna banda altera la señal
+    pub fn is_flat(&self) -> bool {
+        self.bands.iter().all(EqBand::is_neutral)
+    }
+
+    /// Limpia el estado de los filtros
+    pub fn reset(&mut self) {
+        self.filters.iter_mut().for_each(Biquad::reset);
+    }
+
+    /// Ecualiza en el sitio un bloque interleaved
+    pub fn process(&mut self, samples: &mut [f32], sample_rate: u32, channels: u16) {
+        if self.sample_rate != sample_rate || self.channels != channels {
+            self.sample_rate = sample_rate;
+            self.channels = channels;
+            self.configure();
+        }
+
+        for (band, filter) in self.bands.iter().zip(self.filters.iter_mut()) {
+            if !band.is_neutral() {
+                filter.process(samples);
+            }
+        }
+    }
+
+    fn configure(&mut self) {
+        if self.sample_rate == 0 {
+            return;
+        }
+        let channels = self.channels.max(1) as usize;
+        if self.filters.len() != self.bands.len()
+            || self
+                .filters
+                .first()
+                .is_some_and(|f| f.channels() != channels)
+        {
+            self.filters = vec![Biquad::new(channels); self.bands.len()];
+        }
+
+        for (band, filter) in self.bands.iter().zip(self.filters.iter_mut()) {
+            filter.configure(
+                band.kind,
+                self.sample_rate,
+                band.frequency,
+                band.gain_db,
+                band.q,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f64, sample_rate: u32) -> Vec<f32> {
+        (0..sample_rate as usize)
+            .map(|i| {
+                (2.0 * std::f64::consts::PI * freq * i as f64 / sample_rate as f64).sin() as f32
+            })
+            .collect()
+    }
+
+    fn tail_peak_db(samples: &[f32]) -> f64 {
+        let peak = samples[samples.len() / 2..]
+            .iter()
+            .fold(0.0f32, |acc, s| acc.max(s.abs()));
+        20.0 * (peak as f64).log10()
+    }
+
+    #[test]
+    fn test_default_bands() {
+        let bands = EqBand::default_bands();
+        assert_eq!(bands.len(), EQ_BAND_COUNT);
+        assert!(ParametricEq::new(bands).is_flat());
+    }
+
+    #[test]
+    fn test_flat_eq_is_transparent() {
+        let mut eq = ParametricEq::new(EqBand::default_bands());
+        let input = sine(440.0, 44100);
+        let mut samples = input.clone();
+        eq.process(&mut samples, 44100, 1);
+        assert_eq!(samples, input);
+    }
+
+    #[test]
+    fn test_band_boost() {
+        let mut bands = EqBand::default_bands();
+        bands[5].gain_db = 6.0; // 1 kHz
+        let mut eq = ParametricEq::new(bands);
+
+        let mut samples = sine(1000.0, 48000);
+        eq.process(&mut samples, 48000, 1);
+        assert!((tail_peak_db(&samples) - 6.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_bands_are_clamped() {
+        let mut bands = EqBand::default_bands();
+        bands[0].gain_db = 40.0;
+        bands.push(bands[0]);
+        let eq = ParametricEq::new(bands);
+
+        assert_eq!(eq.bands.len(), EQ_BAND_COUNT);
+        assert_eq!(eq.bands[0].gain_db, EQ_MAX_GAIN_DB);
+    }
+
+    #[test]
+    fn test_builtin_presets() {
+        let presets = EqPreset::builtin_presets();
+        assert_eq!(presets[0].name, "Flat");
+        assert!(presets
+            .iter()
+            .all(|p| p.builtin && p.bands.len() == EQ_BAND_COUNT));
+    }
+}
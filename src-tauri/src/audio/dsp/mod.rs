@@ -0,0 +1,18 @@
+//! Procesamiento de señales digitales (DSP)
+//!
+//! AIDEV-NOTE: Dos usos distintos:
+//! - peaks: cálculo de peaks para waveforms (análisis, fuera de tiempo real)
+//! - biquad, eq, chain: procesado por sample en el decode thread.
+//!   `DspChain` se aplica entre la decodificación y la escritura al ring buffer
+//!   (ver `player::decoder::write_processed`). Su última etapa es el limitador
+//!   de la normalización (`player::normalization::Limiter`).
+
+pub mod biquad;
+pub mod chain;
+pub mod eq;
+pub mod peaks;
+
+pub use biquad::{Biquad, FilterType};
+pub use chain::{DspChain, DspSettings, DspStage};
+pub use eq::{EqBand, EqPreset, ParametricEq, EQ_BAND_COUNT};
+pub use peaks::{calculate_peak_value, normalize_peaks, PeakMethod};
@@ -0,0 +1,364 @@
+//! Modelo de tonalidad musical y sus notaciones
+//!
+//! AIDEV-NOTE: Las tonalidades llegan en cualquier notación ("Am", "A minor",
+//! "8A", "1m") desde tags, Beatport o el detector. `MusicalKey::parse` las
+//! reduce a tónica + modo; la forma canónica que se guarda en
+//! `tracks.key_canonical` es el código Camelot ("8A"), que además ordena por
+//! la rueda de quintas. `KeyNotation` es la preferencia de visualización y de
+//! escritura de TKEY.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Nombres de las notas (enarmónicos habituales en tags de DJ)
+const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "Eb", "E", "F", "F#", "G", "Ab", "A", "Bb", "B",
+];
+
+/// Modo de una tonalidad
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyMode {
+    Major,
+    Minor,
+}
+
+/// Notación en la que se muestran y escriben las tonalidades
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyNotation {
+    /// "Am", "F#", "Bbm"
+    #[default]
+    Musical,
+    /// "8A", "2B" (Mixed In Key)
+    Camelot,
+    /// "1m", "6d" (Traktor)
+    OpenKey,
+}
+
+impl KeyNotation {
+    pub const ALL: [KeyNotation; 3] = [
+        KeyNotation::Musical,
+        KeyNotation::Camelot,
+        KeyNotation::OpenKey,
+    ];
+
+    /// Valor guardado en el setting `library.key_notation`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyNotation::Musical => "musical",
+            KeyNotation::Camelot => "camelot",
+            KeyNotation::OpenKey => "openkey",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|n| n.as_str() == value)
+    }
+}
+
+/// Tonalidad musical
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MusicalKey {
+    /// Tónica como clase de altura (0 = C, 9 = A)
+    pub tonic: u8,
+    pub mode: KeyMode,
+}
+
+impl MusicalKey {
+    /// Las 24 tonalidades en orden Camelot (1A, 1B, 2A, ... 12B)
+    pub fn all() -> impl Iterator<Item = MusicalKey> {
+        (1..=12u8).flat_map(|number| {
+            [KeyMode::Minor, KeyMode::Major]
+                .into_iter()
+                .map(move |mode| Self::from_camelot(number, mode))
+        })
+    }
+
+    /// Interpreta una tonalidad en cualquier notación habitual
+    ///
+    /// Acepta notación musical ("Am", "A minor", "F# min", "Bbmaj", "G♭"),
+    /// Camelot ("8A", "08B") y Open Key ("1m", "6d"). Con alternativas
+    /// enarmónicas ("G#m/Abm") se usa la primera. `None` si no se reconoce.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.split('/').next()?.trim();
+        if value.is_empty() {
+            return None;
+        }
+        let value = value.replace('♯', "#").replace('♭', "b");
+
+        if value.starts_with(|c: char| c.is_ascii_digit()) {
+            Self::parse_wheel(&value)
+        } else {
+            Self::parse_musical(&value)
+        }
+    }
+
+    /// Camelot ("8A") u Open Key ("1m")
+    fn parse_wheel(value: &str) -> Option<Self> {
+        let split = value.find(|c: char| !c.is_ascii_digit())?;
+        let number: u8 = value[..split].parse().ok()?;
+        if !(1..=12).contains(&number) {
+            return None;
+        }
+        match value[split..].trim().to_ascii_lowercase().as_str() {
+            "a" => Some(Self::from_camelot(number, KeyMode::Minor)),
+            "b" => Some(Self::from_camelot(number, KeyMode::Major)),
+            "m" => Some(Self::from_open_key(number, KeyMode::Minor)),
+            "d" => Some(Self::from_open_key(number, KeyMode::Major)),
+            _ => None,
+        }
+    }
+
+    /// Nota + alteración + modo ("C#m", "Ab major")
+    fn parse_musical(value: &str) -> Option<Self> {
+        let mut chars = value.chars();
+        let natural: i8 = match chars.next()?.to_ascii_uppercase() {
+            'C' => 0,
+            'D' => 2,
+            'E' => 4,
+            'F' => 5,
+            'G' => 7,
+            'A' => 9,
+            'B' => 11,
+            _ => return None,
+        };
+
+        let rest = chars.as_str().trim_start();
+        let lower = rest.to_ascii_lowercase();
+        let (accidental, rest) = if let Some(rest) = rest.strip_prefix('#') {
+            (1, rest)
+        } else if lower.starts_with("sharp") {
+            (1, &rest[5..])
+        } else if lower.starts_with("flat") {
+            (-1, &rest[4..])
+        } else if let Some(rest) = rest.strip_prefix('b') {
+            // Ningún modo empieza por "b": tras la nota siempre es bemol
+            (-1, rest)
+        } else {
+            (0, rest)
+        };
+
+        let mode = match rest.trim().to_ascii_lowercase().as_str() {
+            "" | "maj" | "major" | "dur" => KeyMode::Major,
+            "m" | "min" | "minor" | "moll" => KeyMode::Minor,
+            _ => return None,
+        };
+
+        Some(Self {
+            tonic: (natural + accidental).rem_euclid(12) as u8,
+            mode,
+        })
+    }
+
+    /// Posición en la rueda de quintas (C = 0, G = 1, D = 2...)
+    fn fifths(tonic: u8) -> u8 {
+        (tonic * 7) % 12
+    }
+
+    /// Tónica de la posición `fifths` en la rueda de quintas
+    fn from_fifths(fifths: u8) -> u8 {
+        // 7 es su propio inverso módulo 12
+        (fifths * 7) % 12
+    }
+
+    fn from_camelot(number: u8, mode: KeyMode) -> Self {
+        // 8B = C, 8A = Am
+        let fifths = (number + 12 - 8) % 12;
+        let major_tonic = Self::from_fifths(fifths);
+        let tonic = match mode {
+            KeyMode::Major => major_tonic,
+            KeyMode::Minor => (major_tonic + 9) % 12,
+        };
+        Self { tonic, mode }
+    }
+
+    fn from_open_key(number: u8, mode: KeyMode) -> Self {
+        // 1d = C = 8B
+        Self::from_camelot((number + 6) % 12 + 1, mode)
+    }
+
+    /// Tónica de la relativa mayor (la misma posición en la rueda)
+    fn relative_major_tonic(&self) -> u8 {
+        match self.mode {
+            KeyMode::Major => self.tonic,
+            KeyMode::Minor => (self.tonic + 3) % 12,
+        }
+    }
+
+    /// Número Camelot (1-12)
+    pub fn camelot_number(&self) -> u8 {
+        (Self::fifths(self.relative_major_tonic()) + 7) % 12 + 1
+    }
+
+    /// Número Open Key (1-12)
+    pub fn open_key_number(&self) -> u8 {
+        (self.camelot_number() + 4) % 12 + 1
+    }
+
+    /// Forma canónica (código Camelot, "8A")
+    pub fn canonical(&self) -> String {
+        self.format(KeyNotation::Camelot)
+    }
+
+    /// Formatea en la notación indicada
+    pub fn format(&self, notation: KeyNotation) -> String {
+        match notation {
+            KeyNotation::Musical => self.to_string(),
+            KeyNotation::Camelot => {
+                let letter = match self.mode {
+                    KeyMode::Minor => 'A',
+                    KeyMode::Major => 'B',
+                };
+                format!("{}{}", self.camelot_number(), letter)
+            }
+            KeyNotation::OpenKey => {
+                let letter = match self.mode {
+                    KeyMode::Minor => 'm',
+                    KeyMode::Major => 'd',
+                };
+                format!("{}{}", self.open_key_number(), letter)
+            }
+        }
+    }
+
+    /// Índice de ordenación (0-23) por la rueda Camelot: 1A, 1B, 2A...
+    pub fn sort_index(&self) -> u8 {
+        let minor_first = match self.mode {
+            KeyMode::Minor => 0,
+            KeyMode::Major => 1,
+        };
+        (self.camelot_number() - 1) * 2 + minor_first
+    }
+}
+
+impl PartialOrd for MusicalKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MusicalKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sort_index().cmp(&other.sort_index())
+    }
+}
+
+impl fmt::Display for MusicalKey {
+    /// Notación estándar: "C", "F#m", "Bbm"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let suffix = match self.mode {
+            KeyMode::Major => "",
+            KeyMode::Minor => "m",
+        };
+        write!(f, "{}{}", NOTE_NAMES[self.tonic as usize % 12], suffix)
+    }
+}
+
+/// Normaliza una tonalidad a la notación indicada
+///
+/// Devuelve la tonalidad formateada y su forma canónica; si no se reconoce
+/// se conserva tal cual y sin forma canónica.
+pub fn normalize_key(value: &str, notation: KeyNotation) -> (String, Option<String>) {
+    match MusicalKey::parse(value) {
+        Some(key) => (key.format(notation), Some(key.canonical())),
+        None => (value.to_string(), None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(tonic: u8, mode: KeyMode) -> MusicalKey {
+        MusicalKey { tonic, mode }
+    }
+
+    #[test]
+    fn test_key_names() {
+        assert_eq!(key(0, KeyMode::Major).to_string(), "C");
+        assert_eq!(key(9, KeyMode::Minor).to_string(), "Am");
+        assert_eq!(key(6, KeyMode::Minor).to_string(), "F#m");
+        assert_eq!(key(10, KeyMode::Major).to_string(), "Bb");
+    }
+
+    #[test]
+    fn test_parse_common_notations() {
+        let a_minor = Some(key(9, KeyMode::Minor));
+        for value in ["Am", "A minor", "A Minor", "amin", "8A", "08A", "8a", "1m", " Am "] {
+            assert_eq!(MusicalKey::parse(value), a_minor, "{}", value);
+        }
+
+        let f_sharp_minor = Some(key(6, KeyMode::Minor));
+        for value in ["F#m", "F# min", "F♯ minor", "Gbm", "G♭m", "11A", "4m", "F#m/Gbm"] {
+            assert_eq!(MusicalKey::parse(value), f_sharp_minor, "{}", value);
+        }
+
+        assert_eq!(MusicalKey::parse("Bb"), Some(key(10, KeyMode::Major)));
+        assert_eq!(MusicalKey::parse("Bbm"), Some(key(10, KeyMode::Minor)));
+        assert_eq!(MusicalKey::parse("B"), Some(key(11, KeyMode::Major)));
+        assert_eq!(MusicalKey::parse("B major"), Some(key(11, KeyMode::Major)));
+        assert_eq!(MusicalKey::parse("Db Major"), Some(key(1, KeyMode::Major)));
+        assert_eq!(MusicalKey::parse("Ab minor"), Some(key(8, KeyMode::Minor)));
+        assert_eq!(MusicalKey::parse("C sharp minor"), Some(key(1, KeyMode::Minor)));
+        assert_eq!(MusicalKey::parse("Cb"), Some(key(11, KeyMode::Major)));
+        assert_eq!(MusicalKey::parse("6d"), Some(key(11, KeyMode::Major)));
+        assert_eq!(MusicalKey::parse("12B"), Some(key(4, KeyMode::Major)));
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown() {
+        for value in ["", "H", "13A", "0B", "8C", "Am7", "Unknown", "o"] {
+            assert_eq!(MusicalKey::parse(value), None, "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_notation_roundtrip() {
+        for key in MusicalKey::all() {
+            for notation in KeyNotation::ALL {
+                assert_eq!(MusicalKey::parse(&key.format(notation)), Some(key));
+            }
+        }
+        assert_eq!(MusicalKey::all().count(), 24);
+    }
+
+    #[test]
+    fn test_wheel_numbers() {
+        let c = key(0, KeyMode::Major);
+        assert_eq!(c.format(KeyNotation::Camelot), "8B");
+        assert_eq!(c.format(KeyNotation::OpenKey), "1d");
+        let e_minor = key(4, KeyMode::Minor);
+        assert_eq!(e_minor.format(KeyNotation::Camelot), "9A");
+        assert_eq!(e_minor.format(KeyNotation::OpenKey), "2m");
+        assert_eq!(key(8, KeyMode::Minor).canonical(), "1A");
+    }
+
+    #[test]
+    fn test_sorts_by_camelot_wheel() {
+        let mut keys: Vec<MusicalKey> = ["10A", "Am", "C", "1A", "Abm", "2B"]
+            .iter()
+            .filter_map(|value| MusicalKey::parse(value))
+            .collect();
+        keys.sort();
+        let sorted: Vec<String> = keys.iter().map(|k| k.canonical()).collect();
+        assert_eq!(sorted, ["1A", "1A", "2B", "8A", "8B", "10A"]);
+    }
+
+    #[test]
+    fn test_normalize_key() {
+        assert_eq!(
+            normalize_key("A minor", KeyNotation::Camelot),
+            ("8A".to_string(), Some("8A".to_string()))
+        );
+        assert_eq!(
+            normalize_key("5A", KeyNotation::Musical),
+            ("Cm".to_string(), Some("5A".to_string()))
+        );
+        assert_eq!(
+            normalize_key("???", KeyNotation::OpenKey),
+            ("???".to_string(), None)
+        );
+    }
+}
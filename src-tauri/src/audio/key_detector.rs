@@ -0,0 +1,290 @@
+use crate::audio::decoder::AudioDecoder;
+use crate::audio::error::AudioError;
+use crate::audio::key::{KeyMode, MusicalKey};
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
+use std::path::Path;
+
+/// Factor de diezmado antes del chromagram (44.1 kHz → ~11 kHz)
+///
+/// AIDEV-NOTE: Las notas útiles para la tonalidad están por debajo de ~2 kHz;
+/// diezmar permite ventanas largas (resolución de ~1.3 Hz en graves) con FFT
+/// pequeñas.
+const DECIMATION: usize = 4;
+
+/// Tamaño de la FFT (en samples diezmados)
+const FFT_SIZE: usize = 8192;
+
+/// Salto entre ventanas (50% de solape)
+const HOP_SIZE: usize = FFT_SIZE / 2;
+
+/// Rango de frecuencias del chromagram (C2 - C7)
+const MIN_FREQ_HZ: f64 = 65.0;
+const MAX_FREQ_HZ: f64 = 2100.0;
+
+/// Variación del chromagram a partir de la cual se considera claramente tonal
+const TONAL_VARIATION: f64 = 0.6;
+
+/// Perfiles de tonalidad de Krumhansl-Kessler (empezando en la tónica)
+const MAJOR_PROFILE: [f64; 12] = [
+    6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+];
+const MINOR_PROFILE: [f64; 12] = [
+    6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17,
+];
+
+/// Análisis de tonalidad de una pista
+#[derive(Debug, Clone)]
+pub struct KeyAnalysis {
+    pub key: MusicalKey,
+    /// Confianza 0-100 (margen sobre la siguiente tonalidad candidata)
+    pub confidence: f64,
+}
+
+/// Detector de tonalidad usando chromagram + correlación con perfiles de tonalidad
+pub struct KeyDetector;
+
+impl KeyDetector {
+    /// Analiza una pista y devuelve tonalidad y confidence
+    ///
+    /// # Arguments
+    /// * `path` - Ruta al archivo de audio
+    ///
+    /// # Errors
+    /// Retorna AudioError si:
+    /// - El archivo no existe o no se puede decodificar
+    /// - El audio es más corto que una ventana de análisis (~3s)
+    /// - No hay contenido tonal (silencio)
+    pub fn analyze(path: &Path) -> Result<KeyAnalysis, AudioError> {
+        let decoded = AudioDecoder::decode_samples(path)?;
+        Self::analyze_samples(
+            &decoded.samples,
+            decoded.sample_rate,
+            decoded.channels as usize,
+        )
+    }
+
+    /// Analiza samples interleaved ya decodificados
+    pub fn analyze_samples(
+        samples: &[f32],
+        sample_rate: u32,
+        channels: usize,
+    ) -> Result<KeyAnalysis, AudioError> {
+        let mono = Self::downmix_and_decimate(samples, channels.max(1));
+        let chroma = Self::chromagram(&mono, sample_rate as f64 / DECIMATION as f64)?;
+        Ok(Self::estimate_key(&chroma))
+    }
+
+    /// Pasa a mono y diezma promediando bloques de `DECIMATION` frames
+    ///
+    /// El promedio hace de filtro paso bajo suficiente para el rango del chromagram.
+    fn downmix_and_decimate(samples: &[f32], channels: usize) -> Vec<f32> {
+        samples
+            .chunks(channels * DECIMATION)
+            .map(|block| block.iter().sum::<f32>() / block.len() as f32)
+            .collect()
+    }
+
+    /// Energía por clase de altura de toda la pista (normalizada por ventana)
+    fn chromagram(samples: &[f32], sample_rate: f64) -> Result<[f64; 12], AudioError> {
+        if samples.len() < FFT_SIZE {
+            return Err(AudioError::AnalysisError(
+                "Audio demasiado corto para análisis".into(),
+            ));
+        }
+
+        let fft = FftPlanner::new().plan_fft_forward(FFT_SIZE);
+        let window: Vec<f32> = (0..FFT_SIZE)
+            .map(|i| {
+                let phase = 2.0 * std::f32::consts::PI * i as f32 / FFT_SIZE as f32;
+                0.5 - 0.5 * phase.cos()
+            })
+            .collect();
+
+        // Clase de altura de cada bin dentro del rango
+        let bin_hz = sample_rate / FFT_SIZE as f64;
+        let first_bin = (MIN_FREQ_HZ / bin_hz).ceil() as usize;
+        let last_bin = ((MAX_FREQ_HZ / bin_hz).floor() as usize).min(FFT_SIZE / 2 - 1);
+        let pitch_classes: Vec<usize> = (first_bin..=last_bin)
+            .map(|bin| {
+                let midi = 69.0 + 12.0 * (bin as f64 * bin_hz / 440.0).log2();
+                (midi.round() as i64).rem_euclid(12) as usize
+            })
+            .collect();
+
+        let mut chroma = [0.0f64; 12];
+        let mut buffer = vec![Complex::default(); FFT_SIZE];
+        let mut frames = 0;
+
+        for start in (0..=samples.len() - FFT_SIZE).step_by(HOP_SIZE) {
+            for ((slot, &sample), &w) in buffer
+                .iter_mut()
+                .zip(&samples[start..start + FFT_SIZE])
+                .zip(&window)
+            {
+                *slot = Complex::new(sample * w, 0.0);
+            }
+            fft.process(&mut buffer);
+
+            let mut frame = [0.0f64; 12];
+            for (bin, &pc) in (first_bin..=last_bin).zip(&pitch_classes) {
+                frame[pc] += buffer[bin].norm() as f64;
+            }
+
+            // Cada ventana pesa lo mismo: los pasajes fuertes no dominan
+            let total: f64 = frame.iter().sum();
+            if total > 1e-3 {
+                for (acc, value) in chroma.iter_mut().zip(frame) {
+                    *acc += value / total;
+                }
+                frames += 1;
+            }
+        }
+
+        if frames == 0 {
+            return Err(AudioError::AnalysisError(
+                "No se detectó contenido tonal".into(),
+            ));
+        }
+        Ok(chroma)
+    }
+
+    /// Correlaciona el chromagram con los 24 perfiles y elige el mejor
+    ///
+    /// La confidence combina la distancia de correlación con la segunda
+    /// candidata (0.2 o más = 100) y lo marcado que es el chromagram: un
+    /// chromagram casi plano (ruido, percusión) correlaciona al azar.
+    fn estimate_key(chroma: &[f64; 12]) -> KeyAnalysis {
+        let mut scores: Vec<(MusicalKey, f64)> = Vec::with_capacity(24);
+        for tonic in 0..12u8 {
+            for (mode, profile) in [
+                (KeyMode::Major, &MAJOR_PROFILE),
+                (KeyMode::Minor, &MINOR_PROFILE),
+            ] {
+                let rotated: Vec<f64> = (0..12)
+                    .map(|pc| profile[(pc + 12 - tonic as usize) % 12])
+                    .collect();
+                scores.push((MusicalKey { tonic, mode }, pearson(chroma, &rotated)));
+            }
+        }
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let (key, best) = scores[0];
+        let margin = ((best - scores[1].1) / 0.2).min(1.0);
+        let tonality = (coefficient_of_variation(chroma) / TONAL_VARIATION).min(1.0);
+        KeyAnalysis {
+            key,
+            confidence: (margin * tonality * 100.0).clamp(0.0, 100.0),
+        }
+    }
+}
+
+/// Desviación típica relativa a la media
+fn coefficient_of_variation(values: &[f64]) -> f64 {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    if mean <= f64::EPSILON {
+        return 0.0;
+    }
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+    variance.sqrt() / mean
+}
+
+/// Coeficiente de correlación de Pearson
+fn pearson(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len() as f64;
+    let mean_a = a.iter().sum::<f64>() / n;
+    let mean_b = b.iter().sum::<f64>() / n;
+
+    let mut cov = 0.0;
+    let mut var_a = 0.0;
+    let mut var_b = 0.0;
+    for (&x, &y) in a.iter().zip(b) {
+        cov += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a).powi(2);
+        var_b += (y - mean_b).powi(2);
+    }
+
+    if var_a <= f64::EPSILON || var_b <= f64::EPSILON {
+        return 0.0;
+    }
+    cov / (var_a * var_b).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 44100;
+
+    /// Genera una progresión de acordes (notas MIDI) en estéreo, 2s por acorde
+    fn render_chords(chords: &[&[u8]]) -> Vec<f32> {
+        let frames_per_chord = SAMPLE_RATE as usize * 2;
+        let mut samples = Vec::with_capacity(chords.len() * frames_per_chord * 2);
+        for chord in chords {
+            for i in 0..frames_per_chord {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                let value: f32 = chord
+                    .iter()
+                    .map(|&note| {
+                        let freq = 440.0 * 2f32.powf((note as f32 - 69.0) / 12.0);
+                        (2.0 * std::f32::consts::PI * freq * t).sin()
+                    })
+                    .sum::<f32>()
+                    * 0.2;
+                samples.push(value);
+                samples.push(value);
+            }
+        }
+        samples
+    }
+
+    #[test]
+    fn test_detects_major_progression() {
+        // I - IV - V - I en G mayor
+        let samples = render_chords(&[
+            &[55, 59, 62, 67],
+            &[60, 64, 67, 72],
+            &[62, 66, 69, 74],
+            &[55, 59, 62, 67],
+        ]);
+        let analysis = KeyDetector::analyze_samples(&samples, SAMPLE_RATE, 2).unwrap();
+        assert_eq!(analysis.key.to_string(), "G");
+        assert!(analysis.confidence > 0.0);
+    }
+
+    #[test]
+    fn test_detects_minor_progression() {
+        // i - iv - V - i en A menor (con la sensible G#)
+        let samples = render_chords(&[
+            &[57, 60, 64, 69],
+            &[62, 65, 69, 74],
+            &[64, 68, 71, 76],
+            &[57, 60, 64, 69],
+        ]);
+        let analysis = KeyDetector::analyze_samples(&samples, SAMPLE_RATE, 2).unwrap();
+        assert_eq!(analysis.key.to_string(), "Am");
+    }
+
+    #[test]
+    fn test_noise_has_low_confidence() {
+        let mut rng = fastrand::Rng::with_seed(7);
+        let samples: Vec<f32> = (0..SAMPLE_RATE as usize * 8)
+            .map(|_| rng.f32() * 0.5 - 0.25)
+            .collect();
+        let analysis = KeyDetector::analyze_samples(&samples, SAMPLE_RATE, 1).unwrap();
+        assert!(analysis.confidence < 20.0, "{}", analysis.confidence);
+    }
+
+    #[test]
+    fn test_rejects_short_and_silent_audio() {
+        let short = vec![0.1; 1000];
+        assert!(KeyDetector::analyze_samples(&short, SAMPLE_RATE, 1).is_err());
+
+        let silence = vec![0.0; SAMPLE_RATE as usize * 4];
+        match KeyDetector::analyze_samples(&silence, SAMPLE_RATE, 1) {
+            Err(AudioError::AnalysisError(msg)) => assert!(msg.contains("tonal")),
+            other => panic!("Expected AnalysisError, got {:?}", other.map(|a| a.key)),
+        }
+    }
+}
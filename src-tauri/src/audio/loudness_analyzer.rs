@@ -0,0 +1,481 @@
+use crate::audio::decoder::AudioDecoder;
+use crate::audio::error::AudioError;
+use std::path::Path;
+
+/// Duración de cada sub-bloque de medida (ms); los bloques de gating se forman con ellos
+const SUB_BLOCK_MS: u32 = 100;
+
+/// Sub-bloques por bloque de momentary loudness (400ms, 75% de solape)
+const MOMENTARY_SUB_BLOCKS: usize = 4;
+
+/// Sub-bloques por bloque de short-term loudness (3s) para el loudness range
+const SHORT_TERM_SUB_BLOCKS: usize = 30;
+
+/// Gate absoluto (LUFS)
+const ABSOLUTE_GATE: f64 = -70.0;
+
+/// Gate relativo para integrated loudness (LU bajo la media)
+const RELATIVE_GATE: f64 = -10.0;
+
+/// Gate relativo para loudness range (LU bajo la media)
+const LRA_RELATIVE_GATE: f64 = -20.0;
+
+/// Factor de sobremuestreo para true peak
+const OVERSAMPLING: usize = 4;
+
+/// Taps del interpolador por fase
+const TAPS_PER_PHASE: usize = 12;
+
+/// Análisis de loudness EBU R128 de una pista
+#[derive(Debug, Clone)]
+pub struct LoudnessAnalysis {
+    /// Integrated loudness (LUFS)
+    pub integrated_lufs: f64,
+    /// True peak (dBTP)
+    pub true_peak_dbtp: f64,
+    /// Loudness range (LU)
+    pub loudness_range: f64,
+}
+
+impl LoudnessAnalysis {
+    /// Ganancia (dB) para llevar la pista al loudness objetivo
+    ///
+    /// La ganancia positiva se limita para que el true peak no supere `ceiling_dbtp`;
+    /// la atenuación nunca se limita.
+    pub fn gain_to_target(&self, target_lufs: f64, ceiling_dbtp: f64) -> f64 {
+        let gain = target_lufs - self.integrated_lufs;
+        if gain > 0.0 {
+            gain.min((ceiling_dbtp - self.true_peak_dbtp).max(0.0))
+        } else {
+            gain
+        }
+    }
+}
+
+/// Analizador de loudness según ITU-R BS.1770-4 / EBU R128
+pub struct LoudnessAnalyzer;
+
+impl LoudnessAnalyzer {
+    /// Analiza una pista y devuelve integrated loudness, true peak y loudness range
+    ///
+    /// # Arguments
+    /// * `path` - Ruta al archivo de audio
+    ///
+    /// # Errors
+    /// Retorna AudioError si:
+    /// - El archivo no existe o no se puede decodificar
+    /// - El audio es más corto que un bloque de medida (400ms)
+    /// - Todo el audio queda por debajo del gate absoluto (silencio)
+    pub fn analyze(path: &Path) -> Result<LoudnessAnalysis, AudioError> {
+        let decoded = AudioDecoder::decode_samples(path)?;
+        Self::analyze_samples(
+            &decoded.samples,
+            decoded.sample_rate,
+            decoded.channels as usize,
+        )
+    }
+
+    /// Analiza samples interleaved ya decodificados
+    pub fn analyze_samples(
+        samples: &[f32],
+        sample_rate: u32,
+        channels: usize,
+    ) -> Result<LoudnessAnalysis, AudioError> {
+        let channels = channels.max(1);
+        let sub_blocks = Self::sub_block_energies(samples, sample_rate, channels);
+
+        if sub_blocks.len() < MOMENTARY_SUB_BLOCKS {
+            return Err(AudioError::AnalysisError(
+                "Audio demasiado corto para análisis de loudness".into(),
+            ));
+        }
+
+        let momentary = Self::block_energies(&sub_blocks, MOMENTARY_SUB_BLOCKS);
+        let integrated_lufs = Self::gated_loudness(&momentary, RELATIVE_GATE).ok_or_else(|| {
+            AudioError::AnalysisError("Audio sin contenido audible (silencio)".into())
+        })?;
+
+        let short_term = Self::block_energies(&sub_blocks, SHORT_TERM_SUB_BLOCKS);
+        let loudness_range = Self::loudness_range(&short_term);
+
+        let true_peak_dbtp = Self::linear_to_db(Self::true_peak(samples, channels));
+
+        Ok(LoudnessAnalysis {
+            integrated_lufs,
+            true_peak_dbtp,
+            loudness_range,
+        })
+    }
+
+    /// Energía media K-weighted (suma de canales) de cada sub-bloque de 100ms
+    ///
+    /// AIDEV-NOTE: Todos los canales pesan 1.0 (mono/estéreo). Los pesos de surround
+    /// de BS.1770 (1.41) no aplican a la biblioteca de un DJ.
+    fn sub_block_energies(samples: &[f32], sample_rate: u32, channels: usize) -> Vec<f64> {
+        let sub_block_len = (sample_rate * SUB_BLOCK_MS / 1000).max(1) as usize;
+        let mut filters: Vec<KWeighting> = (0..channels)
+            .map(|_| KWeighting::new(sample_rate))
+            .collect();
+
+        let mut energies = Vec::with_capacity(samples.len() / channels / sub_block_len + 1);
+        let mut acc = 0.0f64;
+        let mut count = 0;
+
+        for frame in samples.chunks_exact(channels) {
+            for (sample, filter) in frame.iter().zip(filters.iter_mut()) {
+                let weighted = filter.process(*sample as f64);
+                acc += weighted * weighted;
+            }
+            count += 1;
+
+            if count == sub_block_len {
+                energies.push(acc / sub_block_len as f64);
+                acc = 0.0;
+                count = 0;
+            }
+        }
+
+        energies
+    }
+
+    /// Energía media de bloques de `len` sub-bloques con salto de un sub-bloque
+    fn block_energies(sub_blocks: &[f64], len: usize) -> Vec<f64> {
+        sub_blocks
+            .windows(len)
+            .map(|window| window.iter().sum::<f64>() / len as f64)
+            .collect()
+    }
+
+    /// Loudness con gate absoluto + relativo (BS.1770-4)
+    ///
+    /// Retorna `None` si ningún bloque supera el gate absoluto.
+    fn gated_loudness(blocks: &[f64], relative_gate: f64) -> Option<f64> {
+        let above_absolute: Vec<f64> = blocks
+            .iter()
+            .copied()
+            .filter(|&e| Self::energy_to_lufs(e) > ABSOLUTE_GATE)
+            .collect();
+        if above_absolute.is_empty() {
+            return None;
+        }
+
+        let mean = above_absolute.iter().sum::<f64>() / above_absolute.len() as f64;
+        let threshold = Self::energy_to_lufs(mean) + relative_gate;
+
+        let gated: Vec<f64> = above_absolute
+            .into_iter()
+            .filter(|&e| Self::energy_to_lufs(e) > threshold)
+            .collect();
+        if gated.is_empty() {
+            return Some(Self::energy_to_lufs(mean));
+        }
+
+        Some(Self::energy_to_lufs(
+            gated.iter().sum::<f64>() / gated.len() as f64,
+        ))
+    }
+
+    /// Loudness range (EBU Tech 3342): percentil 95 - percentil 10 del short-term
+    fn loudness_range(short_term: &[f64]) -> f64 {
+        let above_absolute: Vec<f64> = short_term
+            .iter()
+            .copied()
+            .filter(|&e| Self::energy_to_lufs(e) > ABSOLUTE_GATE)
+            .collect();
+        if above_absolute.is_empty() {
+            return 0.0;
+        }
+
+        let mean = above_absolute.iter().sum::<f64>() / above_absolute.len() as f64;
+        let threshold = Self::energy_to_lufs(mean) + LRA_RELATIVE_GATE;
+
+        let mut loudness: Vec<f64> = above_absolute
+            .into_iter()
+            .map(Self::energy_to_lufs)
+            .filter(|&l| l > threshold)
+            .collect();
+        if loudness.len() < 2 {
+            return 0.0;
+        }
+        loudness.sort_by(|a, b| a.total_cmp(b));
+
+        let percentile = |p: f64| {
+            let idx = ((loudness.len() - 1) as f64 * p).round() as usize;
+            loudness[idx]
+        };
+        (percentile(0.95) - percentile(0.10)).max(0.0)
+    }
+
+    /// True peak lineal: máximo absoluto de la señal sobremuestreada x4
+    ///
+    /// AIDEV-NOTE: Interpolador polifásico (sinc con ventana de Hann, 48 taps),
+    /// suficiente para detectar los picos inter-sample que recortarían al convertir.
+    fn true_peak(samples: &[f32], channels: usize) -> f64 {
+        let phases = Self::interpolation_phases();
+        let mut peak = 0.0f64;
+
+        for channel in 0..channels {
+            let mut history = [0.0f64; TAPS_PER_PHASE];
+            let mut pos = 0;
+
+            for frame in samples.chunks_exact(channels) {
+                let sample = frame[channel] as f64;
+                peak = peak.max(sample.abs());
+
+                history[pos] = sample;
+                pos = (pos + 1) % TAPS_PER_PHASE;
+
+                for phase in &phases {
+                    let mut acc = 0.0;
+                    for (k, coeff) in phase.iter().enumerate() {
+                        // history más reciente primero
+                        acc += coeff * history[(pos + TAPS_PER_PHASE - 1 - k) % TAPS_PER_PHASE];
+                    }
+                    peak = peak.max(acc.abs());
+                }
+            }
+        }
+
+        peak
+    }
+
+    /// Coeficientes del interpolador separados por fase
+    fn interpolation_phases() -> Vec<[f64; TAPS_PER_PHASE]> {
+        let len = OVERSAMPLING * TAPS_PER_PHASE;
+        let center = (len - 1) as f64 / 2.0;
+
+        (0..OVERSAMPLING)
+            .map(|phase| {
+                let mut coeffs = [0.0; TAPS_PER_PHASE];
+                for (k, coeff) in coeffs.iter_mut().enumerate() {
+                    let n = (k * OVERSAMPLING + phase) as f64;
+                    let x = (n - center) / OVERSAMPLING as f64;
+                    let sinc = if x.abs() < 1e-12 {
+                        1.0
+                    } else {
+                        (std::f64::consts::PI * x).sin() / (std::f64::consts::PI * x)
+                    };
+                    let window =
+                        0.5 - 0.5 * (2.0 * std::f64::consts::PI * (n + 0.5) / len as f64).cos();
+                    *coeff = sinc * window;
+                }
+                coeffs
+            })
+            .collect()
+    }
+
+    /// Energía media → LUFS
+    fn energy_to_lufs(energy: f64) -> f64 {
+        if energy <= 0.0 {
+            f64::NEG_INFINITY
+        } else {
+            -0.691 + 10.0 * energy.log10()
+        }
+    }
+
+    /// Amplitud lineal → dB (mínimo -200 dB para silencio)
+    fn linear_to_db(value: f64) -> f64 {
+        if value <= 1e-10 {
+            -200.0
+        } else {
+            20.0 * value.log10()
+        }
+    }
+}
+
+/// Biquad en forma directa I
+#[derive(Debug, Clone, Copy)]
+struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+    x1: f64,
+    x2: f64,
+    y1: f64,
+    y2: f64,
+}
+
+impl Biquad {
+    fn new(b0: f64, b1: f64, b2: f64, a1: f64, a2: f64) -> Self {
+        Self {
+            b0,
+            b1,
+            b2,
+            a1,
+            a2,
+            x1: 0.0,
+            x2: 0.0,
+            y1: 0.0,
+            y2: 0.0,
+        }
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b0 * x + self.b1 * self.x1 + self.b2 * self.x2
+            - self.a1 * self.y1
+            - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
+
+/// Filtro K-weighting de BS.1770: high-shelf (+4 dB) seguido de high-pass (RLB)
+///
+/// AIDEV-NOTE: Coeficientes calculados para cualquier sample rate a partir de los
+/// parámetros analógicos del estándar; a 48kHz coinciden con la tabla de BS.1770.
+struct KWeighting {
+    shelf: Biquad,
+    highpass: Biquad,
+}
+
+impl KWeighting {
+    fn new(sample_rate: u32) -> Self {
+        let fs = sample_rate as f64;
+
+        // Etapa 1: high-shelf
+        let f0 = 1681.974450955533;
+        let gain_db = 3.999843853973347;
+        let q = 0.7071752369554196;
+        let k = (std::f64::consts::PI * f0 / fs).tan();
+        let vh = 10f64.powf(gain_db / 20.0);
+        let vb = vh.powf(0.4996667741545416);
+        let a0 = 1.0 + k / q + k * k;
+        let shelf = Biquad::new(
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+            2.0 * (k * k - 1.0) / a0,
+            (1.0 - k / q + k * k) / a0,
+        );
+
+        // Etapa 2: high-pass
+        let f0 = 38.13547087602444;
+        let q = 0.5003270373238773;
+        let k = (std::f64::consts::PI * f0 / fs).tan();
+        let a0 = 1.0 + k / q + k * k;
+        let highpass = Biquad::new(
+            1.0,
+            -2.0,
+            1.0,
+            2.0 * (k * k - 1.0) / a0,
+            (1.0 - k / q + k * k) / a0,
+        );
+
+        Self { shelf, highpass }
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        self.highpass.process(self.shelf.process(x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Seno estéreo (mismo contenido en ambos canales) con amplitud en dBFS
+    fn stereo_sine(sample_rate: u32, freq: f64, amplitude_db: f64, seconds: f64) -> Vec<f32> {
+        let amplitude = 10f64.powf(amplitude_db / 20.0);
+        let frames = (sample_rate as f64 * seconds) as usize;
+        (0..frames)
+            .flat_map(|i| {
+                let s = (amplitude
+                    * (2.0 * std::f64::consts::PI * freq * i as f64 / sample_rate as f64).sin())
+                    as f32;
+                [s, s]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ebu_reference_sine() {
+        // EBU Tech 3341: seno 1kHz a -23 dBFS en ambos canales = -23.0 LUFS
+        let samples = stereo_sine(48000, 1000.0, -23.0, 20.0);
+        let analysis = LoudnessAnalyzer::analyze_samples(&samples, 48000, 2).unwrap();
+
+        assert!(
+            (analysis.integrated_lufs - -23.0).abs() < 0.1,
+            "integrated {}",
+            analysis.integrated_lufs
+        );
+        assert!(analysis.loudness_range < 0.5);
+        assert!((analysis.true_peak_dbtp - -23.0).abs() < 0.3);
+    }
+
+    #[test]
+    fn test_sample_rate_independent() {
+        let samples = stereo_sine(44100, 1000.0, -18.0, 10.0);
+        let analysis = LoudnessAnalyzer::analyze_samples(&samples, 44100, 2).unwrap();
+
+        assert!((analysis.integrated_lufs - -18.0).abs() < 0.15);
+    }
+
+    #[test]
+    fn test_true_peak_detects_inter_sample_peaks() {
+        // Seno a fs/4 desfasado 45°: los samples caen a ±0.707 del pico real
+        let sample_rate = 48000;
+        let samples: Vec<f32> = (0..sample_rate)
+            .map(|i| {
+                (std::f64::consts::FRAC_PI_2 * i as f64 + std::f64::consts::FRAC_PI_4).sin() as f32
+            })
+            .collect();
+        let analysis = LoudnessAnalyzer::analyze_samples(&samples, sample_rate, 1).unwrap();
+
+        let sample_peak_db = 20.0 * std::f64::consts::FRAC_1_SQRT_2.log10();
+        assert!(analysis.true_peak_dbtp > sample_peak_db + 2.0);
+        assert!(analysis.true_peak_dbtp.abs() < 0.5);
+    }
+
+    #[test]
+    fn test_loudness_range_of_two_levels() {
+        let mut samples = stereo_sine(48000, 1000.0, -30.0, 20.0);
+        samples.extend(stereo_sine(48000, 1000.0, -20.0, 20.0));
+        let analysis = LoudnessAnalyzer::analyze_samples(&samples, 48000, 2).unwrap();
+
+        assert!(
+            (analysis.loudness_range - 10.0).abs() < 1.0,
+            "LRA {}",
+            analysis.loudness_range
+        );
+    }
+
+    #[test]
+    fn test_silence_is_error() {
+        let samples = vec![0.0f32; 48000 * 2];
+        let result = LoudnessAnalyzer::analyze_samples(&samples, 48000, 2);
+
+        match result {
+            Err(AudioError::AnalysisError(msg)) => assert!(msg.contains("silencio")),
+            _ => panic!("Expected AnalysisError"),
+        }
+    }
+
+    #[test]
+    fn test_too_short_is_error() {
+        let samples = vec![0.1f32; 1000];
+        assert!(LoudnessAnalyzer::analyze_samples(&samples, 48000, 2).is_err());
+    }
+
+    #[test]
+    fn test_gain_to_target() {
+        let analysis = LoudnessAnalysis {
+            integrated_lufs: -20.0,
+            true_peak_dbtp: -3.0,
+            loudness_range: 5.0,
+        };
+        // +6 dB pedidos, pero solo hay 2 dB hasta el techo de -1 dBTP
+        assert!((analysis.gain_to_target(-14.0, -1.0) - 2.0).abs() < 1e-9);
+
+        let loud = LoudnessAnalysis {
+            integrated_lufs: -8.0,
+            true_peak_dbtp: 0.5,
+            loudness_range: 4.0,
+        };
+        assert!((loud.gain_to_target(-14.0, -1.0) - -6.0).abs() < 1e-9);
+    }
+}
@@ -1,35 +1,66 @@
+pub mod beatgrid;
 pub mod beatgrid_detector;
 /// Módulo de audio para Symphony
 ///
 /// AIDEV-NOTE: Arquitectura estilo Musicat - Symphonia (decode) + cpal (output) + rb (ring buffer)
 /// - constants: Configuración del sistema de audio
-/// - output: Salida de audio con ring buffer (cpal + rb)
+/// - output: Salida de audio con ring buffer (cpal + rb, o null/WAV sin tarjeta)
 /// - player: Decode thread y control de reproducción
 /// - decoder: Decodificación de archivos (para análisis)
 /// - waveform: Generación de waveforms
+/// - beatgrid: Anclas de tempo y conversión tiempo ↔ beat (grids dinámicos)
 /// - beatgrid_detector: Detección de BPM y beatgrid
-/// - dsp: Procesamiento de señales digitales (DSP)
+/// - phrase_detector: Downbeats, compases y frases (intro/verse/chorus/bridge/outro)
+/// - cue_detector: Cue points automáticos (primer sonido, secciones, breakdowns, drops, outro)
+/// - key: Modelo de tonalidad y notaciones (musical, Camelot, Open Key)
+/// - key_detector: Detección de tonalidad (chromagram + perfiles de Krumhansl)
+/// - loudness_analyzer: Loudness EBU R128 (integrated, true peak, LRA)
+/// - dsp: Peaks de waveform y cadena DSP en tiempo real (preamp, EQ paramétrico, limitador)
 /// - resampler: Conversión de sample rate (rubato)
+/// - timestretch: Cambio de tempo sin cambiar el tono (WSOLA)
 pub mod constants;
+pub mod cue_detector;
 pub mod decoder;
 pub mod dsp;
 mod error;
+pub mod key;
+pub mod key_detector;
+pub mod loudness_analyzer;
 pub mod output;
+pub mod phrase_detector;
 pub mod player;
 pub mod resampler;
+pub mod timestretch;
 pub mod waveform;
 
-pub use beatgrid_detector::{BeatgridAnalysis, BeatgridDetector};
+pub use beatgrid::{BeatgridAnchor, TempoMap};
+pub use beatgrid_detector::{BeatgridAnalysis, BeatgridDetector, BpmRange};
 pub use constants::*;
+pub use cue_detector::{AutoCue, AutoCueDetector, AutoCueKind};
 pub use decoder::{AudioDecoder, AudioMetadata, DecodedAudio};
-pub use dsp::{calculate_peak_value, normalize_peaks, PeakMethod};
+pub use dsp::{
+    calculate_peak_value, normalize_peaks, DspChain, DspSettings, EqBand, EqPreset, PeakMethod,
+};
 pub use error::{AudioError, AudioResult};
-pub use output::{AudioDeviceInfo, AudioOutput, CpalAudioOutput};
+pub use key::{normalize_key, KeyMode, KeyNotation, MusicalKey};
+pub use key_detector::{KeyAnalysis, KeyDetector};
+pub use loudness_analyzer::{LoudnessAnalysis, LoudnessAnalyzer};
+pub use output::{
+    device_from_setting, list_output_devices, open_output, AudioDeviceInfo, AudioOutput,
+    CpalAudioOutput, DeviceMonitor, FileAudioOutput, LatencyProfile, NullAudioOutput,
+    OutputSettings, DEFAULT_DEVICE,
+};
+pub use phrase_detector::{PhraseAnalysis, PhraseDetector, PhraseKind, PhraseSection};
 pub use player::{
-    AudioPlayer, ErrorPayload, PlaybackState, PlayerControlEvent, StatePayload, TimestampPayload,
+    AudioPlayer, CrossfadeCurve, CrossfadeSettings, CueTrigger, ErrorPayload, LoopRegion,
+    NormalizationSettings, PlayThreshold, PlaybackQueue, PlaybackState, PlayerControlEvent,
+    PlayerRole, PositionHandle, QueueSnapshot, RepeatMode, StatePayload, TempoRange,
+    TempoSettings, TimestampPayload,
 };
 pub use resampler::AudioResampler;
+pub use timestretch::TimeStretcher;
 pub use waveform::{
-    cancel_waveform_generation, generate_waveform_streaming, WaveformCompletePayload, WaveformData,
-    WaveformErrorPayload, WaveformProgressPayload, WaveformState,
+    cancel_waveform_generation, generate_waveform_streaming, load_pyramid, WaveformCompletePayload,
+    WaveformData, WaveformErrorPayload, WaveformMode, WaveformProgressPayload, WaveformRange,
+    WaveformState,
 };
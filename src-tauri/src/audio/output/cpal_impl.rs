@@ -4,21 +4,24 @@
  * Define la interfaz para salidas de audio y su implementación CPAL.
  */
 
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{Stream, StreamConfig};
+use cpal::{BufferSize, Stream, StreamConfig, SupportedBufferSize};
 use rb::{Consumer, Producer, RbConsumer, SpscRb, RB};
 
-use super::super::constants::{PAUSE_VALUE, PLAY_VALUE, RING_BUFFER_SIZE};
+use super::super::constants::{FLUSH_TIMEOUT_MS, PAUSE_VALUE, PLAY_VALUE};
 use super::super::error::{AudioError, AudioResult};
 #[cfg(test)]
 use super::super::DEFAULT_VOLUME;
 use super::device::{find_device_by_name, get_best_config};
+use super::settings::OutputSettings;
 
 /// Información sobre un dispositivo de audio
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
 pub struct AudioDeviceInfo {
     pub name: String,
     pub is_default: bool,
@@ -49,6 +52,35 @@ pub trait AudioOutput: Send {
 
     /// Obtiene el volumen actual
     fn get_volume(&self) -> f64;
+
+    /// Descarta los samples pendientes en el ring buffer
+    ///
+    /// AIDEV-NOTE: Permite reutilizar el output al cambiar de track sin que
+    /// suene la cola del track anterior. Bloquea hasta que el callback confirma
+    /// el vaciado, para no descartar samples escritos después de la llamada.
+    fn flush(&self);
+
+    /// Samples retirados del ring buffer por el callback (sonados o descartados por flush)
+    fn consumed_samples(&self) -> u64;
+
+    /// Samples ya retirados que todavía no se oyen (buffer y latencia del dispositivo)
+    fn latency_samples(&self) -> u64;
+
+    /// Capacidad del ring buffer (en samples interleaved)
+    fn ring_buffer_size(&self) -> usize;
+
+    /// Frames por callback del dispositivo (`None` = el que elija el driver)
+    fn buffer_frames(&self) -> Option<u32>;
+}
+
+/// Progreso del callback, para calcular la posición audible
+///
+/// AIDEV-NOTE: El decode thread solo sabe lo que ha escrito; con esto sabe
+/// cuánto de ello ha llegado al dispositivo (ver player::clock).
+#[derive(Debug, Default)]
+pub(super) struct OutputProgress {
+    pub(super) consumed: AtomicU64,
+    pub(super) latency: AtomicU64,
 }
 
 /// Implementación de salida de audio usando cpal
@@ -65,6 +97,12 @@ pub struct CpalAudioOutput {
     pause_state: Arc<AtomicU32>,
     /// Volumen actual (compartido con el callback)
     volume: Arc<AtomicU32>,
+    /// Petición de vaciado del ring buffer (la atiende el callback)
+    flush_pending: Arc<AtomicBool>,
+    /// Samples retirados por el callback y latencia del dispositivo
+    progress: Arc<OutputProgress>,
+    ring_buffer_size: usize,
+    buffer_frames: Option<u32>,
 }
 
 // SAFETY: CpalAudioOutput es Send porque todos sus campos son Send o están
@@ -90,6 +128,26 @@ impl CpalAudioOutput {
         desired_sample_rate: Option<u32>,
         desired_channels: Option<u16>,
         initial_volume: f64,
+    ) -> AudioResult<Self> {
+        Self::with_settings(
+            device_name,
+            desired_sample_rate,
+            desired_channels,
+            initial_volume,
+            &OutputSettings::default(),
+        )
+    }
+
+    /// Crea la salida con el buffer y perfil de latencia de `settings`
+    ///
+    /// AIDEV-NOTE: `buffer_size` se pide como `BufferSize::Fixed` dentro del rango
+    /// que admite el dispositivo; si el driver no informa del rango se deja el suyo.
+    pub fn with_settings(
+        device_name: Option<&str>,
+        desired_sample_rate: Option<u32>,
+        desired_channels: Option<u16>,
+        initial_volume: f64,
+        settings: &OutputSettings,
     ) -> AudioResult<Self> {
         let host = cpal::default_host();
 
@@ -125,8 +183,22 @@ impl CpalAudioOutput {
             log::info!("📊 Config: {} Hz, {} canales", sample_rate, channels);
         }
 
+        // Periodo del callback dentro del rango del dispositivo
+        let buffer_frames = match supported_config.buffer_size() {
+            SupportedBufferSize::Range { min, max } => {
+                Some(settings.buffer_size.clamp(*min, (*max).max(*min)))
+            }
+            SupportedBufferSize::Unknown => None,
+        };
+        log::info!(
+            "🎚️ Buffer: {:?} frames, perfil {:?}",
+            buffer_frames,
+            settings.profile
+        );
+
         // Crear ring buffer
-        let ring_buffer = SpscRb::<f32>::new(RING_BUFFER_SIZE);
+        let ring_buffer_size = settings.ring_buffer_size(channels);
+        let ring_buffer = SpscRb::<f32>::new(ring_buffer_size);
         let (producer, consumer) = (ring_buffer.producer(), ring_buffer.consumer());
 
         // Estado de pausa compartido
@@ -138,22 +210,46 @@ impl CpalAudioOutput {
         let volume = Arc::new(AtomicU32::new(volume_bits));
         let volume_callback = Arc::clone(&volume);
 
+        // Flag de vaciado (el consumer solo puede usarse desde el callback)
+        let flush_pending = Arc::new(AtomicBool::new(false));
+        let flush_callback = Arc::clone(&flush_pending);
+
+        let progress = Arc::new(OutputProgress::default());
+        let progress_callback = Arc::clone(&progress);
+
         // Configuración del stream
-        let config: StreamConfig = supported_config.into();
+        let mut config: StreamConfig = supported_config.into();
+        if let Some(frames) = buffer_frames {
+            config.buffer_size = BufferSize::Fixed(frames);
+        }
         let channels_count = channels as usize;
 
         // Crear stream con callback
         let stream = device
             .build_output_stream(
                 &config,
-                move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+                move |data: &mut [f32], info: &cpal::OutputCallbackInfo| {
                     audio_callback(
                         data,
                         &consumer,
                         &pause_state_callback,
                         &volume_callback,
+                        &flush_callback,
+                        &progress_callback,
                         channels_count,
                     );
+
+                    // Lo recién entregado más la latencia del dispositivo aún no suena
+                    let timestamp = info.timestamp();
+                    let device_latency = timestamp
+                        .playback
+                        .duration_since(&timestamp.callback)
+                        .unwrap_or_default();
+                    let latency_frames = (device_latency.as_secs_f64() * sample_rate as f64) as u64;
+                    progress_callback.latency.store(
+                        data.len() as u64 + latency_frames * channels_count as u64,
+                        Ordering::SeqCst,
+                    );
                 },
                 move |err| {
                     log::error!("❌ Error en stream de audio: {}", err);
@@ -169,6 +265,10 @@ impl CpalAudioOutput {
             channels,
             pause_state,
             volume,
+            flush_pending,
+            progress,
+            ring_buffer_size,
+            buffer_frames,
         })
     }
 
@@ -232,6 +332,33 @@ impl AudioOutput for CpalAudioOutput {
     fn get_volume(&self) -> f64 {
         f32::from_bits(self.volume.load(Ordering::SeqCst)) as f64
     }
+
+    fn flush(&self) {
+        self.flush_pending.store(true, Ordering::SeqCst);
+        // Despertar el callback si está bloqueado en pausa
+        atomic_wait::wake_all(self.pause_state.as_ref());
+
+        let deadline = Instant::now() + Duration::from_millis(FLUSH_TIMEOUT_MS);
+        while self.flush_pending.load(Ordering::SeqCst) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    fn consumed_samples(&self) -> u64 {
+        self.progress.consumed.load(Ordering::SeqCst)
+    }
+
+    fn latency_samples(&self) -> u64 {
+        self.progress.latency.load(Ordering::SeqCst)
+    }
+
+    fn ring_buffer_size(&self) -> usize {
+        self.ring_buffer_size
+    }
+
+    fn buffer_frames(&self) -> Option<u32> {
+        self.buffer_frames
+    }
 }
 
 /// Callback de audio que lee del ring buffer y escribe al dispositivo
@@ -243,8 +370,20 @@ fn audio_callback(
     consumer: &Consumer<f32>,
     pause_state: &AtomicU32,
     volume: &AtomicU32,
+    flush_pending: &AtomicBool,
+    progress: &OutputProgress,
     _channels: usize,
 ) {
+    // Vaciar samples del track anterior si se pidió
+    // (contarlos antes de confirmar: flush() vuelve en cuanto se baja el flag)
+    if flush_pending.load(Ordering::SeqCst) {
+        let skipped = consumer.skip_pending().unwrap_or(0);
+        progress
+            .consumed
+            .fetch_add(skipped as u64, Ordering::SeqCst);
+        flush_pending.store(false, Ordering::SeqCst);
+    }
+
     // Verificar si estamos en pausa
     if pause_state.load(Ordering::SeqCst) == PAUSE_VALUE {
         // En pausa: llenar con silencio y esperar
@@ -258,6 +397,7 @@ fn audio_callback(
 
     // Leer del ring buffer
     let read = consumer.read(data).unwrap_or(0);
+    progress.consumed.fetch_add(read as u64, Ordering::SeqCst);
 
     // Aplicar volumen a los samples leídos
     for sample in &mut data[..read] {
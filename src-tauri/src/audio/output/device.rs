@@ -1,14 +1,25 @@
 /**
  * Gestión de dispositivos de audio
  * 
- * Funciones para buscar y configurar dispositivos de audio usando CPAL.
+ * Funciones para buscar y configurar dispositivos de audio usando CPAL y
+ * monitor de conexión/desconexión con fallback al dispositivo por defecto.
  */
 
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
 use cpal::traits::{DeviceTrait, HostTrait};
 use cpal::{Device, Host, SupportedStreamConfig};
 
-use super::super::constants::DEFAULT_SAMPLE_RATE;
+use super::super::constants::{DEFAULT_SAMPLE_RATE, DEVICE_POLL_INTERVAL_MS};
 use super::super::error::{AudioError, AudioResult};
+use super::cpal_impl::AudioDeviceInfo;
+use super::headless::HeadlessDevice;
+
+/// Valor de `AudioConfig.output_device` que significa "dispositivo del sistema"
+pub const DEFAULT_DEVICE: &str = "default";
 
 /// Encuentra un dispositivo por nombre
 pub(super) fn find_device_by_name(host: &Host, name: &str) -> AudioResult<Device> {
@@ -110,3 +121,186 @@ pub(super) fn get_best_config(
         "No se encontró configuración de audio válida".to_string(),
     ))
 }
+
+/// Dispositivo preferido según el valor guardado en `AudioConfig.output_device`
+///
+/// `None` (o `"default"`) significa el dispositivo por defecto del sistema.
+pub fn device_from_setting(setting: &str) -> Option<String> {
+    let name = setting.trim();
+    (!name.is_empty() && name != DEFAULT_DEVICE).then(|| name.to_string())
+}
+
+/// Decide en qué dispositivo sonar según el preferido y los conectados
+///
+/// AIDEV-NOTE: Si el preferido no está (interfaz USB desconectada) se usa el
+/// dispositivo por defecto (`None`); cuando vuelve a aparecer se vuelve a él.
+/// Los pseudo-dispositivos (null/file) siempre están disponibles.
+pub fn resolve_output_device(
+    preferred: Option<&str>,
+    available: &[AudioDeviceInfo],
+) -> Option<String> {
+    let name = preferred?;
+    let present = HeadlessDevice::parse(name).is_some() || available.iter().any(|d| d.name == name);
+    present.then(|| name.to_string())
+}
+
+/// Thread que vigila los dispositivos de salida conectados
+///
+/// AIDEV-NOTE: Sondea la lista de dispositivos y llama a `on_change` solo cuando
+/// cambia (incluido un cambio del dispositivo por defecto). El primer sondeo
+/// solo toma la referencia. Se detiene al hacer drop.
+pub struct DeviceMonitor {
+    running: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl DeviceMonitor {
+    /// Arranca el monitor sobre los dispositivos reales (`list_output_devices`)
+    pub fn spawn<F>(on_change: F) -> AudioResult<Self>
+    where
+        F: FnMut(&[AudioDeviceInfo]) + Send + 'static,
+    {
+        Self::with_source(
+            Duration::from_millis(DEVICE_POLL_INTERVAL_MS),
+            super::list_output_devices,
+            on_change,
+        )
+    }
+
+    /// Arranca el monitor con una fuente de dispositivos cualquiera
+    pub fn with_source<L, F>(interval: Duration, mut list: L, mut on_change: F) -> AudioResult<Self>
+    where
+        L: FnMut() -> AudioResult<Vec<AudioDeviceInfo>> + Send + 'static,
+        F: FnMut(&[AudioDeviceInfo]) + Send + 'static,
+    {
+        let running = Arc::new(AtomicBool::new(true));
+        let running_worker = Arc::clone(&running);
+
+        let worker = thread::Builder::new()
+            .name("device-monitor".to_string())
+            .spawn(move || {
+                let mut known: Option<Vec<AudioDeviceInfo>> = None;
+                while running_worker.load(Ordering::SeqCst) {
+                    match list() {
+                        Ok(devices) => {
+                            if known.as_ref().is_some_and(|known| *known != devices) {
+                                log::info!(
+                                    "🔌 Dispositivos de salida: {} disponibles",
+                                    devices.len()
+                                );
+                                on_change(&devices);
+                            }
+                            known = Some(devices);
+                        }
+                        Err(e) => log::warn!("⚠️ Error listando dispositivos: {}", e),
+                    }
+                    // stop() despierta el thread para no esperar el intervalo completo
+                    thread::park_timeout(interval);
+                }
+            })
+            .map_err(|e| {
+                AudioError::PlaybackFailed(format!("Error creando monitor de dispositivos: {}", e))
+            })?;
+
+        Ok(Self {
+            running,
+            worker: Some(worker),
+        })
+    }
+
+    /// Detiene el monitor y espera a su thread
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(worker) = self.worker.take() {
+            worker.thread().unpark();
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for DeviceMonitor {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::sync::Mutex;
+
+    fn device(name: &str, is_default: bool) -> AudioDeviceInfo {
+        AudioDeviceInfo {
+            name: name.to_string(),
+            is_default,
+        }
+    }
+
+    #[test]
+    fn test_device_from_setting() {
+        assert_eq!(device_from_setting("default"), None);
+        assert_eq!(device_from_setting(""), None);
+        assert_eq!(
+            device_from_setting("USB Audio"),
+            Some("USB Audio".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resolve_falls_back_and_returns() {
+        let with_usb = vec![device("Speakers", true), device("USB Audio", false)];
+        let without_usb = vec![device("Speakers", true)];
+
+        assert_eq!(
+            resolve_output_device(Some("USB Audio"), &with_usb),
+            Some("USB Audio".to_string())
+        );
+        assert_eq!(resolve_output_device(Some("USB Audio"), &without_usb), None);
+        assert_eq!(resolve_output_device(None, &with_usb), None);
+        // Los pseudo-dispositivos no dependen de la lista
+        assert_eq!(
+            resolve_output_device(Some("null"), &[]),
+            Some("null".to_string())
+        );
+    }
+
+    #[test]
+    fn test_monitor_reports_changes_only() {
+        // Secuencia: inicial, igual, USB desconectado, cambia el default, igual
+        let snapshots = Arc::new(Mutex::new(vec![
+            vec![device("Speakers", false), device("USB Audio", true)],
+            vec![device("Speakers", false), device("USB Audio", true)],
+            vec![device("Speakers", false)],
+            vec![device("Speakers", true)],
+            vec![device("Speakers", true)],
+        ]));
+        let source = Arc::clone(&snapshots);
+        let (tx, rx) = mpsc::channel();
+
+        let mut monitor = DeviceMonitor::with_source(
+            Duration::from_millis(1),
+            move || {
+                let mut snapshots = source.lock().unwrap();
+                let next = if snapshots.len() > 1 {
+                    snapshots.remove(0)
+                } else {
+                    snapshots[0].clone()
+                };
+                Ok(next)
+            },
+            move |devices| {
+                let _ = tx.send(devices.to_vec());
+            },
+        )
+        .unwrap();
+
+        let first = rx.recv_timeout(Duration::from_secs(2)).unwrap();
+        assert_eq!(first, vec![device("Speakers", false)]);
+        let second = rx.recv_timeout(Duration::from_secs(2)).unwrap();
+        assert_eq!(second, vec![device("Speakers", true)]);
+
+        monitor.stop();
+        assert!(rx.try_recv().is_err());
+    }
+}
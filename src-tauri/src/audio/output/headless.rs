@@ -0,0 +1,611 @@
+/**
+ * Salidas de audio sin tarjeta de sonido (pseudo-dispositivos)
+ *
+ * AIDEV-NOTE: Un thread propio hace de "callback": consume el ring buffer en
+ * bloques de `HEADLESS_BLOCK_MS`, igual que lo haría cpal, y entrega los samples
+ * (con el volumen aplicado) a un sink. Sirve para CI sin sonido, tests de punta a
+ * punta del decode thread y render offline a WAV.
+ *
+ * Nombres de dispositivo reconocidos:
+ * - `null`: descarta el audio en tiempo real
+ * - `null:fast`: descarta el audio tan rápido como se decodifica
+ * - `file:<ruta.wav>`: escribe el audio a un WAV en tiempo real
+ * - `file:fast:<ruta.wav>`: render offline a WAV
+ */
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use rb::{Consumer, Producer, RbConsumer, SpscRb, RB};
+
+use super::super::constants::{
+    DEFAULT_CHANNELS, DEFAULT_SAMPLE_RATE, FLUSH_TIMEOUT_MS, HEADLESS_BLOCK_MS,
+};
+use super::super::error::{AudioError, AudioResult};
+use super::settings::OutputSettings;
+use super::AudioOutput;
+
+/// Pseudo-dispositivo que descarta el audio en tiempo real
+pub const NULL_DEVICE: &str = "null";
+
+/// Pseudo-dispositivo que descarta el audio sin esperar al reloj
+pub const NULL_FAST_DEVICE: &str = "null:fast";
+
+/// Prefijo de los pseudo-dispositivos que escriben a WAV
+pub const FILE_DEVICE_PREFIX: &str = "file:";
+
+const FAST_PREFIX: &str = "fast:";
+
+/// Ritmo al que se consume el ring buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pacing {
+    /// Un bloque por tick, como una tarjeta de sonido
+    Realtime,
+    /// Todo lo que haya, en cuanto llega
+    Fast,
+}
+
+/// Pseudo-dispositivo elegido por nombre
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeadlessDevice {
+    Null { pacing: Pacing },
+    File { path: PathBuf, pacing: Pacing },
+}
+
+impl HeadlessDevice {
+    /// Interpreta un nombre de dispositivo; `None` si es un dispositivo real
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            NULL_DEVICE => Some(HeadlessDevice::Null {
+                pacing: Pacing::Realtime,
+            }),
+            NULL_FAST_DEVICE => Some(HeadlessDevice::Null {
+                pacing: Pacing::Fast,
+            }),
+            _ => {
+                let rest = name.strip_prefix(FILE_DEVICE_PREFIX)?;
+                let (pacing, path) = match rest.strip_prefix(FAST_PREFIX) {
+                    Some(path) => (Pacing::Fast, path),
+                    None => (Pacing::Realtime, rest),
+                };
+                (!path.is_empty()).then(|| HeadlessDevice::File {
+                    path: PathBuf::from(path),
+                    pacing,
+                })
+            }
+        }
+    }
+
+    /// Abre la salida correspondiente
+    pub fn open(
+        &self,
+        desired_sample_rate: Option<u32>,
+        desired_channels: Option<u16>,
+        initial_volume: f64,
+        settings: &OutputSettings,
+    ) -> AudioResult<Box<dyn AudioOutput>> {
+        Ok(match self {
+            HeadlessDevice::Null { pacing } => Box::new(NullAudioOutput::new(
+                desired_sample_rate,
+                desired_channels,
+                initial_volume,
+                *pacing,
+                settings,
+            )?),
+            HeadlessDevice::File { path, pacing } => Box::new(FileAudioOutput::new(
+                path,
+                desired_sample_rate,
+                desired_channels,
+                initial_volume,
+                *pacing,
+                settings,
+            )?),
+        })
+    }
+}
+
+/// Destino de los samples consumidos por una salida headless
+pub trait RenderSink: Send + 'static {
+    /// Recibe un bloque interleaved con el volumen ya aplicado
+    fn write(&mut self, samples: &[f32]);
+
+    /// Cierra el sink al detener la salida
+    fn finish(self);
+}
+
+/// Sink que descarta el audio
+pub struct NullSink;
+
+impl RenderSink for NullSink {
+    fn write(&mut self, _samples: &[f32]) {}
+
+    fn finish(self) {}
+}
+
+/// Sink que escribe un WAV float de 32 bits
+pub struct WavSink {
+    writer: Option<hound::WavWriter<BufWriter<File>>>,
+    path: PathBuf,
+    /// Samples escritos desde la última actualización de la cabecera
+    unflushed: usize,
+    /// Actualizar la cabecera cada segundo de audio: el WAV es legible mientras se escribe
+    flush_every: usize,
+}
+
+impl WavSink {
+    pub fn create(path: &Path, sample_rate: u32, channels: u16) -> AudioResult<Self> {
+        let spec = hound::WavSpec {
+            channels,
+            sample_rate,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let writer = hound::WavWriter::create(path, spec).map_err(|e| {
+            AudioError::IoError(format!("No se pudo crear {}: {}", path.display(), e))
+        })?;
+
+        Ok(Self {
+            writer: Some(writer),
+            path: path.to_path_buf(),
+            unflushed: 0,
+            flush_every: sample_rate as usize * channels as usize,
+        })
+    }
+}
+
+impl RenderSink for WavSink {
+    fn write(&mut self, samples: &[f32]) {
+        let Some(writer) = self.writer.as_mut() else {
+            return;
+        };
+
+        let result = samples
+            .iter()
+            .try_for_each(|sample| writer.write_sample(*sample))
+            .and_then(|_| {
+                self.unflushed += samples.len();
+                if self.unflushed >= self.flush_every {
+                    self.unflushed = 0;
+                    writer.flush()
+                } else {
+                    Ok(())
+                }
+            });
+
+        if let Err(e) = result {
+            // Un error de disco no debe tumbar el decode thread: dejar de escribir
+            log::error!("❌ Error escribiendo {}: {}", self.path.display(), e);
+            self.writer = None;
+        }
+    }
+
+    fn finish(self) {
+        if let Some(writer) = self.writer {
+            match writer.finalize() {
+                Ok(()) => log::info!("💾 Render guardado en {}", self.path.display()),
+                Err(e) => log::error!("❌ Error cerrando {}: {}", self.path.display(), e),
+            }
+        }
+    }
+}
+
+/// Estado compartido entre la salida y su thread de consumo
+struct Shared {
+    running: AtomicBool,
+    paused: AtomicBool,
+    volume: AtomicU32,
+    flush_pending: AtomicBool,
+    /// Samples entregados al sink
+    rendered: AtomicU64,
+    /// Samples retirados del ring buffer (entregados o descartados por flush)
+    consumed: AtomicU64,
+}
+
+/// Salida de audio sin dispositivo: un thread consume el ring buffer hacia un sink
+pub struct HeadlessAudioOutput<S: RenderSink> {
+    producer: Producer<f32>,
+    sample_rate: u32,
+    channels: u16,
+    shared: Arc<Shared>,
+    ring_buffer_size: usize,
+    worker: Option<JoinHandle<()>>,
+    _sink: std::marker::PhantomData<fn() -> S>,
+}
+
+/// Salida que descarta el audio (CI, tests)
+pub type NullAudioOutput = HeadlessAudioOutput<NullSink>;
+
+/// Salida que graba el audio a WAV (render offline)
+pub type FileAudioOutput = HeadlessAudioOutput<WavSink>;
+
+impl NullAudioOutput {
+    pub fn new(
+        desired_sample_rate: Option<u32>,
+        desired_channels: Option<u16>,
+        initial_volume: f64,
+        pacing: Pacing,
+        settings: &OutputSettings,
+    ) -> AudioResult<Self> {
+        log::info!("🔇 Usando salida null ({:?})", pacing);
+        let channels = desired_channels.unwrap_or(DEFAULT_CHANNELS);
+        Self::start(
+            NullSink,
+            desired_sample_rate.unwrap_or(DEFAULT_SAMPLE_RATE),
+            channels,
+            initial_volume,
+            pacing,
+            settings.ring_buffer_size(channels),
+        )
+    }
+}
+
+impl FileAudioOutput {
+    /// Crea (o sobrescribe) el WAV en `path`
+    pub fn new(
+        path: &Path,
+        desired_sample_rate: Option<u32>,
+        desired_channels: Option<u16>,
+        initial_volume: f64,
+        pacing: Pacing,
+        settings: &OutputSettings,
+    ) -> AudioResult<Self> {
+        let sample_rate = desired_sample_rate.unwrap_or(DEFAULT_SAMPLE_RATE);
+        let channels = desired_channels.unwrap_or(DEFAULT_CHANNELS);
+        log::info!(
+            "💾 Usando salida a archivo ({:?}): {}",
+            pacing,
+            path.display()
+        );
+
+        let sink = WavSink::create(path, sample_rate, channels)?;
+        Self::start(
+            sink,
+            sample_rate,
+            channels,
+            initial_volume,
+            pacing,
+            settings.ring_buffer_size(channels),
+        )
+    }
+}
+
+impl<S: RenderSink> HeadlessAudioOutput<S> {
+    fn start(
+        sink: S,
+        sample_rate: u32,
+        channels: u16,
+        initial_volume: f64,
+        pacing: Pacing,
+        ring_buffer_size: usize,
+    ) -> AudioResult<Self> {
+        let ring_buffer = SpscRb::<f32>::new(ring_buffer_size);
+        let (producer, consumer) = (ring_buffer.producer(), ring_buffer.consumer());
+
+        // Como un stream de cpal recién creado: no suena hasta `play()`
+        let shared = Arc::new(Shared {
+            running: AtomicBool::new(true),
+            paused: AtomicBool::new(true),
+            volume: AtomicU32::new((initial_volume.clamp(0.0, 1.0) as f32).to_bits()),
+            flush_pending: AtomicBool::new(false),
+            rendered: AtomicU64::new(0),
+            consumed: AtomicU64::new(0),
+        });
+
+        let worker_shared = Arc::clone(&shared);
+        let worker = thread::Builder::new()
+            .name("headless-output".to_string())
+            .spawn(move || {
+                render_loop(
+                    consumer,
+                    &worker_shared,
+                    sink,
+                    sample_rate,
+                    channels,
+                    pacing,
+                )
+            })
+            .map_err(|e| AudioError::PlaybackFailed(format!("No se pudo crear salida: {}", e)))?;
+
+        Ok(Self {
+            producer,
+            sample_rate,
+            channels,
+            shared,
+            ring_buffer_size,
+            worker: Some(worker),
+            _sink: std::marker::PhantomData,
+        })
+    }
+
+    /// Frames entregados al sink desde que se creó la salida
+    pub fn frames_rendered(&self) -> u64 {
+        self.shared.rendered.load(Ordering::SeqCst) / self.channels.max(1) as u64
+    }
+}
+
+impl<S: RenderSink> AudioOutput for HeadlessAudioOutput<S> {
+    fn get_producer(&self) -> &Producer<f32> {
+        &self.producer
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn play(&self) -> AudioResult<()> {
+        self.shared.paused.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn pause(&self) -> AudioResult<()> {
+        self.shared.paused.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        // Termina el thread y cierra el sink (el WAV queda finalizado)
+        self.shared.paused.store(true, Ordering::SeqCst);
+        self.shared.running.store(false, Ordering::SeqCst);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+
+    fn set_volume(&self, volume: f64) {
+        let clamped = volume.clamp(0.0, 1.0) as f32;
+        self.shared
+            .volume
+            .store(clamped.to_bits(), Ordering::SeqCst);
+    }
+
+    fn get_volume(&self) -> f64 {
+        f32::from_bits(self.shared.volume.load(Ordering::SeqCst)) as f64
+    }
+
+    fn flush(&self) {
+        self.shared.flush_pending.store(true, Ordering::SeqCst);
+
+        let deadline = Instant::now() + Duration::from_millis(FLUSH_TIMEOUT_MS);
+        while self.shared.flush_pending.load(Ordering::SeqCst) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    fn consumed_samples(&self) -> u64 {
+        self.shared.consumed.load(Ordering::SeqCst)
+    }
+
+    fn latency_samples(&self) -> u64 {
+        // Sin dispositivo: lo que sale del ring buffer "suena" al instante
+        0
+    }
+
+    fn ring_buffer_size(&self) -> usize {
+        self.ring_buffer_size
+    }
+
+    fn buffer_frames(&self) -> Option<u32> {
+        Some(block_frames(self.sample_rate) as u32)
+    }
+}
+
+impl<S: RenderSink> Drop for HeadlessAudioOutput<S> {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Frames por bloque de consumo (el "periodo" del pseudo-dispositivo)
+fn block_frames(sample_rate: u32) -> usize {
+    (sample_rate as u64 * HEADLESS_BLOCK_MS / 1000).max(1) as usize
+}
+
+/// Thread de consumo: hace el papel del callback de cpal
+fn render_loop<S: RenderSink>(
+    consumer: Consumer<f32>,
+    shared: &Shared,
+    mut sink: S,
+    sample_rate: u32,
+    channels: u16,
+    pacing: Pacing,
+) {
+    let mut block = vec![0.0f32; block_frames(sample_rate) * channels.max(1) as usize];
+    let tick = Duration::from_millis(HEADLESS_BLOCK_MS);
+    let mut next_tick = Instant::now();
+
+    while shared.running.load(Ordering::SeqCst) {
+        // El flush se atiende también en pausa (igual que en cpal)
+        if shared.flush_pending.load(Ordering::SeqCst) {
+            let skipped = consumer.skip_pending().unwrap_or(0);
+            shared.consumed.fetch_add(skipped as u64, Ordering::SeqCst);
+            shared.flush_pending.store(false, Ordering::SeqCst);
+        }
+
+        if shared.paused.load(Ordering::SeqCst) {
+            thread::sleep(tick);
+            next_tick = Instant::now();
+            continue;
+        }
+
+        let read = consumer.read(&mut block).unwrap_or(0);
+        if read > 0 {
+            let vol = f32::from_bits(shared.volume.load(Ordering::SeqCst));
+            block[..read].iter_mut().for_each(|sample| *sample *= vol);
+            sink.write(&block[..read]);
+            shared.rendered.fetch_add(read as u64, Ordering::SeqCst);
+            shared.consumed.fetch_add(read as u64, Ordering::SeqCst);
+        }
+
+        match pacing {
+            Pacing::Realtime => {
+                // Un bloque por tick aunque no haya samples (underrun = silencio)
+                next_tick += tick;
+                if let Some(wait) = next_tick.checked_duration_since(Instant::now()) {
+                    thread::sleep(wait);
+                }
+            }
+            Pacing::Fast => {
+                if read == 0 {
+                    thread::sleep(Duration::from_millis(1));
+                }
+            }
+        }
+    }
+
+    sink.finish();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rb::RbProducer;
+
+    fn write_all(output: &dyn AudioOutput, samples: &[f32]) {
+        let mut written = 0;
+        while written < samples.len() {
+            written += output
+                .get_producer()
+                .write(&samples[written..])
+                .unwrap_or(0);
+            thread::sleep(Duration::from_micros(100));
+        }
+    }
+
+    fn wait_for(mut done: impl FnMut() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !done() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_parse_devices() {
+        assert_eq!(
+            HeadlessDevice::parse("null"),
+            Some(HeadlessDevice::Null {
+                pacing: Pacing::Realtime
+            })
+        );
+        assert_eq!(
+            HeadlessDevice::parse("null:fast"),
+            Some(HeadlessDevice::Null {
+                pacing: Pacing::Fast
+            })
+        );
+        assert_eq!(
+            HeadlessDevice::parse("file:fast:/tmp/out.wav"),
+            Some(HeadlessDevice::File {
+                path: PathBuf::from("/tmp/out.wav"),
+                pacing: Pacing::Fast
+            })
+        );
+        assert_eq!(
+            HeadlessDevice::parse("file:/tmp/out.wav"),
+            Some(HeadlessDevice::File {
+                path: PathBuf::from("/tmp/out.wav"),
+                pacing: Pacing::Realtime
+            })
+        );
+        assert_eq!(HeadlessDevice::parse("file:"), None);
+        assert_eq!(HeadlessDevice::parse("Built-in Output"), None);
+    }
+
+    #[test]
+    fn test_null_output_waits_for_play() {
+        let output = NullAudioOutput::new(
+            Some(48000),
+            Some(2),
+            1.0,
+            Pacing::Fast,
+            &OutputSettings::default(),
+        )
+        .unwrap();
+        output.get_producer().write(&[0.5; 64]).unwrap();
+        thread::sleep(Duration::from_millis(30));
+        assert_eq!(output.frames_rendered(), 0);
+
+        output.play().unwrap();
+        wait_for(|| output.frames_rendered() == 32);
+        assert_eq!(output.frames_rendered(), 32);
+    }
+
+    #[test]
+    fn test_null_output_realtime_pacing() {
+        let output = NullAudioOutput::new(
+            Some(10000),
+            Some(1),
+            1.0,
+            Pacing::Realtime,
+            &OutputSettings::default(),
+        )
+        .unwrap();
+        output.play().unwrap();
+
+        // 0.1 s de audio no puede consumirse mucho antes de 0.1 s
+        let started = Instant::now();
+        write_all(&output, &[0.0; 1000]);
+        wait_for(|| output.frames_rendered() == 1000);
+        assert_eq!(output.frames_rendered(), 1000);
+        assert!(started.elapsed() >= Duration::from_millis(80));
+    }
+
+    #[test]
+    fn test_flush_while_paused() {
+        let output = NullAudioOutput::new(
+            Some(44100),
+            Some(2),
+            1.0,
+            Pacing::Fast,
+            &OutputSettings::default(),
+        )
+        .unwrap();
+        output.get_producer().write(&[0.5; 128]).unwrap();
+        output.flush();
+        // Los descartados cuentan como retirados para el reloj de posición
+        assert_eq!(output.consumed_samples(), 128);
+
+        output.play().unwrap();
+        thread::sleep(Duration::from_millis(30));
+        assert_eq!(output.frames_rendered(), 0);
+    }
+
+    #[test]
+    fn test_file_output_renders_wav() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("render.wav");
+
+        let mut output = FileAudioOutput::new(
+            &path,
+            Some(22050),
+            Some(2),
+            0.5,
+            Pacing::Fast,
+            &OutputSettings::default(),
+        )
+        .unwrap();
+        output.play().unwrap();
+        let samples: Vec<f32> = (0..4410).map(|i| (i % 100) as f32 / 100.0).collect();
+        write_all(&output, &samples);
+        wait_for(|| output.frames_rendered() == 2205);
+        output.stop();
+
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        let spec = reader.spec();
+        assert_eq!((spec.sample_rate, spec.channels), (22050, 2));
+        let rendered: Vec<f32> = reader.samples::<f32>().map(|s| s.unwrap()).collect();
+        assert_eq!(rendered.len(), samples.len());
+        // El volumen se aplica como en el callback de cpal
+        assert!(rendered
+            .iter()
+            .zip(&samples)
+            .all(|(out, input)| (out - input * 0.5).abs() < 1e-6));
+    }
+}
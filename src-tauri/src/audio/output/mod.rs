@@ -8,19 +8,71 @@
  * ## Estructura
  * 
  * - **cpal_impl.rs**: Trait AudioOutput e implementación CpalAudioOutput
- * - **device.rs**: Gestión de dispositivos de audio (búsqueda y configuración)
+ * - **device.rs**: Gestión de dispositivos de audio (búsqueda, configuración y hot-plug)
+ * - **headless.rs**: Pseudo-dispositivos sin tarjeta de sonido (null y WAV)
+ * - **settings.rs**: Buffer, sample rate y perfiles de latencia de la salida
  * 
  * ## Características
  * 
  * - Ring buffer SPSC para desacoplamiento de threads
- * - Soporte para sample rate y canales nativos del dispositivo
+ * - Soporte para sample rate y canales nativos del dispositivo (modo bit-perfect)
+ * - Tamaño de buffer configurable con perfiles de latencia (low/normal/safe)
  * - Control de volumen con atomic operations
  * - Pausa/resume con atomic-wait
- * - Listado de dispositivos disponibles
+ * - Listado de dispositivos disponibles y monitor de hot-plug
+ * - Salidas headless (`null`, `file:<ruta.wav>`) para CI y render offline
  */
 
 mod cpal_impl;
 mod device;
+mod headless;
+mod settings;
 
 // Re-exportar tipos públicos
 pub use cpal_impl::{AudioDeviceInfo, AudioOutput, CpalAudioOutput};
+pub use device::{device_from_setting, resolve_output_device, DeviceMonitor, DEFAULT_DEVICE};
+pub use headless::{
+    FileAudioOutput, HeadlessDevice, NullAudioOutput, Pacing, FILE_DEVICE_PREFIX, NULL_DEVICE,
+    NULL_FAST_DEVICE,
+};
+pub use settings::{LatencyProfile, OutputSettings};
+
+use super::error::AudioResult;
+
+/// Abre la salida para un nombre de dispositivo
+///
+/// AIDEV-NOTE: Los pseudo-dispositivos (`null`, `file:<ruta.wav>`) se eligen igual
+/// que una tarjeta real; cualquier otro nombre (o `None`) va a cpal.
+pub fn open_output(
+    device_name: Option<&str>,
+    desired_sample_rate: Option<u32>,
+    desired_channels: Option<u16>,
+    initial_volume: f64,
+    settings: &OutputSettings,
+) -> AudioResult<Box<dyn AudioOutput>> {
+    match device_name.and_then(HeadlessDevice::parse) {
+        Some(device) => device.open(
+            desired_sample_rate,
+            desired_channels,
+            initial_volume,
+            settings,
+        ),
+        None => Ok(Box::new(CpalAudioOutput::with_settings(
+            device_name,
+            desired_sample_rate,
+            desired_channels,
+            initial_volume,
+            settings,
+        )?)),
+    }
+}
+
+/// Dispositivos disponibles, incluida la salida null
+pub fn list_output_devices() -> AudioResult<Vec<AudioDeviceInfo>> {
+    let mut devices = CpalAudioOutput::list_devices()?;
+    devices.push(AudioDeviceInfo {
+        name: NULL_DEVICE.to_string(),
+        is_default: false,
+    });
+    Ok(devices)
+}
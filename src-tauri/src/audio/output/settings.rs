@@ -0,0 +1,164 @@
+/**
+ * Configuración de buffers y sample rate de la salida
+ *
+ * AIDEV-NOTE: `AudioConfig.buffer_size` es el periodo del callback del
+ * dispositivo (en frames) y el perfil de latencia decide cuántos periodos
+ * caben en el ring buffer. Con el perfil `Normal` y el buffer por defecto
+ * el ring buffer mide lo mismo que `RING_BUFFER_SIZE`.
+ */
+use serde::{Deserialize, Serialize};
+
+use super::super::constants::{BUFFER_SIZE, MAX_BUFFER_FRAMES, MIN_BUFFER_FRAMES};
+
+/// Perfil de latencia: margen de audio decodificado por delante del dispositivo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum LatencyProfile {
+    /// Respuesta inmediata (cue, scratch); más riesgo de cortes con CPU cargada
+    Low,
+    #[default]
+    Normal,
+    /// Máximo margen frente a cortes (equipos lentos, Bluetooth)
+    Safe,
+}
+
+impl LatencyProfile {
+    /// Periodos del dispositivo que caben en el ring buffer
+    pub fn periods(&self) -> usize {
+        match self {
+            LatencyProfile::Low => 2,
+            LatencyProfile::Normal => 4,
+            LatencyProfile::Safe => 16,
+        }
+    }
+
+    /// Valor guardado en settings (`"low"`, `"normal"`, `"safe"`)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "low" => Some(LatencyProfile::Low),
+            "normal" => Some(LatencyProfile::Normal),
+            "safe" => Some(LatencyProfile::Safe),
+            _ => None,
+        }
+    }
+}
+
+/// Configuración de salida pedida por el usuario
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputSettings {
+    pub profile: LatencyProfile,
+    /// Frames por callback del dispositivo
+    pub buffer_size: u32,
+    /// Sample rate de salida fuera del modo bit-perfect
+    pub sample_rate: u32,
+    /// Abrir el dispositivo al sample rate del archivo (sin resampling) si lo soporta
+    pub bit_perfect: bool,
+}
+
+impl Default for OutputSettings {
+    fn default() -> Self {
+        Self {
+            profile: LatencyProfile::default(),
+            buffer_size: BUFFER_SIZE as u32,
+            sample_rate: 44100,
+            bit_perfect: true,
+        }
+    }
+}
+
+impl OutputSettings {
+    /// Devuelve una copia con buffer y sample rate en rangos válidos
+    pub fn clamped(self) -> Self {
+        Self {
+            buffer_size: self.buffer_size.clamp(MIN_BUFFER_FRAMES, MAX_BUFFER_FRAMES),
+            sample_rate: self.sample_rate.clamp(8000, 384_000),
+            ..self
+        }
+    }
+
+    /// Sample rate a pedir al dispositivo para un archivo a `source_rate`
+    pub fn output_rate(&self, source_rate: u32) -> u32 {
+        if self.bit_perfect {
+            source_rate
+        } else {
+            self.sample_rate
+        }
+    }
+
+    /// Tamaño del ring buffer (en samples interleaved)
+    pub fn ring_buffer_size(&self, channels: u16) -> usize {
+        self.buffer_size as usize * self.profile.periods() * channels.max(1) as usize
+    }
+}
+
+impl From<&crate::config::AudioConfig> for OutputSettings {
+    fn from(config: &crate::config::AudioConfig) -> Self {
+        Self {
+            profile: config.latency_profile,
+            buffer_size: config.buffer_size,
+            sample_rate: config.sample_rate,
+            bit_perfect: config.bit_perfect,
+        }
+        .clamped()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::constants::RING_BUFFER_SIZE;
+
+    #[test]
+    fn test_default_matches_ring_buffer_constant() {
+        let settings = OutputSettings::default();
+        assert_eq!(settings.ring_buffer_size(2), RING_BUFFER_SIZE);
+    }
+
+    #[test]
+    fn test_profiles_scale_ring_buffer() {
+        let low = OutputSettings {
+            profile: LatencyProfile::Low,
+            buffer_size: 256,
+            ..OutputSettings::default()
+        };
+        let safe = OutputSettings {
+            profile: LatencyProfile::Safe,
+            ..low
+        };
+        assert_eq!(low.ring_buffer_size(2), 1024);
+        assert_eq!(safe.ring_buffer_size(2), 8192);
+    }
+
+    #[test]
+    fn test_output_rate_bit_perfect() {
+        let settings = OutputSettings {
+            sample_rate: 48000,
+            ..OutputSettings::default()
+        };
+        assert_eq!(settings.output_rate(96000), 96000);
+
+        let fixed = OutputSettings {
+            bit_perfect: false,
+            ..settings
+        };
+        assert_eq!(fixed.output_rate(96000), 48000);
+    }
+
+    #[test]
+    fn test_clamped_and_parse() {
+        let settings = OutputSettings {
+            buffer_size: 1,
+            sample_rate: 0,
+            ..OutputSettings::default()
+        }
+        .clamped();
+        assert_eq!(settings.buffer_size, MIN_BUFFER_FRAMES);
+        assert_eq!(settings.sample_rate, 8000);
+
+        assert_eq!(LatencyProfile::parse("safe"), Some(LatencyProfile::Safe));
+        assert_eq!(LatencyProfile::parse("ultra"), None);
+        let json = serde_json::to_string(&LatencyProfile::Low).unwrap();
+        assert_eq!(json, "\"low\"");
+    }
+}
@@ -0,0 +1,447 @@
+use crate::audio::beatgrid::TempoMap;
+use crate::audio::decoder::AudioDecoder;
+use crate::audio::error::AudioError;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Compás asumido (4/4)
+///
+/// AIDEV-NOTE: La música de club es 4/4 casi siempre; la fase del downbeat sí
+/// se detecta, el número de beats por compás no.
+pub const BEATS_PER_BAR: u32 = 4;
+
+/// Bloque mínimo de compases con el que se construyen las frases
+const BLOCK_BARS: u32 = 8;
+
+/// Frase más larga que se forma uniendo bloques
+const MAX_PHRASE_BARS: u32 = 32;
+
+/// Diferencia de energía (relativa a la máxima) para unir dos bloques
+const MERGE_TOLERANCE: f64 = 0.15;
+
+/// Ventana al inicio de cada beat en la que se mide el acento (kick)
+const ACCENT_WINDOW_SECONDS: f64 = 0.1;
+
+/// Corte del paso bajo usado para el acento
+const LOW_PASS_HZ: f64 = 150.0;
+
+/// Energía relativa a partir de la cual una frase es un estribillo / drop
+const CHORUS_ENERGY: f64 = 0.8;
+
+/// Energía relativa por debajo de la cual una frase es un puente / breakdown
+const BREAKDOWN_ENERGY: f64 = 0.5;
+
+/// Tipo de sección según su energía y su posición en la pista
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PhraseKind {
+    Intro,
+    Verse,
+    Chorus,
+    Bridge,
+    Outro,
+}
+
+impl PhraseKind {
+    /// Nombre guardado en `phrases.kind`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PhraseKind::Intro => "intro",
+            PhraseKind::Verse => "verse",
+            PhraseKind::Chorus => "chorus",
+            PhraseKind::Bridge => "bridge",
+            PhraseKind::Outro => "outro",
+        }
+    }
+}
+
+/// Frase detectada (grupo de 8, 16 o 32 compases)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhraseSection {
+    /// Primer compás (0 = compás del primer downbeat)
+    pub start_bar: u32,
+    pub bars: u32,
+    /// Inicio y fin en segundos
+    pub start: f64,
+    pub end: f64,
+    pub kind: PhraseKind,
+    /// Energía relativa a la frase más fuerte (0-1)
+    pub energy: f64,
+}
+
+/// Análisis de compases y frases de una pista
+#[derive(Debug, Clone)]
+pub struct PhraseAnalysis {
+    /// Índice (en el beatgrid) del primer beat que es "1" de compás
+    pub downbeat_beat: u32,
+    /// Posición del primer downbeat en segundos
+    pub downbeat: f64,
+    pub beats_per_bar: u32,
+    pub phrases: Vec<PhraseSection>,
+}
+
+/// Detector de downbeats y frases sobre un beatgrid ya calculado
+pub struct PhraseDetector;
+
+/// Bloque de compases consecutivos con su energía media
+#[derive(Debug, Clone, Copy)]
+struct BarBlock {
+    start_bar: u32,
+    bars: u32,
+    energy: f64,
+}
+
+impl PhraseDetector {
+    /// Analiza una pista siguiendo su mapa de tempo
+    ///
+    /// # Errors
+    /// Retorna AudioError si el archivo no se puede decodificar o si la pista
+    /// no llega a dos compases de beatgrid.
+    pub fn analyze(path: &Path, tempo: &TempoMap) -> Result<PhraseAnalysis, AudioError> {
+        let decoded = AudioDecoder::decode_samples(path)?;
+        Self::analyze_samples(
+            &decoded.samples,
+            decoded.sample_rate,
+            decoded.channels as usize,
+            tempo,
+        )
+    }
+
+    /// Analiza samples interleaved ya decodificados
+    pub fn analyze_samples(
+        samples: &[f32],
+        sample_rate: u32,
+        channels: usize,
+        tempo: &TempoMap,
+    ) -> Result<PhraseAnalysis, AudioError> {
+        let channels = channels.max(1);
+        let mono: Vec<f32> = samples
+            .chunks(channels)
+            .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+            .collect();
+        let duration = mono.len() as f64 / sample_rate as f64;
+
+        let beats: Vec<f64> = (0..)
+            .map(|beat| tempo.time_at(beat as f64))
+            .take_while(|&time| time < duration)
+            .collect();
+        if beats.len() < (BEATS_PER_BAR * 2) as usize {
+            return Err(AudioError::AnalysisError(
+                "Audio demasiado corto para detectar compases".into(),
+            ));
+        }
+
+        let (accents, energies) = Self::beat_features(&mono, sample_rate, &beats, duration);
+        let phase = Self::downbeat_phase(&accents, &energies);
+
+        let bar_energies: Vec<f64> = energies[phase..]
+            .chunks_exact(BEATS_PER_BAR as usize)
+            .map(|bar| bar.iter().sum::<f64>() / bar.len() as f64)
+            .collect();
+
+        let bar_time = |bar: u32| {
+            tempo
+                .time_at((phase as u32 + bar * BEATS_PER_BAR) as f64)
+                .min(duration)
+        };
+        let phrases = Self::group_phrases(&bar_energies)
+            .into_iter()
+            .map(|(block, kind, energy)| PhraseSection {
+                start_bar: block.start_bar,
+                bars: block.bars,
+                start: bar_time(block.start_bar),
+                end: bar_time(block.start_bar + block.bars),
+                kind,
+                energy,
+            })
+            .collect();
+
+        Ok(PhraseAnalysis {
+            downbeat_beat: phase as u32,
+            downbeat: beats[phase],
+            beats_per_bar: BEATS_PER_BAR,
+            phrases,
+        })
+    }
+
+    /// Acento de graves al inicio de cada beat y energía RMS de todo el beat
+    fn beat_features(
+        mono: &[f32],
+        sample_rate: u32,
+        beats: &[f64],
+        duration: f64,
+    ) -> (Vec<f64>, Vec<f64>) {
+        // Paso bajo de un polo: suficiente para aislar el kick del resto
+        let rc = 1.0 / (2.0 * std::f64::consts::PI * LOW_PASS_HZ);
+        let dt = 1.0 / sample_rate as f64;
+        let alpha = (dt / (rc + dt)) as f32;
+        let mut state = 0.0f32;
+        let low: Vec<f32> = mono
+            .iter()
+            .map(|&sample| {
+                state += alpha * (sample - state);
+                state
+            })
+            .collect();
+
+        let index = |time: f64| ((time * sample_rate as f64) as usize).min(mono.len());
+        let rms = |samples: &[f32]| {
+            if samples.is_empty() {
+                return 0.0;
+            }
+            (samples.iter().map(|&s| (s * s) as f64).sum::<f64>() / samples.len() as f64).sqrt()
+        };
+
+        beats
+            .iter()
+            .enumerate()
+            .map(|(i, &start)| {
+                let end = beats.get(i + 1).copied().unwrap_or(duration);
+                let accent_end = (start + ACCENT_WINDOW_SECONDS).min(end);
+                (
+                    rms(&low[index(start)..index(accent_end)]),
+                    rms(&mono[index(start)..index(end)]),
+                )
+            })
+            .unzip()
+    }
+
+    /// Fase (0-3) del downbeat dentro del beatgrid
+    ///
+    /// Combina el acento de graves (el kick del "1" suele ser más fuerte) con
+    /// los cambios de energía entre beats (las secciones empiezan en el "1").
+    fn downbeat_phase(accents: &[f64], energies: &[f64]) -> usize {
+        let novelty: Vec<f64> = std::iter::once(0.0)
+            .chain(energies.windows(2).map(|pair| (pair[1] - pair[0]).abs()))
+            .collect();
+
+        let normalized = |values: &[f64], phase: usize| {
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            if mean <= f64::EPSILON {
+                return 0.0;
+            }
+            let in_phase: Vec<f64> = values
+                .iter()
+                .skip(phase)
+                .step_by(BEATS_PER_BAR as usize)
+                .copied()
+                .collect();
+            in_phase.iter().sum::<f64>() / in_phase.len() as f64 / mean
+        };
+
+        (0..BEATS_PER_BAR as usize)
+            .map(|phase| {
+                let score = normalized(accents, phase) + normalized(&novelty, phase);
+                (phase, score)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(0, |(phase, _)| phase)
+    }
+
+    /// Agrupa compases en frases de 8/16/32 y las etiqueta por energía
+    ///
+    /// AIDEV-NOTE: Se parte de bloques de 8 compases y se unen parejas vecinas
+    /// del mismo tamaño con energía parecida (8+8 → 16, 16+16 → 32).
+    fn group_phrases(bar_energies: &[f64]) -> Vec<(BarBlock, PhraseKind, f64)> {
+        let mut blocks: Vec<BarBlock> = Vec::new();
+        for (i, chunk) in bar_energies.chunks(BLOCK_BARS as usize).enumerate() {
+            let block = BarBlock {
+                start_bar: i as u32 * BLOCK_BARS,
+                bars: chunk.len() as u32,
+                energy: chunk.iter().sum::<f64>() / chunk.len() as f64,
+            };
+            // Un resto de menos de medio bloque se une al anterior
+            match blocks.last_mut() {
+                Some(last) if block.bars < BLOCK_BARS / 2 => *last = Self::join(last, &block),
+                _ => blocks.push(block),
+            }
+        }
+
+        let max_energy = blocks.iter().map(|b| b.energy).fold(0.0, f64::max);
+        let similar = |a: &BarBlock, b: &BarBlock| {
+            max_energy <= f64::EPSILON
+                || (a.energy - b.energy).abs() / max_energy <= MERGE_TOLERANCE
+        };
+
+        loop {
+            let mut merged = Vec::with_capacity(blocks.len());
+            let mut i = 0;
+            while i < blocks.len() {
+                match blocks.get(i + 1) {
+                    Some(next)
+                        if blocks[i].bars == next.bars
+                            && blocks[i].bars * 2 <= MAX_PHRASE_BARS
+                            && similar(&blocks[i], next) =>
+                    {
+                        merged.push(Self::join(&blocks[i], next));
+                        i += 2;
+                    }
+                    _ => {
+                        merged.push(blocks[i]);
+                        i += 1;
+                    }
+                }
+            }
+            if merged.len() == blocks.len() {
+                break;
+            }
+            blocks = merged;
+        }
+
+        let last = blocks.len().saturating_sub(1);
+        blocks
+            .iter()
+            .enumerate()
+            .map(|(i, block)| {
+                let energy = if max_energy > f64::EPSILON {
+                    block.energy / max_energy
+                } else {
+                    0.0
+                };
+                let kind = if energy >= CHORUS_ENERGY {
+                    PhraseKind::Chorus
+                } else if i == 0 {
+                    PhraseKind::Intro
+                } else if i == last {
+                    PhraseKind::Outro
+                } else if energy < BREAKDOWN_ENERGY {
+                    PhraseKind::Bridge
+                } else {
+                    PhraseKind::Verse
+                };
+                (*block, kind, energy)
+            })
+            .collect()
+    }
+
+    /// Une dos bloques consecutivos (energía ponderada por compases)
+    fn join(a: &BarBlock, b: &BarBlock) -> BarBlock {
+        let bars = a.bars + b.bars;
+        BarBlock {
+            start_bar: a.start_bar,
+            bars,
+            energy: (a.energy * a.bars as f64 + b.energy * b.bars as f64) / bars as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 22050;
+    const BEAT: f64 = 0.5; // 120 BPM
+
+    /// Sección de la pista sintética: compases, kick y nivel del pad
+    struct Section {
+        bars: usize,
+        kick: bool,
+        pad: f32,
+    }
+
+    /// Pista 4/4 a 120 BPM con un beat de anacrusa antes del primer downbeat
+    fn render(offset: f64, sections: &[Section]) -> Vec<f32> {
+        let total_beats = 1 + sections.iter().map(|s| s.bars * 4).sum::<usize>();
+        let length = ((offset + total_beats as f64 * BEAT + 1.0) * SAMPLE_RATE as f64) as usize;
+        let mut samples = vec![0.0f32; length];
+
+        // Nivel de cada beat (la anacrusa pertenece a la primera sección)
+        let mut beats = vec![(sections[0].kick, sections[0].pad)];
+        for section in sections {
+            beats.extend(std::iter::repeat_n(
+                (section.kick, section.pad),
+                section.bars * 4,
+            ));
+        }
+
+        for (beat, &(kick, pad)) in beats.iter().enumerate() {
+            let start = ((offset + beat as f64 * BEAT) * SAMPLE_RATE as f64) as usize;
+            let end = start + (BEAT * SAMPLE_RATE as f64) as usize;
+            // La anacrusa es el beat 4: el downbeat es el beat 1 del grid
+            let accent = if beat % 4 == 1 { 0.9 } else { 0.5 };
+            for (i, sample) in samples[start..end].iter_mut().enumerate() {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                let global_t = (start + i) as f32 / SAMPLE_RATE as f32;
+                let mut value = pad * (2.0 * std::f32::consts::PI * 330.0 * global_t).sin();
+                if kick {
+                    value +=
+                        accent * (-t * 30.0).exp() * (2.0 * std::f32::consts::PI * 60.0 * t).sin();
+                }
+                *sample = value;
+            }
+        }
+        samples
+    }
+
+    #[test]
+    fn test_detects_downbeat_and_phrases() {
+        let sections = [
+            Section {
+                bars: 16,
+                kick: true,
+                pad: 0.0,
+            },
+            Section {
+                bars: 32,
+                kick: true,
+                pad: 0.3,
+            },
+            Section {
+                bars: 16,
+                kick: false,
+                pad: 0.08,
+            },
+            Section {
+                bars: 32,
+                kick: true,
+                pad: 0.3,
+            },
+            Section {
+                bars: 16,
+                kick: true,
+                pad: 0.0,
+            },
+        ];
+        let samples = render(0.5, &sections);
+        let tempo = TempoMap::constant(120.0, 0.5);
+
+        let analysis = PhraseDetector::analyze_samples(&samples, SAMPLE_RATE, 1, &tempo).unwrap();
+        assert_eq!(analysis.downbeat_beat, 1);
+        assert!((analysis.downbeat - 1.0).abs() < 1e-9);
+
+        let summary: Vec<(u32, u32, PhraseKind)> = analysis
+            .phrases
+            .iter()
+            .map(|p| (p.start_bar, p.bars, p.kind))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (0, 16, PhraseKind::Intro),
+                (16, 32, PhraseKind::Chorus),
+                (48, 16, PhraseKind::Bridge),
+                (64, 32, PhraseKind::Chorus),
+                (96, 16, PhraseKind::Outro),
+            ]
+        );
+        // Las frases empiezan en el downbeat de su compás
+        assert!((analysis.phrases[1].start - (1.0 + 16.0 * 2.0)).abs() < 1e-9);
+        assert_eq!(analysis.phrases[1].end, analysis.phrases[2].start);
+    }
+
+    #[test]
+    fn test_short_audio_is_rejected() {
+        let samples = vec![0.1; SAMPLE_RATE as usize * 2];
+        let tempo = TempoMap::constant(120.0, 0.0);
+        assert!(PhraseDetector::analyze_samples(&samples, SAMPLE_RATE, 1, &tempo).is_err());
+    }
+
+    #[test]
+    fn test_kind_names() {
+        assert_eq!(PhraseKind::Chorus.as_str(), "chorus");
+        assert_eq!(
+            serde_json::to_string(&PhraseKind::Intro).unwrap(),
+            "\"intro\""
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! Reloj de posición audible
+//!
+//! AIDEV-NOTE: El decode thread va por delante de lo que suena: entre medias
+//! están el ring buffer (`RING_BUFFER_SIZE`) y la latencia del dispositivo. El
+//! reloj guarda marcas (samples escritos → posición del track) y las cruza con
+//! los samples que el callback ya ha retirado, así la posición reportada es la
+//! que se oye aunque haya seeks, loops o cambios de tempo en el buffer.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Marca: posición del track al final de un bloque escrito
+#[derive(Debug, Clone, Copy)]
+struct Marker {
+    /// Samples escritos (acumulados) al final del bloque
+    end: u64,
+    /// Posición del track (segundos) al final del bloque
+    position: f64,
+    /// Segundos de track por sample escrito (refleja el tempo)
+    seconds_per_sample: f64,
+}
+
+/// Reloj de posición del decode thread
+#[derive(Debug, Default)]
+pub struct PlaybackClock {
+    /// Samples escritos al ring buffer del output actual
+    written: u64,
+    /// Segundos de track por sample del último bloque escrito
+    seconds_per_sample: f64,
+    markers: VecDeque<Marker>,
+}
+
+impl PlaybackClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Empieza de cero con un output recién creado
+    pub fn reset(&mut self) {
+        self.written = 0;
+        self.markers.clear();
+    }
+
+    /// Olvida las marcas tras vaciar el ring buffer o cambiar de track
+    ///
+    /// El callback cuenta los samples descartados como retirados, así que el
+    /// contador de escritos sigue alineado con el output. Sin marcas previas, lo
+    /// que quede por sonar se atribuye a la siguiente marca (posición 0 del track
+    /// entrante mientras suena la cola del anterior).
+    pub fn flush(&mut self) {
+        self.markers.clear();
+    }
+
+    /// Registra un bloque escrito al ring buffer
+    ///
+    /// `track_seconds` es la duración del bloque en tiempo de track (antes del tempo).
+    pub fn advance(&mut self, samples: usize, track_seconds: f64) {
+        if samples == 0 {
+            return;
+        }
+        self.written += samples as u64;
+        self.seconds_per_sample = track_seconds / samples as f64;
+    }
+
+    /// Samples escritos al output actual
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// Marca la posición del track al final de lo escrito hasta ahora
+    ///
+    /// Los bloques escritos sin marca (cola de un loop, mezcla de crossfade) se
+    /// atribuyen a la siguiente marca.
+    pub fn mark(&mut self, position: f64) {
+        let marker = Marker {
+            end: self.written,
+            position,
+            seconds_per_sample: self.seconds_per_sample,
+        };
+        match self.markers.back_mut() {
+            Some(last) if last.end == marker.end => *last = marker,
+            _ => self.markers.push_back(marker),
+        }
+    }
+
+    /// Posición que está sonando
+    ///
+    /// `consumed` son los samples retirados por el callback y `latency` los que
+    /// de esos aún no han llegado al altavoz. `None` si no hay nada marcado.
+    pub fn audible_position(&mut self, consumed: u64, latency: u64) -> Option<f64> {
+        let heard = consumed.saturating_sub(latency);
+
+        // Descartar bloques que ya sonaron enteros
+        while self.markers.len() > 1 && self.markers[0].end <= heard {
+            self.markers.pop_front();
+        }
+
+        let marker = self.markers.front()?;
+        let behind = marker.end.saturating_sub(heard) as f64 * marker.seconds_per_sample;
+        Some((marker.position - behind).max(0.0))
+    }
+}
+
+/// Lectura de la posición y duración publicadas por un decode thread
+///
+/// AIDEV-NOTE: Permite a los comandos consultar la posición audible sin
+/// pasar por el canal de control.
+#[derive(Debug, Clone)]
+pub struct PositionHandle {
+    position: Arc<AtomicU64>,
+    duration: Arc<AtomicU64>,
+}
+
+impl PositionHandle {
+    pub fn new(position: Arc<AtomicU64>, duration: Arc<AtomicU64>) -> Self {
+        Self { position, duration }
+    }
+
+    /// Posición audible en segundos
+    pub fn position(&self) -> f64 {
+        f64::from_bits(self.position.load(Ordering::SeqCst))
+    }
+
+    /// Duración del track actual en segundos
+    pub fn duration(&self) -> f64 {
+        f64::from_bits(self.duration.load(Ordering::SeqCst))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Escribe `packets` bloques de 1000 samples (0.01 s de track cada uno)
+    fn write_packets(clock: &mut PlaybackClock, start: f64, packets: usize) {
+        for i in 1..=packets {
+            clock.advance(1000, 0.01);
+            clock.mark(start + i as f64 * 0.01);
+        }
+    }
+
+    #[test]
+    fn test_no_markers() {
+        let mut clock = PlaybackClock::new();
+        assert_eq!(clock.audible_position(0, 0), None);
+    }
+
+    #[test]
+    fn test_position_lags_buffered_samples() {
+        let mut clock = PlaybackClock::new();
+        write_packets(&mut clock, 0.0, 10);
+
+        // Decodificado hasta 0.10 s, pero solo han sonado 2500 samples
+        let pos = clock.audible_position(2500, 0).unwrap();
+        assert!((pos - 0.025).abs() < 1e-9);
+
+        // La latencia del dispositivo retrasa aún más lo que se oye
+        let pos = clock.audible_position(3000, 1000).unwrap();
+        assert!((pos - 0.02).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_seek_in_buffer() {
+        let mut clock = PlaybackClock::new();
+        write_packets(&mut clock, 0.0, 4);
+        // Seek sin vaciar el buffer: lo anterior sigue sonando primero
+        write_packets(&mut clock, 30.0, 4);
+
+        let before = clock.audible_position(3500, 0).unwrap();
+        assert!((before - 0.035).abs() < 1e-9);
+        let after = clock.audible_position(4500, 0).unwrap();
+        assert!((after - 30.005).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_tempo_scales_samples() {
+        let mut clock = PlaybackClock::new();
+        // Al 200% un bloque de 0.02 s de track ocupa 1000 samples
+        clock.advance(1000, 0.02);
+        clock.mark(0.02);
+
+        let pos = clock.audible_position(500, 0).unwrap();
+        assert!((pos - 0.01).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_underrun_holds_last_position() {
+        let mut clock = PlaybackClock::new();
+        write_packets(&mut clock, 0.0, 2);
+        assert_eq!(clock.audible_position(5000, 0), Some(0.02));
+    }
+
+    #[test]
+    fn test_flush_keeps_sample_count() {
+        let mut clock = PlaybackClock::new();
+        write_packets(&mut clock, 0.0, 4);
+
+        // Vaciado con 1000 samples sonados: los otros 3000 cuentan como retirados
+        clock.flush();
+        assert_eq!(clock.audible_position(4000, 0), None);
+        write_packets(&mut clock, 10.0, 1);
+        let pos = clock.audible_position(4500, 0).unwrap();
+        assert!((pos - 10.005).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_reset_for_new_output() {
+        let mut clock = PlaybackClock::new();
+        write_packets(&mut clock, 0.0, 4);
+
+        clock.reset();
+        write_packets(&mut clock, 2.0, 2);
+        let pos = clock.audible_position(1000, 0).unwrap();
+        assert!((pos - 2.01).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,367 @@
+//! Crossfade entre tracks consecutivos
+//!
+//! AIDEV-NOTE: Durante la ventana de crossfade el decode thread decodifica dos
+//! `DecoderState` a la vez (saliente y entrante) y mezcla sus samples antes de
+//! escribirlos al ring buffer. El mezclador trabaja sobre buffers interleaved,
+//! así que ambos tracks deben tener el mismo sample rate y canales.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::audio::constants::CROSSFADE_MAX_SECONDS;
+use crate::audio::error::AudioResult;
+
+use super::decoder::decode_packet;
+use super::state::{DecodedPacket, DecoderState, PreloadedTrack};
+
+/// Curva de ganancia del crossfade
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum CrossfadeCurve {
+    /// Rampa lineal (baja ~3 dB en el centro)
+    Linear,
+    /// Potencia constante (seno/coseno)
+    #[default]
+    EqualPower,
+    /// Curva en S (coseno elevado), transición suave en los extremos
+    SCurve,
+}
+
+impl CrossfadeCurve {
+    /// Ganancias (saliente, entrante) para un progreso entre 0.0 y 1.0
+    pub fn gains(&self, progress: f32) -> (f32, f32) {
+        let p = progress.clamp(0.0, 1.0);
+        match self {
+            CrossfadeCurve::Linear => (1.0 - p, p),
+            CrossfadeCurve::EqualPower => {
+                let angle = p * std::f32::consts::FRAC_PI_2;
+                (angle.cos(), angle.sin())
+            }
+            CrossfadeCurve::SCurve => {
+                let s = 0.5 - 0.5 * (p * std::f32::consts::PI).cos();
+                (1.0 - s, s)
+            }
+        }
+    }
+}
+
+/// Configuración de crossfade usada por el decode thread
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrossfadeSettings {
+    pub enabled: bool,
+    /// Duración de la ventana de solapamiento (en segundos)
+    pub seconds: f64,
+    pub curve: CrossfadeCurve,
+}
+
+impl CrossfadeSettings {
+    /// Devuelve una copia con la duración limitada a un rango válido
+    pub fn clamped(self) -> Self {
+        Self {
+            seconds: self.seconds.clamp(0.0, CROSSFADE_MAX_SECONDS),
+            ..self
+        }
+    }
+
+    /// Indica si el crossfade está activo con una duración utilizable
+    pub fn is_active(&self) -> bool {
+        self.enabled && self.seconds > 0.0
+    }
+}
+
+impl From<&crate::config::AudioConfig> for CrossfadeSettings {
+    fn from(config: &crate::config::AudioConfig) -> Self {
+        Self {
+            enabled: config.crossfade_enabled,
+            seconds: config.crossfade_seconds,
+            curve: config.crossfade_curve,
+        }
+        .clamped()
+    }
+}
+
+/// Mezclador de dos streams interleaved con la curva seleccionada
+pub struct CrossfadeMixer {
+    curve: CrossfadeCurve,
+    channels: usize,
+    total_frames: usize,
+    mixed_frames: usize,
+    outgoing: VecDeque<f32>,
+    incoming: VecDeque<f32>,
+    outgoing_finished: bool,
+    incoming_finished: bool,
+}
+
+impl CrossfadeMixer {
+    /// Crea un mezclador para una ventana de `seconds` segundos
+    pub fn new(curve: CrossfadeCurve, sample_rate: u32, channels: u16, seconds: f64) -> Self {
+        let total_frames = (sample_rate as f64 * seconds.max(0.0)).round() as usize;
+        Self {
+            curve,
+            channels: channels.max(1) as usize,
+            total_frames: total_frames.max(1),
+            mixed_frames: 0,
+            outgoing: VecDeque::new(),
+            incoming: VecDeque::new(),
+            outgoing_finished: false,
+            incoming_finished: false,
+        }
+    }
+
+    /// Añade samples del track saliente
+    pub fn push_outgoing(&mut self, samples: &[f32]) {
+        self.outgoing.extend(samples);
+    }
+
+    /// Añade samples del track entrante
+    pub fn push_incoming(&mut self, samples: &[f32]) {
+        self.incoming.extend(samples);
+    }
+
+    /// Marca el fin del track saliente; el resto de la ventana se mezcla con silencio
+    pub fn finish_outgoing(&mut self) {
+        self.outgoing_finished = true;
+    }
+
+    /// Marca el fin del track entrante; el resto de la ventana es solo fade out
+    pub fn finish_incoming(&mut self) {
+        self.incoming_finished = true;
+    }
+
+    /// Indica si hay que decodificar más del track saliente antes de mezclar
+    pub fn needs_outgoing(&self) -> bool {
+        !self.outgoing_finished
+            && (self.incoming_finished || self.outgoing.len() <= self.incoming.len())
+    }
+
+    /// Indica si queda track entrante por decodificar
+    pub fn needs_incoming(&self) -> bool {
+        !self.incoming_finished
+    }
+
+    /// Progreso del crossfade (0.0 - 1.0)
+    pub fn progress(&self) -> f32 {
+        (self.mixed_frames as f32 / self.total_frames as f32).min(1.0)
+    }
+
+    /// Indica si la ventana de crossfade ya se completó
+    pub fn is_complete(&self) -> bool {
+        self.mixed_frames >= self.total_frames
+    }
+
+    /// Mezcla todos los frames disponibles en ambos buffers
+    pub fn mix(&mut self) -> Vec<f32> {
+        let available = match (self.outgoing_finished, self.incoming_finished) {
+            (false, false) => self.outgoing.len().min(self.incoming.len()),
+            (true, false) => self.incoming.len(),
+            (false, true) => self.outgoing.len(),
+            (true, true) => self.outgoing.len().max(self.incoming.len()),
+        };
+        let remaining = self.total_frames.saturating_sub(self.mixed_frames);
+        let frames = (available / self.channels).min(remaining);
+
+        let mut mixed = Vec::with_capacity(frames * self.channels);
+        for _ in 0..frames {
+            let (gain_out, gain_in) = self.curve.gains(self.progress());
+            for _ in 0..self.channels {
+                let out = self.outgoing.pop_front().unwrap_or(0.0);
+                let inc = self.incoming.pop_front().unwrap_or(0.0);
+                mixed.push(out * gain_out + inc * gain_in);
+            }
+            self.mixed_frames += 1;
+        }
+        // Sin nada más que decodificar en ningún lado la ventana se cierra ya
+        if self.outgoing_finished && self.incoming_finished {
+            self.mixed_frames = self.total_frames;
+        }
+        mixed
+    }
+
+    /// Samples del track entrante que sobran tras completar la ventana
+    pub fn take_remaining_incoming(&mut self) -> Vec<f32> {
+        self.incoming.drain(..).collect()
+    }
+}
+
+/// Crossfade en curso: track entrante + mezclador
+pub struct ActiveCrossfade {
+    pub incoming: DecoderState,
+    pub incoming_duration: f64,
+    pub mixer: CrossfadeMixer,
+    /// Posición del primer paquete saliente mezclado
+    outgoing_start: Option<f64>,
+}
+
+impl ActiveCrossfade {
+    /// Inicia el crossfade con el track precargado
+    ///
+    /// AIDEV-NOTE: La ventana nunca supera la mitad del track entrante para que
+    /// un track corto no termine dentro de su propio fade-in.
+    pub fn new(next: PreloadedTrack, settings: CrossfadeSettings) -> Self {
+        let seconds = if next.duration > 0.0 {
+            settings.seconds.min(next.duration / 2.0)
+        } else {
+            settings.seconds
+        };
+        let mut mixer = CrossfadeMixer::new(
+            settings.curve,
+            next.decoder.sample_rate,
+            next.decoder.channels,
+            seconds,
+        );
+        mixer.push_incoming(&next.primed);
+
+        Self {
+            incoming: next.decoder,
+            incoming_duration: next.duration,
+            mixer,
+            outgoing_start: None,
+        }
+    }
+
+    /// Posición del track saliente al final de lo mezclado hasta ahora
+    ///
+    /// `None` hasta decodificar el primer paquete saliente.
+    pub fn outgoing_position(&self) -> Option<f64> {
+        let rate = self.incoming.sample_rate.max(1) as f64;
+        self.outgoing_start
+            .map(|start| start + self.mixer.mixed_frames as f64 / rate)
+    }
+
+    /// Decodifica un paquete del track que va por detrás y devuelve los samples mezclados
+    ///
+    /// AIDEV-NOTE: Si falla la decodificación se da por terminado solo el lado
+    /// que falló (el otro sigue sonando hasta completar la ventana) y se
+    /// devuelve el error para que el decode thread lo registre.
+    pub fn step(&mut self, outgoing: &mut DecoderState) -> AudioResult<Vec<f32>> {
+        if self.mixer.needs_outgoing() {
+            match decode_packet(outgoing) {
+                Ok(DecodedPacket::Samples { samples, position }) => {
+                    self.outgoing_start.get_or_insert(position);
+                    self.mixer.push_outgoing(&samples);
+                }
+                Ok(DecodedPacket::Skipped(_)) => {}
+                Ok(DecodedPacket::EndOfTrack) => self.mixer.finish_outgoing(),
+                Err(e) => {
+                    self.mixer.finish_outgoing();
+                    return Err(e);
+                }
+            }
+        } else if self.mixer.needs_incoming() {
+            match decode_packet(&mut self.incoming) {
+                Ok(DecodedPacket::Samples { samples, .. }) => self.mixer.push_incoming(&samples),
+                Ok(DecodedPacket::Skipped(_)) => {}
+                // Track entrante más corto que la ventana: cerrar el crossfade
+                Ok(DecodedPacket::EndOfTrack) => self.mixer.mixed_frames = self.mixer.total_frames,
+                Err(e) => {
+                    self.mixer.finish_incoming();
+                    return Err(e);
+                }
+            }
+        }
+        Ok(self.mixer.mix())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curve_endpoints() {
+        for curve in [
+            CrossfadeCurve::Linear,
+            CrossfadeCurve::EqualPower,
+            CrossfadeCurve::SCurve,
+        ] {
+            let (out0, in0) = curve.gains(0.0);
+            let (out1, in1) = curve.gains(1.0);
+            assert!((out0 - 1.0).abs() < 1e-6 && in0.abs() < 1e-6);
+            assert!(out1.abs() < 1e-6 && (in1 - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_equal_power_keeps_energy() {
+        for i in 0..=10 {
+            let (out, inc) = CrossfadeCurve::EqualPower.gains(i as f32 / 10.0);
+            assert!((out * out + inc * inc - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_curve_serialize() {
+        let json = serde_json::to_string(&CrossfadeCurve::SCurve).unwrap();
+        assert_eq!(json, "\"sCurve\"");
+        let curve: CrossfadeCurve = serde_json::from_str("\"equalPower\"").unwrap();
+        assert_eq!(curve, CrossfadeCurve::EqualPower);
+    }
+
+    #[test]
+    fn test_mixer_fades_from_outgoing_to_incoming() {
+        // 10 frames mono a 10 Hz = ventana de 1 segundo
+        let mut mixer = CrossfadeMixer::new(CrossfadeCurve::Linear, 10, 1, 1.0);
+        mixer.push_outgoing(&[1.0; 10]);
+        mixer.push_incoming(&[0.5; 10]);
+
+        let mixed = mixer.mix();
+        assert_eq!(mixed.len(), 10);
+        assert!((mixed[0] - 1.0).abs() < 1e-6);
+        assert!((mixed[9] - (0.1 + 0.45)).abs() < 1e-5);
+        assert!(mixer.is_complete());
+    }
+
+    #[test]
+    fn test_mixer_waits_for_both_streams() {
+        let mut mixer = CrossfadeMixer::new(CrossfadeCurve::Linear, 10, 2, 1.0);
+        mixer.push_outgoing(&[1.0; 8]);
+        assert!(mixer.mix().is_empty());
+        assert!(!mixer.needs_outgoing());
+
+        mixer.push_incoming(&[1.0; 4]);
+        assert_eq!(mixer.mix().len(), 4);
+    }
+
+    #[test]
+    fn test_mixer_outgoing_finished_pads_silence() {
+        let mut mixer = CrossfadeMixer::new(CrossfadeCurve::Linear, 4, 1, 1.0);
+        mixer.push_incoming(&[1.0; 6]);
+        mixer.finish_outgoing();
+
+        let mixed = mixer.mix();
+        assert_eq!(mixed.len(), 4);
+        assert!(mixer.is_complete());
+        assert_eq!(mixer.take_remaining_incoming(), vec![1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_mixer_incoming_finished_fades_out() {
+        let mut mixer = CrossfadeMixer::new(CrossfadeCurve::Linear, 4, 1, 1.0);
+        mixer.push_incoming(&[1.0]);
+        mixer.push_outgoing(&[1.0; 2]);
+        mixer.finish_incoming();
+        assert!(mixer.needs_outgoing());
+
+        // El saliente sigue mezclándose con silencio hasta que se acaba
+        assert_eq!(mixer.mix().len(), 2);
+        assert!(!mixer.is_complete());
+
+        mixer.finish_outgoing();
+        assert!(mixer.mix().is_empty());
+        assert!(mixer.is_complete());
+    }
+
+    #[test]
+    fn test_settings_clamped() {
+        let settings = CrossfadeSettings {
+            enabled: true,
+            seconds: 120.0,
+            curve: CrossfadeCurve::Linear,
+        }
+        .clamped();
+        assert_eq!(settings.seconds, CROSSFADE_MAX_SECONDS);
+        assert!(settings.is_active());
+    }
+}
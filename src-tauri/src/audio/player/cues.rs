@@ -0,0 +1,194 @@
+//! Hot cues: disparo por hotkey y pre-escucha estilo CDJ
+//!
+//! AIDEV-NOTE: El frontend solo envía "hot cue N" y el tipo de pulsación; el
+//! decode thread resuelve la posición en la tabla `cue_points` y `HotCueState`
+//! decide el seek y el transporte. Con el track en pausa, mantener pulsado un
+//! hot cue lo reproduce desde el cue y al soltar vuelve al cue y pausa.
+
+use serde::{Deserialize, Serialize};
+
+/// Tipo de pulsación de un hot cue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum CueTrigger {
+    /// Saltar al cue y reproducir
+    #[default]
+    Jump,
+    /// Botón pulsado (pre-escucha si está en pausa)
+    Press,
+    /// Botón soltado (fin de la pre-escucha)
+    Release,
+}
+
+/// Transporte a aplicar tras el seek de un hot cue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    Play,
+    Pause,
+    /// Mantener el estado actual
+    Keep,
+}
+
+impl Transport {
+    /// `Some(true)` para reproducir, `Some(false)` para pausar
+    pub fn playing(self) -> Option<bool> {
+        match self {
+            Transport::Play => Some(true),
+            Transport::Pause => Some(false),
+            Transport::Keep => None,
+        }
+    }
+}
+
+/// Acción resultante de un hot cue
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CueCommand {
+    /// Posición a la que saltar (segundos)
+    pub position: f64,
+    pub transport: Transport,
+}
+
+/// Estado de los hot cues del reproductor
+#[derive(Debug, Default)]
+pub struct HotCueState {
+    /// Hot cue mantenido en pre-escucha y su posición
+    preview: Option<(u8, f64)>,
+}
+
+impl HotCueState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Traduce una pulsación a un seek y un transporte
+    ///
+    /// `cue_position` es la posición guardada del hot cue (no hace falta para
+    /// `Release`). Devuelve `None` si no hay nada que hacer.
+    pub fn trigger(
+        &mut self,
+        hotkey: u8,
+        trigger: CueTrigger,
+        cue_position: Option<f64>,
+        is_paused: bool,
+    ) -> Option<CueCommand> {
+        match trigger {
+            CueTrigger::Jump => {
+                self.preview = None;
+                Some(CueCommand {
+                    position: cue_position?,
+                    transport: Transport::Play,
+                })
+            }
+            CueTrigger::Press => {
+                let position = cue_position?;
+                if is_paused || self.preview.is_some() {
+                    // Pulsar otro hot cue durante una pre-escucha la traslada a ese cue
+                    self.preview = Some((hotkey, position));
+                    Some(CueCommand {
+                        position,
+                        transport: Transport::Play,
+                    })
+                } else {
+                    Some(CueCommand {
+                        position,
+                        transport: Transport::Keep,
+                    })
+                }
+            }
+            CueTrigger::Release => {
+                let (held, position) = self.preview?;
+                if held != hotkey {
+                    return None;
+                }
+                self.preview = None;
+                Some(CueCommand {
+                    position,
+                    transport: Transport::Pause,
+                })
+            }
+        }
+    }
+
+    /// `true` mientras se mantiene un hot cue en pre-escucha
+    pub fn is_previewing(&self) -> bool {
+        self.preview.is_some()
+    }
+
+    /// Cancela la pre-escucha (pausa/resume manual, cambio de track)
+    pub fn reset(&mut self) {
+        self.preview = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jump_plays_from_cue() {
+        let mut cues = HotCueState::new();
+        let cmd = cues.trigger(3, CueTrigger::Jump, Some(42.5), true).unwrap();
+        assert_eq!(cmd.position, 42.5);
+        assert_eq!(cmd.transport, Transport::Play);
+        assert!(!cues.is_previewing());
+    }
+
+    #[test]
+    fn test_unassigned_cue_does_nothing() {
+        let mut cues = HotCueState::new();
+        assert!(cues.trigger(1, CueTrigger::Jump, None, false).is_none());
+        assert!(cues.trigger(1, CueTrigger::Press, None, true).is_none());
+        assert!(!cues.is_previewing());
+    }
+
+    #[test]
+    fn test_press_while_playing_keeps_playing() {
+        let mut cues = HotCueState::new();
+        let cmd = cues
+            .trigger(2, CueTrigger::Press, Some(10.0), false)
+            .unwrap();
+        assert_eq!(cmd.transport, Transport::Keep);
+
+        // Soltar sin pre-escucha no hace nada
+        assert!(cues.trigger(2, CueTrigger::Release, None, false).is_none());
+    }
+
+    #[test]
+    fn test_hold_to_preview_release_to_return() {
+        let mut cues = HotCueState::new();
+        let press = cues
+            .trigger(1, CueTrigger::Press, Some(30.0), true)
+            .unwrap();
+        assert_eq!(press.transport, Transport::Play);
+        assert!(cues.is_previewing());
+
+        // Soltar otro botón no termina la pre-escucha
+        assert!(cues.trigger(2, CueTrigger::Release, None, false).is_none());
+
+        let release = cues.trigger(1, CueTrigger::Release, None, false).unwrap();
+        assert_eq!(release.position, 30.0);
+        assert_eq!(release.transport, Transport::Pause);
+        assert!(!cues.is_previewing());
+    }
+
+    #[test]
+    fn test_press_during_preview_moves_it() {
+        let mut cues = HotCueState::new();
+        cues.trigger(1, CueTrigger::Press, Some(30.0), true);
+        let cmd = cues
+            .trigger(4, CueTrigger::Press, Some(90.0), false)
+            .unwrap();
+        assert_eq!(cmd.transport, Transport::Play);
+
+        assert!(cues.trigger(1, CueTrigger::Release, None, false).is_none());
+        let release = cues.trigger(4, CueTrigger::Release, None, false).unwrap();
+        assert_eq!(release.position, 90.0);
+    }
+
+    #[test]
+    fn test_trigger_deserialize() {
+        let trigger: CueTrigger = serde_json::from_str("\"release\"").unwrap();
+        assert_eq!(trigger, CueTrigger::Release);
+        assert_eq!(CueTrigger::default(), CueTrigger::Jump);
+    }
+}
@@ -2,44 +2,178 @@
 
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::mpsc::{Receiver, TryRecvError};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
 
-use crate::audio::constants::TIMESTAMP_INTERVAL_MS;
-use crate::audio::output::{AudioOutput, CpalAudioOutput};
+use crate::audio::constants::{LOOP_MIN_MS, METER_INTERVAL_MS, TIMESTAMP_INTERVAL_MS};
+use crate::audio::dsp::{DspChain, DspSettings};
+use crate::audio::error::AudioResult;
+use crate::audio::loudness_analyzer::LoudnessAnalysis;
+use crate::audio::output::{
+    device_from_setting, open_output, resolve_output_device, AudioOutput, OutputSettings,
+};
+use crate::config::AppConfig;
+use crate::db::models::QueueItem;
+use crate::db::{queries, DbPool};
+
+use tauri::Manager;
 
+use super::clock::PlaybackClock;
+use super::crossfade::{ActiveCrossfade, CrossfadeSettings};
+use super::cues::{CueTrigger, HotCueState, Transport};
 use super::decoder::{
-    decode_next_frame, open_audio_file, probe_file_sample_rate, seek_to_position,
+    decode_next_frame, open_audio_file, preload_track, probe_file_sample_rate, seek_to_position,
+    write_processed,
 };
-use super::events::{emit_end_of_track, emit_error, emit_state, emit_timestamp};
-use super::state::{DecodeResult, DecoderState};
-use super::types::PlayerControlEvent;
+use super::events::{
+    emit_end_of_track, emit_error, emit_loop_changed, emit_meter, emit_output_config,
+    emit_play_recorded, emit_queue_changed, emit_state, emit_timestamp, emit_track_changed,
+};
+use super::history::{CompletedPlay, PlayThreshold, PlayTracker};
+use super::looping::{ActiveLoop, LoopRegion};
+use super::meter::LevelMeter;
+use super::normalization::NormalizationSettings;
+use super::queue::PlaybackQueue;
+use super::rate::RateConverter;
+use super::state::{DecodeResult, DecoderState, PreloadedTrack};
+use super::tempo::{TempoProcessor, TempoSettings};
+use super::types::{OutputConfigPayload, PlayerControlEvent, PlayerRole};
 
 /// Loop principal de decodificación
 ///
 /// AIDEV-NOTE: Este loop corre en su propio thread y procesa comandos del player.
 /// Decodifica audio con Symphonia y lo escribe al ring buffer de cpal.
+/// `role` decide el namespace de los eventos y si aplica crossfade.
+#[allow(clippy::too_many_arguments)]
 pub fn decode_loop<R: tauri::Runtime>(
     control_rx: Receiver<PlayerControlEvent>,
     app_handle: tauri::AppHandle<R>,
+    role: PlayerRole,
     state: Arc<AtomicBool>,
     position: Arc<AtomicU64>,
     duration: Arc<AtomicU64>,
     volume: Arc<AtomicU64>,
+    queue: Arc<Mutex<PlaybackQueue>>,
 ) {
     let mut audio_output: Option<Box<dyn AudioOutput>> = None;
-    let mut current_device: Option<String> = None;
     let mut is_paused = false;
     let mut last_timestamp_emit = Instant::now();
+    let mut last_meter_emit = Instant::now();
 
     // Decodificador y estado de reproducción
     let mut decoder_state: Option<DecoderState> = None;
 
+    // Siguiente track precargado (gapless) y formato (sample rate, canales) pedido al output;
+    // con el modo bit-perfect es el del archivo
+    let mut next_track: Option<PreloadedTrack> = None;
+    let mut output_format: Option<(u32, u16)> = None;
+
+    // Cola: si next_track salió de la cola y último path intentado (para no reintentar fallos)
+    let mut next_from_queue = false;
+    let mut queue_preload_path: Option<String> = None;
+
+    // Crossfade: configuración inicial desde settings.json y mezcla en curso
+    let config = AppConfig::load();
+
+    // Dispositivo preferido (el principal lo toma de settings.json) y el que suena;
+    // difieren mientras el preferido está desconectado
+    let mut preferred_device = match role {
+        PlayerRole::Main => device_from_setting(&config.audio.output_device),
+        PlayerRole::Preview => None,
+    };
+    let mut current_device = preferred_device.clone();
+
+    // Buffer, sample rate y perfil de latencia con los que se abre el output
+    let mut output_settings = OutputSettings::from(&config.audio);
+    let mut crossfade_settings = CrossfadeSettings::from(&config.audio);
+    if role == PlayerRole::Preview {
+        // La pre-escucha nunca encadena tracks con crossfade
+        crossfade_settings.enabled = false;
+    }
+    let mut crossfade: Option<ActiveCrossfade> = None;
+
+    // Pitch fader: arranca en 0% con el key lock guardado
+    let tempo_settings = TempoSettings::from(&config.audio);
+    let mut tempo = TempoProcessor::new(tempo_settings.rate(), tempo_settings.key_lock);
+
+    // Normalización de loudness: ganancia por track (el limitador va en la cadena DSP)
+    let mut normalization = NormalizationSettings::from(&config.audio);
+
+    // Cadena DSP (preamp, EQ, limitador). La pre-escucha no aplica el EQ del usuario
+    let mut dsp = match role {
+        PlayerRole::Main => DspChain::new(config.audio.dsp.clone()),
+        PlayerRole::Preview => DspChain::new(DspSettings::default()),
+    };
+    dsp.set_limiter_required(normalization.enabled);
+
+    // Loop activo sobre el track actual (sustituye a decode_next_frame)
+    let mut active_loop: Option<ActiveLoop> = None;
+
+    // Hot cue mantenido en pre-escucha (CDJ)
+    let mut hot_cues = HotCueState::new();
+
+    // Conversión al sample rate del output (bypass si coincide con el del archivo)
+    let mut resampler = RateConverter::new();
+
+    // Posición audible: cruza lo escrito con lo que el output ya ha reproducido
+    let mut clock = PlaybackClock::new();
+
+    // Niveles y espectro de lo que suena (después de DSP y volumen)
+    let mut meter = LevelMeter::new();
+
+    // Historial: tiempo escuchado del track actual (solo cuenta el principal)
+    let mut play_threshold = PlayThreshold::from(&config.audio);
+    let mut play_tracker = PlayTracker::new();
+
     loop {
         // Procesar comandos (non-blocking mientras decodificamos)
         match control_rx.try_recv() {
             Ok(event) => {
+                // Recrear el output tras cambiar de dispositivo o de configuración
+                let mut reopen_output = false;
+
+                // AIDEV-NOTE: Un hot cue se traduce a un Seek normal más un cambio de
+                // transporte que se aplica después del seek
+                let (event, transport) = match event {
+                    PlayerControlEvent::JumpToCue { hotkey, trigger } => {
+                        let Some(ds) = decoder_state.as_ref() else {
+                            continue;
+                        };
+                        let cue_position = match trigger {
+                            CueTrigger::Release => None,
+                            _ => hot_cue_position(&app_handle, &ds.path, hotkey),
+                        };
+                        let Some(cmd) = hot_cues.trigger(hotkey, trigger, cue_position, is_paused)
+                        else {
+                            continue;
+                        };
+                        log::info!(
+                            "🎯 Hot cue {} ({:?}) → {:.3}s",
+                            hotkey,
+                            trigger,
+                            cmd.position
+                        );
+
+                        // Un cue fuera del loop activo lo desactiva
+                        if active_loop.as_ref().is_some_and(|lp| {
+                            let region = lp.region();
+                            cmd.position < region.start || cmd.position >= region.end
+                        }) {
+                            active_loop = None;
+                            emit_loop_changed(&app_handle, role, None);
+                        }
+
+                        (
+                            PlayerControlEvent::Seek {
+                                position: cmd.position,
+                            },
+                            cmd.transport,
+                        )
+                    }
+                    event => (event, Transport::Keep),
+                };
+
                 match event {
                     PlayerControlEvent::StreamFile {
                         path,
@@ -49,54 +183,97 @@ pub fn decode_loop<R: tauri::Runtime>(
                         log::info!("🎵 StreamFile: {}", path);
 
                         // Detener decodificación actual
+                        // Un StreamFile explícito invalida el track precargado
                         decoder_state = None;
+                        next_track = None;
+                        next_from_queue = false;
+                        queue_preload_path = None;
+                        crossfade = None;
+                        active_loop = None;
+                        hot_cues.reset();
+                        tempo.reset();
+                        dsp.reset();
+                        resampler.reset();
 
                         // AIDEV-NOTE: Estilo Musicat - probe el archivo primero para obtener sample rate y canales
                         // Luego crear/recrear output con esos parámetros
-                        let (codec_sample_rate, codec_channels) =
-                            match probe_file_sample_rate(&path) {
-                                Ok((rate, channels)) => {
-                                    log::info!("📊 Archivo: {} Hz, {} canales", rate, channels);
-                                    (Some(rate), Some(channels))
+                        let requested_format = match probe_file_sample_rate(&path) {
+                            Ok((rate, channels)) => {
+                                log::info!("📊 Archivo: {} Hz, {} canales", rate, channels);
+                                Some((rate, channels))
+                            }
+                            Err(e) => {
+                                log::error!("❌ Error obteniendo info del archivo: {}", e);
+                                None
+                            }
+                        };
+
+                        // Formato a pedir al dispositivo (el del archivo en modo bit-perfect)
+                        let source_rate = requested_format.map(|(rate, _)| rate);
+                        let requested_format = requested_format
+                            .map(|(rate, channels)| (output_settings.output_rate(rate), channels));
+
+                        // AIDEV-NOTE: Solo recrear el output si cambia el formato. Si coincide,
+                        // basta con vaciar el ring buffer y el dispositivo sigue abierto (sin gap).
+                        let reuse_output = audio_output.is_some()
+                            && requested_format.is_some()
+                            && requested_format == output_format;
+
+                        if reuse_output {
+                            log::info!("♻️ Reutilizando output (mismo formato)");
+                            if let Some(ref output) = audio_output {
+                                output.flush();
+                            }
+                            clock.flush();
+                        } else {
+                            // AIDEV-NOTE: Cerrar output anterior de forma segura antes de crear uno nuevo
+                            // Esto evita race conditions con el callback de audio de cpal
+                            if let Some(ref mut output) = audio_output {
+                                output.stop();
+                            }
+                            // Pequeña pausa para asegurar que el stream de cpal se ha detenido completamente
+                            thread::sleep(Duration::from_millis(10));
+                            audio_output = None; // Destruir output anterior
+                            output_format = None;
+
+                            // Recrear output con el sample rate y canales del archivo
+                            // Esto permite que el dispositivo se configure correctamente si lo soporta
+                            match open_device_output(
+                                &mut current_device,
+                                requested_format.map(|(rate, _)| rate),
+                                requested_format.map(|(_, channels)| channels),
+                                vol,
+                                &output_settings,
+                            ) {
+                                Ok(output) => {
+                                    report_output_config(
+                                        &app_handle,
+                                        role,
+                                        output.as_ref(),
+                                        current_device.as_deref(),
+                                        source_rate,
+                                        &output_settings,
+                                    );
+                                    audio_output = Some(output);
+                                    output_format = requested_format;
+                                    clock.reset();
                                 }
                                 Err(e) => {
-                                    log::error!("❌ Error obteniendo info del archivo: {}", e);
-                                    (None, None)
+                                    log::error!("❌ Error creando output: {}", e);
+                                    emit_error(&app_handle, role, &e.to_string(), true);
+                                    continue;
                                 }
-                            };
-
-                        // AIDEV-NOTE: Cerrar output anterior de forma segura antes de crear uno nuevo
-                        // Esto evita race conditions con el callback de audio de cpal
-                        if let Some(ref mut output) = audio_output {
-                            output.stop();
-                        }
-                        // Pequeña pausa para asegurar que el stream de cpal se ha detenido completamente
-                        thread::sleep(Duration::from_millis(10));
-                        audio_output = None; // Destruir output anterior
-
-                        // Recrear output con el sample rate y canales del archivo
-                        // Esto permite que el dispositivo se configure correctamente si lo soporta
-                        match CpalAudioOutput::new(
-                            current_device.as_deref(),
-                            codec_sample_rate,
-                            codec_channels,
-                            vol,
-                        ) {
-                            Ok(output) => {
-                                audio_output = Some(Box::new(output));
-                            }
-                            Err(e) => {
-                                log::error!("❌ Error creando output: {}", e);
-                                emit_error(&app_handle, &e.to_string(), true);
-                                continue;
                             }
                         }
 
                         // Abrir archivo y preparar decodificador
                         match open_audio_file(&path, seek, audio_output.as_ref().unwrap().as_ref())
                         {
-                            Ok((state_new, dur)) => {
+                            Ok((mut state_new, dur)) => {
+                                state_new.gain = track_gain(&app_handle, &normalization, &path);
+                                active_loop = stored_loop(&app_handle, role, &state_new);
                                 decoder_state = Some(state_new);
+                                play_tracker.start(&path, dur);
                                 duration.store(dur.to_bits(), Ordering::SeqCst);
                                 position.store(seek.unwrap_or(0.0).to_bits(), Ordering::SeqCst);
 
@@ -110,25 +287,44 @@ pub fn decode_loop<R: tauri::Runtime>(
                                 is_paused = false;
                                 volume.store(vol.to_bits(), Ordering::SeqCst);
 
-                                emit_state(&app_handle, true);
+                                emit_state(&app_handle, role, true);
                             }
                             Err(e) => {
                                 log::error!("❌ Error abriendo archivo: {}", e);
-                                emit_error(&app_handle, &e.to_string(), false);
+                                emit_error(&app_handle, role, &e.to_string(), false);
                             }
                         }
                     }
 
                     PlayerControlEvent::Seek { position: seek_pos } => {
+                        // Un seek durante el crossfade lo cancela; el track entrante
+                        // vuelve a quedar precargado para la transición normal
+                        if let Some(xf) = crossfade.take() {
+                            log::info!("↩️ Seek durante crossfade, cancelando mezcla");
+                            next_track = preload_track(&xf.incoming.path, xf.incoming.gain).ok();
+                        }
+                        tempo.reset();
+                        dsp.reset();
+                        resampler.reset();
+                        if let Some(ref mut lp) = active_loop {
+                            lp.reset();
+                        }
+                        // Respuesta inmediata: descartar lo que queda en el ring buffer
+                        if let Some(ref output) = audio_output {
+                            output.flush();
+                        }
+                        clock.flush();
                         if let Some(ref mut ds) = decoder_state {
                             log::info!("🎯 Seek to {}s", seek_pos);
                             if let Err(e) = seek_to_position(ds, seek_pos) {
                                 log::error!("❌ Error en seek: {}", e);
-                                emit_error(&app_handle, &e.to_string(), false);
+                                emit_error(&app_handle, role, &e.to_string(), false);
                             } else {
                                 position.store(seek_pos.to_bits(), Ordering::SeqCst);
+                                play_tracker.seek(seek_pos);
                                 emit_timestamp(
                                     &app_handle,
+                                    role,
                                     seek_pos,
                                     f64::from_bits(duration.load(Ordering::SeqCst)),
                                 );
@@ -144,71 +340,331 @@ pub fn decode_loop<R: tauri::Runtime>(
                     }
 
                     PlayerControlEvent::Pause => {
+                        hot_cues.reset();
                         if let Some(ref output) = audio_output {
                             let _ = output.pause();
                         }
                         state.store(false, Ordering::SeqCst);
                         is_paused = true;
-                        emit_state(&app_handle, false);
+                        emit_state(&app_handle, role, false);
                     }
 
                     PlayerControlEvent::Resume => {
+                        hot_cues.reset();
                         if let Some(ref output) = audio_output {
                             let _ = output.play();
                         }
                         state.store(true, Ordering::SeqCst);
                         is_paused = false;
-                        emit_state(&app_handle, true);
+                        emit_state(&app_handle, role, true);
                     }
 
                     PlayerControlEvent::Stop => {
                         decoder_state = None;
+                        next_track = None;
+                        next_from_queue = false;
+                        queue_preload_path = None;
+                        crossfade = None;
+                        active_loop = None;
+                        hot_cues.reset();
+                        tempo.reset();
+                        dsp.reset();
+                        resampler.reset();
+                        output_format = None;
                         if let Some(ref mut output) = audio_output {
                             output.stop();
                         }
                         audio_output = None;
+                        clock.reset();
+                        play_tracker.stop();
                         state.store(false, Ordering::SeqCst);
                         position.store(0.0f64.to_bits(), Ordering::SeqCst);
                         is_paused = false;
-                        emit_state(&app_handle, false);
+                        emit_state(&app_handle, role, false);
                     }
 
                     PlayerControlEvent::ChangeAudioDevice { device_name } => {
                         log::info!("🔊 Cambiando dispositivo: {:?}", device_name);
-                        current_device = device_name.clone();
+                        preferred_device = device_name.clone();
+                        current_device = device_name;
+                        reopen_output = true;
+                    }
 
-                        // Recrear output con nuevo dispositivo
-                        // Usar el sample rate del decoder actual si existe
-                        let vol = f64::from_bits(volume.load(Ordering::SeqCst));
-                        let codec_sample_rate = decoder_state.as_ref().map(|ds| ds.sample_rate);
-                        // TODO: Almacenar canales en DecoderState para poder usarlos aquí
-                        let codec_channels = None; // Por ahora usar default
+                    PlayerControlEvent::DevicesChanged { devices } => {
+                        let target = resolve_output_device(preferred_device.as_deref(), &devices);
+                        if target != current_device {
+                            match &target {
+                                Some(name) => log::info!("🔌 Volviendo al dispositivo {}", name),
+                                None => log::warn!(
+                                    "⚠️ {:?} desconectado, usando el dispositivo por defecto",
+                                    preferred_device
+                                ),
+                            }
+                            current_device = target;
+                            reopen_output = true;
+                        }
+                    }
 
-                        if audio_output.is_some() {
-                            if let Some(ref mut output) = audio_output {
-                                output.stop();
+                    PlayerControlEvent::SetOutputSettings { settings } => {
+                        let settings = settings.clamped();
+                        log::info!(
+                            "🎚️ Output: buffer {} frames, perfil {:?}, {} Hz, bit-perfect={}",
+                            settings.buffer_size,
+                            settings.profile,
+                            settings.sample_rate,
+                            settings.bit_perfect
+                        );
+                        if settings != output_settings {
+                            output_settings = settings;
+                            reopen_output = true;
+                        }
+                    }
+
+                    PlayerControlEvent::EnqueueNext { path } => {
+                        log::info!("⏭️ EnqueueNext: {}", path);
+                        let gain = track_gain(&app_handle, &normalization, &path);
+                        match preload_track(&path, gain) {
+                            Ok(preloaded) => {
+                                log::info!(
+                                    "✅ Siguiente track precargado: {} Hz, {} canales, {:.1}s",
+                                    preloaded.decoder.sample_rate,
+                                    preloaded.decoder.channels,
+                                    preloaded.duration
+                                );
+                                next_track = Some(preloaded);
+                                next_from_queue = false;
+                            }
+                            Err(e) => {
+                                log::error!("❌ Error precargando siguiente track: {}", e);
+                                next_track = None;
+                                next_from_queue = false;
+                                emit_error(&app_handle, role, &e.to_string(), false);
+                            }
+                        }
+                    }
+
+                    PlayerControlEvent::QueueUpdated => {
+                        // El siguiente de la cola puede haber cambiado: descartar la precarga
+                        if next_from_queue {
+                            next_track = None;
+                            next_from_queue = false;
+                        }
+                        queue_preload_path = None;
+                    }
+
+                    PlayerControlEvent::SetCrossfade { settings } => {
+                        crossfade_settings = settings.clamped();
+                        crossfade_settings.enabled &= role == PlayerRole::Main;
+                        log::info!(
+                            "🎚️ Crossfade: enabled={}, {:.1}s, {:?}",
+                            crossfade_settings.enabled,
+                            crossfade_settings.seconds,
+                            crossfade_settings.curve
+                        );
+                    }
+
+                    PlayerControlEvent::SetTempo { rate } => {
+                        log::info!("🎚️ Tempo: {:.3}x", rate);
+                        tempo.set_rate(rate);
+                    }
+
+                    PlayerControlEvent::SetKeyLock { enabled } => {
+                        log::info!("🔒 Key lock: {}", enabled);
+                        tempo.set_key_lock(enabled);
+                    }
+
+                    PlayerControlEvent::SetNormalization { settings } => {
+                        normalization = settings.clamped();
+                        dsp.set_limiter_required(normalization.enabled);
+                        log::info!(
+                            "🔊 Normalización: enabled={}, objetivo {:.1} LUFS",
+                            normalization.enabled,
+                            normalization.target_lufs
+                        );
+
+                        // Recalcular la ganancia de los tracks ya abiertos
+                        if let Some(ref mut ds) = decoder_state {
+                            ds.gain = track_gain(&app_handle, &normalization, &ds.path);
+                        }
+                        if let Some(ref mut xf) = crossfade {
+                            xf.incoming.gain =
+                                track_gain(&app_handle, &normalization, &xf.incoming.path);
+                        }
+                        if let Some(ref mut next) = next_track {
+                            // El primer paquete ya se decodificó con la ganancia anterior
+                            let gain = track_gain(&app_handle, &normalization, &next.decoder.path);
+                            let ratio = gain / next.decoder.gain;
+                            next.primed.iter_mut().for_each(|s| *s *= ratio);
+                            next.decoder.gain = gain;
+                        }
+                    }
+
+                    PlayerControlEvent::SetDsp { settings } => {
+                        if role == PlayerRole::Main {
+                            log::info!(
+                                "🎛️ DSP: enabled={}, preamp {:.1} dB, limitador={}",
+                                settings.enabled,
+                                settings.preamp_db,
+                                settings.limiter_enabled
+                            );
+                            dsp.set_settings(settings);
+                        }
+                    }
+
+                    PlayerControlEvent::SetLoop { path, region } => {
+                        let Some(ds) = decoder_state.as_ref().filter(|ds| ds.path == path) else {
+                            log::warn!("⚠️ Loop ignorado: {} no está sonando", path);
+                            continue;
+                        };
+                        if !region.is_valid() {
+                            log::warn!(
+                                "⚠️ Loop ignorado: {:.3}s → {:.3}s (mínimo {} ms)",
+                                region.start,
+                                region.end,
+                                LOOP_MIN_MS
+                            );
+                            continue;
+                        }
+                        log::info!("🔁 Loop: {:.3}s → {:.3}s", region.start, region.end);
+
+                        // Redimensionar en vivo: la cola retenida del pase anterior se escribe ya
+                        let tail = match active_loop.as_mut() {
+                            Some(lp) => lp.set_region(region),
+                            None => {
+                                active_loop =
+                                    Some(ActiveLoop::new(region, ds.sample_rate, ds.channels));
+                                Vec::new()
                             }
-                            match CpalAudioOutput::new(
+                        };
+                        if let Some(ref output) = audio_output {
+                            write_processed(
+                                output.as_ref(),
+                                &tail,
+                                ds.sample_rate,
+                                ds.channels,
+                                &mut tempo,
+                                &mut dsp,
+                                &mut resampler,
+                                &mut clock,
+                                &mut meter,
+                            );
+                        }
+                        emit_loop_changed(&app_handle, role, Some(region));
+                    }
+
+                    PlayerControlEvent::ClearLoop { path } => {
+                        let Some(ds) = decoder_state.as_ref().filter(|ds| ds.path == path) else {
+                            continue;
+                        };
+                        if let Some(lp) = active_loop.take() {
+                            log::info!("➡️ Loop desactivado");
+                            if let Some(ref output) = audio_output {
+                                write_processed(
+                                    output.as_ref(),
+                                    &lp.into_tail(),
+                                    ds.sample_rate,
+                                    ds.channels,
+                                    &mut tempo,
+                                    &mut dsp,
+                                    &mut resampler,
+                                    &mut clock,
+                                    &mut meter,
+                                );
+                            }
+                            emit_loop_changed(&app_handle, role, None);
+                        }
+                    }
+
+                    PlayerControlEvent::JumpToCue { .. } => {
+                        // Ya traducido a Seek arriba
+                    }
+
+                    PlayerControlEvent::SetPlayThreshold { threshold } => {
+                        play_threshold = threshold.clamped();
+                        log::info!(
+                            "📈 Umbral de reproducción: {:.0}% o {:.0}s",
+                            play_threshold.percent,
+                            play_threshold.seconds
+                        );
+                    }
+                }
+
+                // AIDEV-NOTE: El decoder va por delante de lo que suena; lo que quedaba
+                // en el buffer del output anterior se pierde, así que se vuelve a la
+                // última posición audible para no saltar audio al cambiar de dispositivo
+                if reopen_output && audio_output.is_some() {
+                    if let Some(ref mut output) = audio_output {
+                        output.stop();
+                    }
+                    audio_output = None;
+
+                    // Usar el sample rate del decoder actual si existe
+                    let vol = f64::from_bits(volume.load(Ordering::SeqCst));
+                    let codec_sample_rate = decoder_state.as_ref().map(|ds| ds.sample_rate);
+                    let requested_format = decoder_state
+                        .as_ref()
+                        .map(|ds| (output_settings.output_rate(ds.sample_rate), ds.channels));
+
+                    match open_device_output(
+                        &mut current_device,
+                        requested_format.map(|(rate, _)| rate),
+                        requested_format.map(|(_, channels)| channels),
+                        vol,
+                        &output_settings,
+                    ) {
+                        Ok(output) => {
+                            report_output_config(
+                                &app_handle,
+                                role,
+                                output.as_ref(),
                                 current_device.as_deref(),
                                 codec_sample_rate,
-                                codec_channels,
-                                vol,
-                            ) {
-                                Ok(output) => {
-                                    audio_output = Some(Box::new(output));
-                                    if !is_paused {
-                                        let _ = audio_output.as_ref().unwrap().play();
-                                    }
+                                &output_settings,
+                            );
+                            audio_output = Some(output);
+                            output_format = requested_format;
+                            clock.reset();
+                            resampler.reset();
+                            if let Some(ref mut ds) = decoder_state {
+                                if let Some(xf) = crossfade.take() {
+                                    next_track =
+                                        preload_track(&xf.incoming.path, xf.incoming.gain).ok();
                                 }
-                                Err(e) => {
-                                    log::error!("❌ Error cambiando dispositivo: {}", e);
-                                    emit_error(&app_handle, &e.to_string(), true);
-                                    audio_output = None;
+                                let heard = f64::from_bits(position.load(Ordering::SeqCst));
+                                if let Err(e) = seek_to_position(ds, heard) {
+                                    log::warn!(
+                                        "⚠️ No se pudo recuperar la posición {:.3}s: {}",
+                                        heard,
+                                        e
+                                    );
                                 }
+                                tempo.reset();
+                                dsp.reset();
+                                if let Some(ref mut lp) = active_loop {
+                                    lp.reset();
+                                }
+                            }
+                            if !is_paused {
+                                let _ = audio_output.as_ref().unwrap().play();
                             }
                         }
+                        Err(e) => {
+                            log::error!("❌ Error cambiando dispositivo: {}", e);
+                            emit_error(&app_handle, role, &e.to_string(), true);
+                            output_format = None;
+                        }
                     }
                 }
+
+                // Transporte pedido por el hot cue, una vez hecho el seek
+                if let Some(play) = transport.playing().filter(|play| *play == is_paused) {
+                    if let Some(ref output) = audio_output {
+                        let _ = if play { output.play() } else { output.pause() };
+                    }
+                    state.store(play, Ordering::SeqCst);
+                    is_paused = !play;
+                    emit_state(&app_handle, role, play);
+                }
             }
             Err(TryRecvError::Empty) => {
                 // No hay comandos, continuar decodificando
@@ -222,36 +678,333 @@ pub fn decode_loop<R: tauri::Runtime>(
 
         // Decodificar siguiente frame si no está pausado
         if !is_paused {
-            if let (Some(ref mut ds), Some(ref output)) = (&mut decoder_state, &audio_output) {
-                match decode_next_frame(ds, output.as_ref()) {
-                    Ok(DecodeResult::Continue(pos)) => {
-                        position.store(pos.to_bits(), Ordering::SeqCst);
-
-                        // Emitir timestamp periódicamente
-                        if last_timestamp_emit.elapsed()
-                            >= Duration::from_millis(TIMESTAMP_INTERVAL_MS)
-                        {
-                            let dur = f64::from_bits(duration.load(Ordering::SeqCst));
-                            emit_timestamp(&app_handle, pos, dur);
-                            last_timestamp_emit = Instant::now();
+            // AIDEV-NOTE: El crossfade solo cambia de dónde salen los samples; la
+            // posición, el medidor, el conteo de escuchas y la precarga se
+            // actualizan igual que sin él (con la posición del track saliente).
+            let result = if crossfade.is_some() {
+                // Mezclar ambos tracks mientras dura el crossfade
+                let mut mixed_position = None;
+                if let (Some(xf), Some(ds), Some(output)) =
+                    (crossfade.as_mut(), decoder_state.as_mut(), audio_output.as_ref())
+                {
+                    match xf.step(ds) {
+                        Ok(mixed) => {
+                            write_processed(
+                                output.as_ref(),
+                                &mixed,
+                                ds.sample_rate,
+                                ds.channels,
+                                &mut tempo,
+                                &mut dsp,
+                                &mut resampler,
+                                &mut clock,
+                                &mut meter,
+                            );
+                            mixed_position = xf.outgoing_position();
+                            if let Some(pos) = mixed_position {
+                                clock.mark(pos);
+                            }
+                        }
+                        // El lado que falló ya se ha dado por terminado
+                        Err(e) => log::warn!("⚠️ Error decodificando durante crossfade: {}", e),
+                    }
+
+                    if xf.mixer.is_complete() {
+                        let rest = xf.mixer.take_remaining_incoming();
+                        write_processed(
+                            output.as_ref(),
+                            &rest,
+                            ds.sample_rate,
+                            ds.channels,
+                            &mut tempo,
+                            &mut dsp,
+                            &mut resampler,
+                            &mut clock,
+                            &mut meter,
+                        );
+                    }
+                } else {
+                    // Sin track saliente u output no hay nada que mezclar
+                    crossfade = None;
+                }
+
+                if crossfade.as_ref().is_some_and(|xf| xf.mixer.is_complete()) {
+                    if let Some(xf) = crossfade.take() {
+                        log::info!("🔀 Crossfade completado: {}", xf.incoming.path);
+                        emit_track_changed(
+                            &app_handle,
+                            role,
+                            &xf.incoming.path,
+                            xf.incoming_duration,
+                        );
+                        duration.store(xf.incoming_duration.to_bits(), Ordering::SeqCst);
+                        clock.flush();
+                        play_tracker.start(&xf.incoming.path, xf.incoming_duration);
+                        active_loop = stored_loop(&app_handle, role, &xf.incoming);
+                        decoder_state = Some(xf.incoming);
+                    }
+                    continue;
+                }
+
+                match mixed_position {
+                    Some(pos) => Some(Ok(DecodeResult::Continue(pos))),
+                    // Aún no hay nada mezclado (o se acaba de cancelar)
+                    None => continue,
+                }
+            } else {
+                match (&mut decoder_state, &audio_output) {
+                    (Some(ds), Some(output)) => Some(match active_loop.as_mut() {
+                        Some(lp) => lp.step(ds).map(|step| {
+                            write_processed(
+                                output.as_ref(),
+                                &step.samples,
+                                ds.sample_rate,
+                                ds.channels,
+                                &mut tempo,
+                                &mut dsp,
+                                &mut resampler,
+                                &mut clock,
+                                &mut meter,
+                            );
+                            clock.mark(step.position);
+                            DecodeResult::Continue(step.position)
+                        }),
+                        None => decode_next_frame(
+                            ds,
+                            output.as_ref(),
+                            &mut tempo,
+                            &mut dsp,
+                            &mut resampler,
+                            &mut clock,
+                            &mut meter,
+                        ),
+                    }),
+                    _ => None,
+                }
+            };
+
+            match result {
+                Some(Ok(DecodeResult::Continue(pos))) => {
+                    // AIDEV-NOTE: `pos` es lo decodificado (va por delante del ring buffer
+                    // y la latencia del dispositivo); al frontend se reporta lo que suena.
+                    // El crossfade sí se dispara con la posición decodificada.
+                    let audible = audio_output
+                        .as_ref()
+                        .and_then(|output| {
+                            clock.audible_position(
+                                output.consumed_samples(),
+                                output.latency_samples(),
+                            )
+                        })
+                        .unwrap_or(pos);
+                    position.store(audible.to_bits(), Ordering::SeqCst);
+
+                    if role == PlayerRole::Main {
+                        if let Some(play) = play_tracker.update(audible, &play_threshold) {
+                            record_play(&app_handle, role, play);
+                        }
+                    }
+
+                    // Emitir timestamp periódicamente
+                    if last_timestamp_emit.elapsed() >= Duration::from_millis(TIMESTAMP_INTERVAL_MS)
+                    {
+                        let dur = f64::from_bits(duration.load(Ordering::SeqCst));
+                        emit_timestamp(&app_handle, role, audible, dur);
+                        last_timestamp_emit = Instant::now();
+                    }
+
+                    // Emitir niveles y espectro a ritmo fijo
+                    if last_meter_emit.elapsed() >= Duration::from_millis(METER_INTERVAL_MS) {
+                        if let Some(output) = audio_output.as_ref() {
+                            let heard = output
+                                .consumed_samples()
+                                .saturating_sub(output.latency_samples());
+                            let volume = output.get_volume() as f32;
+                            if let Some(payload) = meter.measure(heard, volume) {
+                                emit_meter(&app_handle, role, payload);
+                            }
+                        }
+                        last_meter_emit = Instant::now();
+                    }
+
+                    // Precargar el siguiente de la cola para la transición gapless/crossfade
+                    if next_track.is_none() {
+                        let upcoming = queue.lock().unwrap().peek_next(true);
+                        if let Some(item) = upcoming {
+                            if queue_preload_path.as_deref() != Some(item.path.as_str()) {
+                                queue_preload_path = Some(item.path.clone());
+                                let gain = track_gain(&app_handle, &normalization, &item.path);
+                                match preload_track(&item.path, gain) {
+                                    Ok(preloaded) => {
+                                        log::info!("📥 Siguiente de la cola precargado: {}", item.path);
+                                        next_track = Some(preloaded);
+                                        next_from_queue = true;
+                                    }
+                                    Err(e) => {
+                                        log::warn!("⚠️ Error precargando siguiente de la cola: {}", e);
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    // Iniciar crossfade al entrar en la ventana final del track
+                    // AIDEV-NOTE: Formatos distintos no se pueden mezclar sin resampling;
+                    // en ese caso se usa la transición gapless en el fin de track.
+                    // La ventana es de tiempo real: con el pitch fader abarca más/menos track
+                    let dur = f64::from_bits(duration.load(Ordering::SeqCst));
+                    let format_matches = match (&next_track, &decoder_state) {
+                        (Some(next), Some(ds)) => {
+                            (next.decoder.sample_rate, next.decoder.channels)
+                                == (ds.sample_rate, ds.channels)
+                        }
+                        _ => false,
+                    };
+                    if crossfade_settings.is_active()
+                        && crossfade.is_none()
+                        && active_loop.is_none()
+                        && format_matches
+                        && dur > 0.0
+                        && pos >= dur - crossfade_settings.seconds * tempo.rate()
+                    {
+                        if let Some(next) = take_next_track(
+                            &app_handle,
+                            role,
+                            &normalization,
+                            &mut next_track,
+                            &mut next_from_queue,
+                            &queue,
+                        ) {
+                            log::info!("🔀 Iniciando crossfade hacia: {}", next.decoder.path);
+                            queue_preload_path = None;
+                            // Mezclar el equivalente en tiempo de track a la ventana pedida
+                            let window = CrossfadeSettings {
+                                seconds: crossfade_settings.seconds * tempo.rate(),
+                                ..crossfade_settings
+                            };
+                            crossfade = Some(ActiveCrossfade::new(next, window));
                         }
                     }
-                    Ok(DecodeResult::EndOfTrack) => {
+                }
+                Some(Ok(DecodeResult::EndOfTrack)) => {
+                    queue_preload_path = None;
+                    if let Some(next) = take_next_track(
+                        &app_handle,
+                        role,
+                        &normalization,
+                        &mut next_track,
+                        &mut next_from_queue,
+                        &queue,
+                    ) {
+                        // AIDEV-NOTE: Transición gapless - el siguiente track se escribe al
+                        // mismo ring buffer sin detener el stream de cpal
+                        let next_format = (
+                            output_settings.output_rate(next.decoder.sample_rate),
+                            next.decoder.channels,
+                        );
+                        if output_format != Some(next_format) {
+                            log::info!(
+                                "🔁 Formato distinto ({} Hz, {} canales), recreando output",
+                                next_format.0,
+                                next_format.1
+                            );
+                            if let Some(ref output) = audio_output {
+                                // Dejar sonar la cola del track actual antes de cerrar el stream
+                                thread::sleep(ring_buffer_duration(output.as_ref()));
+                            }
+                            if let Some(ref mut output) = audio_output {
+                                output.stop();
+                            }
+                            audio_output = None;
+                            output_format = None;
+
+                            let vol = f64::from_bits(volume.load(Ordering::SeqCst));
+                            match open_device_output(
+                                &mut current_device,
+                                Some(next_format.0),
+                                Some(next_format.1),
+                                vol,
+                                &output_settings,
+                            ) {
+                                Ok(output) => {
+                                    report_output_config(
+                                        &app_handle,
+                                        role,
+                                        output.as_ref(),
+                                        current_device.as_deref(),
+                                        Some(next.decoder.sample_rate),
+                                        &output_settings,
+                                    );
+                                    let _ = output.play();
+                                    audio_output = Some(output);
+                                    output_format = Some(next_format);
+                                    clock.reset();
+                                }
+                                Err(e) => {
+                                    log::error!("❌ Error creando output: {}", e);
+                                    emit_error(&app_handle, role, &e.to_string(), true);
+                                    decoder_state = None;
+                                    state.store(false, Ordering::SeqCst);
+                                    emit_state(&app_handle, role, false);
+                                    continue;
+                                }
+                            }
+                        }
+
+                        log::info!("⏭️ Transición gapless a: {}", next.decoder.path);
+                        // La cola del track anterior que queda en el buffer cuenta como 0s
+                        clock.flush();
+                        resampler.reset();
+                        if let Some(ref output) = audio_output {
+                            write_processed(
+                                output.as_ref(),
+                                &next.primed,
+                                next.decoder.sample_rate,
+                                next.decoder.channels,
+                                &mut tempo,
+                                &mut dsp,
+                                &mut resampler,
+                                &mut clock,
+                                &mut meter,
+                            );
+                        }
+                        emit_track_changed(
+                            &app_handle,
+                            role,
+                            &next.decoder.path,
+                            next.duration,
+                        );
+                        emit_timestamp(&app_handle, role, 0.0, next.duration);
+                        last_timestamp_emit = Instant::now();
+
+                        duration.store(next.duration.to_bits(), Ordering::SeqCst);
+                        position.store(0.0f64.to_bits(), Ordering::SeqCst);
+                        play_tracker.start(&next.decoder.path, next.duration);
+                        active_loop = stored_loop(&app_handle, role, &next.decoder);
+                        decoder_state = Some(next.decoder);
+                    } else {
                         log::info!("🏁 Fin del track");
                         decoder_state = None;
+                        play_tracker.stop();
                         state.store(false, Ordering::SeqCst);
                         position.store(0.0f64.to_bits(), Ordering::SeqCst);
-                        emit_end_of_track(&app_handle);
-                        emit_state(&app_handle, false);
+                        emit_end_of_track(&app_handle, role);
+                        emit_state(&app_handle, role, false);
                     }
-                    Err(e) => {
-                        log::warn!("⚠️ Error decodificando: {}", e);
-                        // No es crítico, intentar continuar
+                }
+                Some(Err(e)) => {
+                    log::warn!("⚠️ Error decodificando: {}", e);
+                    // No es crítico, intentar continuar; un loop que no puede volver
+                    // a su inicio se desactiva para no repetir el error en cada paquete
+                    if active_loop.take().is_some() {
+                        emit_error(&app_handle, role, &e.to_string(), false);
+                        emit_loop_changed(&app_handle, role, None);
                     }
                 }
-            } else {
-                // Sin decodificador activo, esperar un poco
-                thread::sleep(Duration::from_millis(10));
+                None => {
+                    // Sin decodificador activo, esperar un poco
+                    thread::sleep(Duration::from_millis(10));
+                }
             }
         } else {
             // Pausado, esperar
@@ -259,3 +1012,272 @@ pub fn decode_loop<R: tauri::Runtime>(
         }
     }
 }
+
+/// Abre el output en el dispositivo activo, o en el por defecto si no está
+///
+/// AIDEV-NOTE: Si el dispositivo ha desaparecido se deja `current_device` en
+/// `None`; el monitor de hot-plug vuelve al preferido cuando reaparece.
+fn open_device_output(
+    current_device: &mut Option<String>,
+    desired_sample_rate: Option<u32>,
+    desired_channels: Option<u16>,
+    initial_volume: f64,
+    settings: &OutputSettings,
+) -> AudioResult<Box<dyn AudioOutput>> {
+    match open_output(
+        current_device.as_deref(),
+        desired_sample_rate,
+        desired_channels,
+        initial_volume,
+        settings,
+    ) {
+        Err(e) if current_device.is_some() => {
+            log::warn!(
+                "⚠️ No se pudo abrir {:?} ({}), usando el dispositivo por defecto",
+                current_device,
+                e
+            );
+            *current_device = None;
+            open_output(
+                None,
+                desired_sample_rate,
+                desired_channels,
+                initial_volume,
+                settings,
+            )
+        }
+        result => result,
+    }
+}
+
+/// Informa al frontend de la configuración con la que quedó abierto el output
+///
+/// AIDEV-NOTE: El dispositivo puede no aceptar lo pedido (sample rate o buffer
+/// fuera de rango); se reporta lo efectivo, no la configuración guardada.
+fn report_output_config<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    role: PlayerRole,
+    output: &dyn AudioOutput,
+    device: Option<&str>,
+    source_sample_rate: Option<u32>,
+    settings: &OutputSettings,
+) {
+    let payload = OutputConfigPayload {
+        device: device.map(str::to_string),
+        sample_rate: output.sample_rate(),
+        source_sample_rate,
+        channels: output.channels(),
+        buffer_frames: output.buffer_frames(),
+        ring_buffer_size: output.ring_buffer_size(),
+        latency_ms: ring_buffer_duration(output).as_secs_f64() * 1000.0,
+        profile: settings.profile,
+        resampling: source_sample_rate.is_some_and(|rate| rate != output.sample_rate()),
+    };
+    log::info!(
+        "🔊 Output efectivo: {} Hz, buffer {:?} frames, {:.0} ms, resampling={}",
+        payload.sample_rate,
+        payload.buffer_frames,
+        payload.latency_ms,
+        payload.resampling
+    );
+    emit_output_config(app_handle, role, payload);
+}
+
+/// Toma el track para la siguiente transición, avanzando la cola si procede
+///
+/// AIDEV-NOTE: Un track precargado manualmente (`EnqueueNext`) tiene prioridad y no
+/// mueve la cola. Si no hay nada precargado se intenta abrir el siguiente de la cola.
+fn take_next_track<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    role: PlayerRole,
+    normalization: &NormalizationSettings,
+    next_track: &mut Option<PreloadedTrack>,
+    next_from_queue: &mut bool,
+    queue: &Mutex<PlaybackQueue>,
+) -> Option<PreloadedTrack> {
+    let from_queue = std::mem::take(next_from_queue);
+
+    if let Some(next) = next_track.take() {
+        if from_queue {
+            advance_queue(app_handle, role, queue);
+        }
+        return Some(next);
+    }
+
+    let item = advance_queue(app_handle, role, queue)?;
+    match preload_track(&item.path, track_gain(app_handle, normalization, &item.path)) {
+        Ok(next) => Some(next),
+        Err(e) => {
+            log::error!("❌ Error abriendo siguiente de la cola: {}", e);
+            emit_error(app_handle, role, &e.to_string(), false);
+            None
+        }
+    }
+}
+
+/// Avanza la cola tras un fin de track, notifica al frontend y la persiste
+fn advance_queue<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    role: PlayerRole,
+    queue: &Mutex<PlaybackQueue>,
+) -> Option<QueueItem> {
+    let (item, snapshot, saved) = {
+        let mut queue = queue.lock().unwrap();
+        let item = queue.advance(true);
+        (item, queue.snapshot(), queue.to_saved())
+    };
+
+    if item.is_some() {
+        emit_queue_changed(app_handle, role, &snapshot);
+
+        // Guardar en un thread aparte para no bloquear la decodificación
+        if let Some(pool) = app_handle.try_state::<DbPool>() {
+            let pool = pool.inner().clone();
+            thread::spawn(move || match pool.get() {
+                Ok(mut conn) => {
+                    if let Err(e) = queries::save_queue(&mut conn, &saved) {
+                        log::warn!("⚠️ Error guardando cola: {}", e);
+                    }
+                }
+                Err(e) => log::warn!("⚠️ Error obteniendo conexión: {}", e),
+            });
+        }
+    }
+
+    item
+}
+
+/// Registra una escucha en el historial y actualiza el play count del track
+///
+/// AIDEV-NOTE: Se escribe en un thread aparte (como la cola) para no bloquear
+/// la decodificación. Los archivos fuera de la biblioteca no se registran.
+fn record_play<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    role: PlayerRole,
+    play: CompletedPlay,
+) {
+    let Some(pool) = app_handle.try_state::<DbPool>() else {
+        return;
+    };
+    let pool = pool.inner().clone();
+    let app_handle = app_handle.clone();
+    thread::spawn(move || {
+        let result = pool
+            .get()
+            .map_err(|e| e.to_string())
+            .and_then(|mut conn| {
+                queries::record_play_by_path(&mut conn, &play.path, play.seconds_played)
+                    .map_err(|e| e.to_string())
+            });
+        match result {
+            Ok(Some(_)) => {
+                log::info!(
+                    "📈 Reproducción registrada ({:.0}s): {}",
+                    play.seconds_played,
+                    play.path
+                );
+                emit_play_recorded(&app_handle, role, &play.path, play.seconds_played);
+            }
+            Ok(None) => log::debug!("Reproducción fuera de la biblioteca: {}", play.path),
+            Err(e) => log::warn!("⚠️ Error registrando reproducción: {}", e),
+        }
+    });
+}
+
+/// Loop activo guardado (`is_active`) para el track que empieza a sonar
+///
+/// AIDEV-NOTE: Solo el reproductor principal honra los loops guardados; la
+/// pre-escucha siempre suena de corrido. Notifica al frontend en ambos casos.
+fn stored_loop<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    role: PlayerRole,
+    ds: &DecoderState,
+) -> Option<ActiveLoop> {
+    if role != PlayerRole::Main {
+        return None;
+    }
+
+    let stored = app_handle.try_state::<DbPool>().and_then(|pool| {
+        let conn = pool.get().ok()?;
+        match queries::get_active_loop_by_path(&conn, &ds.path) {
+            Ok(stored) => stored,
+            Err(e) => {
+                log::warn!("⚠️ Error leyendo loops de {}: {}", ds.path, e);
+                None
+            }
+        }
+    });
+
+    let region = stored
+        .map(|item| LoopRegion {
+            start: item.loop_start,
+            end: item.loop_end,
+        })
+        .filter(LoopRegion::is_valid);
+    if let Some(region) = region {
+        log::info!("🔁 Loop guardado: {:.3}s → {:.3}s", region.start, region.end);
+    }
+    emit_loop_changed(app_handle, role, region);
+
+    region.map(|region| ActiveLoop::new(region, ds.sample_rate, ds.channels))
+}
+
+/// Posición guardada del hot cue `hotkey` del track `path`
+fn hot_cue_position<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    path: &str,
+    hotkey: u8,
+) -> Option<f64> {
+    let pool = app_handle.try_state::<DbPool>()?;
+    let conn = pool.get().ok()?;
+    match queries::get_hot_cue_by_path(&conn, path, hotkey as i32) {
+        Ok(Some(cue)) => Some(cue.position),
+        Ok(None) => {
+            log::warn!("⚠️ Hot cue {} sin asignar en {}", hotkey, path);
+            None
+        }
+        Err(e) => {
+            log::warn!("⚠️ Error leyendo hot cues de {}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Ganancia de normalización de un track según su loudness guardado
+///
+/// AIDEV-NOTE: Solo consulta SQLite con la normalización activa. Un track sin
+/// análisis (ni ReplayGain importado) suena sin ganancia.
+fn track_gain<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    normalization: &NormalizationSettings,
+    path: &str,
+) -> f32 {
+    if !normalization.enabled {
+        return 1.0;
+    }
+
+    let loudness = app_handle.try_state::<DbPool>().and_then(|pool| {
+        let conn = pool.get().ok()?;
+        match queries::get_loudness_by_path(&conn, path) {
+            Ok(loudness) => loudness,
+            Err(e) => {
+                log::warn!("⚠️ Error leyendo loudness de {}: {}", path, e);
+                None
+            }
+        }
+    });
+
+    let loudness = loudness.as_ref().map(LoudnessAnalysis::from);
+    let gain = normalization.track_gain(loudness.as_ref());
+    log::info!("🔊 Ganancia de normalización {:.2} para {}", gain, path);
+    gain
+}
+
+/// Tiempo aproximado que tarda en sonar un ring buffer lleno
+fn ring_buffer_duration(output: &dyn AudioOutput) -> Duration {
+    let samples_per_second = output.sample_rate() as f64 * output.channels().max(1) as f64;
+    if samples_per_second <= 0.0 {
+        return Duration::ZERO;
+    }
+    Duration::from_secs_f64(output.ring_buffer_size() as f64 / samples_per_second)
+}
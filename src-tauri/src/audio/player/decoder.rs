@@ -13,10 +13,15 @@ use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
 use symphonia::core::units::Time;
 
+use crate::audio::dsp::DspChain;
 use crate::audio::error::{AudioError, AudioResult};
 use crate::audio::output::AudioOutput;
 
-use super::state::{DecodeResult, DecoderState};
+use super::clock::PlaybackClock;
+use super::meter::LevelMeter;
+use super::rate::RateConverter;
+use super::state::{DecodeResult, DecodedPacket, DecoderState, PreloadedTrack};
+use super::tempo::TempoProcessor;
 
 /// Obtiene el sample rate y número de canales de un archivo sin crear el decoder completo
 ///
@@ -70,6 +75,38 @@ pub fn open_audio_file(
     seek: Option<f64>,
     output: &dyn AudioOutput,
 ) -> AudioResult<(DecoderState, f64)> {
+    let (decoder_state, duration) = open_decoder(path, seek)?;
+
+    let output_sample_rate = output.sample_rate();
+    let output_channels = output.channels() as usize;
+
+    log::info!("========== AUDIO STREAM INFO ==========");
+    log::info!("Codec sample rate: {}", decoder_state.sample_rate);
+    log::info!("Output device sample rate: {}", output_sample_rate);
+    log::info!("Output channels: {}", output_channels);
+    log::info!("Codec channels: {}", decoder_state.channels);
+
+    // AIDEV-NOTE: NO crear resampler aquí.
+    // Si el dispositivo no soporta el sample rate del archivo (o el modo bit-perfect
+    // está desactivado) la conversión la hace `RateConverter` en `write_processed`.
+    if decoder_state.sample_rate != output_sample_rate {
+        log::info!(
+            "🔄 Sample rate distinto: codec {} Hz vs device {} Hz, resampling en la salida",
+            decoder_state.sample_rate,
+            output_sample_rate
+        );
+    } else {
+        log::info!("✅ Sample rates match - no resampling needed");
+    }
+
+    Ok((decoder_state, duration))
+}
+
+/// Abre un archivo y crea su decodificador, sin depender de un output
+///
+/// AIDEV-NOTE: `enable_gapless` recorta el encoder delay/padding (MP3 LAME, AAC)
+/// para que la transición entre tracks consecutivos no tenga silencio extra.
+pub fn open_decoder(path: &str, seek: Option<f64>) -> AudioResult<(DecoderState, f64)> {
     let file = std::fs::File::open(path)?;
     let mss = MediaSourceStream::new(Box::new(file), Default::default());
 
@@ -81,7 +118,7 @@ pub fn open_audio_file(
 
     // Probar formato
     let format_opts = FormatOptions {
-        enable_gapless: false,
+        enable_gapless: true,
         ..Default::default()
     };
     let metadata_opts = MetadataOptions::default();
@@ -102,42 +139,20 @@ pub fn open_audio_file(
     let track_id = track.id;
     let codec_params = &track.codec_params;
 
-    // Obtener time_base y sample rates
+    // Obtener time_base, sample rate y canales del codec
     let time_base = codec_params
         .time_base
         .unwrap_or(symphonia::core::units::TimeBase::new(1, 44100));
-    let codec_sample_rate = codec_params.sample_rate.unwrap_or(44100);
-    let output_sample_rate = output.sample_rate();
-    let output_channels = output.channels() as usize;
-
-    log::info!("========== AUDIO STREAM INFO ==========");
-    log::info!("Codec sample rate: {:?}", codec_params.sample_rate);
-    log::info!("Output device sample rate: {}", output_sample_rate);
-    log::info!("Output channels: {}", output_channels);
-    log::info!("Codec channels: {:?}", codec_params.channels);
-
-    // AIDEV-NOTE: NO crear resampler aquí.
-    // Confiamos en que el dispositivo está configurado al sample rate correcto del archivo.
-    // Si los sample rates no coinciden, es porque el dispositivo NO soporta el sample rate del archivo,
-    // y en ese caso el audio sonará más rápido/lento (bug conocido que necesita implementación
-    // de resampler personalizado estilo Musicat, no rubato).
-    if codec_sample_rate != output_sample_rate {
-        log::warn!(
-            "⚠️ SAMPLE RATE MISMATCH: codec {} Hz vs device {} Hz",
-            codec_sample_rate,
-            output_sample_rate
-        );
-        log::warn!("⚠️ Audio puede sonar a velocidad incorrecta - el dispositivo debería haberse configurado al sample rate del archivo");
-    } else {
-        log::info!("✅ Sample rates match - no resampling needed");
-    }
-
-    // Para duración y otros cálculos, usamos el sample rate del codec
-    let sample_rate = codec_sample_rate;
+    let sample_rate = codec_params.sample_rate.unwrap_or(44100);
+    let channels = codec_params
+        .channels
+        .map(|ch| ch.count() as u16)
+        .unwrap_or(2);
 
     // Calcular duración
     let duration = if let Some(n_frames) = codec_params.n_frames {
-        time_base.calc_time(n_frames).seconds as f64
+        let time = time_base.calc_time(n_frames);
+        time.seconds as f64 + time.frac
     } else {
         0.0
     };
@@ -154,6 +169,9 @@ pub fn open_audio_file(
         track_id,
         time_base,
         sample_rate,
+        channels,
+        path: path.to_string(),
+        gain: 1.0,
     };
 
     // Seek inicial si se especificó
@@ -164,6 +182,36 @@ pub fn open_audio_file(
     Ok((decoder_state, duration))
 }
 
+/// Abre el siguiente track y decodifica su primer paquete por adelantado
+///
+/// AIDEV-NOTE: Se llama al recibir `EnqueueNext`, mientras el track actual sigue sonando.
+/// Así en el fin de track solo hay que escribir `primed` al mismo ring buffer.
+/// `gain` es la ganancia de normalización del track (ya aplicada a `primed`).
+pub fn preload_track(path: &str, gain: f32) -> AudioResult<PreloadedTrack> {
+    let (mut decoder, duration) = open_decoder(path, None)?;
+    decoder.gain = gain;
+
+    // Saltar paquetes vacíos del inicio (p.ej. frames de encoder delay)
+    loop {
+        match decode_packet(&mut decoder)? {
+            DecodedPacket::Samples { samples, .. } if !samples.is_empty() => {
+                return Ok(PreloadedTrack {
+                    decoder,
+                    duration,
+                    primed: samples,
+                });
+            }
+            DecodedPacket::Samples { .. } | DecodedPacket::Skipped(_) => continue,
+            DecodedPacket::EndOfTrack => {
+                return Err(AudioError::DecodingFailed(format!(
+                    "El archivo no contiene audio: {}",
+                    path
+                )));
+            }
+        }
+    }
+}
+
 /// Salta a una posición específica
 pub fn seek_to_position(ds: &mut DecoderState, position: f64) -> AudioResult<()> {
     let seek_to = SeekTo::Time {
@@ -182,17 +230,48 @@ pub fn seek_to_position(ds: &mut DecoderState, position: f64) -> AudioResult<()>
 }
 
 /// Decodifica el siguiente frame y lo escribe al ring buffer
+///
+/// Marca en el reloj la posición del final del frame escrito.
 pub fn decode_next_frame(
     ds: &mut DecoderState,
     output: &dyn AudioOutput,
+    tempo: &mut TempoProcessor,
+    dsp: &mut DspChain,
+    rate: &mut RateConverter,
+    clock: &mut PlaybackClock,
+    meter: &mut LevelMeter,
 ) -> AudioResult<DecodeResult> {
+    match decode_packet(ds)? {
+        DecodedPacket::Samples { samples, position } => {
+            write_processed(
+                output,
+                &samples,
+                ds.sample_rate,
+                ds.channels,
+                tempo,
+                dsp,
+                rate,
+                clock,
+                meter,
+            );
+            let frames = samples.len() / ds.channels.max(1) as usize;
+            clock.mark(position + frames as f64 / ds.sample_rate.max(1) as f64);
+            Ok(DecodeResult::Continue(position))
+        }
+        DecodedPacket::Skipped(position) => Ok(DecodeResult::Continue(position)),
+        DecodedPacket::EndOfTrack => Ok(DecodeResult::EndOfTrack),
+    }
+}
+
+/// Lee y decodifica el siguiente paquete sin escribirlo al ring buffer
+pub fn decode_packet(ds: &mut DecoderState) -> AudioResult<DecodedPacket> {
     // Leer siguiente paquete
     let packet = match ds.format_reader.next_packet() {
         Ok(p) => p,
         Err(symphonia::core::errors::Error::IoError(e))
             if e.kind() == std::io::ErrorKind::UnexpectedEof =>
         {
-            return Ok(DecodeResult::EndOfTrack);
+            return Ok(DecodedPacket::EndOfTrack);
         }
         Err(e) => {
             return Err(AudioError::DecodingFailed(format!(
@@ -202,10 +281,14 @@ pub fn decode_next_frame(
         }
     };
 
+    // Calcular posición del paquete
+    // AIDEV-NOTE: Incluir la fracción: los loops necesitan la posición exacta al sample
+    let time = ds.time_base.calc_time(packet.ts());
+    let pos = time.seconds as f64 + time.frac;
+
     // Ignorar paquetes de otros tracks
     if packet.track_id() != ds.track_id {
-        let pos = ds.time_base.calc_time(packet.ts()).seconds as f64;
-        return Ok(DecodeResult::Continue(pos));
+        return Ok(DecodedPacket::Skipped(pos));
     }
 
     // Decodificar
@@ -221,14 +304,52 @@ pub fn decode_next_frame(
     let mut sample_buf = SampleBuffer::<f32>::new(duration, spec);
     sample_buf.copy_interleaved_ref(decoded);
 
-    let samples = sample_buf.samples();
+    let mut samples = sample_buf.samples().to_vec();
+    if ds.gain != 1.0 {
+        samples.iter_mut().for_each(|s| *s *= ds.gain);
+    }
 
-    // AIDEV-NOTE: Sin resampling - confiamos en que el dispositivo está configurado
-    // al sample rate correcto del archivo (ver probe_file_sample_rate + CpalAudioOutput::new)
+    Ok(DecodedPacket::Samples {
+        samples,
+        position: pos,
+    })
+}
 
-    // Escribir al ring buffer
-    // AIDEV-NOTE: Si el buffer está lleno, esto bloqueará brevemente.
-    // Esto es intencional para sincronizar decode con playback.
+/// Aplica tempo, la cadena DSP y la conversión de sample rate y escribe el
+/// resultado al ring buffer
+///
+/// AIDEV-NOTE: Cadena de salida común a todas las escrituras del decode thread
+/// (frame normal, mezcla de crossfade y primer paquete gapless). Con el pitch
+/// fader a 0%, la cadena DSP en bypass y el output al rate del archivo no copia
+/// los samples. Lo escrito se anota en el reloj de posición y en el medidor; la
+/// marca la pone quien conoce la posición.
+#[allow(clippy::too_many_arguments)]
+pub fn write_processed(
+    output: &dyn AudioOutput,
+    samples: &[f32],
+    sample_rate: u32,
+    channels: u16,
+    tempo: &mut TempoProcessor,
+    dsp: &mut DspChain,
+    rate: &mut RateConverter,
+    clock: &mut PlaybackClock,
+    meter: &mut LevelMeter,
+) {
+    let processed = tempo.process(samples, sample_rate, channels);
+    let processed = dsp.process_cow(processed, sample_rate, channels);
+    let processed = rate.process(processed, sample_rate, output.sample_rate(), channels);
+    write_samples(output, &processed);
+    meter.feed(&processed, clock.written(), output.sample_rate(), channels);
+
+    let track_seconds = samples.len() as f64 / (sample_rate.max(1) as f64 * channels.max(1) as f64);
+    clock.advance(processed.len(), track_seconds);
+}
+
+/// Escribe samples interleaved al ring buffer del output
+///
+/// AIDEV-NOTE: Si el buffer está lleno, esto bloqueará brevemente.
+/// Esto es intencional para sincronizar decode con playback.
+pub fn write_samples(output: &dyn AudioOutput, samples: &[f32]) {
     let producer = output.get_producer();
     let mut written = 0;
     while written < samples.len() {
@@ -239,9 +360,72 @@ pub fn decode_next_frame(
         }
         written += n;
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Genera un WAV estéreo de prueba (seno de 440 Hz)
+    fn write_test_wav(path: &Path, sample_rate: u32, seconds: f32) {
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        let total = (sample_rate as f32 * seconds) as usize;
+        for i in 0..total {
+            let t = i as f32 / sample_rate as f32;
+            let value = ((t * 440.0 * 2.0 * std::f32::consts::PI).sin() * 16000.0) as i16;
+            writer.write_sample(value).unwrap();
+            writer.write_sample(value).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    #[test]
+    fn test_open_decoder_reads_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tone.wav");
+        write_test_wav(&path, 48000, 1.0);
+
+        let (ds, duration) = open_decoder(path.to_str().unwrap(), None).unwrap();
+        assert_eq!(ds.sample_rate, 48000);
+        assert_eq!(ds.channels, 2);
+        assert!((duration - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_preload_track_primes_first_packet() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("next.wav");
+        write_test_wav(&path, 44100, 0.5);
+
+        let preloaded = preload_track(path.to_str().unwrap(), 1.0).unwrap();
+        assert!(!preloaded.primed.is_empty());
+        assert_eq!(preloaded.primed.len() % 2, 0);
+        assert_eq!(preloaded.decoder.path, path.to_str().unwrap());
+    }
 
-    // Calcular posición actual
-    let pos = ds.time_base.calc_time(packet.ts()).seconds as f64;
+    #[test]
+    fn test_preload_track_missing_file() {
+        assert!(preload_track("/nonexistent/track.flac", 1.0).is_err());
+    }
 
-    Ok(DecodeResult::Continue(pos))
+    #[test]
+    fn test_decode_packet_applies_gain() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gain.wav");
+        write_test_wav(&path, 44100, 0.2);
+        let path = path.to_str().unwrap();
+
+        let unity = preload_track(path, 1.0).unwrap();
+        let halved = preload_track(path, 0.5).unwrap();
+        assert_eq!(unity.primed.len(), halved.primed.len());
+        for (a, b) in unity.primed.iter().zip(&halved.primed) {
+            assert!((a * 0.5 - b).abs() < 1e-6);
+        }
+    }
 }
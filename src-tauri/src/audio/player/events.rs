@@ -1,41 +1,139 @@
 //! Emisión de eventos Tauri al frontend
+//!
+//! AIDEV-NOTE: El nombre del evento depende del rol del reproductor
+//! (`audio:timestamp` vs `preview:timestamp`), ver `PlayerRole::event`.
 
 use tauri::Emitter;
 
-use super::types::{ErrorPayload, PlaybackState, StatePayload, TimestampPayload};
+use crate::audio::output::AudioDeviceInfo;
+
+use super::looping::LoopRegion;
+use super::queue::QueueSnapshot;
+use super::types::{
+    ErrorPayload, MeterPayload, OutputConfigPayload, PlayRecordedPayload, PlaybackState,
+    PlayerRole, StatePayload, TimestampPayload, TrackChangedPayload,
+};
 
 /// Emite evento de timestamp al frontend
 pub fn emit_timestamp<R: tauri::Runtime>(
     app_handle: &tauri::AppHandle<R>,
+    role: PlayerRole,
     position: f64,
     duration: f64,
 ) {
-    let _ = app_handle.emit("audio:timestamp", TimestampPayload { position, duration });
+    let _ = app_handle.emit(
+        &role.event("timestamp"),
+        TimestampPayload { position, duration },
+    );
+}
+
+/// Emite niveles y espectro de lo que está sonando
+pub fn emit_meter<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    role: PlayerRole,
+    payload: MeterPayload,
+) {
+    let _ = app_handle.emit(&role.event("meter"), payload);
 }
 
 /// Emite evento de estado al frontend
-pub fn emit_state<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, is_playing: bool) {
+pub fn emit_state<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    role: PlayerRole,
+    is_playing: bool,
+) {
     let state = if is_playing {
         PlaybackState::Playing
     } else {
         PlaybackState::Stopped
     };
-    let _ = app_handle.emit("audio:state", StatePayload { is_playing, state });
+    let _ = app_handle.emit(&role.event("state"), StatePayload { is_playing, state });
 }
 
 /// Emite evento de fin de track
-pub fn emit_end_of_track<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) {
-    let _ = app_handle.emit("audio:end_of_track", ());
+pub fn emit_end_of_track<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, role: PlayerRole) {
+    let _ = app_handle.emit(&role.event("end_of_track"), ());
+}
+
+/// Emite evento de cambio de track tras una transición gapless
+pub fn emit_track_changed<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    role: PlayerRole,
+    path: &str,
+    duration: f64,
+) {
+    let _ = app_handle.emit(
+        &role.event("track_changed"),
+        TrackChangedPayload {
+            path: path.to_string(),
+            duration,
+        },
+    );
+}
+
+/// Emite evento de cambio en la cola de reproducción
+pub fn emit_queue_changed<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    role: PlayerRole,
+    snapshot: &QueueSnapshot,
+) {
+    let _ = app_handle.emit(&role.event("queue_changed"), snapshot.clone());
+}
+
+/// Emite evento de cambio de loop (`None` = sin loop activo)
+pub fn emit_loop_changed<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    role: PlayerRole,
+    region: Option<LoopRegion>,
+) {
+    let _ = app_handle.emit(&role.event("loop_changed"), region);
+}
+
+/// Emite evento de reproducción registrada (play count actualizado)
+pub fn emit_play_recorded<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    role: PlayerRole,
+    path: &str,
+    seconds_played: f64,
+) {
+    let _ = app_handle.emit(
+        &role.event("play_recorded"),
+        PlayRecordedPayload {
+            path: path.to_string(),
+            seconds_played,
+        },
+    );
+}
+
+/// Nombre del evento de cambio de dispositivos (común a ambos reproductores)
+pub const DEVICES_CHANGED_EVENT: &str = "audio:devices-changed";
+
+/// Emite la lista de dispositivos tras una conexión/desconexión
+pub fn emit_devices_changed<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    devices: &[AudioDeviceInfo],
+) {
+    let _ = app_handle.emit(DEVICES_CHANGED_EVENT, devices.to_vec());
+}
+
+/// Emite la configuración efectiva del output tras abrirlo
+pub fn emit_output_config<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    role: PlayerRole,
+    payload: OutputConfigPayload,
+) {
+    let _ = app_handle.emit(&role.event("output_config"), payload);
 }
 
 /// Emite evento de error
 pub fn emit_error<R: tauri::Runtime>(
     app_handle: &tauri::AppHandle<R>,
+    role: PlayerRole,
     message: &str,
     is_critical: bool,
 ) {
     let _ = app_handle.emit(
-        "audio:error",
+        &role.event("error"),
         ErrorPayload {
             message: message.to_string(),
             is_critical,
@@ -0,0 +1,196 @@
+//! Detección de escuchas para el historial de reproducción
+//!
+//! AIDEV-NOTE: Una escucha cuenta cuando el tiempo escuchado de verdad (posición
+//! audible, sin contar seeks ni saltos de loop) supera el umbral: un porcentaje
+//! de la duración o un máximo de segundos, lo que llegue antes. Cada track se
+//! registra como mucho una vez por reproducción.
+
+use serde::{Deserialize, Serialize};
+
+/// Saltos de posición mayores que esto (en segundos) se tratan como seek
+const MAX_POSITION_STEP: f64 = 1.0;
+
+/// Umbral para contar una escucha
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayThreshold {
+    /// Porcentaje de la duración del track (1-100)
+    pub percent: f64,
+    /// Segundos a partir de los cuales cuenta aunque no se llegue al porcentaje
+    pub seconds: f64,
+}
+
+impl Default for PlayThreshold {
+    fn default() -> Self {
+        Self {
+            percent: 50.0,
+            seconds: 240.0,
+        }
+    }
+}
+
+impl PlayThreshold {
+    /// Devuelve una copia con valores en rangos válidos
+    pub fn clamped(self) -> Self {
+        Self {
+            percent: self.percent.clamp(1.0, 100.0),
+            seconds: self.seconds.max(1.0),
+        }
+    }
+
+    /// Segundos a escuchar de un track de `duration` segundos
+    pub fn required_seconds(&self, duration: f64) -> f64 {
+        if duration > 0.0 {
+            (duration * self.percent / 100.0).min(self.seconds)
+        } else {
+            self.seconds
+        }
+    }
+}
+
+impl From<&crate::config::AudioConfig> for PlayThreshold {
+    fn from(config: &crate::config::AudioConfig) -> Self {
+        Self {
+            percent: config.play_count_percent,
+            seconds: config.play_count_seconds,
+        }
+        .clamped()
+    }
+}
+
+/// Escucha que ha superado el umbral
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletedPlay {
+    pub path: String,
+    pub seconds_played: f64,
+}
+
+/// Acumula el tiempo escuchado del track actual
+#[derive(Debug, Default)]
+pub struct PlayTracker {
+    path: Option<String>,
+    duration: f64,
+    listened: f64,
+    last_position: Option<f64>,
+    recorded: bool,
+}
+
+impl PlayTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Empieza a contar un track nuevo (desde la primera posición que llegue)
+    pub fn start(&mut self, path: &str, duration: f64) {
+        *self = Self {
+            path: Some(path.to_string()),
+            duration,
+            ..Self::default()
+        };
+    }
+
+    /// Deja de contar (stop o fin de la reproducción)
+    pub fn stop(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Salto explícito: lo saltado no cuenta como escuchado
+    pub fn seek(&mut self, position: f64) {
+        self.last_position = Some(position);
+    }
+
+    /// Actualiza con la posición audible; retorna la escucha al superar el umbral
+    pub fn update(&mut self, position: f64, threshold: &PlayThreshold) -> Option<CompletedPlay> {
+        let step = self.last_position.map_or(0.0, |last| position - last);
+        self.last_position = Some(position);
+        if step > 0.0 && step <= MAX_POSITION_STEP {
+            self.listened += step;
+        }
+
+        if self.recorded || self.listened < threshold.required_seconds(self.duration) {
+            return None;
+        }
+        self.recorded = true;
+        self.path.clone().map(|path| CompletedPlay {
+            path,
+            seconds_played: self.listened,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Avanza de `from` a `to` en pasos de 50 ms
+    fn play(
+        tracker: &mut PlayTracker,
+        threshold: &PlayThreshold,
+        from: f64,
+        to: f64,
+    ) -> Option<CompletedPlay> {
+        let mut completed = None;
+        let mut position = from;
+        while position < to {
+            position = (position + 0.05).min(to);
+            completed = completed.or(tracker.update(position, threshold));
+        }
+        completed
+    }
+
+    #[test]
+    fn test_required_seconds() {
+        let threshold = PlayThreshold::default();
+        assert_eq!(threshold.required_seconds(180.0), 90.0);
+        // Un track largo cuenta a los 4 minutos
+        assert_eq!(threshold.required_seconds(900.0), 240.0);
+        assert_eq!(threshold.required_seconds(0.0), 240.0);
+    }
+
+    #[test]
+    fn test_counts_once_after_threshold() {
+        let threshold = PlayThreshold::default();
+        let mut tracker = PlayTracker::new();
+        tracker.start("/music/a.mp3", 100.0);
+
+        assert_eq!(play(&mut tracker, &threshold, 0.0, 49.0), None);
+        let completed = play(&mut tracker, &threshold, 49.0, 51.0).unwrap();
+        assert_eq!(completed.path, "/music/a.mp3");
+        assert!((completed.seconds_played - 50.0).abs() < 0.1);
+
+        // Seguir escuchando no vuelve a contar
+        assert_eq!(play(&mut tracker, &threshold, 51.0, 100.0), None);
+    }
+
+    #[test]
+    fn test_seek_does_not_count() {
+        let threshold = PlayThreshold::default();
+        let mut tracker = PlayTracker::new();
+        tracker.start("/music/a.mp3", 100.0);
+
+        play(&mut tracker, &threshold, 0.0, 10.0);
+        // Ni el seek explícito ni un salto detectado por la posición cuentan
+        tracker.seek(80.0);
+        assert_eq!(play(&mut tracker, &threshold, 80.0, 90.0), None);
+        assert_eq!(tracker.update(30.0, &threshold), None);
+        assert!(play(&mut tracker, &threshold, 30.0, 61.0).is_some());
+    }
+
+    #[test]
+    fn test_stop_and_restart() {
+        let threshold = PlayThreshold {
+            percent: 10.0,
+            seconds: 240.0,
+        };
+        let mut tracker = PlayTracker::new();
+        tracker.start("/music/a.mp3", 100.0);
+        assert!(play(&mut tracker, &threshold, 0.0, 11.0).is_some());
+
+        tracker.stop();
+        assert_eq!(tracker.update(12.0, &threshold), None);
+
+        // Volver a reproducir el mismo track cuenta otra vez
+        tracker.start("/music/a.mp3", 100.0);
+        assert!(play(&mut tracker, &threshold, 0.0, 11.0).is_some());
+    }
+}
@@ -0,0 +1,507 @@
+//! Reproducción de loops sin clicks
+//!
+//! AIDEV-NOTE: Con un loop activo el decode thread no llama a `decode_next_frame`
+//! sino a `ActiveLoop::step`. Los frames previos al seam pasan tal cual; los
+//! últimos `LOOP_SEAM_MS` antes de `end` se retienen (`tail`). Al llegar a `end`
+//! se hace seek a `start - seam`, se decodifica la cabeza y se mezcla la cola
+//! (fade out) con los frames justo anteriores a `start` (fade in, fundido lineal
+//! porque ambos lados suelen estar muy correlados), así la reproducción continúa
+//! exactamente en `start` al sample y sin click.
+
+use serde::{Deserialize, Serialize};
+
+use crate::audio::constants::{LOOP_MIN_MS, LOOP_SEAM_MS};
+use crate::audio::error::{AudioError, AudioResult};
+
+use super::crossfade::CrossfadeCurve;
+use super::decoder::{decode_packet, seek_to_position};
+use super::state::{DecodedPacket, DecoderState};
+
+/// Región de un loop (segundos de track)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoopRegion {
+    pub start: f64,
+    pub end: f64,
+}
+
+impl LoopRegion {
+    /// Loop con principio y fin en orden y al menos `LOOP_MIN_MS` de longitud
+    pub fn is_valid(&self) -> bool {
+        self.start >= 0.0 && self.end - self.start >= LOOP_MIN_MS / 1000.0
+    }
+}
+
+/// Bloque listo para escribir tras un paso del loop
+pub struct LoopStep {
+    /// Samples interleaved (puede estar vacío si todo quedó retenido en la cola)
+    pub samples: Vec<f32>,
+    /// Posición del track tras el bloque (en segundos)
+    pub position: f64,
+}
+
+/// Loop activo sobre el track en reproducción
+pub struct ActiveLoop {
+    region: LoopRegion,
+    sample_rate: u32,
+    channels: usize,
+    /// Frames [end - seam, end) del pase actual, pendientes de mezclar
+    tail: Vec<f32>,
+    /// Resto de la cabeza tras la última vuelta (frame inicial y samples),
+    /// pendiente de repartir en el siguiente paso
+    carry: Option<(u64, Vec<f32>)>,
+}
+
+impl ActiveLoop {
+    pub fn new(region: LoopRegion, sample_rate: u32, channels: u16) -> Self {
+        Self {
+            region,
+            sample_rate: sample_rate.max(1),
+            channels: channels.max(1) as usize,
+            tail: Vec::new(),
+            carry: None,
+        }
+    }
+
+    /// Región actual
+    pub fn region(&self) -> LoopRegion {
+        self.region
+    }
+
+    /// Cambia la región en vivo
+    ///
+    /// Devuelve la cola retenida del pase anterior, que hay que escribir tal cual
+    /// para no perder audio.
+    pub fn set_region(&mut self, region: LoopRegion) -> Vec<f32> {
+        self.region = region;
+        std::mem::take(&mut self.tail)
+    }
+
+    /// Desactiva el loop devolviendo la cola retenida
+    ///
+    /// Incluye el resto de cabeza pendiente: el decoder ya está detrás de él.
+    pub fn into_tail(self) -> Vec<f32> {
+        let mut samples = self.tail;
+        if let Some((_, carry)) = self.carry {
+            samples.extend_from_slice(&carry);
+        }
+        samples
+    }
+
+    /// Olvida la cola retenida (tras un seek la continuidad ya se ha roto)
+    pub fn reset(&mut self) {
+        self.tail.clear();
+        self.carry = None;
+    }
+
+    /// Decodifica el siguiente paquete aplicando el loop
+    ///
+    /// AIDEV-NOTE: Como mucho una vuelta por paso. Lo que sobra de la cabeza
+    /// se guarda en `carry` y se reparte en el siguiente: con un loop más corto
+    /// que un paquete ese resto vuelve a llegar a `end`, y procesarlo aquí
+    /// mismo no terminaría nunca.
+    pub fn step(&mut self, ds: &mut DecoderState) -> AudioResult<LoopStep> {
+        let (frame, pending, force_wrap) = match self.carry.take() {
+            Some((frame, samples)) => (frame, samples, false),
+            None => match decode_packet(ds)? {
+                DecodedPacket::Samples { samples, position } => {
+                    (self.frame_of(position), samples, false)
+                }
+                DecodedPacket::Skipped(position) => {
+                    return Ok(LoopStep {
+                        samples: Vec::new(),
+                        position,
+                    })
+                }
+                // El loop acaba más allá del final real del track: volver desde aquí
+                DecodedPacket::EndOfTrack => (self.frame_of(self.region.end), Vec::new(), true),
+            },
+        };
+
+        let mut out = Vec::with_capacity(pending.len());
+        let reached_end = self.route(frame, &pending, &mut out);
+        if !reached_end && !force_wrap {
+            let frames = (pending.len() / self.channels) as u64;
+            return Ok(LoopStep {
+                samples: out,
+                position: (frame + frames) as f64 / self.sample_rate as f64,
+            });
+        }
+
+        let (head_frame, head) = self.read_head(ds)?;
+        if head.is_empty() {
+            return Err(AudioError::PlaybackFailed(format!(
+                "El loop empieza fuera del track ({:.3}s)",
+                self.region.start
+            )));
+        }
+        let used = self.mix_seam(head_frame, &head, &mut out);
+        let frame = head_frame + used as u64;
+        let rest = head[used * self.channels..].to_vec();
+        if !rest.is_empty() {
+            self.carry = Some((frame, rest));
+        }
+
+        Ok(LoopStep {
+            samples: out,
+            position: frame as f64 / self.sample_rate as f64,
+        })
+    }
+
+    /// Reparte un bloque entre la salida y la cola; `true` si llega a `end`
+    fn route(&mut self, frame: u64, samples: &[f32], out: &mut Vec<f32>) -> bool {
+        let ch = self.channels;
+        let frames = (samples.len() / ch) as u64;
+        let end = self.frame_of(self.region.end);
+        let seam_start = end.saturating_sub(self.seam_frames());
+
+        if frame >= end {
+            // El cabezal ya pasó el final (loop activado o encogido por detrás):
+            // el principio de este bloque hace de cola para el fundido
+            if self.tail.is_empty() {
+                let n = frames.min(self.seam_frames()) as usize;
+                self.tail.extend_from_slice(&samples[..n * ch]);
+            }
+            return true;
+        }
+
+        let pass = seam_start.saturating_sub(frame).min(frames) as usize;
+        let keep = (end - frame).min(frames) as usize;
+        out.extend_from_slice(&samples[..pass * ch]);
+        if keep > pass {
+            self.tail.extend_from_slice(&samples[pass * ch..keep * ch]);
+        }
+
+        frame + frames >= end
+    }
+
+    /// Salta al principio del loop y decodifica al menos `seam` frames
+    ///
+    /// Devuelve el frame inicial de la cabeza y sus samples, que empiezan
+    /// `seam` frames antes de `start` (o en 0 si el loop empieza antes).
+    fn read_head(&self, ds: &mut DecoderState) -> AudioResult<(u64, Vec<f32>)> {
+        let ch = self.channels;
+        let seam = self.seam_frames() as usize;
+        let head_start = self.frame_of(self.region.start).saturating_sub(seam as u64);
+        seek_to_position(ds, head_start as f64 / self.sample_rate as f64)?;
+
+        let mut first_frame = None;
+        let mut head = Vec::new();
+        while head.len() / ch < seam {
+            match decode_packet(ds)? {
+                DecodedPacket::Samples { samples, position } => {
+                    let frame = self.frame_of(position);
+                    let frames = (samples.len() / ch) as u64;
+                    if frame + frames <= head_start {
+                        continue;
+                    }
+                    // El seek puede caer antes: descartar hasta el frame pedido
+                    let skip = head_start.saturating_sub(frame) as usize;
+                    first_frame.get_or_insert(frame.max(head_start));
+                    head.extend_from_slice(&samples[skip * ch..]);
+                }
+                DecodedPacket::Skipped(_) => continue,
+                DecodedPacket::EndOfTrack => break,
+            }
+        }
+
+        Ok((first_frame.unwrap_or(head_start), head))
+    }
+
+    /// Escribe la cola mezclada con la pre-roll de la cabeza
+    ///
+    /// La cola (fade out) termina en `end` y la pre-roll (fade in) en `start`, así
+    /// ambas quedan alineadas. Devuelve los frames de cabeza consumidos: la
+    /// reproducción sigue en `start`.
+    fn mix_seam(&mut self, head_frame: u64, head: &[f32], out: &mut Vec<f32>) -> usize {
+        let ch = self.channels;
+        let tail = std::mem::take(&mut self.tail);
+        let tail_frames = tail.len() / ch;
+        let pre_roll = (self.frame_of(self.region.start).saturating_sub(head_frame) as usize)
+            .min(head.len() / ch);
+        let seam = tail_frames.min(pre_roll);
+
+        // Lo que no se puede solapar (loop pegado al inicio del track) suena tal cual
+        let unmixed = (tail_frames - seam) * ch;
+        out.extend_from_slice(&tail[..unmixed]);
+
+        let incoming = &head[(pre_roll - seam) * ch..pre_roll * ch];
+        for i in 0..seam {
+            let progress = (i as f32 + 0.5) / seam as f32;
+            let (fade_out, fade_in) = CrossfadeCurve::Linear.gains(progress);
+            for c in 0..ch {
+                out.push(tail[unmixed + i * ch + c] * fade_out + incoming[i * ch + c] * fade_in);
+            }
+        }
+
+        pre_roll
+    }
+
+    /// Frames de solapamiento en el seam (como mucho medio loop)
+    fn seam_frames(&self) -> u64 {
+        let seam = (LOOP_SEAM_MS / 1000.0 * self.sample_rate as f64).round() as u64;
+        let length = self
+            .frame_of(self.region.end)
+            .saturating_sub(self.frame_of(self.region.start));
+        seam.min(length / 2).max(1)
+    }
+
+    /// Segundos → índice de frame
+    fn frame_of(&self, seconds: f64) -> u64 {
+        (seconds.max(0.0) * self.sample_rate as f64).round() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::player::decoder::open_decoder;
+
+    const RATE: u32 = 8000;
+
+    /// WAV estéreo donde cada frame vale su índice (módulo 30000), para
+    /// comprobar posiciones exactas
+    fn write_ramp_wav(path: &std::path::Path, frames: usize) {
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: RATE,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for i in 0..frames {
+            let value = (i % 30000) as i16;
+            writer.write_sample(value).unwrap();
+            writer.write_sample(value).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    fn frame_value(sample: f32) -> i64 {
+        (sample * 32768.0).round() as i64
+    }
+
+    /// Reproduce `steps` pasos y devuelve el canal izquierdo
+    fn play(active: &mut ActiveLoop, ds: &mut DecoderState, steps: usize) -> Vec<f32> {
+        let mut left = Vec::new();
+        for _ in 0..steps {
+            let step = active.step(ds).unwrap();
+            left.extend(step.samples.iter().step_by(2));
+        }
+        left
+    }
+
+    #[test]
+    fn test_loop_wraps_sample_accurately() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ramp.wav");
+        write_ramp_wav(&path, RATE as usize * 2);
+
+        let (mut ds, _) = open_decoder(path.to_str().unwrap(), None).unwrap();
+        let region = LoopRegion {
+            start: 0.5,
+            end: 0.75,
+        };
+        let mut active = ActiveLoop::new(region, RATE, 2);
+        let left = play(&mut active, &mut ds, 40);
+
+        let start = 4000i64;
+        let end = 6000i64;
+        let seam = (LOOP_SEAM_MS / 1000.0 * RATE as f64) as usize;
+
+        // Hasta el seam la rampa es continua desde 0
+        let values: Vec<i64> = left.iter().map(|s| frame_value(*s)).collect();
+        let seam_start = (end as usize) - seam;
+        assert!(values[..seam_start]
+            .iter()
+            .enumerate()
+            .all(|(i, v)| *v == i as i64));
+
+        // Tras el seam se continúa exactamente en `start`
+        let after = seam_start + seam;
+        assert_eq!(values[after], start);
+        assert_eq!(values[after + 1], start + 1);
+
+        // Segunda vuelta: misma posición relativa
+        let period = (end - start) as usize;
+        assert_eq!(values[after + period], start);
+        assert!(values.iter().all(|v| *v < end));
+    }
+
+    #[test]
+    fn test_seam_has_no_jump() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ramp.wav");
+        write_ramp_wav(&path, RATE as usize);
+
+        let (mut ds, _) = open_decoder(path.to_str().unwrap(), None).unwrap();
+        let mut active = ActiveLoop::new(
+            LoopRegion {
+                start: 0.25,
+                end: 0.5,
+            },
+            RATE,
+            2,
+        );
+        let values: Vec<i64> = play(&mut active, &mut ds, 20)
+            .iter()
+            .map(|s| frame_value(*s))
+            .collect();
+
+        // Sin fundido el salto sería de 2000 de golpe; con él se reparte entre
+        // los frames del seam
+        let max_step = values
+            .windows(2)
+            .map(|w| (w[1] - w[0]).abs())
+            .max()
+            .unwrap();
+        let seam = (LOOP_SEAM_MS / 1000.0 * RATE as f64) as i64;
+        assert!(max_step <= 2000 / seam + 1, "salto máximo {}", max_step);
+    }
+
+    #[test]
+    fn test_loop_beyond_track_end_wraps_at_eof() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("short.wav");
+        write_ramp_wav(&path, RATE as usize / 2);
+
+        let (mut ds, _) = open_decoder(path.to_str().unwrap(), None).unwrap();
+        let mut active = ActiveLoop::new(
+            LoopRegion {
+                start: 0.25,
+                end: 10.0,
+            },
+            RATE,
+            2,
+        );
+        let values: Vec<i64> = play(&mut active, &mut ds, 30)
+            .iter()
+            .map(|s| frame_value(*s))
+            .collect();
+
+        // Nunca termina: vuelve a 0.25s al llegar al final del archivo
+        assert!(values.len() > RATE as usize);
+        assert!(values[RATE as usize / 2..].contains(&2000));
+    }
+
+    #[test]
+    fn test_shrinking_behind_playhead_jumps_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ramp.wav");
+        write_ramp_wav(&path, RATE as usize * 2);
+
+        let (mut ds, _) = open_decoder(path.to_str().unwrap(), Some(1.0)).unwrap();
+        let mut active = ActiveLoop::new(
+            LoopRegion {
+                start: 0.1,
+                end: 0.2,
+            },
+            RATE,
+            2,
+        );
+        let values: Vec<i64> = play(&mut active, &mut ds, 5)
+            .iter()
+            .map(|s| frame_value(*s))
+            .collect();
+
+        assert!(values.contains(&800));
+        assert!(values.iter().skip(100).all(|v| *v < 1600));
+    }
+
+    #[test]
+    fn test_loop_shorter_than_packet() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ramp.wav");
+        write_ramp_wav(&path, RATE as usize * 2);
+
+        let (mut ds, _) = open_decoder(path.to_str().unwrap(), None).unwrap();
+        // 20ms = 160 frames, muy por debajo de un paquete
+        let mut active = ActiveLoop::new(
+            LoopRegion {
+                start: 0.5,
+                end: 0.52,
+            },
+            RATE,
+            2,
+        );
+        let mut values = Vec::new();
+        for _ in 0..200 {
+            let step = active.step(&mut ds).unwrap();
+            // Una vuelta por paso: nunca más de un paquete más un loop
+            assert!(step.samples.len() <= 2 * (RATE as usize + 160));
+            values.extend(step.samples.iter().step_by(2).map(|s| frame_value(*s)));
+        }
+
+        // Tras llegar al loop solo suenan frames de [start - seam, end)
+        let seam = (LOOP_SEAM_MS / 1000.0 * RATE as f64) as i64;
+        let looping = &values[4160..];
+        assert!(looping.len() > 10 * 160);
+        assert!(looping.iter().all(|v| (4000 - seam..4160).contains(v)));
+        assert!(looping.contains(&4000));
+    }
+
+    #[test]
+    fn test_empty_region_does_not_hang() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ramp.wav");
+        write_ramp_wav(&path, RATE as usize);
+
+        let (mut ds, _) = open_decoder(path.to_str().unwrap(), None).unwrap();
+        let mut active = ActiveLoop::new(
+            LoopRegion {
+                start: 0.5,
+                end: 0.5,
+            },
+            RATE,
+            2,
+        );
+        for _ in 0..50 {
+            let step = active.step(&mut ds).unwrap();
+            assert!(step.samples.len() <= 2 * RATE as usize);
+        }
+    }
+
+    #[test]
+    fn test_set_region_returns_tail() {
+        let mut active = ActiveLoop::new(
+            LoopRegion {
+                start: 1.0,
+                end: 2.0,
+            },
+            RATE,
+            2,
+        );
+        let mut out = Vec::new();
+        // Bloque que termina dentro del seam: parte queda retenida
+        let samples = vec![0.1f32; 2 * 16000];
+        assert!(!active.route(0, &samples[..2 * 15990], &mut out));
+        assert!(!active.tail.is_empty());
+
+        let tail = active.set_region(LoopRegion {
+            start: 1.0,
+            end: 3.0,
+        });
+        assert_eq!(out.len() + tail.len(), 2 * 15990);
+        assert!(active.tail.is_empty());
+    }
+
+    #[test]
+    fn test_region_is_valid() {
+        assert!(LoopRegion {
+            start: 1.0,
+            end: 2.0
+        }
+        .is_valid());
+        assert!(!LoopRegion {
+            start: 2.0,
+            end: 1.0
+        }
+        .is_valid());
+        // Más corto que un paquete
+        assert!(!LoopRegion {
+            start: 1.0,
+            end: 1.02
+        }
+        .is_valid());
+    }
+}
@@ -0,0 +1,299 @@
+//! Medidor de niveles y espectro de la salida
+//!
+//! AIDEV-NOTE: Se alimenta con lo que el decode thread escribe al ring buffer
+//! (después de tempo, DSP y resampling) y se mide en el punto que está sonando:
+//! igual que `PlaybackClock`, cruza los samples escritos con los que el output
+//! ya ha reproducido. El volumen se aplica en el callback de cpal, así que se
+//! multiplica al medir.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+
+use super::types::{ChannelLevel, MeterPayload};
+
+/// Frames de la ventana de medida (~46 ms a 44.1 kHz)
+pub const METER_WINDOW_FRAMES: usize = 2048;
+
+/// Bandas del espectro (espaciado logarítmico)
+pub const SPECTRUM_BANDS: usize = 32;
+
+/// Rango de frecuencias del espectro (Hz)
+const SPECTRUM_MIN_HZ: f32 = 20.0;
+const SPECTRUM_MAX_HZ: f32 = 20000.0;
+
+/// Suelo del espectro (dBFS)
+pub const SPECTRUM_FLOOR_DB: f32 = -90.0;
+
+/// Máximo de samples guardados a la espera de sonar (ring buffer + latencia)
+const MAX_HISTORY_SAMPLES: usize = 1 << 20;
+
+/// Medidor del decode thread
+pub struct LevelMeter {
+    /// Últimos samples escritos (interleaved)
+    history: VecDeque<f32>,
+    /// Samples escritos (según el reloj) al final de `history`
+    end: u64,
+    sample_rate: u32,
+    channels: usize,
+    fft: Arc<dyn Fft<f32>>,
+    /// Ventana de Hann
+    window: Vec<f32>,
+    buffer: Vec<Complex<f32>>,
+}
+
+impl Default for LevelMeter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LevelMeter {
+    pub fn new() -> Self {
+        let fft = FftPlanner::new().plan_fft_forward(METER_WINDOW_FRAMES);
+        let window = (0..METER_WINDOW_FRAMES)
+            .map(|i| {
+                let phase = 2.0 * std::f32::consts::PI * i as f32 / METER_WINDOW_FRAMES as f32;
+                0.5 - 0.5 * phase.cos()
+            })
+            .collect();
+        Self {
+            history: VecDeque::new(),
+            end: 0,
+            sample_rate: 0,
+            channels: 0,
+            fft,
+            window,
+            buffer: vec![Complex::default(); METER_WINDOW_FRAMES],
+        }
+    }
+
+    /// Registra un bloque escrito al ring buffer
+    ///
+    /// `written` son los samples que el reloj llevaba escritos antes del bloque.
+    /// Si no encaja con lo anterior (output nuevo, cambio de formato) se descarta
+    /// el historial.
+    pub fn feed(&mut self, samples: &[f32], written: u64, sample_rate: u32, channels: u16) {
+        let channels = channels.max(1) as usize;
+        if written != self.end || sample_rate != self.sample_rate || channels != self.channels {
+            self.history.clear();
+            self.sample_rate = sample_rate;
+            self.channels = channels;
+        }
+        self.history.extend(samples);
+        self.end = written + samples.len() as u64;
+
+        if self.history.len() > MAX_HISTORY_SAMPLES {
+            // Quitar frames completos para no desalinear los canales
+            let excess = self.history.len() - MAX_HISTORY_SAMPLES;
+            let excess = excess.div_ceil(channels) * channels;
+            self.history.drain(..excess.min(self.history.len()));
+        }
+    }
+
+    /// Mide la ventana que termina en el sample `heard` (lo que está sonando)
+    ///
+    /// `volume` es el volumen del output (0.0 - 1.0). `None` si no hay audio
+    /// escrito que haya llegado a sonar.
+    pub fn measure(&mut self, heard: u64, volume: f32) -> Option<MeterPayload> {
+        let channels = self.channels;
+        if channels == 0 || self.history.is_empty() {
+            return None;
+        }
+
+        let start = self.end - self.history.len() as u64;
+        if heard <= start {
+            return None;
+        }
+        // Posición de `heard` en el historial, alineada a frames
+        let heard_index = (heard.min(self.end) - start) as usize / channels * channels;
+
+        // Lo anterior a la ventana ya no se va a medir
+        let window_samples = METER_WINDOW_FRAMES * channels;
+        let window_start = heard_index.saturating_sub(window_samples);
+        self.history.drain(..window_start);
+        let window_len = heard_index - window_start;
+        if window_len == 0 {
+            return None;
+        }
+
+        let levels = self.levels(window_len, volume);
+        let spectrum = self.spectrum(window_len, volume);
+        Some(MeterPayload { levels, spectrum })
+    }
+
+    /// RMS y pico por canal de los primeros `len` samples del historial
+    fn levels(&self, len: usize, volume: f32) -> Vec<ChannelLevel> {
+        let channels = self.channels;
+        let frames = (len / channels).max(1) as f32;
+        let mut sums = vec![0.0f32; channels];
+        let mut peaks = vec![0.0f32; channels];
+        for (i, &sample) in self.history.range(..len).enumerate() {
+            let ch = i % channels;
+            sums[ch] += sample * sample;
+            peaks[ch] = peaks[ch].max(sample.abs());
+        }
+
+        sums.iter()
+            .zip(&peaks)
+            .map(|(&sum, &peak)| ChannelLevel {
+                rms: (sum / frames).sqrt() * volume,
+                peak: peak * volume,
+            })
+            .collect()
+    }
+
+    /// Espectro (mezcla mono) de los primeros `len` samples del historial
+    fn spectrum(&mut self, len: usize, volume: f32) -> Vec<f32> {
+        let channels = self.channels;
+        let frames = len / channels;
+
+        // Ventana alineada a la derecha: si falta audio, se rellena con silencio
+        let offset = METER_WINDOW_FRAMES - frames;
+        self.buffer.fill(Complex::default());
+        for frame in 0..frames {
+            let sum: f32 = self
+                .history
+                .range(frame * channels..(frame + 1) * channels)
+                .sum();
+            let mono = sum / channels as f32;
+            self.buffer[offset + frame] = Complex::new(mono * self.window[offset + frame], 0.0);
+        }
+        self.fft.process(&mut self.buffer);
+
+        // Escala para que un seno a 0 dBFS dé 0 dB (ganancia coherente de Hann = 0.5)
+        let scale = 4.0 / METER_WINDOW_FRAMES as f32 * volume;
+        let bin_hz = self.sample_rate as f32 / METER_WINDOW_FRAMES as f32;
+        let nyquist_bin = METER_WINDOW_FRAMES / 2;
+        let max_hz = SPECTRUM_MAX_HZ.min(self.sample_rate as f32 / 2.0);
+        let ratio = (max_hz / SPECTRUM_MIN_HZ).max(1.0);
+
+        (0..SPECTRUM_BANDS)
+            .map(|band| {
+                let low = SPECTRUM_MIN_HZ * ratio.powf(band as f32 / SPECTRUM_BANDS as f32);
+                let high = SPECTRUM_MIN_HZ * ratio.powf((band + 1) as f32 / SPECTRUM_BANDS as f32);
+                let first = ((low / bin_hz).ceil() as usize).clamp(1, nyquist_bin);
+                let last = ((high / bin_hz).ceil() as usize).clamp(first, nyquist_bin);
+                // Bandas más estrechas que un bin: el bin de su frecuencia central
+                let bins = if last > first {
+                    first..last
+                } else {
+                    let center = ((low * high).sqrt() / bin_hz).round() as usize;
+                    let center = center.clamp(1, nyquist_bin - 1);
+                    center..center + 1
+                };
+
+                let magnitude = self.buffer[bins]
+                    .iter()
+                    .map(|bin| bin.norm())
+                    .fold(0.0f32, f32::max)
+                    * scale;
+                if magnitude > 0.0 {
+                    (20.0 * magnitude.log10()).max(SPECTRUM_FLOOR_DB)
+                } else {
+                    SPECTRUM_FLOOR_DB
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Seno estéreo de `seconds` segundos (canal derecho a mitad de amplitud)
+    fn sine(freq: f32, amplitude: f32, sample_rate: u32, seconds: f32) -> Vec<f32> {
+        let frames = (sample_rate as f32 * seconds) as usize;
+        (0..frames)
+            .flat_map(|i| {
+                let t = i as f32 / sample_rate as f32;
+                let value = (t * freq * 2.0 * std::f32::consts::PI).sin() * amplitude;
+                [value, value * 0.5]
+            })
+            .collect()
+    }
+
+    /// Banda del espectro que contiene `freq`
+    fn band_of(freq: f32, sample_rate: u32) -> usize {
+        let ratio = SPECTRUM_MAX_HZ.min(sample_rate as f32 / 2.0) / SPECTRUM_MIN_HZ;
+        ((freq / SPECTRUM_MIN_HZ).ln() / ratio.ln() * SPECTRUM_BANDS as f32) as usize
+    }
+
+    #[test]
+    fn test_levels_and_spectrum_of_sine() {
+        let mut meter = LevelMeter::new();
+        let samples = sine(1000.0, 0.5, 44100, 0.5);
+        meter.feed(&samples, 0, 44100, 2);
+
+        let payload = meter.measure(samples.len() as u64, 1.0).unwrap();
+        assert_eq!(payload.levels.len(), 2);
+        assert!((payload.levels[0].peak - 0.5).abs() < 0.01);
+        assert!((payload.levels[0].rms - 0.5 / 2f32.sqrt()).abs() < 0.01);
+        assert!((payload.levels[1].rms - 0.25 / 2f32.sqrt()).abs() < 0.01);
+
+        // Mezcla mono a 0.375 de amplitud: ~-8.5 dBFS en la banda de 1 kHz
+        assert_eq!(payload.spectrum.len(), SPECTRUM_BANDS);
+        let band = band_of(1000.0, 44100);
+        let expected = 20.0 * 0.375f32.log10();
+        assert!(
+            (payload.spectrum[band] - expected).abs() < 1.5,
+            "{:?}",
+            payload.spectrum
+        );
+        assert!(payload.spectrum[band_of(100.0, 44100)] < expected - 40.0);
+        assert!(payload.spectrum[band_of(10000.0, 44100)] < expected - 40.0);
+    }
+
+    #[test]
+    fn test_measures_what_is_heard() {
+        let mut meter = LevelMeter::new();
+        let silence = vec![0.0f32; 44100 * 2];
+        let tone = sine(440.0, 0.8, 44100, 1.0);
+        meter.feed(&silence, 0, 44100, 2);
+        meter.feed(&tone, silence.len() as u64, 44100, 2);
+
+        // Todo escrito, pero aún suena el silencio
+        let payload = meter.measure(silence.len() as u64 / 2, 1.0).unwrap();
+        assert_eq!(payload.levels[0].peak, 0.0);
+        assert!(payload.spectrum.iter().all(|&db| db == SPECTRUM_FLOOR_DB));
+
+        let payload = meter
+            .measure((silence.len() + tone.len()) as u64, 1.0)
+            .unwrap();
+        assert!((payload.levels[0].peak - 0.8).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_volume_scales_levels() {
+        let mut meter = LevelMeter::new();
+        let samples = sine(1000.0, 0.5, 48000, 0.2);
+        meter.feed(&samples, 0, 48000, 2);
+
+        let full = meter.measure(samples.len() as u64, 1.0).unwrap();
+        let half = meter.measure(samples.len() as u64, 0.5).unwrap();
+        assert!((half.levels[0].peak - full.levels[0].peak * 0.5).abs() < 1e-4);
+        let band = band_of(1000.0, 48000);
+        assert!((full.spectrum[band] - half.spectrum[band] - 6.02).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_discontinuity_resets_history() {
+        let mut meter = LevelMeter::new();
+        let tone = sine(440.0, 0.8, 44100, 0.2);
+        meter.feed(&tone, 0, 44100, 2);
+
+        // Output nuevo: el contador de escritos vuelve a cero
+        let silence = vec![0.0f32; 4096];
+        meter.feed(&silence, 0, 44100, 2);
+        let payload = meter.measure(silence.len() as u64, 1.0).unwrap();
+        assert_eq!(payload.levels[0].peak, 0.0);
+
+        // Nada ha sonado todavía
+        assert!(LevelMeter::new().measure(0, 1.0).is_none());
+        meter.feed(&tone, silence.len() as u64, 44100, 2);
+        assert!(meter.measure(0, 1.0).is_none());
+    }
+}
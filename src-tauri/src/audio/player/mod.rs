@@ -1,15 +1,36 @@
 //! Reproductor de audio modular
 
+pub mod clock;
+pub mod crossfade;
+pub mod cues;
 pub mod decode_loop;
 pub mod decoder;
 pub mod events;
+pub mod history;
+pub mod looping;
+pub mod meter;
+pub mod normalization;
 pub mod player;
+pub mod queue;
+pub mod rate;
 pub mod state;
+pub mod tempo;
 pub mod types;
 
 // Re-exportar los tipos públicos principales
+pub use clock::PositionHandle;
+pub use crossfade::{CrossfadeCurve, CrossfadeSettings};
+pub use cues::CueTrigger;
+pub use history::PlayThreshold;
+pub use looping::LoopRegion;
+pub use normalization::NormalizationSettings;
 pub use player::AudioPlayer;
-pub use types::{ErrorPayload, PlaybackState, PlayerControlEvent, StatePayload, TimestampPayload};
+pub use queue::{PlaybackQueue, QueueSnapshot, RepeatMode};
+pub use tempo::{TempoRange, TempoSettings};
+pub use types::{
+    ChannelLevel, ErrorPayload, MeterPayload, OutputConfigPayload, PlaybackState,
+    PlayerControlEvent, PlayerRole, StatePayload, TimestampPayload, TrackChangedPayload,
+};
 
 #[cfg(test)]
 mod tests {
@@ -22,6 +43,13 @@ mod tests {
         assert_eq!(json, "\"playing\"");
     }
 
+    #[test]
+    fn test_player_role_event_names() {
+        assert_eq!(PlayerRole::Main.event("timestamp"), "audio:timestamp");
+        assert_eq!(PlayerRole::Preview.event("timestamp"), "preview:timestamp");
+        assert_eq!(PlayerRole::Preview.event("end_of_track"), "preview:end_of_track");
+    }
+
     #[test]
     fn test_timestamp_payload_serialize() {
         let payload = TimestampPayload {
@@ -33,6 +61,17 @@ mod tests {
         assert!(json.contains("180"));
     }
 
+    #[test]
+    fn test_track_changed_payload_serialize() {
+        let payload = TrackChangedPayload {
+            path: "/music/next.flac".to_string(),
+            duration: 245.5,
+        };
+        let json = serde_json::to_string(&payload).unwrap();
+        assert!(json.contains("/music/next.flac"));
+        assert!(json.contains("245.5"));
+    }
+
     #[test]
     fn test_error_payload_serialize() {
         let payload = ErrorPayload {
@@ -0,0 +1,324 @@
+//! Normalización de loudness durante la reproducción
+//!
+//! AIDEV-NOTE: Cada `DecoderState` lleva su propia ganancia (calculada con el
+//! loudness EBU R128 guardado en `loudness_analysis`) y `decode_packet` la aplica
+//! a los samples, así en un crossfade cada track se mezcla ya normalizado.
+//! La ganancia positiva se limita por el true peak del track para no pasar del
+//! techo del limitador. El `Limiter` es la última etapa de `dsp::DspChain` y se
+//! fuerza (con techo duro) mientras la normalización está activa: ReplayGain
+//! importado sin true peak fiable, EQ con ganancia.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+
+use crate::audio::constants::{
+    LIMITER_CEILING_DB, LIMITER_KNEE_DB, LIMITER_RELEASE_MS, NORMALIZATION_MAX_BOOST_DB,
+};
+use crate::audio::loudness_analyzer::LoudnessAnalysis;
+
+/// Configuración de normalización usada por el decode thread
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizationSettings {
+    pub enabled: bool,
+    /// Loudness objetivo (LUFS), p.ej. -14 (streaming) o -18 (ReplayGain)
+    pub target_lufs: f64,
+}
+
+impl NormalizationSettings {
+    /// Devuelve una copia con el objetivo limitado a un rango razonable
+    pub fn clamped(self) -> Self {
+        Self {
+            target_lufs: self.target_lufs.clamp(-30.0, -5.0),
+            ..self
+        }
+    }
+
+    /// Ganancia lineal para un track (1.0 si está desactivada o sin análisis)
+    pub fn track_gain(&self, loudness: Option<&LoudnessAnalysis>) -> f32 {
+        match loudness {
+            Some(loudness) if self.enabled => {
+                let gain_db = loudness
+                    .gain_to_target(self.target_lufs, LIMITER_CEILING_DB)
+                    .min(NORMALIZATION_MAX_BOOST_DB);
+                db_to_linear(gain_db) as f32
+            }
+            _ => 1.0,
+        }
+    }
+}
+
+impl From<&crate::config::AudioConfig> for NormalizationSettings {
+    fn from(config: &crate::config::AudioConfig) -> Self {
+        Self {
+            enabled: config.normalization_enabled,
+            target_lufs: config.normalization_target_lufs,
+        }
+        .clamped()
+    }
+}
+
+/// Loudness guardado de un track (sin loudness range si viene de tags)
+impl From<&crate::db::models::Loudness> for LoudnessAnalysis {
+    fn from(loudness: &crate::db::models::Loudness) -> Self {
+        Self {
+            integrated_lufs: loudness.integrated_lufs,
+            true_peak_dbtp: loudness.true_peak_dbtp,
+            loudness_range: loudness.loudness_range.unwrap_or(0.0),
+        }
+    }
+}
+
+/// Limitador de picos sin lookahead
+///
+/// AIDEV-NOTE: Ataque instantáneo (la ganancia baja en el mismo frame que supera
+/// el techo, así la salida nunca pasa de `LIMITER_CEILING_DB`) y release
+/// exponencial. La reducción es común a todos los canales para no mover la imagen.
+/// Con `set_soft_knee` la curva empieza a reducir `LIMITER_KNEE_DB / 2` por
+/// debajo del techo (rodilla cuadrática) en vez de cortar justo en él.
+pub struct Limiter {
+    enabled: bool,
+    ceiling: f32,
+    soft_knee: bool,
+    /// Nivel lineal a partir del cual actúa la curva (el techo sin rodilla)
+    knee_start: f32,
+    release_ms: f64,
+    /// Coeficiente de release para el sample rate actual
+    release_coeff: f32,
+    sample_rate: u32,
+    /// Ganancia actual del limitador (1.0 = sin reducción)
+    envelope: f32,
+}
+
+impl Limiter {
+    pub fn new(enabled: bool) -> Self {
+        let ceiling = db_to_linear(LIMITER_CEILING_DB) as f32;
+        Self {
+            enabled,
+            ceiling,
+            soft_knee: false,
+            knee_start: ceiling,
+            release_ms: LIMITER_RELEASE_MS,
+            release_coeff: 0.0,
+            sample_rate: 0,
+            envelope: 1.0,
+        }
+    }
+
+    /// Activa/desactiva el limitador
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if self.enabled != enabled {
+            self.enabled = enabled;
+            self.envelope = 1.0;
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Rodilla suave (true) o techo duro (false, por defecto)
+    pub fn set_soft_knee(&mut self, soft_knee: bool) {
+        self.soft_knee = soft_knee;
+        self.knee_start = if soft_knee {
+            db_to_linear(LIMITER_CEILING_DB - LIMITER_KNEE_DB / 2.0) as f32
+        } else {
+            self.ceiling
+        };
+    }
+
+    /// Olvida la reducción en curso (seek o cambio de track)
+    pub fn reset(&mut self) {
+        self.envelope = 1.0;
+    }
+
+    /// Reduce en el sitio los picos por encima del techo (o de la rodilla)
+    pub fn process(&mut self, samples: &mut [f32], sample_rate: u32, channels: u16) {
+        if !self.enabled {
+            return;
+        }
+        if self.sample_rate != sample_rate {
+            self.sample_rate = sample_rate;
+            let release_samples = self.release_ms / 1000.0 * sample_rate.max(1) as f64;
+            self.release_coeff = (1.0 - (-1.0 / release_samples).exp()) as f32;
+        }
+
+        for frame in samples.chunks_mut(channels.max(1) as usize) {
+            let peak = frame.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+
+            // Release hacia 1.0 y ataque instantáneo si el frame pide más reducción
+            let mut envelope = self.envelope + (1.0 - self.envelope) * self.release_coeff;
+            if peak * envelope > self.knee_start {
+                envelope = envelope.min(self.target_gain(peak));
+            }
+            self.envelope = envelope;
+
+            if envelope < 1.0 {
+                frame.iter_mut().for_each(|s| *s *= envelope);
+            }
+        }
+    }
+
+    /// Versión para bloques prestados: copia solo si el limitador está activo
+    pub fn process_cow<'a>(
+        &mut self,
+        samples: Cow<'a, [f32]>,
+        sample_rate: u32,
+        channels: u16,
+    ) -> Cow<'a, [f32]> {
+        if !self.enabled {
+            return samples;
+        }
+        let mut owned = samples.into_owned();
+        self.process(&mut owned, sample_rate, channels);
+        Cow::Owned(owned)
+    }
+
+    /// Ganancia lineal que la curva asigna a un pico
+    fn target_gain(&self, peak: f32) -> f32 {
+        if !self.soft_knee {
+            return self.ceiling / peak;
+        }
+
+        let input_db = linear_to_db(peak as f64);
+        let overshoot = input_db - LIMITER_CEILING_DB;
+        let half_knee = LIMITER_KNEE_DB / 2.0;
+        let output_db = if overshoot <= -half_knee {
+            input_db
+        } else if overshoot < half_knee {
+            input_db - (overshoot + half_knee).powi(2) / (2.0 * LIMITER_KNEE_DB)
+        } else {
+            LIMITER_CEILING_DB
+        };
+        db_to_linear(output_db - input_db) as f32
+    }
+}
+
+/// dB → ganancia lineal
+fn db_to_linear(db: f64) -> f64 {
+    10f64.powf(db / 20.0)
+}
+
+/// Ganancia lineal → dB
+fn linear_to_db(value: f64) -> f64 {
+    20.0 * value.max(1e-10).log10()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(enabled: bool, target_lufs: f64) -> NormalizationSettings {
+        NormalizationSettings {
+            enabled,
+            target_lufs,
+        }
+    }
+
+    fn loudness(integrated_lufs: f64, true_peak_dbtp: f64) -> LoudnessAnalysis {
+        LoudnessAnalysis {
+            integrated_lufs,
+            true_peak_dbtp,
+            loudness_range: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_track_gain() {
+        // -8 LUFS a -14 LUFS: -6 dB
+        let gain = settings(true, -14.0).track_gain(Some(&loudness(-8.0, 0.0)));
+        assert!((gain - 0.501).abs() < 0.001);
+
+        // Sin análisis o desactivada: unidad
+        assert_eq!(settings(true, -14.0).track_gain(None), 1.0);
+        assert_eq!(
+            settings(false, -14.0).track_gain(Some(&loudness(-8.0, 0.0))),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_track_gain_boost_is_capped() {
+        let gain = settings(true, -14.0).track_gain(Some(&loudness(-40.0, -30.0)));
+        let expected = db_to_linear(NORMALIZATION_MAX_BOOST_DB) as f32;
+        assert!((gain - expected).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_track_gain_respects_true_peak() {
+        // -20 LUFS a -14 pediría +6 dB, pero con -4 dBTP solo caben 3 dB
+        let gain = settings(true, -14.0).track_gain(Some(&loudness(-20.0, -4.0)));
+        let expected = db_to_linear(3.0) as f32;
+        assert!((gain - expected).abs() < 1e-4);
+
+        // La atenuación no depende del true peak
+        let gain = settings(true, -14.0).track_gain(Some(&loudness(-8.0, 0.5)));
+        assert!((gain - 0.501).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_settings_clamped() {
+        assert_eq!(settings(true, -50.0).clamped().target_lufs, -30.0);
+        assert_eq!(settings(true, 0.0).clamped().target_lufs, -5.0);
+    }
+
+    #[test]
+    fn test_limiter_never_exceeds_ceiling() {
+        let mut limiter = Limiter::new(true);
+        let ceiling = db_to_linear(LIMITER_CEILING_DB) as f32;
+
+        let mut samples: Vec<f32> = (0..4410)
+            .flat_map(|i| {
+                let s = 1.8 * (i as f32 * 0.05).sin();
+                [s, -s]
+            })
+            .collect();
+        limiter.process(&mut samples, 44100, 2);
+
+        assert!(samples.iter().all(|s| s.abs() <= ceiling + 1e-6));
+    }
+
+    #[test]
+    fn test_limiter_releases_after_peak() {
+        let mut limiter = Limiter::new(true);
+        let mut samples = vec![2.0f32; 2];
+        samples.extend(vec![0.5f32; 44100 * 2]);
+        limiter.process(&mut samples, 44100, 2);
+
+        // Tras 1 segundo (>> release) la señal baja ya no se atenúa
+        assert!((samples.last().unwrap() - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_limiter_disabled_is_bypass() {
+        let mut limiter = Limiter::new(false);
+        let input = [1.5f32, -1.5];
+        let out = limiter.process_cow(Cow::Borrowed(&input), 44100, 2);
+        assert!(matches!(out, Cow::Borrowed(_)));
+        assert_eq!(out[0], 1.5);
+    }
+
+    #[test]
+    fn test_limiter_soft_knee() {
+        let ceiling = db_to_linear(LIMITER_CEILING_DB) as f32;
+        let in_knee = db_to_linear(LIMITER_CEILING_DB - LIMITER_KNEE_DB / 4.0) as f32;
+
+        // Sin rodilla, lo que no llega al techo pasa intacto
+        let mut hard = Limiter::new(true);
+        let mut samples = [in_knee, -in_knee];
+        hard.process(&mut samples, 44100, 2);
+        assert_eq!(samples, [in_knee, -in_knee]);
+
+        // Con rodilla ya hay reducción por debajo del techo, y el techo se respeta
+        let mut soft = Limiter::new(true);
+        soft.set_soft_knee(true);
+        let mut samples = [in_knee, -in_knee];
+        soft.process(&mut samples, 44100, 2);
+        assert!(samples[0] < in_knee);
+
+        let mut samples: Vec<f32> = (0..4410).map(|i| 1.8 * (i as f32 * 0.05).sin()).collect();
+        soft.process(&mut samples, 44100, 1);
+        assert!(samples.iter().all(|s| s.abs() <= ceiling + 1e-6));
+    }
+}
@@ -11,14 +11,16 @@
 use std::path::Path;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::mpsc::{self, Sender};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
 
 use crate::audio::constants::DEFAULT_VOLUME;
 use crate::audio::error::AudioResult;
 
+use super::clock::PositionHandle;
 use super::decode_loop::decode_loop;
-use super::types::PlayerControlEvent;
+use super::queue::PlaybackQueue;
+use super::types::{PlayerControlEvent, PlayerRole};
 
 /// Reproductor de audio principal
 ///
@@ -42,11 +44,36 @@ pub struct AudioPlayer<R: tauri::Runtime> {
 }
 
 impl<R: tauri::Runtime> AudioPlayer<R> {
-    /// Crea un nuevo reproductor de audio
+    /// Crea el reproductor principal
     ///
     /// # Arguments
     /// * `app_handle` - Handle de Tauri para emitir eventos
-    pub fn new(app_handle: tauri::AppHandle<R>) -> AudioResult<Self> {
+    /// * `queue` - Cola de reproducción compartida con los comandos
+    pub fn new(
+        app_handle: tauri::AppHandle<R>,
+        queue: Arc<Mutex<PlaybackQueue>>,
+    ) -> AudioResult<Self> {
+        Self::with_role(app_handle, PlayerRole::Main, queue)
+    }
+
+    /// Crea el reproductor de pre-escucha (auriculares)
+    ///
+    /// AIDEV-NOTE: Independiente del principal: su propio decode thread, output,
+    /// posición, volumen y eventos `preview:*`. No usa cola (una vacía nunca avanza).
+    pub fn preview(app_handle: tauri::AppHandle<R>) -> AudioResult<Self> {
+        Self::with_role(
+            app_handle,
+            PlayerRole::Preview,
+            Arc::new(Mutex::new(PlaybackQueue::new())),
+        )
+    }
+
+    /// Crea un reproductor con el rol indicado
+    pub fn with_role(
+        app_handle: tauri::AppHandle<R>,
+        role: PlayerRole,
+        queue: Arc<Mutex<PlaybackQueue>>,
+    ) -> AudioResult<Self> {
         let (control_tx, control_rx) = mpsc::channel();
 
         // Estado compartido
@@ -65,10 +92,12 @@ impl<R: tauri::Runtime> AudioPlayer<R> {
             decode_loop(
                 control_rx,
                 app_handle,
+                role,
                 state_clone,
                 position_clone,
                 duration_clone,
                 volume_clone,
+                queue,
             );
         });
 
@@ -182,6 +211,44 @@ impl<R: tauri::Runtime> AudioPlayer<R> {
             })
     }
 
+    /// Precarga el siguiente track para transición gapless
+    pub fn enqueue_next(&self, path: &Path) -> AudioResult<()> {
+        self.control_tx
+            .send(PlayerControlEvent::EnqueueNext {
+                path: path.to_string_lossy().to_string(),
+            })
+            .map_err(|e| {
+                crate::audio::error::AudioError::PlaybackFailed(format!(
+                    "Error enviando comando: {}",
+                    e
+                ))
+            })
+    }
+
+    /// Cambia la velocidad de reproducción (1.0 = original)
+    pub fn set_tempo(&self, rate: f64) -> AudioResult<()> {
+        self.control_tx
+            .send(PlayerControlEvent::SetTempo { rate })
+            .map_err(|e| {
+                crate::audio::error::AudioError::PlaybackFailed(format!(
+                    "Error enviando comando: {}",
+                    e
+                ))
+            })
+    }
+
+    /// Activa/desactiva key lock
+    pub fn set_key_lock(&self, enabled: bool) -> AudioResult<()> {
+        self.control_tx
+            .send(PlayerControlEvent::SetKeyLock { enabled })
+            .map_err(|e| {
+                crate::audio::error::AudioError::PlaybackFailed(format!(
+                    "Error enviando comando: {}",
+                    e
+                ))
+            })
+    }
+
     /// Obtiene el estado actual
     pub fn is_playing(&self) -> bool {
         self.state.load(Ordering::SeqCst)
@@ -197,8 +264,203 @@ impl<R: tauri::Runtime> AudioPlayer<R> {
         f64::from_bits(self.duration.load(Ordering::SeqCst))
     }
 
+    /// Handle de solo lectura a la posición audible y la duración
+    ///
+    /// Sigue siendo válido aunque el player se olvide con `mem::forget`.
+    pub fn position_handle(&self) -> PositionHandle {
+        PositionHandle::new(Arc::clone(&self.position), Arc::clone(&self.duration))
+    }
+
     /// Obtiene el volumen actual
     pub fn get_volume(&self) -> f64 {
         f64::from_bits(self.volume.load(Ordering::SeqCst))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    use tauri::test::{mock_app, MockRuntime};
+
+    /// Margen (segundos) al comparar posiciones: el reloj avanza por bloques
+    const POSITION_TOLERANCE: f64 = 0.05;
+
+    /// Genera un WAV mono de prueba (rampa, para detectar saltos)
+    fn write_test_wav(path: &Path, sample_rate: u32, frames: usize) {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for i in 0..frames {
+            writer.write_sample((i % 20000) as i16).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    /// Reproductor principal sobre un pseudo-dispositivo (la app debe seguir viva)
+    fn headless_player(device: &str) -> (tauri::App<MockRuntime>, AudioPlayer<MockRuntime>) {
+        let app = mock_app();
+        let player = AudioPlayer::new(
+            app.handle().clone(),
+            Arc::new(Mutex::new(PlaybackQueue::new())),
+        )
+        .unwrap();
+        player.set_audio_device(Some(device.to_string())).unwrap();
+        (app, player)
+    }
+
+    fn wait_for(mut done: impl FnMut() -> bool) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Instant::now() < deadline {
+            if done() {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        false
+    }
+
+    /// Lee un WAV renderizado cuando su cabecera ya refleja `frames` frames
+    fn read_render(
+        path: &Path,
+        frames: u32,
+    ) -> Option<hound::WavReader<std::io::BufReader<std::fs::File>>> {
+        let reader = hound::WavReader::open(path).ok()?;
+        (reader.duration() == frames).then_some(reader)
+    }
+
+    #[test]
+    fn test_headless_playback_reaches_end() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("track.wav");
+        write_test_wav(&path, 44100, 22050);
+
+        let (_app, player) = headless_player("null:fast");
+        player.play(&path, None).unwrap();
+        assert!(wait_for(|| player.get_duration() > 0.0));
+        assert!((player.get_duration() - 0.5).abs() < 0.01);
+
+        // Sin tarjeta ni reloj, el track termina enseguida
+        assert!(wait_for(|| !player.is_playing()));
+        assert!(player.get_position().abs() < POSITION_TOLERANCE);
+    }
+
+    #[test]
+    fn test_headless_pause_and_seek() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("track.wav");
+        write_test_wav(&path, 44100, 44100 * 10);
+
+        let (_app, player) = headless_player("null");
+        player.play(&path, None).unwrap();
+        assert!(wait_for(|| player.get_position() > 0.0));
+
+        player.pause().unwrap();
+        assert!(wait_for(|| !player.is_playing()));
+        let paused_at = player.get_position();
+        thread::sleep(Duration::from_millis(100));
+        assert!((player.get_position() - paused_at).abs() < POSITION_TOLERANCE);
+
+        player.seek(5.0).unwrap();
+        assert!(wait_for(
+            || (player.get_position() - 5.0).abs() < POSITION_TOLERANCE
+        ));
+
+        player.resume().unwrap();
+        assert!(wait_for(|| player.get_position() > 5.0 + POSITION_TOLERANCE));
+        // En tiempo real no puede ir mucho más rápido que el reloj
+        assert!(player.get_position() < 6.0);
+        player.stop().unwrap();
+    }
+
+    #[test]
+    fn test_offline_render_to_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("track.wav");
+        let render = dir.path().join("render.wav");
+        write_test_wav(&path, 22050, 22050);
+
+        let (_app, player) = headless_player(&format!("file:fast:{}", render.display()));
+        player.play(&path, None).unwrap();
+        assert!(wait_for(|| player.get_duration() > 0.0));
+        assert!(wait_for(|| !player.is_playing()));
+
+        // Dejar que la salida vacíe el ring buffer y cerrarla (finaliza el WAV)
+        thread::sleep(Duration::from_millis(100));
+        player.stop().unwrap();
+        assert!(wait_for(|| read_render(&render, 22050).is_some()));
+
+        let mut reader = read_render(&render, 22050).unwrap();
+        assert_eq!(reader.spec().sample_rate, 22050);
+        let rendered: Vec<f32> = reader.samples::<f32>().map(|s| s.unwrap()).collect();
+        let expected = (0..10000).map(|i| i as f32 / 32768.0);
+        assert!(rendered[..10000]
+            .iter()
+            .zip(expected)
+            .all(|(out, input)| (out - input).abs() < 1e-4));
+    }
+
+    #[test]
+    fn test_position_advances_during_crossfade() {
+        use super::super::crossfade::{CrossfadeCurve, CrossfadeSettings};
+
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first.wav");
+        let second = dir.path().join("second.wav");
+        write_test_wav(&first, 44100, 44100 * 4);
+        write_test_wav(&second, 44100, 44100 * 6);
+
+        let (_app, player) = headless_player("null");
+        player
+            .get_control_tx()
+            .send(PlayerControlEvent::SetCrossfade {
+                settings: CrossfadeSettings {
+                    enabled: true,
+                    seconds: 2.0,
+                    curve: CrossfadeCurve::Linear,
+                },
+            })
+            .unwrap();
+        player.play(&first, None).unwrap();
+        player.enqueue_next(&second).unwrap();
+
+        // La ventana empieza a los 2s del primer track: la posición tiene que
+        // seguir avanzando antes de que el segundo (6s) pase a ser el actual
+        assert!(wait_for(|| {
+            player.get_position() > 3.0 && (player.get_duration() - 4.0).abs() < 0.01
+        }));
+        assert!(wait_for(|| (player.get_duration() - 6.0).abs() < 0.01));
+        player.stop().unwrap();
+    }
+
+    #[test]
+    fn test_device_switch_mid_track() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("track.wav");
+        let render = dir.path().join("switch.wav");
+        write_test_wav(&path, 44100, 44100 * 2);
+
+        let (_app, player) = headless_player("null");
+        player.play(&path, None).unwrap();
+        assert!(wait_for(|| player.get_position() > 0.2));
+
+        // El cambio de dispositivo conserva el track y su posición
+        player
+            .set_audio_device(Some(format!("file:fast:{}", render.display())))
+            .unwrap();
+        assert!(wait_for(|| !player.is_playing()));
+        player.stop().unwrap();
+
+        assert!(wait_for(
+            || hound::WavReader::open(&render).is_ok_and(|reader| reader.duration() > 0)
+        ));
+        let reader = hound::WavReader::open(&render).unwrap();
+        assert_eq!(reader.spec().sample_rate, 44100);
+        assert!(reader.duration() < 44100 * 2);
+    }
+}
@@ -0,0 +1,477 @@
+//! Cola de reproducción con shuffle y repetición
+//!
+//! AIDEV-NOTE: La cola vive en `AudioPlayerState` (Arc<Mutex<>>) y la comparten
+//! los comandos Tauri y el decode thread. El decode thread la consulta para
+//! precargar el siguiente track (gapless/crossfade) y la avanza al fin de track.
+//! `items` conserva el orden original; `order` es el orden de reproducción.
+
+use serde::{Deserialize, Serialize};
+
+use crate::db::models::{QueueItem, SavedQueue};
+
+/// Modo de repetición de la cola
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RepeatMode {
+    #[default]
+    Off,
+    /// Repetir el track actual
+    One,
+    /// Repetir toda la cola
+    All,
+}
+
+impl RepeatMode {
+    /// Nombre usado en la base de datos
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RepeatMode::Off => "off",
+            RepeatMode::One => "one",
+            RepeatMode::All => "all",
+        }
+    }
+
+    /// Parsea el nombre de la base de datos (desconocido = Off)
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "one" => RepeatMode::One,
+            "all" => RepeatMode::All,
+            _ => RepeatMode::Off,
+        }
+    }
+}
+
+/// Estado de la cola enviado al frontend
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueSnapshot {
+    pub items: Vec<QueueItem>,
+    pub order: Vec<usize>,
+    pub current: Option<usize>,
+    pub current_item: Option<QueueItem>,
+    pub shuffle: bool,
+    pub repeat: RepeatMode,
+    pub active: bool,
+}
+
+/// Cola de reproducción
+pub struct PlaybackQueue {
+    items: Vec<QueueItem>,
+    order: Vec<usize>,
+    /// Posición actual dentro de `order` (None = antes del primer track)
+    current: Option<usize>,
+    shuffle: bool,
+    repeat: RepeatMode,
+    /// La cola solo dirige la reproducción si el último play salió de ella
+    active: bool,
+    rng: fastrand::Rng,
+}
+
+impl PlaybackQueue {
+    /// Crea una cola vacía
+    pub fn new() -> Self {
+        Self::with_rng(fastrand::Rng::new())
+    }
+
+    /// Crea una cola vacía con semilla fija (orden de shuffle reproducible)
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(fastrand::Rng::with_seed(seed))
+    }
+
+    fn with_rng(rng: fastrand::Rng) -> Self {
+        Self {
+            items: Vec::new(),
+            order: Vec::new(),
+            current: None,
+            shuffle: false,
+            repeat: RepeatMode::Off,
+            active: false,
+            rng,
+        }
+    }
+
+    /// Restaura una cola guardada en la base de datos
+    pub fn restore(&mut self, saved: SavedQueue) {
+        let valid_order = saved.order.len() == saved.items.len()
+            && saved.order.iter().all(|&index| index < saved.items.len());
+
+        self.order = if valid_order {
+            saved.order
+        } else {
+            (0..saved.items.len()).collect()
+        };
+        self.items = saved.items;
+        self.current = saved
+            .current
+            .filter(|&position| position < self.order.len());
+        self.shuffle = saved.shuffle;
+        self.repeat = RepeatMode::parse(&saved.repeat_mode);
+        self.active = self.current.is_some();
+    }
+
+    /// Estado para persistir en la base de datos
+    pub fn to_saved(&self) -> SavedQueue {
+        SavedQueue {
+            items: self.items.clone(),
+            order: self.order.clone(),
+            current: self.current,
+            shuffle: self.shuffle,
+            repeat_mode: self.repeat.as_str().to_string(),
+        }
+    }
+
+    /// Estado para el frontend
+    pub fn snapshot(&self) -> QueueSnapshot {
+        QueueSnapshot {
+            items: self.items.clone(),
+            order: self.order.clone(),
+            current: self.current,
+            current_item: self.current_item(),
+            shuffle: self.shuffle,
+            repeat: self.repeat,
+            active: self.active,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Activa/desactiva el avance automático (play_track suelto la desactiva)
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active;
+    }
+
+    pub fn repeat(&self) -> RepeatMode {
+        self.repeat
+    }
+
+    pub fn set_repeat(&mut self, repeat: RepeatMode) {
+        self.repeat = repeat;
+    }
+
+    pub fn shuffle(&self) -> bool {
+        self.shuffle
+    }
+
+    /// Activa/desactiva shuffle manteniendo el track actual
+    ///
+    /// AIDEV-NOTE: Al activar, el track actual pasa a la primera posición del
+    /// orden aleatorio para que "siguiente" no repita tracks ya escuchados.
+    pub fn set_shuffle(&mut self, shuffle: bool) {
+        let current_index = self.current_index();
+        self.shuffle = shuffle;
+
+        if shuffle {
+            let mut rest: Vec<usize> = (0..self.items.len())
+                .filter(|&index| Some(index) != current_index)
+                .collect();
+            self.rng.shuffle(&mut rest);
+            self.order = current_index.into_iter().chain(rest).collect();
+            self.current = current_index.map(|_| 0);
+        } else {
+            self.order = (0..self.items.len()).collect();
+            self.current = current_index;
+        }
+    }
+
+    /// Reemplaza la cola y la deja lista para reproducir desde `start`
+    ///
+    /// Retorna el item en `start` (índice en el orden original).
+    pub fn replace(&mut self, items: Vec<QueueItem>, start: Option<usize>) -> Option<QueueItem> {
+        self.items = items;
+        self.order = (0..self.items.len()).collect();
+        self.current = None;
+        self.active = true;
+
+        let start = start.filter(|&index| index < self.items.len());
+        if let Some(index) = start {
+            self.current = Some(index);
+        }
+        if self.shuffle {
+            self.set_shuffle(true);
+        }
+
+        self.current_item()
+    }
+
+    /// Añade tracks al final de la cola
+    ///
+    /// Con shuffle activo se insertan en posiciones aleatorias después del actual.
+    pub fn append(&mut self, items: Vec<QueueItem>) {
+        for item in items {
+            let index = self.items.len();
+            self.items.push(item);
+
+            if self.shuffle {
+                let min = self.current.map(|position| position + 1).unwrap_or(0);
+                let position = self.rng.usize(min..=self.order.len());
+                self.order.insert(position, index);
+            } else {
+                self.order.push(index);
+            }
+        }
+    }
+
+    /// Elimina un track de la cola (índice en el orden original)
+    ///
+    /// Si se elimina el actual, "siguiente" continúa con el que venía después.
+    pub fn remove(&mut self, index: usize) -> Option<QueueItem> {
+        if index >= self.items.len() {
+            return None;
+        }
+
+        let removed = self.items.remove(index);
+        let position = self.order.iter().position(|&i| i == index)?;
+        self.order.remove(position);
+        for i in self.order.iter_mut() {
+            if *i > index {
+                *i -= 1;
+            }
+        }
+
+        if let Some(current) = self.current {
+            if position <= current {
+                self.current = current.checked_sub(1);
+            }
+        }
+
+        Some(removed)
+    }
+
+    /// Vacía la cola
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.order.clear();
+        self.current = None;
+        self.active = false;
+    }
+
+    /// Índice (orden original) del track actual
+    pub fn current_index(&self) -> Option<usize> {
+        self.current
+            .and_then(|position| self.order.get(position).copied())
+    }
+
+    /// Track actual
+    pub fn current_item(&self) -> Option<QueueItem> {
+        self.current_index()
+            .and_then(|index| self.items.get(index).cloned())
+    }
+
+    /// Salta a un track de la cola (índice en el orden original)
+    pub fn jump_to(&mut self, index: usize) -> Option<QueueItem> {
+        let position = self.order.iter().position(|&i| i == index)?;
+        self.current = Some(position);
+        self.active = true;
+        self.current_item()
+    }
+
+    /// Track que sonará después del actual, sin avanzar
+    ///
+    /// `auto` indica fin de track natural: con repeat-one se repite el actual.
+    pub fn peek_next(&self, auto: bool) -> Option<QueueItem> {
+        if !self.active {
+            return None;
+        }
+        self.next_position(auto)
+            .and_then(|position| self.order.get(position))
+            .and_then(|&index| self.items.get(index).cloned())
+    }
+
+    /// Avanza al siguiente track
+    pub fn advance(&mut self, auto: bool) -> Option<QueueItem> {
+        if !self.active {
+            return None;
+        }
+        let position = self.next_position(auto)?;
+        self.current = Some(position);
+        self.current_item()
+    }
+
+    /// Retrocede al track anterior (con repeat-all, del primero salta al último)
+    pub fn previous(&mut self) -> Option<QueueItem> {
+        if self.order.is_empty() {
+            return None;
+        }
+
+        let position = match self.current {
+            Some(0) | None if self.repeat == RepeatMode::All => self.order.len() - 1,
+            Some(0) | None => 0,
+            Some(position) => position - 1,
+        };
+        self.current = Some(position);
+        self.active = true;
+        self.current_item()
+    }
+
+    fn next_position(&self, auto: bool) -> Option<usize> {
+        if self.order.is_empty() {
+            return None;
+        }
+
+        let Some(current) = self.current else {
+            return Some(0);
+        };
+
+        if auto && self.repeat == RepeatMode::One {
+            return Some(current);
+        }
+
+        if current + 1 < self.order.len() {
+            Some(current + 1)
+        } else if self.repeat != RepeatMode::Off {
+            Some(0)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for PlaybackQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(n: usize) -> Vec<QueueItem> {
+        (0..n)
+            .map(|i| QueueItem {
+                track_id: format!("track-{}", i),
+                path: format!("/music/{}.mp3", i),
+            })
+            .collect()
+    }
+
+    fn track_id(item: Option<QueueItem>) -> Option<String> {
+        item.map(|item| item.track_id)
+    }
+
+    #[test]
+    fn test_replace_and_advance() {
+        let mut queue = PlaybackQueue::with_seed(1);
+        let first = queue.replace(items(3), Some(0));
+        assert_eq!(track_id(first), Some("track-0".to_string()));
+
+        assert_eq!(track_id(queue.advance(false)), Some("track-1".to_string()));
+        assert_eq!(track_id(queue.advance(false)), Some("track-2".to_string()));
+        assert_eq!(queue.advance(false), None);
+    }
+
+    #[test]
+    fn test_repeat_one_only_on_auto_advance() {
+        let mut queue = PlaybackQueue::with_seed(1);
+        queue.replace(items(3), Some(1));
+        queue.set_repeat(RepeatMode::One);
+
+        assert_eq!(track_id(queue.peek_next(true)), Some("track-1".to_string()));
+        assert_eq!(track_id(queue.advance(true)), Some("track-1".to_string()));
+        assert_eq!(track_id(queue.advance(false)), Some("track-2".to_string()));
+    }
+
+    #[test]
+    fn test_repeat_all_wraps() {
+        let mut queue = PlaybackQueue::with_seed(1);
+        queue.replace(items(2), Some(1));
+        queue.set_repeat(RepeatMode::All);
+
+        assert_eq!(track_id(queue.advance(true)), Some("track-0".to_string()));
+        assert_eq!(track_id(queue.previous()), Some("track-1".to_string()));
+    }
+
+    #[test]
+    fn test_previous_stops_at_first() {
+        let mut queue = PlaybackQueue::with_seed(1);
+        queue.replace(items(3), Some(0));
+        assert_eq!(track_id(queue.previous()), Some("track-0".to_string()));
+    }
+
+    #[test]
+    fn test_shuffle_keeps_current_first() {
+        let mut queue = PlaybackQueue::with_seed(42);
+        queue.replace(items(10), Some(4));
+        queue.set_shuffle(true);
+
+        assert_eq!(queue.current, Some(0));
+        assert_eq!(queue.current_index(), Some(4));
+        let mut sorted = queue.order.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..10).collect::<Vec<_>>());
+
+        queue.set_shuffle(false);
+        assert_eq!(queue.order, (0..10).collect::<Vec<_>>());
+        assert_eq!(queue.current_index(), Some(4));
+    }
+
+    #[test]
+    fn test_shuffle_visits_every_track_once() {
+        let mut queue = PlaybackQueue::with_seed(7);
+        queue.replace(items(8), Some(0));
+        queue.set_shuffle(true);
+
+        let mut seen = vec![queue.current_index().unwrap()];
+        while queue.advance(true).is_some() {
+            seen.push(queue.current_index().unwrap());
+        }
+        seen.sort();
+        assert_eq!(seen, (0..8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_remove_current_continues_with_next() {
+        let mut queue = PlaybackQueue::with_seed(1);
+        queue.replace(items(3), Some(1));
+
+        let removed = queue.remove(1);
+        assert_eq!(track_id(removed), Some("track-1".to_string()));
+        assert_eq!(track_id(queue.advance(false)), Some("track-2".to_string()));
+    }
+
+    #[test]
+    fn test_inactive_queue_does_not_advance() {
+        let mut queue = PlaybackQueue::with_seed(1);
+        queue.replace(items(3), Some(0));
+        queue.set_active(false);
+
+        assert_eq!(queue.peek_next(true), None);
+        assert_eq!(queue.advance(true), None);
+    }
+
+    #[test]
+    fn test_saved_roundtrip() {
+        let mut queue = PlaybackQueue::with_seed(3);
+        queue.replace(items(5), Some(2));
+        queue.set_shuffle(true);
+        queue.set_repeat(RepeatMode::All);
+
+        let saved = queue.to_saved();
+        assert_eq!(saved.repeat_mode, "all");
+
+        let mut restored = PlaybackQueue::with_seed(3);
+        restored.restore(saved);
+        assert_eq!(restored.order, queue.order);
+        assert_eq!(restored.current_item(), queue.current_item());
+        assert_eq!(restored.repeat(), RepeatMode::All);
+        assert!(restored.shuffle());
+        assert!(restored.is_active());
+    }
+
+    #[test]
+    fn test_repeat_mode_serialize() {
+        assert_eq!(serde_json::to_string(&RepeatMode::One).unwrap(), "\"one\"");
+        assert_eq!(RepeatMode::parse("all"), RepeatMode::All);
+        assert_eq!(RepeatMode::parse("bogus"), RepeatMode::Off);
+    }
+}
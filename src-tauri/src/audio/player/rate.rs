@@ -0,0 +1,99 @@
+//! Conversión al sample rate del output
+//!
+//! AIDEV-NOTE: Último paso de la cadena de salida (después de tempo y DSP).
+//! Si el dispositivo se abrió al sample rate del archivo (modo bit-perfect) los
+//! samples pasan sin copia; solo se usa `AudioResampler` cuando el dispositivo
+//! no soporta ese rate o el usuario fijó uno.
+
+use std::borrow::Cow;
+
+use crate::audio::resampler::AudioResampler;
+
+/// Conversor de sample rate del decode thread
+#[derive(Default)]
+pub struct RateConverter {
+    /// Formato (entrada, salida, canales) para el que se creó el resampler
+    format: Option<(u32, u32, usize)>,
+    resampler: Option<AudioResampler>,
+}
+
+impl RateConverter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Descarta el audio en buffer (seek, cambio de track o de output)
+    pub fn reset(&mut self) {
+        if let Some(ref mut resampler) = self.resampler {
+            resampler.reset();
+        }
+    }
+
+    /// Indica si el último bloque pasó por el resampler
+    pub fn is_resampling(&self) -> bool {
+        self.resampler.is_some()
+    }
+
+    /// Convierte un bloque interleaved de `input_rate` a `output_rate`
+    pub fn process<'a>(
+        &mut self,
+        samples: Cow<'a, [f32]>,
+        input_rate: u32,
+        output_rate: u32,
+        channels: u16,
+    ) -> Cow<'a, [f32]> {
+        let format = (input_rate, output_rate, channels.max(1) as usize);
+        if self.format != Some(format) {
+            self.format = Some(format);
+            self.resampler = None;
+            if input_rate != output_rate {
+                match AudioResampler::new(input_rate, output_rate, format.2) {
+                    Ok(resampler) => self.resampler = Some(resampler),
+                    Err(e) => log::error!("❌ Error creando resampler de salida: {}", e),
+                }
+            }
+        }
+
+        let Some(ref mut resampler) = self.resampler else {
+            return samples;
+        };
+        match resampler.process_buffered(&samples) {
+            Ok(resampled) => Cow::Owned(resampled),
+            Err(e) => {
+                log::warn!("⚠️ Error en resampling de salida: {}", e);
+                samples
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bypass_when_rates_match() {
+        let mut converter = RateConverter::new();
+        let samples = [0.1f32, 0.2, 0.3, 0.4];
+        let out = converter.process(Cow::Borrowed(&samples), 96000, 96000, 2);
+        assert!(matches!(out, Cow::Borrowed(_)));
+        assert!(!converter.is_resampling());
+    }
+
+    #[test]
+    fn test_resamples_to_output_rate() {
+        let mut converter = RateConverter::new();
+        let input = vec![0.0f32; 48000 * 2];
+        let mut total = 0;
+        for chunk in input.chunks(4096) {
+            total += converter
+                .process(Cow::Borrowed(chunk), 48000, 44100, 2)
+                .len();
+        }
+        assert!(converter.is_resampling());
+
+        // Un segundo a 48 kHz son ~44100 frames (menos lo que queda en buffer)
+        let frames = total / 2;
+        assert!(frames > 40000 && frames <= 44100, "frames = {}", frames);
+    }
+}
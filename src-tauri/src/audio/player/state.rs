@@ -7,6 +7,12 @@ pub struct DecoderState {
     pub track_id: u32,
     pub time_base: symphonia::core::units::TimeBase,
     pub sample_rate: u32,
+    /// Número de canales del codec
+    pub channels: u16,
+    /// Ruta del archivo que se está decodificando
+    pub path: String,
+    /// Ganancia de normalización aplicada a cada paquete (1.0 = sin cambio)
+    pub gain: f32,
 }
 
 /// Resultado de decodificación
@@ -16,3 +22,23 @@ pub enum DecodeResult {
     /// Fin del track
     EndOfTrack,
 }
+
+/// Paquete decodificado pendiente de escribir al ring buffer
+pub enum DecodedPacket {
+    /// Samples interleaved f32 y posición del paquete (en segundos)
+    Samples { samples: Vec<f32>, position: f64 },
+    /// Paquete sin samples (otro track del contenedor)
+    Skipped(f64),
+    /// Fin del track
+    EndOfTrack,
+}
+
+/// Track siguiente abierto por adelantado para la transición gapless
+///
+/// AIDEV-NOTE: Se crea con `EnqueueNext`. El primer paquete ya viene decodificado
+/// (`primed`) para que el cambio de track no espere a symphonia.
+pub struct PreloadedTrack {
+    pub decoder: DecoderState,
+    pub duration: f64,
+    pub primed: Vec<f32>,
+}
@@ -0,0 +1,295 @@
+//! Control de tempo (pitch fader) con key lock
+//!
+//! AIDEV-NOTE: El procesado se aplica a los samples decodificados antes de
+//! escribirlos al ring buffer. La posición que reporta el decode thread sale del
+//! timestamp de cada paquete, así que sigue en tiempo del track aunque la
+//! velocidad cambie (solo el ring buffer acumula tiempo "de reloj").
+//! - Vinilo: resampling con `AudioResampler` (cambia velocidad y tono)
+//! - Key lock: time-stretching WSOLA con `TimeStretcher` (solo velocidad)
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+
+use crate::audio::resampler::AudioResampler;
+use crate::audio::timestretch::TimeStretcher;
+
+/// Rango del pitch fader
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum TempoRange {
+    /// ±8%
+    #[default]
+    #[serde(rename = "8")]
+    Eight,
+    /// ±16%
+    #[serde(rename = "16")]
+    Sixteen,
+    /// ±50%
+    #[serde(rename = "50")]
+    Fifty,
+}
+
+impl TempoRange {
+    /// Desviación máxima en porcentaje
+    pub fn max_percent(&self) -> f64 {
+        match self {
+            TempoRange::Eight => 8.0,
+            TempoRange::Sixteen => 16.0,
+            TempoRange::Fifty => 50.0,
+        }
+    }
+}
+
+/// Estado del pitch fader
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TempoSettings {
+    /// Desviación de tempo en porcentaje (+3.5 = 3.5% más rápido)
+    pub percent: f64,
+    pub range: TempoRange,
+    /// Mantener el tono al cambiar la velocidad
+    pub key_lock: bool,
+}
+
+impl TempoSettings {
+    /// Devuelve una copia con el porcentaje limitado al rango
+    pub fn clamped(self) -> Self {
+        let max = self.range.max_percent();
+        Self {
+            percent: self.percent.clamp(-max, max),
+            ..self
+        }
+    }
+
+    /// Velocidad de reproducción (1.0 = original)
+    pub fn rate(&self) -> f64 {
+        1.0 + self.percent / 100.0
+    }
+}
+
+impl From<&crate::config::AudioConfig> for TempoSettings {
+    /// El fader siempre arranca en 0%; rango y key lock son preferencias guardadas
+    fn from(config: &crate::config::AudioConfig) -> Self {
+        Self {
+            percent: 0.0,
+            range: config.tempo_range,
+            key_lock: config.key_lock,
+        }
+    }
+}
+
+/// Procesador de tempo del decode thread
+///
+/// AIDEV-NOTE: Mientras nunca se haya movido el fader los samples pasan sin copia.
+/// Una vez activo, el procesador sigue en el camino aunque se vuelva a 0% para no
+/// perder el audio que tiene en buffer (a 1.0 WSOLA reconstruye la señal exacta);
+/// vuelve a bypass en el siguiente `reset` (seek o cambio de track).
+pub struct TempoProcessor {
+    rate: f64,
+    key_lock: bool,
+    /// Formato (sample rate, canales) para el que se crearon los procesadores
+    format: Option<(u32, usize)>,
+    vinyl: Option<AudioResampler>,
+    stretcher: Option<TimeStretcher>,
+}
+
+impl TempoProcessor {
+    pub fn new(rate: f64, key_lock: bool) -> Self {
+        Self {
+            rate,
+            key_lock,
+            format: None,
+            vinyl: None,
+            stretcher: None,
+        }
+    }
+
+    /// Velocidad actual
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// Cambia la velocidad en caliente
+    pub fn set_rate(&mut self, rate: f64) {
+        self.rate = rate;
+        if let Some(ref mut stretcher) = self.stretcher {
+            stretcher.set_rate(rate);
+        }
+        if let Some(ref mut vinyl) = self.vinyl {
+            if let Err(e) = vinyl.set_ratio_relative(1.0 / rate) {
+                log::warn!("⚠️ Error cambiando velocidad de vinilo: {}", e);
+            }
+        }
+    }
+
+    /// Activa/desactiva key lock (el modo anterior se descarta)
+    pub fn set_key_lock(&mut self, key_lock: bool) {
+        if self.key_lock != key_lock {
+            self.key_lock = key_lock;
+            self.vinyl = None;
+            self.stretcher = None;
+        }
+    }
+
+    /// Descarta el audio en buffer (seek o cambio de track)
+    pub fn reset(&mut self) {
+        if self.is_unity() {
+            self.vinyl = None;
+            self.stretcher = None;
+        } else {
+            if let Some(ref mut vinyl) = self.vinyl {
+                vinyl.reset();
+            }
+            if let Some(ref mut stretcher) = self.stretcher {
+                stretcher.reset();
+            }
+        }
+    }
+
+    /// Procesa un bloque de samples interleaved
+    pub fn process<'a>(
+        &mut self,
+        samples: &'a [f32],
+        sample_rate: u32,
+        channels: u16,
+    ) -> Cow<'a, [f32]> {
+        let format = (sample_rate, channels.max(1) as usize);
+        if self.format != Some(format) {
+            // Cambio de formato (transición entre tracks): recrear procesadores
+            self.format = Some(format);
+            self.vinyl = None;
+            self.stretcher = None;
+        }
+
+        let engaged = self.vinyl.is_some() || self.stretcher.is_some();
+        if self.is_unity() && !engaged {
+            return Cow::Borrowed(samples);
+        }
+
+        if self.key_lock {
+            let rate = self.rate;
+            let stretcher = self.stretcher.get_or_insert_with(|| {
+                log::info!("🎛️ Key lock activo ({:.3}x)", rate);
+                let mut stretcher = TimeStretcher::new(format.0, format.1);
+                stretcher.set_rate(rate);
+                stretcher
+            });
+            return Cow::Owned(stretcher.process(samples));
+        }
+
+        if self.vinyl.is_none() {
+            log::info!("🎛️ Modo vinilo activo ({:.3}x)", self.rate);
+            match AudioResampler::new(format.0, format.0, format.1) {
+                Ok(mut vinyl) => {
+                    if let Err(e) = vinyl.set_ratio_relative(1.0 / self.rate) {
+                        log::warn!("⚠️ Error configurando velocidad de vinilo: {}", e);
+                    }
+                    self.vinyl = Some(vinyl);
+                }
+                Err(e) => {
+                    log::error!("❌ Error creando resampler de vinilo: {}", e);
+                    return Cow::Borrowed(samples);
+                }
+            }
+        }
+
+        match self
+            .vinyl
+            .as_mut()
+            .map(|vinyl| vinyl.process_buffered(samples))
+        {
+            Some(Ok(resampled)) => Cow::Owned(resampled),
+            Some(Err(e)) => {
+                log::warn!("⚠️ Error en resampling de vinilo: {}", e);
+                Cow::Borrowed(samples)
+            }
+            None => Cow::Borrowed(samples),
+        }
+    }
+
+    fn is_unity(&self) -> bool {
+        (self.rate - 1.0).abs() < 1e-6
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tempo_settings_clamped() {
+        let settings = TempoSettings {
+            percent: 12.0,
+            range: TempoRange::Eight,
+            key_lock: false,
+        }
+        .clamped();
+        assert_eq!(settings.percent, 8.0);
+        assert!((settings.rate() - 1.08).abs() < 1e-9);
+
+        let settings = TempoSettings {
+            percent: -40.0,
+            range: TempoRange::Fifty,
+            key_lock: true,
+        }
+        .clamped();
+        assert_eq!(settings.percent, -40.0);
+        assert!((settings.rate() - 0.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_tempo_range_serialize() {
+        assert_eq!(
+            serde_json::to_string(&TempoRange::Sixteen).unwrap(),
+            "\"16\""
+        );
+        let range: TempoRange = serde_json::from_str("\"50\"").unwrap();
+        assert_eq!(range, TempoRange::Fifty);
+    }
+
+    #[test]
+    fn test_processor_bypass_at_unity() {
+        let mut processor = TempoProcessor::new(1.0, false);
+        let samples = [0.1f32, 0.2, 0.3, 0.4];
+        let out = processor.process(&samples, 44100, 2);
+        assert!(matches!(out, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_processor_modes_change_length() {
+        let input = vec![0.0f32; 44100 * 2];
+
+        for key_lock in [false, true] {
+            let mut processor = TempoProcessor::new(1.0, key_lock);
+            processor.set_rate(1.25);
+
+            let mut total = 0;
+            for block in input.chunks(2048) {
+                total += processor.process(block, 44100, 2).len();
+            }
+
+            // ~1/1.25 de la entrada (menos la latencia de cada procesador)
+            let expected = input.len() as f64 / 1.25;
+            assert!(
+                (total as f64) < expected * 1.02 && (total as f64) > expected * 0.85,
+                "key_lock={} total={}",
+                key_lock,
+                total
+            );
+        }
+    }
+
+    #[test]
+    fn test_reset_returns_to_bypass_at_unity() {
+        let mut processor = TempoProcessor::new(1.1, true);
+        processor.process(&[0.0; 512], 44100, 2);
+
+        processor.set_rate(1.0);
+        let out = processor.process(&[0.0; 4], 44100, 2);
+        assert!(matches!(out, Cow::Owned(_)));
+
+        processor.reset();
+        let out = processor.process(&[0.0; 4], 44100, 2);
+        assert!(matches!(out, Cow::Borrowed(_)));
+    }
+}
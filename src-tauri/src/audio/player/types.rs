@@ -1,5 +1,13 @@
 //! Tipos y estructuras de datos para el reproductor de audio
 
+use super::crossfade::CrossfadeSettings;
+use super::cues::CueTrigger;
+use super::history::PlayThreshold;
+use super::looping::LoopRegion;
+use crate::audio::dsp::DspSettings;
+use crate::audio::output::{AudioDeviceInfo, LatencyProfile, OutputSettings};
+use super::normalization::NormalizationSettings;
+
 /// Estado de reproducción
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
 #[serde(rename_all = "lowercase")]
@@ -9,6 +17,33 @@ pub enum PlaybackState {
     Stopped,
 }
 
+/// Rol de un reproductor
+///
+/// AIDEV-NOTE: Cada rol tiene su propio decode thread, output y namespace de
+/// eventos: `audio:*` para el principal y `preview:*` para la pre-escucha.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerRole {
+    /// Salida principal (sala/master)
+    Main,
+    /// Pre-escucha en auriculares (segundo dispositivo)
+    Preview,
+}
+
+impl PlayerRole {
+    /// Prefijo de los eventos emitidos por este reproductor
+    pub fn event_prefix(&self) -> &'static str {
+        match self {
+            PlayerRole::Main => "audio",
+            PlayerRole::Preview => "preview",
+        }
+    }
+
+    /// Nombre completo de un evento (p.ej. "preview:timestamp")
+    pub fn event(&self, name: &str) -> String {
+        format!("{}:{}", self.event_prefix(), name)
+    }
+}
+
 /// Evento de control enviado al decode thread
 #[derive(Debug)]
 pub enum PlayerControlEvent {
@@ -28,13 +63,40 @@ pub enum PlayerControlEvent {
     Resume,
     /// Detener y liberar recursos
     Stop,
-    /// Cambiar dispositivo de audio
+    /// Cambiar dispositivo de audio (pasa a ser el preferido)
     ChangeAudioDevice { device_name: Option<String> },
+    /// Cambió la lista de dispositivos conectados (monitor de hot-plug)
+    DevicesChanged { devices: Vec<AudioDeviceInfo> },
+    /// Cambiar buffer, sample rate o perfil de latencia (recrea el output)
+    SetOutputSettings { settings: OutputSettings },
+    /// Precargar el siguiente track para transición gapless
+    EnqueueNext { path: String },
+    /// La cola cambió: descartar el siguiente track precargado desde la cola
+    QueueUpdated,
+    /// Cambiar configuración de crossfade en caliente
+    SetCrossfade { settings: CrossfadeSettings },
+    /// Cambiar velocidad de reproducción (1.0 = original)
+    SetTempo { rate: f64 },
+    /// Activar/desactivar key lock (tempo sin cambio de tono)
+    SetKeyLock { enabled: bool },
+    /// Cambiar configuración de normalización de loudness
+    SetNormalization { settings: NormalizationSettings },
+    /// Cambiar la cadena DSP (preamp, EQ, limitador) en vivo
+    SetDsp { settings: DspSettings },
+    /// Activar o redimensionar un loop del track `path` (se ignora si suena otro)
+    SetLoop { path: String, region: LoopRegion },
+    /// Desactivar el loop del track `path` y seguir reproduciendo desde donde esté
+    ClearLoop { path: String },
+    /// Disparar el hot cue `hotkey` (1-8) del track actual
+    JumpToCue { hotkey: u8, trigger: CueTrigger },
+    /// Cambiar el umbral para contar una reproducción en el historial
+    SetPlayThreshold { threshold: PlayThreshold },
 }
 
 /// Payload para evento de timestamp
 #[derive(Clone, serde::Serialize)]
 pub struct TimestampPayload {
+    /// Posición que se está oyendo (descontando ring buffer y latencia del dispositivo)
     pub position: f64,
     pub duration: f64,
 }
@@ -46,9 +108,60 @@ pub struct StatePayload {
     pub state: PlaybackState,
 }
 
+/// Payload para evento de cambio de track (transición gapless)
+#[derive(Clone, serde::Serialize)]
+pub struct TrackChangedPayload {
+    pub path: String,
+    pub duration: f64,
+}
+
 /// Payload para evento de error
 #[derive(Clone, serde::Serialize)]
 pub struct ErrorPayload {
     pub message: String,
     pub is_critical: bool,
 }
+
+/// Payload para evento de reproducción registrada en el historial
+#[derive(Clone, serde::Serialize)]
+pub struct PlayRecordedPayload {
+    pub path: String,
+    pub seconds_played: f64,
+}
+
+/// Payload con la configuración efectiva del output (tras abrirlo)
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct OutputConfigPayload {
+    /// Dispositivo abierto (`None` = el por defecto del sistema)
+    pub device: Option<String>,
+    /// Sample rate del dispositivo
+    pub sample_rate: u32,
+    /// Sample rate del archivo que suena (si hay uno)
+    pub source_sample_rate: Option<u32>,
+    pub channels: u16,
+    /// Frames por callback (`None` = el que elija el driver)
+    pub buffer_frames: Option<u32>,
+    /// Capacidad del ring buffer en samples interleaved
+    pub ring_buffer_size: usize,
+    /// Audio que cabe en el ring buffer (ms)
+    pub latency_ms: f64,
+    pub profile: LatencyProfile,
+    /// El decode thread convierte al sample rate del dispositivo
+    pub resampling: bool,
+}
+
+/// Nivel de un canal (lineal, 1.0 = 0 dBFS)
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct ChannelLevel {
+    pub rms: f32,
+    pub peak: f32,
+}
+
+/// Payload para evento de medidores (niveles y espectro de lo que suena)
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MeterPayload {
+    /// Nivel por canal, después de DSP y volumen
+    pub levels: Vec<ChannelLevel>,
+    /// Magnitud por banda en dBFS, de graves a agudos (ver `meter::SPECTRUM_BANDS`)
+    pub spectrum: Vec<f32>,
+}
@@ -40,6 +40,8 @@ pub struct AudioResampler {
     channels: usize,
     /// Resampler interno de rubato
     resampler: SincFixedIn<f32>,
+    /// Samples interleaved pendientes hasta completar un chunk (ver `process_buffered`)
+    pending: Vec<f32>,
 }
 
 impl AudioResampler {
@@ -102,6 +104,7 @@ impl AudioResampler {
             output_rate,
             channels,
             resampler,
+            pending: Vec::new(),
         })
     }
 
@@ -167,6 +170,45 @@ impl AudioResampler {
         Ok(interleaved_output)
     }
 
+    /// Procesa samples interleaved acumulando entre llamadas hasta completar chunks
+    ///
+    /// AIDEV-NOTE: A diferencia de `process`, no descarta nada: los frames que no
+    /// llenan un chunk completo se guardan para la siguiente llamada. Pensado para
+    /// streaming (paquetes de tamaño variable del decoder).
+    pub fn process_buffered(&mut self, input: &[f32]) -> Result<Vec<f32>, String> {
+        self.pending.extend_from_slice(input);
+
+        let mut output = Vec::new();
+        loop {
+            let frames_needed = self.resampler.input_frames_next();
+            let samples_needed = frames_needed * self.channels;
+            if self.pending.len() < samples_needed {
+                break;
+            }
+
+            let planar_input = self.interleaved_to_planar(&self.pending, frames_needed);
+            let planar_output = self
+                .resampler
+                .process(&planar_input, None)
+                .map_err(|e| format!("Resampling failed: {}", e))?;
+            output.extend(self.planar_to_interleaved(&planar_output));
+
+            self.pending.drain(..samples_needed);
+        }
+
+        Ok(output)
+    }
+
+    /// Cambia el ratio de conversión relativo al ratio inicial
+    ///
+    /// Permite variar la velocidad en caliente (modo vinilo). El rango válido es
+    /// 0.5 - 2.0 (`max_resample_ratio_relative`). Con rampa para evitar clicks.
+    pub fn set_ratio_relative(&mut self, relative_ratio: f64) -> Result<(), String> {
+        self.resampler
+            .set_resample_ratio_relative(relative_ratio, true)
+            .map_err(|e| format!("Failed to set resample ratio: {}", e))
+    }
+
     /// Convierte samples de formato interleaved a planar
     ///
     /// Input: `[L0, R0, L1, R1, L2, R2, ...]`
@@ -225,6 +267,7 @@ impl AudioResampler {
     /// Útil cuando se cambia de track o se hace seek
     pub fn reset(&mut self) {
         self.resampler.reset();
+        self.pending.clear();
         log::debug!("🔄 Resampler reset");
     }
 }
@@ -279,6 +322,33 @@ mod tests {
         assert_eq!(result.unwrap().len(), 0);
     }
 
+    #[test]
+    fn test_process_buffered_keeps_partial_chunks() {
+        let mut resampler = AudioResampler::new(44100, 44100, 2).unwrap();
+
+        // 600 frames no llenan un chunk de 1024: se guardan sin producir salida
+        let first = resampler.process_buffered(&[0.1; 1200]).unwrap();
+        assert!(first.is_empty());
+
+        // Con otros 600 frames ya se completa un chunk
+        let second = resampler.process_buffered(&[0.1; 1200]).unwrap();
+        assert!(!second.is_empty());
+        assert_eq!(second.len() % 2, 0);
+    }
+
+    #[test]
+    fn test_set_ratio_relative_changes_output_length() {
+        let mut resampler = AudioResampler::new(44100, 44100, 1).unwrap();
+        resampler.set_ratio_relative(0.5).unwrap();
+
+        // Tras la rampa inicial, 0.5 produce ~la mitad de frames
+        let input = vec![0.0f32; 1024 * 8];
+        let output = resampler.process_buffered(&input).unwrap();
+        assert!(output.len() < input.len() * 3 / 4);
+
+        assert!(resampler.set_ratio_relative(4.0).is_err());
+    }
+
     #[test]
     fn test_downsample_48_to_44() {
         let mut resampler = AudioResampler::new(48000, 44100, 2).unwrap();
@@ -0,0 +1,301 @@
+//! # Módulo de Time-Stretching
+//!
+//! Cambia la velocidad de reproducción sin alterar el tono (key lock) usando
+//! WSOLA (Waveform Similarity Overlap-Add).
+//!
+//! ## Algoritmo:
+//! - La salida se construye con frames de ~40ms ventaneados (Hann) y solapados al 50%
+//! - En la entrada, cada frame avanza `hop * rate` en vez de `hop`
+//! - Antes de copiar un frame se busca (±8ms) el desplazamiento cuya forma de onda
+//!   mejor continúa el frame anterior, evitando cancelaciones de fase
+//!
+//! ## Uso:
+//! ```rust,no_run
+//! # use symphony_lib::audio::TimeStretcher;
+//! # let samples = vec![0.0f32; 4096];
+//! let mut stretcher = TimeStretcher::new(44100, 2);
+//! stretcher.set_rate(1.08); // +8% sin cambiar el tono
+//! let stretched = stretcher.process(&samples);
+//! ```
+
+/// Duración de cada frame de análisis (ms)
+const FRAME_MS: f64 = 40.0;
+
+/// Margen de búsqueda del mejor solapamiento (ms)
+const SEEK_MS: f64 = 8.0;
+
+/// Time-stretcher WSOLA para samples interleaved
+///
+/// AIDEV-NOTE: Streaming: `process` acepta bloques de cualquier tamaño y retorna lo
+/// que ya se pueda sintetizar. Introduce ~1 frame (40ms) de latencia.
+/// La correlación se calcula sobre la mezcla mono y el desplazamiento elegido se
+/// aplica a todos los canales, así la imagen estéreo no se desfasa.
+pub struct TimeStretcher {
+    channels: usize,
+    /// Tamaño del frame en frames de audio (par)
+    frame_len: usize,
+    /// Salto de síntesis (frame_len / 2)
+    hop: usize,
+    /// Margen de búsqueda en frames
+    seek_len: usize,
+    /// Ventana de Hann periódica (suma 1 al solapar al 50%)
+    window: Vec<f32>,
+    /// Velocidad (1.0 = original, 1.08 = +8%)
+    rate: f64,
+    /// Entrada interleaved pendiente de consumir
+    input: Vec<f32>,
+    /// Posición nominal (en frames dentro de `input`) del próximo frame de análisis
+    analysis_pos: f64,
+    /// Inicio de la continuación natural del último frame copiado
+    natural_pos: Option<usize>,
+    /// Mitad final del último frame ventaneado, pendiente de solapar
+    overlap: Vec<f32>,
+}
+
+impl TimeStretcher {
+    /// Crea un nuevo time-stretcher a velocidad 1.0
+    pub fn new(sample_rate: u32, channels: usize) -> Self {
+        let channels = channels.max(1);
+        let frame_len = ((sample_rate as f64 * FRAME_MS / 1000.0) as usize).max(64) & !1;
+        let hop = frame_len / 2;
+        let seek_len = (sample_rate as f64 * SEEK_MS / 1000.0) as usize;
+
+        let window = (0..frame_len)
+            .map(|i| {
+                let phase = 2.0 * std::f64::consts::PI * i as f64 / frame_len as f64;
+                (0.5 - 0.5 * phase.cos()) as f32
+            })
+            .collect();
+
+        Self {
+            channels,
+            frame_len,
+            hop,
+            seek_len,
+            window,
+            rate: 1.0,
+            input: Vec::new(),
+            analysis_pos: 0.0,
+            natural_pos: None,
+            overlap: vec![0.0; hop * channels],
+        }
+    }
+
+    /// Cambia la velocidad (se aplica desde el siguiente frame)
+    pub fn set_rate(&mut self, rate: f64) {
+        self.rate = rate.clamp(0.25, 4.0);
+    }
+
+    /// Velocidad actual
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// Número de canales
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Descarta todo el audio pendiente (seek o cambio de track)
+    pub fn reset(&mut self) {
+        self.input.clear();
+        self.analysis_pos = 0.0;
+        self.natural_pos = None;
+        self.overlap.iter_mut().for_each(|s| *s = 0.0);
+    }
+
+    /// Procesa samples interleaved y retorna los samples estirados disponibles
+    pub fn process(&mut self, samples: &[f32]) -> Vec<f32> {
+        self.input.extend_from_slice(samples);
+
+        let mut output = Vec::new();
+        while let Some(start) = self.next_frame_start() {
+            self.overlap_add(start, &mut output);
+
+            self.natural_pos = Some(start + self.hop);
+            self.analysis_pos += self.hop as f64 * self.rate;
+            self.discard_consumed();
+        }
+
+        output
+    }
+
+    /// Frames de audio disponibles en la entrada
+    fn input_frames(&self) -> usize {
+        self.input.len() / self.channels
+    }
+
+    /// Elige el inicio del siguiente frame, o `None` si falta entrada
+    fn next_frame_start(&self) -> Option<usize> {
+        let nominal = self.analysis_pos.round() as usize;
+        let available = self.input_frames();
+
+        let Some(natural) = self.natural_pos else {
+            // Primer frame: sin referencia con la que alinear
+            return (nominal + self.frame_len <= available).then_some(nominal);
+        };
+
+        let search_end = nominal + self.seek_len;
+        if search_end + self.frame_len > available || natural + self.hop > available {
+            return None;
+        }
+        let search_start = nominal.saturating_sub(self.seek_len);
+
+        // Correlación normalizada contra la continuación natural (solo la zona de solape)
+        let mut best_start = nominal;
+        let mut best_score = f32::MIN;
+        for candidate in search_start..=search_end {
+            let mut dot = 0.0f32;
+            let mut energy = 0.0f32;
+            // Paso 2: precisión suficiente a una fracción del coste
+            for i in (0..self.hop).step_by(2) {
+                let a = self.mono(candidate + i);
+                dot += a * self.mono(natural + i);
+                energy += a * a;
+            }
+            let score = dot / (energy.sqrt() + 1e-9);
+            if score > best_score {
+                best_score = score;
+                best_start = candidate;
+            }
+        }
+
+        Some(best_start)
+    }
+
+    /// Suma el frame ventaneado a la salida: mitad inicial + solape previo
+    fn overlap_add(&mut self, start: usize, output: &mut Vec<f32>) {
+        let ch = self.channels;
+        let base = start * ch;
+
+        for i in 0..self.hop {
+            let w = self.window[i];
+            for c in 0..ch {
+                output.push(self.overlap[i * ch + c] + self.input[base + i * ch + c] * w);
+            }
+        }
+
+        for i in 0..self.hop {
+            let w = self.window[self.hop + i];
+            for c in 0..ch {
+                self.overlap[i * ch + c] = self.input[base + (self.hop + i) * ch + c] * w;
+            }
+        }
+    }
+
+    /// Libera la entrada que ya no puede volver a usarse
+    fn discard_consumed(&mut self) {
+        let lowest_needed = (self.analysis_pos as usize)
+            .saturating_sub(self.seek_len)
+            .min(self.natural_pos.unwrap_or(usize::MAX));
+        if lowest_needed == 0 {
+            return;
+        }
+
+        self.input.drain(..lowest_needed * self.channels);
+        self.analysis_pos -= lowest_needed as f64;
+        self.natural_pos = self.natural_pos.map(|pos| pos - lowest_needed);
+    }
+
+    /// Muestra mono (promedio de canales) de un frame de entrada
+    fn mono(&self, frame: usize) -> f32 {
+        let base = frame * self.channels;
+        self.input[base..base + self.channels].iter().sum::<f32>() / self.channels as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(sample_rate: u32, freq: f32, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .map(|i| (2.0 * std::f32::consts::PI * freq * i as f32 / sample_rate as f32).sin())
+            .collect()
+    }
+
+    /// Frecuencia dominante estimada por cruces por cero
+    fn zero_crossing_freq(samples: &[f32], sample_rate: u32) -> f32 {
+        let crossings = samples
+            .windows(2)
+            .filter(|w| w[0] < 0.0 && w[1] >= 0.0)
+            .count();
+        crossings as f32 * sample_rate as f32 / samples.len() as f32
+    }
+
+    #[test]
+    fn test_unity_rate_reconstructs_input() {
+        let mut stretcher = TimeStretcher::new(8000, 1);
+        let input = sine(8000, 200.0, 8000);
+        let output = stretcher.process(&input);
+
+        // A velocidad 1.0 el solape de Hann reconstruye la señal (salvo el primer medio frame)
+        let hop = stretcher.hop;
+        for i in hop..output.len() {
+            assert!((output[i] - input[i]).abs() < 1e-3, "sample {} difiere", i);
+        }
+    }
+
+    #[test]
+    fn test_rate_changes_length() {
+        let sample_rate = 8000;
+        let input = sine(sample_rate, 220.0, sample_rate as usize * 4);
+
+        let mut faster = TimeStretcher::new(sample_rate, 1);
+        faster.set_rate(1.25);
+        let fast_len = faster.process(&input).len() as f64;
+
+        let mut slower = TimeStretcher::new(sample_rate, 1);
+        slower.set_rate(0.8);
+        let slow_len = slower.process(&input).len() as f64;
+
+        let expected_fast = input.len() as f64 / 1.25;
+        let expected_slow = input.len() as f64 / 0.8;
+        assert!((fast_len - expected_fast).abs() / expected_fast < 0.05);
+        assert!((slow_len - expected_slow).abs() / expected_slow < 0.05);
+    }
+
+    #[test]
+    fn test_key_lock_preserves_pitch() {
+        let sample_rate = 8000;
+        let input = sine(sample_rate, 250.0, sample_rate as usize * 3);
+
+        let mut stretcher = TimeStretcher::new(sample_rate, 1);
+        stretcher.set_rate(1.16);
+        let output = stretcher.process(&input);
+
+        let freq = zero_crossing_freq(&output[800..], sample_rate);
+        assert!((freq - 250.0).abs() < 10.0, "frecuencia {}", freq);
+    }
+
+    #[test]
+    fn test_streaming_in_small_blocks() {
+        let sample_rate = 8000;
+        let input: Vec<f32> = sine(sample_rate, 300.0, 16000)
+            .into_iter()
+            .flat_map(|s| [s, -s])
+            .collect();
+
+        let mut stretcher = TimeStretcher::new(sample_rate, 2);
+        stretcher.set_rate(1.5);
+        let mut output = Vec::new();
+        for block in input.chunks(333 * 2) {
+            output.extend(stretcher.process(block));
+        }
+
+        assert_eq!(output.len() % 2, 0);
+        // Los canales siguen en oposición de fase: el desplazamiento es común
+        for frame in output.chunks(2) {
+            assert!((frame[0] + frame[1]).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_reset_clears_pending_input() {
+        let mut stretcher = TimeStretcher::new(8000, 1);
+        stretcher.process(&[0.5; 100]);
+        stretcher.reset();
+        assert!(stretcher.input.is_empty());
+        assert!(stretcher.natural_pos.is_none());
+    }
+}
@@ -0,0 +1,158 @@
+//! Energía por bandas para el waveform de tres colores
+//!
+//! AIDEV-NOTE: Cada ventana del seek sampling se pasa por una FFT y la potencia
+//! de los bins se reparte entre graves, medios y agudos (cortes en
+//! `WAVEFORM_LOW_CROSSOVER_HZ` y `WAVEFORM_HIGH_CROSSOVER_HZ`). Por Parseval,
+//! el RMS de cada banda es comparable al RMS de la señal completa.
+
+use std::sync::Arc;
+
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+
+use crate::audio::constants::{WAVEFORM_HIGH_CROSSOVER_HZ, WAVEFORM_LOW_CROSSOVER_HZ};
+use crate::audio::dsp;
+
+use super::types::BandPeak;
+
+/// Separa ventanas mono en tres bandas
+pub struct BandSplitter {
+    fft: Arc<dyn Fft<f32>>,
+    buffer: Vec<Complex<f32>>,
+    /// Primer bin de medios y de agudos
+    mid_bin: usize,
+    high_bin: usize,
+}
+
+impl BandSplitter {
+    /// `size` es el máximo de samples por ventana (tamaño de la FFT)
+    pub fn new(sample_rate: u32, size: usize) -> Self {
+        let size = size.max(2);
+        let bin_hz = sample_rate.max(1) as f32 / size as f32;
+        let nyquist_bin = size / 2;
+        let mid_bin = ((WAVEFORM_LOW_CROSSOVER_HZ / bin_hz).round() as usize).clamp(1, nyquist_bin);
+        let high_bin =
+            ((WAVEFORM_HIGH_CROSSOVER_HZ / bin_hz).round() as usize).clamp(mid_bin, nyquist_bin);
+
+        Self {
+            fft: FftPlanner::new().plan_fft_forward(size),
+            buffer: vec![Complex::default(); size],
+            mid_bin,
+            high_bin,
+        }
+    }
+
+    /// RMS de cada banda en una ventana de samples mono (con signo)
+    pub fn split(&mut self, samples: &[f32]) -> BandPeak {
+        let size = self.buffer.len();
+        let len = samples.len().min(size);
+        if len < 2 {
+            return BandPeak::default();
+        }
+
+        // Ventana de Hann sobre los samples disponibles; el resto es relleno de ceros
+        self.buffer.fill(Complex::default());
+        let mut window_power = 0.0f32;
+        for (i, &sample) in samples[..len].iter().enumerate() {
+            let phase = 2.0 * std::f32::consts::PI * i as f32 / (len - 1) as f32;
+            let w = 0.5 - 0.5 * phase.cos();
+            window_power += w * w;
+            self.buffer[i] = Complex::new(sample * w, 0.0);
+        }
+        self.fft.process(&mut self.buffer);
+
+        // Solo media FFT: cada bin cuenta doble (su espejo negativo)
+        let scale = 2.0 / (size as f32 * window_power.max(f32::EPSILON));
+        let band_rms = |bins: std::ops::Range<usize>| -> f32 {
+            let energy: f32 = self.buffer[bins].iter().map(|bin| bin.norm_sqr()).sum();
+            (energy * scale).sqrt()
+        };
+
+        BandPeak {
+            low: band_rms(1..self.mid_bin),
+            mid: band_rms(self.mid_bin..self.high_bin),
+            high: band_rms(self.high_bin..size / 2),
+        }
+    }
+}
+
+/// Normaliza cada banda al rango 0.0-1.0 por separado
+///
+/// AIDEV-NOTE: Los graves tienen mucha más energía que los agudos; con un máximo
+/// común los hi-hats quedarían invisibles.
+pub fn normalize_bands(bands: &mut [BandPeak]) {
+    let mut low: Vec<f32> = bands.iter().map(|b| b.low).collect();
+    let mut mid: Vec<f32> = bands.iter().map(|b| b.mid).collect();
+    let mut high: Vec<f32> = bands.iter().map(|b| b.high).collect();
+    dsp::normalize_peaks(&mut low);
+    dsp::normalize_peaks(&mut mid);
+    dsp::normalize_peaks(&mut high);
+
+    for (i, band) in bands.iter_mut().enumerate() {
+        *band = BandPeak {
+            low: low[i],
+            mid: mid[i],
+            high: high[i],
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, sample_rate: u32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| (i as f32 / sample_rate as f32 * freq * 2.0 * std::f32::consts::PI).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_split_routes_tones_to_bands() {
+        let mut splitter = BandSplitter::new(44100, 4096);
+        let rms = 1.0 / 2f32.sqrt();
+
+        let kick = splitter.split(&sine(60.0, 44100, 4096));
+        assert!((kick.low - rms).abs() < 0.05, "{:?}", kick);
+        assert!(kick.mid < 0.05 && kick.high < 0.05, "{:?}", kick);
+
+        let vocal = splitter.split(&sine(800.0, 44100, 4096));
+        assert!((vocal.mid - rms).abs() < 0.05, "{:?}", vocal);
+        assert!(vocal.low < 0.05 && vocal.high < 0.05, "{:?}", vocal);
+
+        let hihat = splitter.split(&sine(8000.0, 44100, 4096));
+        assert!((hihat.high - rms).abs() < 0.05, "{:?}", hihat);
+        assert!(hihat.low < 0.05 && hihat.mid < 0.05, "{:?}", hihat);
+    }
+
+    #[test]
+    fn test_split_short_window() {
+        let mut splitter = BandSplitter::new(48000, 4096);
+        // Menos samples que la FFT: el nivel no depende del relleno
+        let peak = splitter.split(&sine(100.0, 48000, 2048));
+        assert!((peak.low - 1.0 / 2f32.sqrt()).abs() < 0.05, "{:?}", peak);
+        assert_eq!(splitter.split(&[]), BandPeak::default());
+    }
+
+    #[test]
+    fn test_normalize_bands_per_band() {
+        let mut bands = vec![
+            BandPeak {
+                low: 0.8,
+                mid: 0.1,
+                high: 0.02,
+            },
+            BandPeak {
+                low: 0.4,
+                mid: 0.2,
+                high: 0.01,
+            },
+        ];
+        normalize_bands(&mut bands);
+        assert_eq!(bands[0].low, 1.0);
+        assert_eq!(bands[1].low, 0.5);
+        assert_eq!(bands[1].mid, 1.0);
+        assert_eq!(bands[0].high, 1.0);
+        assert_eq!(bands[1].high, 0.5);
+    }
+}
@@ -1,19 +1,29 @@
 //! Operaciones de cache de waveform
 //!
 //! AIDEV-NOTE: Migrado a DbPool + spawn_blocking para evitar bloquear el runtime de Tokio.
+//! Una fila por track y modo (`waveforms.format`); las filas con una versión
+//! anterior a `WaveformMode::version` se tratan como miss y se regeneran.
+//! Los peaks se guardan en binario (ver `codec`).
 
 use tauri::{AppHandle, Emitter};
 
-use super::types::WaveformCompletePayload;
+use super::codec::{PeakPrecision, WaveformBlob};
+use super::pyramid::{PYRAMID_FORMAT, PYRAMID_VERSION};
+use super::types::{BandPeak, WaveformCompletePayload, WaveformMode, WaveformPeaks};
 use crate::db::{queries, DbPool};
 
 /// Verifica si existe waveform en cache y la emite si existe
 pub async fn check_and_emit_cached(
     track_id: &str,
+    mode: WaveformMode,
     pool: &DbPool,
     app: &AppHandle,
-) -> Option<Vec<f32>> {
-    log::info!("🔍 Checking waveform cache for track {}...", track_id);
+) -> Option<WaveformPeaks> {
+    log::info!(
+        "🔍 Checking waveform cache for track {} ({})...",
+        track_id,
+        mode.as_str()
+    );
 
     let pool_clone = pool.clone();
     let track_id_owned = track_id.to_string();
@@ -21,36 +31,51 @@ pub async fn check_and_emit_cached(
     // Ejecutar en thread de bloqueo para no bloquear el runtime de Tokio
     let result = tokio::task::spawn_blocking(move || {
         let conn = pool_clone.get().ok()?;
-        queries::get_waveform(&conn, &track_id_owned).ok().flatten()
+        queries::get_waveform(&conn, &track_id_owned, mode.as_str())
+            .ok()
+            .flatten()
     })
     .await
     .ok()
     .flatten();
 
     if let Some(waveform) = result {
-        log::info!("✅ Waveform cache HIT for track {}", track_id);
+        if waveform.version != mode.version() {
+            log::info!(
+                "♻️ Waveform cache OUTDATED for track {} (v{} → v{})",
+                track_id,
+                waveform.version,
+                mode.version()
+            );
+            return None;
+        }
 
-        // Deserializar peaks (almacenados como JSON String en DB)
-        let data_str = String::from_utf8_lossy(&waveform.data);
-        let peaks: Vec<f32> = serde_json::from_str(&data_str).unwrap_or_default();
+        let Some(data) = decode_cached(&waveform.data, mode) else {
+            log::warn!("⚠️ Waveform cache UNREADABLE for track {}", track_id);
+            return None;
+        };
+
+        log::info!("✅ Waveform cache HIT for track {}", track_id);
 
         log::info!(
             "📤 Emitting waveform:complete event (from cache) - {} peaks",
-            peaks.len()
+            data.peaks.len()
         );
 
         app.emit(
             "waveform:complete",
             WaveformCompletePayload {
                 track_id: track_id.to_string(),
-                peaks: peaks.clone(),
+                mode,
+                peaks: data.peaks.clone(),
+                bands: data.bands.clone(),
             },
         )
         .ok();
 
         log::info!("✅ waveform:complete event emitted successfully");
 
-        return Some(peaks);
+        return Some(data);
     }
 
     log::info!("🎵 Waveform cache MISS - generating for track {}", track_id);
@@ -58,17 +83,114 @@ pub async fn check_and_emit_cached(
 }
 
 /// Guarda waveform generado en cache
-pub async fn save_to_cache(track_id: &str, peaks: &[f32], pool: &DbPool) {
-    let data_json = serde_json::to_string(peaks).unwrap_or_default();
+pub async fn save_to_cache(
+    track_id: &str,
+    mode: WaveformMode,
+    data: &WaveformPeaks,
+    pool: &DbPool,
+) {
+    let blob = encode_cached(data, mode);
     let pool_clone = pool.clone();
     let track_id_owned = track_id.to_string();
 
     // Ejecutar en thread de bloqueo
     let _ = tokio::task::spawn_blocking(move || {
         if let Ok(conn) = pool_clone.get() {
-            let _ = queries::save_waveform(&conn, &track_id_owned, &data_json);
+            let _ = queries::save_waveform(
+                &conn,
+                &track_id_owned,
+                mode.as_str(),
+                mode.version(),
+                &blob,
+            );
             log::info!("💾 Waveform saved to database");
         }
     })
     .await;
 }
+
+/// Versión vigente de cada formato guardado en `waveforms`
+///
+/// AIDEV-NOTE: La usa el GC de la caché para borrar filas que ya no se leerían.
+pub fn current_versions() -> Vec<(&'static str, i32)> {
+    WaveformMode::ALL
+        .iter()
+        .map(|mode| (mode.as_str(), mode.version()))
+        .chain([(PYRAMID_FORMAT, PYRAMID_VERSION)])
+        .collect()
+}
+
+/// Serializa los peaks para la cache
+///
+/// AIDEV-NOTE: Una serie para mono; en modo bandas, peaks + graves/medios/agudos.
+/// u16 sobra para el overview (~800 peaks, unos pocos KB por track).
+fn encode_cached(data: &WaveformPeaks, mode: WaveformMode) -> Vec<u8> {
+    let mut series = vec![data.peaks.clone()];
+    if mode == WaveformMode::Bands {
+        series.push(data.bands.iter().map(|band| band.low).collect());
+        series.push(data.bands.iter().map(|band| band.mid).collect());
+        series.push(data.bands.iter().map(|band| band.high).collect());
+    }
+    WaveformBlob {
+        duration: 0.0,
+        series,
+    }
+    .encode(PeakPrecision::U16)
+}
+
+/// Deserializa los peaks guardados por `encode_cached`
+fn decode_cached(data: &[u8], mode: WaveformMode) -> Option<WaveformPeaks> {
+    let mut series = WaveformBlob::decode(data)?.series.into_iter();
+    let peaks = series.next()?;
+    let bands = match mode {
+        WaveformMode::Mono => Vec::new(),
+        WaveformMode::Bands => {
+            let (low, mid, high) = (series.next()?, series.next()?, series.next()?);
+            if [low.len(), mid.len(), high.len()] != [peaks.len(); 3] {
+                return None;
+            }
+            (0..peaks.len())
+                .map(|i| BandPeak {
+                    low: low[i],
+                    mid: mid[i],
+                    high: high[i],
+                })
+                .collect()
+        }
+    };
+    Some(WaveformPeaks { peaks, bands })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_roundtrip() {
+        let mono = WaveformPeaks {
+            peaks: vec![0.25, 1.0],
+            bands: Vec::new(),
+        };
+        let blob = encode_cached(&mono, WaveformMode::Mono);
+        let decoded = decode_cached(&blob, WaveformMode::Mono).unwrap();
+        assert!((decoded.peaks[0] - 0.25).abs() < 1e-4);
+        assert_eq!(decoded.peaks[1], 1.0);
+        assert!(decoded.bands.is_empty());
+
+        let bands = WaveformPeaks {
+            peaks: vec![0.5],
+            bands: vec![BandPeak {
+                low: 1.0,
+                mid: 0.5,
+                high: 0.0,
+            }],
+        };
+        let blob = encode_cached(&bands, WaveformMode::Bands);
+        let decoded = decode_cached(&blob, WaveformMode::Bands).unwrap();
+        assert_eq!(decoded.bands[0].low, 1.0);
+        assert!((decoded.bands[0].mid - 0.5).abs() < 1e-4);
+        // Un blob mono no sirve como bandas
+        let mono_blob = encode_cached(&mono, WaveformMode::Mono);
+        assert!(decode_cached(&mono_blob, WaveformMode::Bands).is_none());
+    }
+}
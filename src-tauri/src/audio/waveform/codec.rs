@@ -0,0 +1,211 @@
+//! Formato binario de la caché de waveforms
+//!
+//! AIDEV-NOTE: Los peaks se guardan cuantizados (u8/u16) en vez de JSON con
+//! floats: 1-2 bytes por peak frente a ~10. Un blob es `WAVEFORM_BLOB_MAGIC`,
+//! una cabecera bincode (versión del formato, precisión, compresión) y el
+//! cuerpo bincode, comprimido con deflate solo si así ocupa menos. Un blob que
+//! no se entiende (JSON antiguo, versión futura) se trata como miss.
+
+use std::io::{Read, Write};
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+/// Prefijo de los blobs binarios (distingue del JSON anterior)
+pub const WAVEFORM_BLOB_MAGIC: &[u8; 4] = b"SYWF";
+
+/// Versión del formato del blob (no del algoritmo de generación)
+pub const WAVEFORM_BLOB_VERSION: u8 = 1;
+
+/// Bits por peak guardado
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PeakPrecision {
+    U8,
+    U16,
+}
+
+impl PeakPrecision {
+    fn max_value(self) -> f32 {
+        match self {
+            PeakPrecision::U8 => u8::MAX as f32,
+            PeakPrecision::U16 => u16::MAX as f32,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct BlobHeader {
+    version: u8,
+    precision: PeakPrecision,
+    compressed: bool,
+}
+
+/// Cuerpo del blob: cada serie son los bytes little-endian de sus peaks
+#[derive(Serialize, Deserialize)]
+struct BlobBody {
+    duration: f64,
+    series: Vec<Vec<u8>>,
+}
+
+/// Contenido de un blob de waveform
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WaveformBlob {
+    /// Duración del track en segundos (0.0 si el formato no la necesita)
+    pub duration: f64,
+    /// Series de peaks normalizados (0.0 - 1.0)
+    pub series: Vec<Vec<f32>>,
+}
+
+impl WaveformBlob {
+    /// Serializa con la precisión indicada
+    pub fn encode(&self, precision: PeakPrecision) -> Vec<u8> {
+        let body = BlobBody {
+            duration: self.duration,
+            series: self
+                .series
+                .iter()
+                .map(|peaks| quantize(peaks, precision))
+                .collect(),
+        };
+        let body = bincode::serialize(&body).unwrap_or_default();
+
+        // Deflate solo si compensa (series cortas o ruidosas apenas comprimen)
+        let deflated = deflate(&body).filter(|deflated| deflated.len() < body.len());
+        let header = BlobHeader {
+            version: WAVEFORM_BLOB_VERSION,
+            precision,
+            compressed: deflated.is_some(),
+        };
+
+        let mut blob = WAVEFORM_BLOB_MAGIC.to_vec();
+        blob.extend(bincode::serialize(&header).unwrap_or_default());
+        blob.extend(deflated.unwrap_or(body));
+        blob
+    }
+
+    /// Deserializa un blob de `encode`; `None` si no es un blob válido
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let data = data.strip_prefix(WAVEFORM_BLOB_MAGIC.as_slice())?;
+        let header: BlobHeader = bincode::deserialize(data).ok()?;
+        if header.version != WAVEFORM_BLOB_VERSION {
+            return None;
+        }
+        let header_len = bincode::serialized_size(&header).ok()? as usize;
+        let body = &data[header_len..];
+
+        let body: BlobBody = if header.compressed {
+            let mut inflated = Vec::new();
+            DeflateDecoder::new(body).read_to_end(&mut inflated).ok()?;
+            bincode::deserialize(&inflated).ok()?
+        } else {
+            bincode::deserialize(body).ok()?
+        };
+
+        Some(Self {
+            duration: body.duration,
+            series: body
+                .series
+                .iter()
+                .map(|bytes| dequantize(bytes, header.precision))
+                .collect(),
+        })
+    }
+}
+
+fn quantize(peaks: &[f32], precision: PeakPrecision) -> Vec<u8> {
+    let max = precision.max_value();
+    let levels = peaks
+        .iter()
+        .map(|&peak| (peak.clamp(0.0, 1.0) * max).round());
+    match precision {
+        PeakPrecision::U8 => levels.map(|level| level as u8).collect(),
+        PeakPrecision::U16 => levels
+            .flat_map(|level| (level as u16).to_le_bytes())
+            .collect(),
+    }
+}
+
+fn dequantize(bytes: &[u8], precision: PeakPrecision) -> Vec<f32> {
+    let max = precision.max_value();
+    match precision {
+        PeakPrecision::U8 => bytes.iter().map(|&level| level as f32 / max).collect(),
+        PeakPrecision::U16 => bytes
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]) as f32 / max)
+            .collect(),
+    }
+}
+
+fn deflate(data: &[u8]) -> Option<Vec<u8>> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).ok()?;
+    encoder.finish().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_within_quantization_step() {
+        let peaks: Vec<f32> = (0..1000).map(|i| (i as f32 * 0.37).sin().abs()).collect();
+        let blob = WaveformBlob {
+            duration: 215.5,
+            series: vec![peaks.clone(), vec![0.0, 1.0, 1.5, -0.5]],
+        };
+
+        for (precision, step) in [
+            (PeakPrecision::U8, 1.0 / 255.0),
+            (PeakPrecision::U16, 1.0 / 65535.0),
+        ] {
+            let decoded = WaveformBlob::decode(&blob.encode(precision)).unwrap();
+            assert_eq!(decoded.duration, 215.5);
+            assert!(peaks
+                .iter()
+                .zip(&decoded.series[0])
+                .all(|(a, b)| (a - b).abs() <= step / 2.0 + f32::EPSILON));
+            // Fuera de rango se satura
+            assert_eq!(decoded.series[1], vec![0.0, 1.0, 1.0, 0.0]);
+        }
+    }
+
+    #[test]
+    fn test_compact_compared_to_json() {
+        // Silencio al principio y al final: deflate compensa
+        let mut peaks = vec![0.0f32; 2000];
+        peaks.extend((0..6000).map(|i| (i % 97) as f32 / 97.0));
+        peaks.extend(vec![0.0f32; 2000]);
+        let json = serde_json::to_string(&peaks).unwrap();
+
+        let blob = WaveformBlob {
+            duration: 0.0,
+            series: vec![peaks],
+        }
+        .encode(PeakPrecision::U16);
+        assert!(
+            blob.len() * 4 < json.len(),
+            "{} vs {}",
+            blob.len(),
+            json.len()
+        );
+    }
+
+    #[test]
+    fn test_rejects_legacy_and_corrupt_data() {
+        assert!(WaveformBlob::decode(b"[0.5,0.25]").is_none());
+        assert!(WaveformBlob::decode(b"").is_none());
+
+        let mut blob = WaveformBlob {
+            duration: 1.0,
+            series: vec![vec![0.5; 16]],
+        }
+        .encode(PeakPrecision::U8);
+        // Versión de formato desconocida
+        blob[WAVEFORM_BLOB_MAGIC.len()] = WAVEFORM_BLOB_VERSION + 1;
+        assert!(WaveformBlob::decode(&blob).is_none());
+        blob.truncate(WAVEFORM_BLOB_MAGIC.len() + 2);
+        assert!(WaveformBlob::decode(&blob).is_none());
+    }
+}
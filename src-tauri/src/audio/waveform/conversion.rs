@@ -1,6 +1,6 @@
 //! Conversión de audio a mono
 
-use symphonia::core::audio::{AudioBufferRef, Signal};
+use symphonia::core::audio::{AudioBufferRef, SampleBuffer, Signal};
 
 /// Convierte AudioBufferRef a mono (promedio de canales)
 pub fn convert_to_mono(audio_buf: &AudioBufferRef) -> Vec<f32> {
@@ -36,3 +36,17 @@ pub fn convert_to_mono(audio_buf: &AudioBufferRef) -> Vec<f32> {
         _ => Vec::new(),
     }
 }
+
+/// Mezcla AudioBufferRef a mono conservando el signo (para análisis en frecuencia)
+pub fn mix_to_mono(audio_buf: &AudioBufferRef) -> Vec<f32> {
+    let spec = *audio_buf.spec();
+    let channels = spec.channels.count().max(1);
+    let mut sample_buf = SampleBuffer::<f32>::new(audio_buf.capacity() as u64, spec);
+    sample_buf.copy_interleaved_ref(audio_buf.clone());
+
+    sample_buf
+        .samples()
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
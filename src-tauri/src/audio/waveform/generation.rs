@@ -16,8 +16,9 @@ use crate::audio::{
     dsp, AudioError, AudioResult,
 };
 
-use super::conversion::convert_to_mono;
-use super::types::WaveformProgressPayload;
+use super::bands::{normalize_bands, BandSplitter};
+use super::conversion::{convert_to_mono, mix_to_mono};
+use super::types::{BandPeak, WaveformMode, WaveformPeaks, WaveformProgressPayload};
 
 /// Genera peaks con algoritmo optimizado de SEEK SAMPLING
 ///
@@ -34,15 +35,23 @@ use super::types::WaveformProgressPayload;
 /// Para una canción de 6 minutos:
 /// - Solo decodificamos ~800 posiciones x 4096 samples = ~3.3M samples
 /// - En lugar de ~16M samples (full decode)
+///
+/// En modo `Bands` cada ventana se separa además en graves/medios/agudos con
+/// `BandSplitter`, sobre las mismas posiciones.
 pub async fn generate_and_stream_peaks(
     track_id: &str,
     track_path: &str,
     duration: f64,
+    mode: WaveformMode,
     app: &AppHandle,
     cancel_token: &CancellationToken,
-) -> AudioResult<Vec<f32>> {
+) -> AudioResult<WaveformPeaks> {
     let start_time = std::time::Instant::now();
-    log::info!("🔧 generate_and_stream_peaks START (SEEK SAMPLING) for track {}", track_id);
+    log::info!(
+        "🔧 generate_and_stream_peaks START (SEEK SAMPLING, {}) for track {}",
+        mode.as_str(),
+        track_id
+    );
     log::info!("   Path: {}", track_path);
     log::info!("   Duration: {:.2}s", duration);
 
@@ -101,6 +110,11 @@ pub async fn generate_and_stream_peaks(
     );
 
     let mut peaks: Vec<f32> = Vec::with_capacity(target_peaks + 10);
+    let mut splitter = match mode {
+        WaveformMode::Mono => None,
+        WaveformMode::Bands => Some(BandSplitter::new(sample_rate as u32, samples_per_peak)),
+    };
+    let mut bands: Vec<BandPeak> = Vec::new();
     let mut last_emitted_peak_count = 0usize;
     let mut last_logged_progress = 0i32;
 
@@ -120,12 +134,17 @@ pub async fn generate_and_stream_peaks(
         ).is_err() {
             // Si el seek falla, usar el último valor o 0
             peaks.push(peaks.last().copied().unwrap_or(0.0));
+            if splitter.is_some() {
+                bands.push(bands.last().copied().unwrap_or_default());
+            }
             continue;
         }
 
         // Decodificar unos pocos paquetes en esta posición
         let mut samples: Vec<f32> = Vec::with_capacity(samples_per_peak);
-        
+        // Mezcla mono con signo (solo para el análisis por bandas)
+        let mut signed: Vec<f32> = Vec::new();
+
         for _ in 0..packets_per_position {
             let packet = match format.next_packet() {
                 Ok(p) => p,
@@ -143,6 +162,9 @@ pub async fn generate_and_stream_peaks(
 
             let mono = convert_to_mono(&decoded);
             samples.extend(mono);
+            if splitter.is_some() {
+                signed.extend(mix_to_mono(&decoded));
+            }
 
             if samples.len() >= samples_per_peak {
                 break;
@@ -158,6 +180,15 @@ pub async fn generate_and_stream_peaks(
         };
         peaks.push(peak);
 
+        if let Some(splitter) = splitter.as_mut() {
+            let band = if !signed.is_empty() {
+                splitter.split(&signed[..signed.len().min(samples_per_peak)])
+            } else {
+                bands.last().copied().unwrap_or_default()
+            };
+            bands.push(band);
+        }
+
         // Emitir progreso periódicamente
         if peaks.len() % WAVEFORM_PEAKS_PER_PACKET == 0 {
             let progress = (peaks.len() as f32 / target_peaks as f32).min(0.99);
@@ -181,13 +212,19 @@ pub async fn generate_and_stream_peaks(
             };
 
             if !new_peaks.is_empty() {
+                let new_bands = bands
+                    .get(last_emitted_peak_count..)
+                    .unwrap_or_default()
+                    .to_vec();
                 let _ = app.emit(
                     "waveform:progress",
                     WaveformProgressPayload {
                         track_id: track_id.to_string(),
+                        mode,
                         progress,
                         peaks_so_far: peaks.len(),
                         partial_peaks: new_peaks,
+                        partial_bands: new_bands,
                     },
                 );
                 last_emitted_peak_count = peaks.len();
@@ -206,11 +243,15 @@ pub async fn generate_and_stream_peaks(
     // Normalizar peaks (normalize_peaks modifica in-place)
     let mut normalized = peaks;
     dsp::normalize_peaks(&mut normalized);
+    normalize_bands(&mut bands);
 
     log::info!(
         "✅ Normalization complete - returning {} peaks",
         normalized.len()
     );
 
-    Ok(normalized)
+    Ok(WaveformPeaks {
+        peaks: normalized,
+        bands,
+    })
 }
@@ -12,19 +12,27 @@
 /// 2. Si no existe, generar con streaming:
 ///    - Decodificar audio con symphonia
 ///    - Calcular peaks con ventanas deslizantes (WAVEFORM_WINDOW_SIZE)
+///    - En modo `Bands`, además energía de graves/medios/agudos por ventana
 ///    - Emitir eventos cada WAVEFORM_PEAKS_PER_PACKET paquetes
-/// 3. Guardar en DB al finalizar
+/// 3. Guardar en DB al finalizar (binario cuantizado, ver `codec`)
+///
+/// Para el zoom, `pyramid` genera bajo demanda niveles de detalle del track
+/// entero y sirve solo la ventana visible.
 
+pub mod bands;
 pub mod cache;
+pub mod codec;
 pub mod conversion;
 pub mod generation;
+pub mod pyramid;
 pub mod streaming;
 pub mod types;
 
+pub use pyramid::{load_pyramid, WaveformPyramid, WaveformRange};
 pub use streaming::{cancel_waveform_generation, generate_waveform_streaming};
 pub use types::{
-    WaveformCompletePayload, WaveformData, WaveformErrorPayload, WaveformProgressPayload,
-    WaveformState,
+    BandPeak, WaveformCompletePayload, WaveformData, WaveformErrorPayload, WaveformMode,
+    WaveformPeaks, WaveformProgressPayload, WaveformState,
 };
 
 #[cfg(test)]
@@ -0,0 +1,366 @@
+//! Pirámide de resoluciones del waveform para vistas con zoom
+//!
+//! AIDEV-NOTE: El overview (`generate_and_stream_peaks`) usa seek sampling y
+//! solo sirve a ~800 peaks. Para editar cues con zoom se decodifica el track
+//! entero una vez a `WAVEFORM_DETAIL_PEAKS_PER_SECOND` y se guardan niveles
+//! más gruesos (`WAVEFORM_PYRAMID_LEVELS`) sacados de ese detalle. Cada petición
+//! de rango elige el nivel más grueso que da la resolución pedida, así el
+//! frontend nunca recibe más peaks de los que va a dibujar.
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::audio::constants::{WAVEFORM_DETAIL_PEAKS_PER_SECOND, WAVEFORM_PYRAMID_LEVELS};
+use crate::audio::decoder::AudioDecoder;
+use crate::audio::{dsp, AudioError, AudioResult};
+use crate::db::{queries, DbPool};
+
+use super::codec::{PeakPrecision, WaveformBlob};
+use super::types::WaveformState;
+
+/// Formato de la pirámide en `waveforms.format`
+pub const PYRAMID_FORMAT: &str = "pyramid";
+
+/// Versión del algoritmo de la pirámide (subirla regenera las cachés)
+pub const PYRAMID_VERSION: i32 = 1;
+
+/// Pirámides que se mantienen en memoria (zoom y scroll piden rangos seguidos)
+const PYRAMID_MEMORY_CACHE: usize = 4;
+
+/// Niveles de detalle de un track, del más grueso al más fino
+///
+/// Todos los niveles cubren el track entero con peaks equiespaciados y
+/// normalizados con el mismo máximo (el del detalle).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WaveformPyramid {
+    pub duration: f64,
+    pub levels: Vec<Vec<f32>>,
+}
+
+/// Peaks de una ventana del track
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WaveformRange {
+    /// Inicio y fin reales de los peaks (alineados al nivel usado)
+    pub start: f64,
+    pub end: f64,
+    /// Densidad de los peaks devueltos
+    pub peaks_per_second: f64,
+    pub peaks: Vec<f32>,
+}
+
+impl WaveformPyramid {
+    /// Construye la pirámide a partir de samples interleaved del track entero
+    pub fn from_samples(samples: &[f32], sample_rate: u32, channels: u16) -> Self {
+        let channels = channels.max(1) as usize;
+        let frames = samples.len() / channels;
+        let duration = frames as f64 / sample_rate.max(1) as f64;
+        let frames_per_peak =
+            (sample_rate as usize / WAVEFORM_DETAIL_PEAKS_PER_SECOND).max(1) * channels;
+
+        // RMS de la mezcla mono (promedio de valores absolutos, como el overview)
+        let mut detail: Vec<f32> = samples
+            .chunks(frames_per_peak)
+            .map(|window| {
+                let mono: Vec<f32> = window
+                    .chunks(channels)
+                    .map(|frame| frame.iter().map(|s| s.abs()).sum::<f32>() / channels as f32)
+                    .collect();
+                dsp::calculate_peak_value(&mono, dsp::PeakMethod::Rms)
+            })
+            .collect();
+        dsp::normalize_peaks(&mut detail);
+
+        Self::from_detail(detail, duration)
+    }
+
+    /// Construye los niveles gruesos a partir del nivel de detalle (ya normalizado)
+    pub fn from_detail(detail: Vec<f32>, duration: f64) -> Self {
+        let mut levels: Vec<Vec<f32>> = WAVEFORM_PYRAMID_LEVELS
+            .iter()
+            .filter(|&&target| target < detail.len())
+            .map(|&target| downsample_max(&detail, target))
+            .collect();
+        levels.push(detail);
+        Self { duration, levels }
+    }
+
+    /// Serializa para la cache en DB
+    ///
+    /// AIDEV-NOTE: u8 basta para dibujar (256 alturas) y deja el detalle de un
+    /// track de 5 min en ~45 KB antes de comprimir.
+    pub fn encode(&self) -> Vec<u8> {
+        WaveformBlob {
+            duration: self.duration,
+            series: self.levels.clone(),
+        }
+        .encode(PeakPrecision::U8)
+    }
+
+    /// Deserializa un blob de `encode`
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let blob = WaveformBlob::decode(data)?;
+        if blob.series.is_empty() {
+            return None;
+        }
+        Some(Self {
+            duration: blob.duration,
+            levels: blob.series,
+        })
+    }
+
+    /// Peaks entre `start` y `end` (segundos) con como mucho `resolution` valores
+    ///
+    /// Si ni el nivel de detalle llega a `resolution` en la ventana, devuelve
+    /// los peaks de detalle que haya (el frontend interpola).
+    pub fn range(&self, start: f64, end: f64, resolution: usize) -> WaveformRange {
+        let start = start.clamp(0.0, self.duration);
+        let end = end.clamp(start, self.duration);
+        let empty = WaveformRange {
+            start,
+            end,
+            peaks_per_second: 0.0,
+            peaks: Vec::new(),
+        };
+        let span = end - start;
+        if span <= 0.0 || resolution == 0 || self.duration <= 0.0 {
+            return empty;
+        }
+
+        let density = |level: &Vec<f32>| level.len() as f64 / self.duration;
+        let Some(level) = self
+            .levels
+            .iter()
+            .find(|level| span * density(level) >= resolution as f64)
+            .or(self.levels.last())
+        else {
+            return empty;
+        };
+
+        let level_density = density(level);
+        let first = ((start * level_density).floor() as usize).min(level.len());
+        let last = ((end * level_density).ceil() as usize).clamp(first, level.len());
+        let window = &level[first..last];
+
+        let (peaks, peaks_per_second) = if window.len() > resolution {
+            let density = level_density * resolution as f64 / window.len() as f64;
+            (downsample_max(window, resolution), density)
+        } else {
+            (window.to_vec(), level_density)
+        };
+
+        WaveformRange {
+            start: first as f64 / level_density,
+            end: last as f64 / level_density,
+            peaks_per_second,
+            peaks,
+        }
+    }
+}
+
+/// Reduce `peaks` a `target` valores tomando el máximo de cada tramo
+///
+/// AIDEV-NOTE: Máximo y no media: un golpe de bombo no debe desaparecer al alejar el zoom.
+fn downsample_max(peaks: &[f32], target: usize) -> Vec<f32> {
+    if target == 0 || peaks.is_empty() {
+        return Vec::new();
+    }
+    (0..target)
+        .map(|i| {
+            let from = i * peaks.len() / target;
+            let to = ((i + 1) * peaks.len() / target).max(from + 1);
+            peaks[from..to.min(peaks.len())]
+                .iter()
+                .copied()
+                .fold(0.0f32, f32::max)
+        })
+        .collect()
+}
+
+/// Pirámides recientes en memoria (las más usadas al final)
+#[derive(Default)]
+pub struct PyramidCache {
+    entries: VecDeque<(String, Arc<WaveformPyramid>)>,
+}
+
+impl PyramidCache {
+    pub fn get(&mut self, track_id: &str) -> Option<Arc<WaveformPyramid>> {
+        let index = self.entries.iter().position(|(id, _)| id == track_id)?;
+        let entry = self.entries.remove(index)?;
+        let pyramid = entry.1.clone();
+        self.entries.push_back(entry);
+        Some(pyramid)
+    }
+
+    pub fn insert(&mut self, track_id: &str, pyramid: Arc<WaveformPyramid>) {
+        self.entries.retain(|(id, _)| id != track_id);
+        if self.entries.len() >= PYRAMID_MEMORY_CACHE {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((track_id.to_string(), pyramid));
+    }
+
+    pub fn remove(&mut self, track_id: &str) {
+        self.entries.retain(|(id, _)| id != track_id);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Obtiene la pirámide de un track: memoria → cache en DB → generación
+///
+/// AIDEV-NOTE: La generación decodifica el track entero (segundos en tracks
+/// largos); se hace en `spawn_blocking` y el resultado se guarda en ambas cachés.
+pub async fn load_pyramid(
+    track_id: &str,
+    state: &WaveformState,
+    pool: &DbPool,
+) -> AudioResult<Arc<WaveformPyramid>> {
+    if let Some(pyramid) = state.pyramids.lock().await.get(track_id) {
+        return Ok(pyramid);
+    }
+
+    let pool = pool.clone();
+    let track_id_owned = track_id.to_string();
+    let pyramid = tokio::task::spawn_blocking(move || -> AudioResult<WaveformPyramid> {
+        let conn = pool
+            .get()
+            .map_err(|e| AudioError::WaveformGenerationFailed(e.to_string()))?;
+
+        let cached = queries::get_waveform(&conn, &track_id_owned, PYRAMID_FORMAT)
+            .ok()
+            .flatten()
+            .filter(|waveform| waveform.version == PYRAMID_VERSION)
+            .and_then(|waveform| WaveformPyramid::decode(&waveform.data));
+        if let Some(pyramid) = cached {
+            log::info!("✅ Waveform pyramid cache HIT for track {}", track_id_owned);
+            return Ok(pyramid);
+        }
+
+        let track = queries::get_track(&conn, &track_id_owned)
+            .map_err(|e| AudioError::WaveformGenerationFailed(e.to_string()))?;
+        log::info!("🔧 Generating waveform pyramid for {}", track.path);
+        let start_time = std::time::Instant::now();
+
+        let decoded = AudioDecoder::decode_samples(Path::new(&track.path))?;
+        let pyramid =
+            WaveformPyramid::from_samples(&decoded.samples, decoded.sample_rate, decoded.channels);
+        log::info!(
+            "🏁 Waveform pyramid complete: {:?} peaks in {:.2}s",
+            pyramid.levels.iter().map(Vec::len).collect::<Vec<_>>(),
+            start_time.elapsed().as_secs_f64()
+        );
+
+        if let Err(e) = queries::save_waveform(
+            &conn,
+            &track_id_owned,
+            PYRAMID_FORMAT,
+            PYRAMID_VERSION,
+            &pyramid.encode(),
+        ) {
+            log::warn!("⚠️ Error guardando pirámide de waveform: {}", e);
+        }
+        Ok(pyramid)
+    })
+    .await
+    .map_err(|e| AudioError::WaveformGenerationFailed(format!("Task join error: {}", e)))??;
+
+    let pyramid = Arc::new(pyramid);
+    state
+        .pyramids
+        .lock()
+        .await
+        .insert(track_id, pyramid.clone());
+    Ok(pyramid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pirámide de 100 s con 150 peaks/s (rampa de 0 a 1)
+    fn ramp_pyramid() -> WaveformPyramid {
+        let len = 100 * WAVEFORM_DETAIL_PEAKS_PER_SECOND;
+        let detail = (0..len).map(|i| i as f32 / (len - 1) as f32).collect();
+        WaveformPyramid::from_detail(detail, 100.0)
+    }
+
+    #[test]
+    fn test_levels_coarse_to_fine() {
+        let pyramid = ramp_pyramid();
+        let sizes: Vec<usize> = pyramid.levels.iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![800, 8000, 15000]);
+        // El máximo se conserva en todos los niveles
+        assert!(pyramid
+            .levels
+            .iter()
+            .all(|level| level.last() == Some(&1.0)));
+    }
+
+    #[test]
+    fn test_short_track_skips_levels() {
+        let pyramid = WaveformPyramid::from_detail(vec![0.5; 1000], 6.6);
+        assert_eq!(pyramid.levels.len(), 2);
+        assert_eq!(pyramid.levels[0].len(), 800);
+    }
+
+    #[test]
+    fn test_range_picks_coarsest_sufficient_level() {
+        let pyramid = ramp_pyramid();
+
+        // Todo el track a 800 px: nivel overview
+        let range = pyramid.range(0.0, 100.0, 800);
+        assert_eq!((range.start, range.end), (0.0, 100.0));
+        assert_eq!(range.peaks.len(), 800);
+        assert_eq!(range.peaks_per_second, 8.0);
+
+        // 10 s a 1000 px: 8k no llega (800 peaks), se usa el detalle reducido
+        let range = pyramid.range(40.0, 50.0, 1000);
+        assert_eq!(range.peaks.len(), 1000);
+        assert!((range.start - 40.0).abs() < 0.01 && (range.end - 50.0).abs() < 0.01);
+
+        // 1 s a 1000 px: más zoom que el detalle, 150 peaks
+        let range = pyramid.range(10.0, 11.0, 1000);
+        assert_eq!(range.peaks.len(), 150);
+        assert_eq!(range.peaks_per_second, 150.0);
+    }
+
+    #[test]
+    fn test_range_out_of_bounds() {
+        let pyramid = ramp_pyramid();
+        let range = pyramid.range(90.0, 500.0, 100);
+        assert_eq!((range.start, range.end), (90.0, 100.0));
+        assert_eq!(range.peaks.len(), 100);
+        assert!(pyramid.range(120.0, 130.0, 100).peaks.is_empty());
+    }
+
+    #[test]
+    fn test_from_samples_detail_rate() {
+        // 2 s de estéreo a 44.1 kHz: 150 peaks/s
+        let samples = vec![0.5f32; 44100 * 2 * 2];
+        let pyramid = WaveformPyramid::from_samples(&samples, 44100, 2);
+        assert!((pyramid.duration - 2.0).abs() < 1e-9);
+        let detail = pyramid.levels.last().unwrap();
+        assert!((detail.len() as i64 - 300).abs() <= 1, "{}", detail.len());
+        assert!(detail.iter().all(|&p| (p - 1.0).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_pyramid_cache_evicts_oldest() {
+        let pyramid = Arc::new(WaveformPyramid::from_detail(vec![1.0], 1.0));
+        let mut cache = PyramidCache::default();
+        for i in 0..PYRAMID_MEMORY_CACHE {
+            cache.insert(&format!("t{}", i), pyramid.clone());
+        }
+        // Usar t0 lo mueve al final; el siguiente insert expulsa t1
+        assert!(cache.get("t0").is_some());
+        cache.insert("new", pyramid.clone());
+        assert!(cache.get("t0").is_some());
+        assert!(cache.get("t1").is_none());
+    }
+}
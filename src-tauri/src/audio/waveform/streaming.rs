@@ -9,7 +9,7 @@ use crate::db::DbPool;
 
 use super::cache::{check_and_emit_cached, save_to_cache};
 use super::generation::generate_and_stream_peaks;
-use super::types::{WaveformCompletePayload, WaveformErrorPayload, WaveformState};
+use super::types::{WaveformCompletePayload, WaveformErrorPayload, WaveformMode, WaveformState};
 
 /// Genera waveform con streaming progresivo
 ///
@@ -26,6 +26,7 @@ pub async fn generate_waveform_streaming(
     track_id: String,
     track_path: String,
     duration: f64,
+    mode: WaveformMode,
     app: AppHandle,
     state: Arc<WaveformState>,
     pool: Arc<DbPool>,
@@ -34,9 +35,13 @@ pub async fn generate_waveform_streaming(
     log::info!("Track ID: {}", track_id);
     log::info!("Track Path: {}", track_path);
     log::info!("Duration: {:.2}s", duration);
+    log::info!("Mode: {}", mode.as_str());
 
     // 1. Verificar cache en DB (usando pool)
-    if check_and_emit_cached(&track_id, &pool, &app).await.is_some() {
+    if check_and_emit_cached(&track_id, mode, &pool, &app)
+        .await
+        .is_some()
+    {
         return Ok(());
     }
 
@@ -58,28 +63,34 @@ pub async fn generate_waveform_streaming(
             &track_id_clone,
             &track_path,
             duration,
+            mode,
             &app_clone,
             &cancel_token,
         )
         .await;
 
         match result {
-            Ok(peaks) => {
-                log::info!("✅ Waveform generation SUCCESS - {} peaks", peaks.len());
+            Ok(data) => {
+                log::info!(
+                    "✅ Waveform generation SUCCESS - {} peaks",
+                    data.peaks.len()
+                );
 
                 // Guardar en DB usando pool
-                save_to_cache(&track_id_clone, &peaks, &pool_clone).await;
+                save_to_cache(&track_id_clone, mode, &data, &pool_clone).await;
 
                 // Emitir evento final
                 log::info!(
                     "📤 Emitting waveform:complete event (from generation) - {} peaks",
-                    peaks.len()
+                    data.peaks.len()
                 );
                 let _ = app_clone.emit(
                     "waveform:complete",
                     WaveformCompletePayload {
                         track_id: track_id_clone.clone(),
-                        peaks: peaks.clone(),
+                        mode,
+                        peaks: data.peaks,
+                        bands: data.bands,
                     },
                 );
                 log::info!("✅ waveform:complete event emitted successfully");
@@ -2,19 +2,24 @@
 
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 use tokio_util::sync::CancellationToken;
 
+use super::pyramid::PyramidCache;
+
 /// Estado de waveform con cancelación
 pub struct WaveformState {
     /// Track ID → CancellationToken
     pub active_generations: Arc<RwLock<std::collections::HashMap<String, CancellationToken>>>,
+    /// Pirámides de detalle usadas recientemente (zoom)
+    pub pyramids: Arc<Mutex<PyramidCache>>,
 }
 
 impl WaveformState {
     pub fn new() -> Self {
         Self {
             active_generations: Arc::new(RwLock::new(std::collections::HashMap::new())),
+            pyramids: Arc::new(Mutex::new(PyramidCache::default())),
         }
     }
 }
@@ -25,15 +30,76 @@ impl Default for WaveformState {
     }
 }
 
+/// Tipo de waveform a generar
+///
+/// AIDEV-NOTE: Cada modo se cachea por separado (`waveforms.format`). Subir la
+/// versión de un modo al cambiar su algoritmo invalida las cachés antiguas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WaveformMode {
+    /// Un valor RMS por ventana (overview de un color)
+    #[default]
+    Mono,
+    /// Energía de graves, medios y agudos por ventana (estilo RGB de Rekordbox/Serato)
+    Bands,
+}
+
+impl WaveformMode {
+    pub const ALL: [WaveformMode; 2] = [WaveformMode::Mono, WaveformMode::Bands];
+
+    /// Modo a partir de `waveforms.format`
+    pub fn from_format(format: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.as_str() == format)
+    }
+
+    /// Nombre guardado en `waveforms.format`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WaveformMode::Mono => "mono",
+            WaveformMode::Bands => "bands",
+        }
+    }
+
+    /// Versión del algoritmo de generación
+    pub fn version(&self) -> i32 {
+        match self {
+            WaveformMode::Mono => 1,
+            WaveformMode::Bands => 1,
+        }
+    }
+}
+
+/// Energía por bandas de una ventana
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BandPeak {
+    pub low: f32,
+    pub mid: f32,
+    pub high: f32,
+}
+
+/// Resultado de la generación (peaks normalizados 0.0 - 1.0)
+///
+/// `bands` solo se rellena en modo `Bands`, con un valor por peak.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WaveformPeaks {
+    pub peaks: Vec<f32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bands: Vec<BandPeak>,
+}
+
 /// Evento de progreso (emitido cada WAVEFORM_PEAKS_PER_PACKET paquetes)
 /// AIDEV-NOTE: Incluye peaks parciales para streaming progresivo visual
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WaveformProgressPayload {
     pub track_id: String,
+    pub mode: WaveformMode,
     pub progress: f32, // 0.0 - 1.0
     pub peaks_so_far: usize,
     pub partial_peaks: Vec<f32>, // Peaks generados hasta ahora (streaming)
+    /// Bandas de los mismos peaks (solo en modo `Bands`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub partial_bands: Vec<BandPeak>,
 }
 
 /// Evento de completado con datos finales
@@ -41,7 +107,11 @@ pub struct WaveformProgressPayload {
 #[serde(rename_all = "camelCase")]
 pub struct WaveformCompletePayload {
     pub track_id: String,
+    pub mode: WaveformMode,
     pub peaks: Vec<f32>, // Peaks normalizados
+    /// Bandas normalizadas (solo en modo `Bands`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub bands: Vec<BandPeak>,
 }
 
 /// Evento de error
@@ -1,15 +1,25 @@
-//! Comandos Tauri para análisis de audio: beatgrid, cue points y loops
+//! Comandos Tauri para análisis de audio: beatgrid, loudness, tonalidad, frases,
+//! cue points, loops y cola de análisis en background
 //!
 //! AIDEV-NOTE: Migrado a DbPool + spawn_blocking para evitar bloquear el runtime de Tokio.
 //! Todas las operaciones de base de datos se ejecutan en threads dedicados del pool de Tokio.
 
+use crate::analysis::{
+    analyze_cues as detect_cues, analyze_phrases as detect_phrases, AnalysisKind, AnalysisQueue,
+    AnalysisStatus, PRIORITY_NORMAL,
+};
+use crate::audio::beatgrid::BeatgridAnchor;
 use crate::audio::beatgrid_detector::BeatgridDetector;
+use crate::audio::key_detector::KeyDetector;
+use crate::audio::loudness_analyzer::LoudnessAnalyzer;
 use crate::db::{
-    models::{Beatgrid, CuePoint, Loop},
+    models::{AnalysisJob, Beatgrid, CuePoint, Downbeat, Loop, Loudness, Phrase, TrackKey},
     queries, DbPool,
 };
+use crate::library::metadata::{write_key, write_replaygain, ReplayGain};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
+use std::sync::Arc;
 use tauri::State;
 
 // ============================================================================
@@ -24,6 +34,8 @@ pub struct BeatgridResponse {
     pub offset: f64,
     pub confidence: Option<f64>,
     pub analyzed_at: String,
+    /// Cambios de tempo; vacío si el grid es de tempo constante
+    pub anchors: Vec<BeatgridAnchor>,
 }
 
 impl From<Beatgrid> for BeatgridResponse {
@@ -34,6 +46,109 @@ impl From<Beatgrid> for BeatgridResponse {
             offset: beatgrid.offset,
             confidence: beatgrid.confidence,
             analyzed_at: beatgrid.analyzed_at,
+            anchors: beatgrid.anchors,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoudnessResponse {
+    pub track_id: String,
+    pub integrated_lufs: f64,
+    pub true_peak_dbtp: f64,
+    pub loudness_range: Option<f64>,
+    /// Ganancia ReplayGain equivalente (referencia -18 LUFS)
+    pub replay_gain_db: f64,
+    pub source: String,
+    pub analyzed_at: String,
+}
+
+impl From<Loudness> for LoudnessResponse {
+    fn from(loudness: Loudness) -> Self {
+        Self {
+            replay_gain_db: ReplayGain::from_loudness(
+                loudness.integrated_lufs,
+                loudness.true_peak_dbtp,
+            )
+            .track_gain_db,
+            track_id: loudness.track_id,
+            integrated_lufs: loudness.integrated_lufs,
+            true_peak_dbtp: loudness.true_peak_dbtp,
+            loudness_range: loudness.loudness_range,
+            source: loudness.source,
+            analyzed_at: loudness.analyzed_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyResponse {
+    pub track_id: String,
+    pub key: String,
+    pub confidence: f64,
+    pub analyzed_at: String,
+}
+
+impl From<TrackKey> for KeyResponse {
+    fn from(key: TrackKey) -> Self {
+        Self {
+            track_id: key.track_id,
+            key: key.key,
+            confidence: key.confidence,
+            analyzed_at: key.analyzed_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhraseResponse {
+    pub start_bar: u32,
+    pub bars: u32,
+    pub start: f64,
+    pub end: f64,
+    /// "intro", "verse", "chorus", "bridge" o "outro"
+    pub kind: String,
+    pub energy: f64,
+}
+
+impl From<Phrase> for PhraseResponse {
+    fn from(phrase: Phrase) -> Self {
+        Self {
+            start_bar: phrase.start_bar,
+            bars: phrase.bars,
+            start: phrase.start_time,
+            end: phrase.end_time,
+            kind: phrase.kind,
+            energy: phrase.energy,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhraseAnalysisResponse {
+    pub track_id: String,
+    /// Índice del primer beat que es "1" de compás
+    pub downbeat_beat: u32,
+    /// Posición del primer downbeat en segundos
+    pub downbeat: f64,
+    pub beats_per_bar: u32,
+    pub phrases: Vec<PhraseResponse>,
+    pub analyzed_at: String,
+}
+
+impl PhraseAnalysisResponse {
+    fn new(downbeat: Downbeat, phrases: Vec<Phrase>) -> Self {
+        Self {
+            track_id: downbeat.track_id,
+            downbeat_beat: downbeat.beat,
+            downbeat: downbeat.position,
+            beats_per_bar: downbeat.beats_per_bar,
+            phrases: phrases.into_iter().map(PhraseResponse::from).collect(),
+            analyzed_at: downbeat.analyzed_at,
         }
     }
 }
@@ -72,6 +187,8 @@ pub struct CuePointResponse {
     #[serde(rename = "type")]
     pub cue_type: String,
     pub hotkey: Option<i32>,
+    /// "user" o "auto" (análisis automático)
+    pub source: String,
     pub created_at: String,
 }
 
@@ -85,6 +202,7 @@ impl From<CuePoint> for CuePointResponse {
             color: cue.color,
             cue_type: cue.cue_type,
             hotkey: cue.hotkey,
+            source: cue.source,
             created_at: cue.created_at,
         }
     }
@@ -149,25 +267,36 @@ pub async fn analyze_beatgrid(
     track_path: String,
     pool: State<'_, DbPool>,
 ) -> Result<BeatgridResponse, String> {
-    // Analizar en thread separado para no bloquear UI
+    // Analizar en thread separado para no bloquear UI, con el rango de tempo preferido
     let path = track_path.clone();
-    let analysis = tokio::task::spawn_blocking(move || BeatgridDetector::analyze(Path::new(&path)))
-        .await
-        .map_err(|e| format!("Error en task: {}", e))?
-        .map_err(|e| format!("Error de análisis: {}", e))?;
+    let settings_pool = pool.inner().clone();
+    let analysis = tokio::task::spawn_blocking(move || {
+        let range = {
+            let conn = settings_pool.get().map_err(|e| e.to_string())?;
+            queries::get_bpm_range(&conn).map_err(|e| format!("Error leyendo settings: {}", e))?
+        };
+        BeatgridDetector::analyze_in_range(Path::new(&path), range)
+            .map_err(|e| format!("Error de análisis: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Error en task: {}", e))??;
 
     // Guardar en DB usando el pool
     let pool = pool.inner().clone();
     let track_id_clone = track_id.clone();
-    let bpm = analysis.bpm;
-    let offset = analysis.offset;
-    let confidence = analysis.confidence;
 
     tokio::task::spawn_blocking(move || {
         let conn = pool.get().map_err(|e| e.to_string())?;
 
-        queries::upsert_beatgrid(&conn, &track_id_clone, bpm, offset, Some(confidence))
-            .map_err(|e| format!("Error guardando beatgrid: {}", e))?;
+        queries::upsert_beatgrid(
+            &conn,
+            &track_id_clone,
+            analysis.bpm,
+            analysis.offset,
+            Some(analysis.confidence),
+            &analysis.anchors,
+        )
+        .map_err(|e| format!("Error guardando beatgrid: {}", e))?;
 
         // Obtener beatgrid guardado con timestamp
         let saved = queries::get_beatgrid(&conn, &track_id_clone)
@@ -197,6 +326,34 @@ pub async fn get_beatgrid(
     .map_err(|e| format!("Task join error: {}", e))?
 }
 
+/// Obtiene las posiciones (segundos) de los beats entre `start` y `end`
+///
+/// Sigue los cambios de tempo del grid: es lo que debe pintar la vista del
+/// grid en lugar de extrapolar desde `bpm` y `offset`. Vacío si no hay beatgrid.
+#[tauri::command]
+pub async fn get_beat_positions(
+    track_id: String,
+    start: f64,
+    end: f64,
+    pool: State<'_, DbPool>,
+) -> Result<Vec<f64>, String> {
+    let pool = pool.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        let Some(map) = queries::get_tempo_map(&conn, &track_id)
+            .map_err(|e| format!("Error obteniendo beatgrid: {}", e))?
+        else {
+            return Ok(Vec::new());
+        };
+        let duration = queries::get_track(&conn, &track_id)
+            .map_err(|e| format!("Error obteniendo pista: {}", e))?
+            .duration;
+        Ok(map.beats_between(start, end, duration))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
 /// Actualiza offset del beatgrid (ajuste manual fino)
 #[tauri::command]
 pub async fn update_beatgrid_offset(
@@ -227,6 +384,250 @@ pub async fn delete_beatgrid(track_id: String, pool: State<'_, DbPool>) -> Resul
     .map_err(|e| format!("Task join error: {}", e))?
 }
 
+// ============================================================================
+// Loudness Commands
+// ============================================================================
+
+/// Analiza el loudness EBU R128 de una pista
+///
+/// Guarda integrated loudness, true peak y loudness range en la base de datos.
+/// Si `write_tags` es true, escribe además REPLAYGAIN_TRACK_* (e iTunNORM en MP3)
+/// en el archivo.
+#[tauri::command]
+pub async fn analyze_loudness(
+    track_id: String,
+    track_path: String,
+    write_tags: Option<bool>,
+    pool: State<'_, DbPool>,
+) -> Result<LoudnessResponse, String> {
+    // Analizar en thread separado para no bloquear UI
+    let path = track_path.clone();
+    let analysis = tokio::task::spawn_blocking(move || LoudnessAnalyzer::analyze(Path::new(&path)))
+        .await
+        .map_err(|e| format!("Error en task: {}", e))?
+        .map_err(|e| format!("Error de análisis: {}", e))?;
+
+    log::info!(
+        "🔊 Loudness de {}: {:.1} LUFS, {:.1} dBTP, LRA {:.1} LU",
+        track_path,
+        analysis.integrated_lufs,
+        analysis.true_peak_dbtp,
+        analysis.loudness_range
+    );
+
+    if write_tags.unwrap_or(false) {
+        let replay_gain =
+            ReplayGain::from_loudness(analysis.integrated_lufs, analysis.true_peak_dbtp);
+        let path = track_path.clone();
+        tokio::task::spawn_blocking(move || write_replaygain(Path::new(&path), &replay_gain))
+            .await
+            .map_err(|e| format!("Error en task: {}", e))?
+            .map_err(|e| format!("Error escribiendo ReplayGain: {}", e))?;
+    }
+
+    // Guardar en DB usando el pool
+    let pool = pool.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+
+        queries::upsert_loudness(
+            &conn,
+            &track_id,
+            analysis.integrated_lufs,
+            analysis.true_peak_dbtp,
+            Some(analysis.loudness_range),
+            "analysis",
+        )
+        .map_err(|e| format!("Error guardando loudness: {}", e))?;
+
+        let saved = queries::get_loudness(&conn, &track_id)
+            .map_err(|e| format!("Error obteniendo loudness: {}", e))?
+            .ok_or_else(|| "Loudness no encontrado después de guardar".to_string())?;
+
+        Ok(LoudnessResponse::from(saved))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Obtiene el loudness de una pista si existe
+#[tauri::command]
+pub async fn get_loudness(
+    track_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<Option<LoudnessResponse>, String> {
+    let pool = pool.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        queries::get_loudness(&conn, &track_id)
+            .map(|opt| opt.map(LoudnessResponse::from))
+            .map_err(|e| format!("Error obteniendo loudness: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+// ============================================================================
+// Key Commands
+// ============================================================================
+
+/// Detecta la tonalidad de una pista
+///
+/// Guarda tonalidad y confidence en la base de datos (y en `tracks.key` si la
+/// pista no tenía y la confidence es suficiente). Si `write_tags` es true,
+/// escribe además TKEY en el archivo y la pone como tonalidad de la pista.
+#[tauri::command]
+pub async fn analyze_key(
+    track_id: String,
+    track_path: String,
+    write_tags: Option<bool>,
+    pool: State<'_, DbPool>,
+) -> Result<KeyResponse, String> {
+    // Analizar en thread separado para no bloquear UI
+    let path = track_path.clone();
+    let analysis = tokio::task::spawn_blocking(move || KeyDetector::analyze(Path::new(&path)))
+        .await
+        .map_err(|e| format!("Error en task: {}", e))?
+        .map_err(|e| format!("Error de análisis: {}", e))?;
+    let key = analysis.key.to_string();
+
+    log::info!(
+        "🎹 Tonalidad de {}: {} (confidence {:.0})",
+        track_path,
+        key,
+        analysis.confidence
+    );
+
+    let write_tags = write_tags.unwrap_or(false);
+    if write_tags {
+        // TKEY en la notación preferida de la biblioteca
+        let pool = pool.inner().clone();
+        let path = track_path.clone();
+        let detected = analysis.key;
+        tokio::task::spawn_blocking(move || {
+            let conn = pool.get().map_err(|e| e.to_string())?;
+            let notation = queries::get_key_notation(&conn)
+                .map_err(|e| format!("Error obteniendo notación: {}", e))?;
+            write_key(Path::new(&path), &detected.format(notation))
+                .map_err(|e| format!("Error escribiendo tonalidad: {}", e))
+        })
+        .await
+        .map_err(|e| format!("Error en task: {}", e))??;
+    }
+
+    // Guardar en DB usando el pool
+    let pool = pool.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+
+        queries::upsert_key_analysis(&conn, &track_id, &key, analysis.confidence)
+            .map_err(|e| format!("Error guardando tonalidad: {}", e))?;
+
+        // El archivo ya lleva la tonalidad detectada: la pista también
+        if write_tags {
+            queries::update_track_metadata(
+                &conn,
+                &track_id,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(&key),
+                None,
+            )
+            .map_err(|e| format!("Error actualizando tonalidad: {}", e))?;
+        }
+
+        let saved = queries::get_key_analysis(&conn, &track_id)
+            .map_err(|e| format!("Error obteniendo tonalidad: {}", e))?
+            .ok_or_else(|| "Tonalidad no encontrada después de guardar".to_string())?;
+
+        Ok(KeyResponse::from(saved))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Obtiene la tonalidad detectada de una pista si existe
+#[tauri::command]
+pub async fn get_key_analysis(
+    track_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<Option<KeyResponse>, String> {
+    let pool = pool.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        queries::get_key_analysis(&conn, &track_id)
+            .map(|opt| opt.map(KeyResponse::from))
+            .map_err(|e| format!("Error obteniendo tonalidad: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+// ============================================================================
+// Phrase Commands
+// ============================================================================
+
+/// Detecta downbeats y frases (intro/verse/chorus/bridge/outro) de una pista
+///
+/// Sigue el beatgrid guardado; si la pista no tiene, se detecta uno sobre la
+/// marcha. Reemplaza el análisis de frases anterior.
+#[tauri::command]
+pub async fn analyze_phrases(
+    track_id: String,
+    track_path: String,
+    pool: State<'_, DbPool>,
+) -> Result<PhraseAnalysisResponse, String> {
+    let pool = pool.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let analysis = detect_phrases(&pool, &track_id, Path::new(&track_path))?;
+        log::info!(
+            "🎼 Frases de {}: downbeat en beat {}, {} frases",
+            track_path,
+            analysis.downbeat_beat,
+            analysis.phrases.len()
+        );
+
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        load_phrase_analysis(&conn, &track_id)?
+            .ok_or_else(|| "Frases no encontradas después de guardar".to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Obtiene downbeat y frases de una pista si se han analizado
+#[tauri::command]
+pub async fn get_phrases(
+    track_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<Option<PhraseAnalysisResponse>, String> {
+    let pool = pool.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        load_phrase_analysis(&conn, &track_id)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+fn load_phrase_analysis(
+    conn: &rusqlite::Connection,
+    track_id: &str,
+) -> Result<Option<PhraseAnalysisResponse>, String> {
+    let Some(downbeat) = queries::get_downbeat(conn, track_id)
+        .map_err(|e| format!("Error obteniendo downbeat: {}", e))?
+    else {
+        return Ok(None);
+    };
+    let phrases = queries::get_phrases(conn, track_id)
+        .map_err(|e| format!("Error obteniendo frases: {}", e))?;
+    Ok(Some(PhraseAnalysisResponse::new(downbeat, phrases)))
+}
+
 // ============================================================================
 // Cue Point Commands
 // ============================================================================
@@ -261,6 +662,7 @@ pub async fn create_cue_point(
             color: request.color.unwrap_or_else(|| "#FFFFFF".to_string()),
             cue_type: request.cue_type,
             hotkey: request.hotkey,
+            source: "user".to_string(),
             created_at: chrono::Utc::now().to_rfc3339(),
         })
     })
@@ -285,6 +687,31 @@ pub async fn get_cue_points(
     .map_err(|e| format!("Task join error: {}", e))?
 }
 
+/// Genera cue points automáticos (intro, main, break, drop, outro)
+///
+/// Se ajustan al beatgrid si la pista lo tiene. Re-ejecutable: sustituye los
+/// cues automáticos anteriores y respeta los del usuario. Retorna todos los
+/// cue points de la pista.
+#[tauri::command]
+pub async fn analyze_cues(
+    track_id: String,
+    track_path: String,
+    pool: State<'_, DbPool>,
+) -> Result<Vec<CuePointResponse>, String> {
+    let pool = pool.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let cues = detect_cues(&pool, &track_id, Path::new(&track_path))?;
+        log::info!(
+            "📍 Cues automáticos de {}: {} cue points",
+            track_path,
+            cues.iter().filter(|c| c.source == "auto").count()
+        );
+        Ok(cues.into_iter().map(CuePointResponse::from).collect())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
 /// Actualiza un cue point existente
 #[tauri::command]
 pub async fn update_cue_point(
@@ -413,6 +840,108 @@ pub async fn delete_loop(id: String, pool: State<'_, DbPool>) -> Result<(), Stri
     .map_err(|e| format!("Task join error: {}", e))?
 }
 
+// ============================================================================
+// Analysis Queue Commands
+// ============================================================================
+
+/// Encola el análisis de toda la biblioteca ("analizar todo esta noche")
+///
+/// Sin `force` solo se encolan los análisis que faltan.
+#[tauri::command]
+pub async fn analyze_library(
+    force: Option<bool>,
+    queue: State<'_, Arc<AnalysisQueue>>,
+) -> Result<usize, String> {
+    let queue = queue.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        queue.enqueue(
+            None,
+            &AnalysisKind::ALL,
+            PRIORITY_NORMAL,
+            force.unwrap_or(false),
+        )
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Encola análisis de tracks concretos (por defecto, todos los tipos)
+#[tauri::command]
+pub async fn enqueue_analysis(
+    track_ids: Vec<String>,
+    kinds: Option<Vec<AnalysisKind>>,
+    force: Option<bool>,
+    queue: State<'_, Arc<AnalysisQueue>>,
+) -> Result<usize, String> {
+    let queue = queue.inner().clone();
+    let kinds = kinds.unwrap_or_else(|| AnalysisKind::ALL.to_vec());
+    tokio::task::spawn_blocking(move || {
+        queue.enqueue(
+            Some(&track_ids),
+            &kinds,
+            PRIORITY_NORMAL,
+            force.unwrap_or(false),
+        )
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Analiza primero el track seleccionado
+#[tauri::command]
+pub async fn prioritize_analysis(
+    track_id: String,
+    queue: State<'_, Arc<AnalysisQueue>>,
+) -> Result<usize, String> {
+    let queue = queue.inner().clone();
+    tokio::task::spawn_blocking(move || queue.prioritize(&track_id))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Pausa la cola (los análisis en curso terminan)
+#[tauri::command]
+pub fn pause_analysis(queue: State<'_, Arc<AnalysisQueue>>) {
+    queue.pause();
+}
+
+/// Reanuda la cola
+#[tauri::command]
+pub fn resume_analysis(queue: State<'_, Arc<AnalysisQueue>>) {
+    queue.resume();
+}
+
+/// Estado de la cola (pendientes, en curso, fallidos, completados)
+#[tauri::command]
+pub async fn get_analysis_status(
+    queue: State<'_, Arc<AnalysisQueue>>,
+) -> Result<AnalysisStatus, String> {
+    let queue = queue.inner().clone();
+    tokio::task::spawn_blocking(move || queue.status())
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Análisis fallidos con su error
+#[tauri::command]
+pub async fn get_failed_analysis_jobs(
+    queue: State<'_, Arc<AnalysisQueue>>,
+) -> Result<Vec<AnalysisJob>, String> {
+    let queue = queue.inner().clone();
+    tokio::task::spawn_blocking(move || queue.failed_jobs())
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Vacía la cola, incluidos los fallidos
+#[tauri::command]
+pub async fn clear_analysis_queue(queue: State<'_, Arc<AnalysisQueue>>) -> Result<usize, String> {
+    let queue = queue.inner().clone();
+    tokio::task::spawn_blocking(move || queue.clear())
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -15,11 +15,19 @@ use std::sync::{Arc, Mutex};
 use std::sync::mpsc;
 use tauri::{AppHandle, Manager, State};
 
+use crate::audio::player::events::emit_devices_changed;
+use crate::audio::waveform::cache as waveform_cache;
+use crate::audio::waveform::pyramid::PYRAMID_FORMAT;
 use crate::audio::{
-    cancel_waveform_generation, generate_waveform_streaming, AudioDecoder, AudioDeviceInfo,
-    AudioMetadata, CpalAudioOutput, PlayerControlEvent, WaveformState,
+    cancel_waveform_generation, generate_waveform_streaming, list_output_devices, load_pyramid,
+    AudioDecoder, AudioDeviceInfo, AudioMetadata, CrossfadeCurve, CrossfadeSettings, CueTrigger,
+    DeviceMonitor, DspSettings, EqPreset, LoopRegion, NormalizationSettings, OutputSettings,
+    PlaybackQueue, PlayerControlEvent, PositionHandle, TempoRange, TempoSettings, WaveformMode,
+    WaveformRange, WaveformState, DEFAULT_DEVICE, DEFAULT_VOLUME, LOOP_MIN_MS,
 };
-use crate::db::DbPool;
+use crate::config::AppConfig;
+use crate::db::models::{Loop, SavedQueue, WaveformCacheStats};
+use crate::db::{queries, DbPool};
 
 // ============================================================================
 // ESTADO GLOBAL
@@ -32,15 +40,29 @@ use crate::db::DbPool;
 pub struct AudioPlayerState {
     /// Canal para enviar comandos al decode thread
     pub control_tx: Arc<Mutex<Option<Sender<PlayerControlEvent>>>>,
+    /// Cola de reproducción (compartida con el decode thread)
+    pub queue: Arc<Mutex<PlaybackQueue>>,
+    /// Estado del pitch fader (el decode thread solo conoce la velocidad resultante)
+    pub tempo: Arc<Mutex<TempoSettings>>,
+    /// Posición audible publicada por el decode thread
+    pub position: Arc<Mutex<Option<PositionHandle>>>,
 }
 
 impl AudioPlayerState {
     pub fn new() -> Self {
         Self {
             control_tx: Arc::new(Mutex::new(None)),
+            queue: Arc::new(Mutex::new(PlaybackQueue::new())),
+            tempo: Arc::new(Mutex::new(TempoSettings::from(&AppConfig::load().audio))),
+            position: Arc::new(Mutex::new(None)),
         }
     }
 
+    /// Restaura la cola guardada en la base de datos al iniciar la app
+    pub fn restore_queue(&self, saved: SavedQueue) {
+        self.queue.lock().unwrap().restore(saved);
+    }
+
     pub fn set_control_tx(&self, tx: Sender<PlayerControlEvent>) {
         *self.control_tx.lock().unwrap() = Some(tx);
     }
@@ -60,6 +82,56 @@ impl Default for AudioPlayerState {
     }
 }
 
+/// Estado global del reproductor de pre-escucha (auriculares)
+///
+/// AIDEV-NOTE: Segundo AudioPlayer independiente del principal, con su propio
+/// dispositivo (`AudioConfig::preview_device`), volumen y eventos `preview:*`.
+/// Su decode thread se crea una vez y vive lo que la app: `preview_stop` solo
+/// libera el dispositivo y el siguiente `preview_track` lo reutiliza.
+pub struct PreviewPlayerState {
+    /// Canal para enviar comandos al decode thread de pre-escucha
+    pub control_tx: Arc<Mutex<Option<Sender<PlayerControlEvent>>>>,
+    /// Volumen de pre-escucha (se conserva entre tracks)
+    pub volume: Arc<Mutex<f64>>,
+}
+
+impl PreviewPlayerState {
+    pub fn new() -> Self {
+        Self {
+            control_tx: Arc::new(Mutex::new(None)),
+            volume: Arc::new(Mutex::new(DEFAULT_VOLUME)),
+        }
+    }
+
+    pub fn set_control_tx(&self, tx: Sender<PlayerControlEvent>) {
+        *self.control_tx.lock().unwrap() = Some(tx);
+    }
+
+    pub fn get_control_tx(&self) -> Option<Sender<PlayerControlEvent>> {
+        self.control_tx.lock().unwrap().clone()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.control_tx.lock().unwrap().is_some()
+    }
+
+    /// Envía un comando al decode thread de pre-escucha
+    fn send(&self, event: PlayerControlEvent) -> Result<(), String> {
+        match self.get_control_tx() {
+            Some(tx) => tx
+                .send(event)
+                .map_err(|e| format!("Error enviando comando: {}", e)),
+            None => Err("No hay pre-escucha activa".to_string()),
+        }
+    }
+}
+
+impl Default for PreviewPlayerState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Respuesta del estado de reproducción
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlaybackStateResponse {
@@ -92,11 +164,13 @@ pub struct AudioBlobResponse {
 /// - Verificación de cache en SQLite (usando DbPool + spawn_blocking)
 /// - Generación streaming si no existe
 /// - Eventos: waveform:progress, waveform:complete, waveform:error
+/// - `mode`: `mono` (por defecto) o `bands` (graves/medios/agudos)
 #[tauri::command]
 pub async fn get_waveform(
     track_id: String,
     track_path: String,
     duration: f64,
+    mode: Option<WaveformMode>,
     app: AppHandle,
     waveform_state: State<'_, Arc<WaveformState>>,
     pool: State<'_, DbPool>,
@@ -111,6 +185,7 @@ pub async fn get_waveform(
         track_id,
         track_path,
         duration,
+        mode.unwrap_or_default(),
         app,
         waveform_state.inner().clone(),
         Arc::new(pool.inner().clone()),
@@ -119,6 +194,49 @@ pub async fn get_waveform(
     .map_err(|e| e.to_string())
 }
 
+/// Obtiene los peaks de una ventana del track para vistas con zoom
+///
+/// AIDEV-NOTE: Sirve desde la pirámide de detalle (ver `audio::waveform::pyramid`),
+/// que se genera la primera vez que se pide un rango del track. `resolution` es
+/// el máximo de peaks a devolver; por defecto, el setting `ui.waveform_resolution`.
+#[tauri::command]
+pub async fn get_waveform_range(
+    track_id: String,
+    start: f64,
+    end: f64,
+    resolution: Option<usize>,
+    waveform_state: State<'_, Arc<WaveformState>>,
+    pool: State<'_, DbPool>,
+) -> Result<WaveformRange, String> {
+    let resolution = match resolution {
+        Some(resolution) => resolution,
+        None => {
+            let pool = pool.inner().clone();
+            tokio::task::spawn_blocking(move || default_waveform_resolution(&pool))
+                .await
+                .map_err(|e| format!("Task join error: {}", e))?
+        }
+    };
+
+    let pyramid = load_pyramid(&track_id, &waveform_state, &pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(pyramid.range(start, end, resolution))
+}
+
+/// Resolución de waveform configurada (setting de la DB, si no la de settings.json)
+fn default_waveform_resolution(pool: &DbPool) -> usize {
+    pool.get()
+        .ok()
+        .and_then(|conn| {
+            queries::get_setting(&conn, "ui.waveform_resolution")
+                .ok()
+                .flatten()
+        })
+        .and_then(|setting| setting.value.parse().ok())
+        .unwrap_or_else(|| AppConfig::load().ui.waveform_resolution as usize)
+}
+
 /// Cancela generación de waveform en progreso
 #[tauri::command]
 pub async fn cancel_waveform(
@@ -138,6 +256,7 @@ pub async fn cancel_waveform(
 #[tauri::command]
 pub async fn clear_waveform_cache(
     pool: State<'_, DbPool>,
+    waveform_state: State<'_, Arc<WaveformState>>,
 ) -> Result<usize, String> {
     log::info!("🧹 clear_waveform_cache: Limpiando cache de waveforms...");
 
@@ -149,18 +268,78 @@ pub async fn clear_waveform_cache(
     })
     .await
     .map_err(|e| format!("Task join error: {}", e))??;
+    waveform_state.pyramids.lock().await.clear();
 
     log::info!("✅ Cache limpiado: {} waveforms eliminados", deleted);
     Ok(deleted)
 }
 
+/// Ocupación de la caché de waveforms (por formato) y de la base de datos
+#[tauri::command]
+pub async fn get_waveform_cache_stats(
+    pool: State<'_, DbPool>,
+) -> Result<WaveformCacheStats, String> {
+    let pool = pool.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        queries::get_waveform_cache_stats(&conn).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Recolecta basura de la caché de waveforms
+///
+/// AIDEV-NOTE: Borra filas de tracks eliminados y de formatos o versiones que
+/// ya no se leerían; con `max_size_mb`, también las más antiguas hasta caber.
+/// Si borra algo, hace VACUUM para que `symphony.db` encoja de verdad.
+/// Las pirámides borradas también salen de la caché en memoria.
+#[tauri::command]
+pub async fn gc_waveform_cache(
+    max_size_mb: Option<u64>,
+    pool: State<'_, DbPool>,
+    waveform_state: State<'_, Arc<WaveformState>>,
+) -> Result<usize, String> {
+    log::info!("🧹 gc_waveform_cache: max_size_mb={:?}", max_size_mb);
+
+    let pool = pool.inner().clone();
+    let removed = tokio::task::spawn_blocking(move || {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        let current = waveform_cache::current_versions();
+        let max_bytes =
+            max_size_mb.map(|mb| i64::try_from(mb.saturating_mul(1024 * 1024)).unwrap_or(i64::MAX));
+        let removed = queries::gc_waveforms(&conn, &current, max_bytes)
+            .map_err(|e| format!("Error limpiando cache: {}", e))?;
+        if !removed.is_empty() {
+            conn.execute_batch("VACUUM")
+                .map_err(|e| format!("Error compactando base de datos: {}", e))?;
+        }
+        Ok::<_, String>(removed)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    {
+        let mut pyramids = waveform_state.pyramids.lock().await;
+        for (track_id, format) in &removed {
+            if format == PYRAMID_FORMAT {
+                pyramids.remove(track_id);
+            }
+        }
+    }
+
+    log::info!("✅ GC de waveforms: {} filas eliminadas", removed.len());
+    Ok(removed.len())
+}
+
 // ============================================================================
 // COMANDOS TAURI - PLAYBACK
 // ============================================================================
 
 /// Reproduce una pista de audio
 ///
-/// AIDEV-NOTE: Crea el player y guarda el Sender del canal de control
+/// AIDEV-NOTE: Crea el player y guarda el Sender del canal de control.
+/// Un play suelto desactiva el avance automático de la cola.
 #[tauri::command]
 pub async fn play_track(
     path: String,
@@ -170,7 +349,19 @@ pub async fn play_track(
     log::info!("========== PLAY_TRACK COMMAND START ==========");
     log::info!("Path received: {}", path);
 
-    let path_buf = PathBuf::from(&path);
+    player_state.queue.lock().unwrap().set_active(false);
+    start_playback(&path, app_handle, &player_state)
+}
+
+/// Envía un archivo al decode thread, creando el player si no existe
+///
+/// AIDEV-NOTE: Compartido por play_track y los comandos de la cola
+pub(crate) fn start_playback(
+    path: &str,
+    app_handle: AppHandle,
+    player_state: &AudioPlayerState,
+) -> Result<(), String> {
+    let path_buf = PathBuf::from(path);
 
     if !path_buf.exists() {
         log::error!("File does not exist: {:?}", path_buf);
@@ -192,13 +383,22 @@ pub async fn play_track(
 
     // Crear nuevo player
     log::info!("Creating new AudioPlayer...");
-    let player = crate::audio::AudioPlayer::new(app_handle).map_err(|e| {
-        log::error!("Failed to create player: {}", e);
-        e.to_string()
-    })?;
+    let player = crate::audio::AudioPlayer::new(app_handle, Arc::clone(&player_state.queue))
+        .map_err(|e| {
+            log::error!("Failed to create player: {}", e);
+            e.to_string()
+        })?;
 
-    // Guardar el sender
+    // Guardar el sender y el handle de posición
     player_state.set_control_tx(player.get_control_tx());
+    *player_state.position.lock().unwrap() = Some(player.position_handle());
+
+    // El pitch fader sobrevive a stop/play: aplicarlo al nuevo player
+    let tempo = *player_state.tempo.lock().unwrap();
+    player
+        .set_key_lock(tempo.key_lock)
+        .and_then(|_| player.set_tempo(tempo.rate()))
+        .map_err(|e| e.to_string())?;
 
     // Reproducir
     log::info!("Calling player.play()...");
@@ -244,6 +444,7 @@ pub fn stop_playback(player_state: State<'_, AudioPlayerState>) -> Result<(), St
             .map_err(|e| format!("Error enviando comando: {}", e))?;
         // Limpiar el control_tx
         *player_state.control_tx.lock().unwrap() = None;
+        *player_state.position.lock().unwrap() = None;
         Ok(())
     } else {
         Err("No hay reproductor activo".to_string())
@@ -268,6 +469,447 @@ pub async fn seek_to_position(
     }
 }
 
+/// Precarga el siguiente track para reproducción gapless
+///
+/// AIDEV-NOTE: El decode thread abre y decodifica el primer paquete por adelantado.
+/// Al terminar el track actual continúa con este sin detener el output y emite
+/// `audio:track_changed`. Un `play_track` posterior descarta el track precargado.
+#[tauri::command]
+pub fn enqueue_next_track(
+    path: String,
+    player_state: State<'_, AudioPlayerState>,
+) -> Result<(), String> {
+    log::info!("enqueue_next_track command: {}", path);
+
+    let path_buf = PathBuf::from(&path);
+    if !path_buf.exists() {
+        return Err(format!("El archivo no existe: {:?}", path_buf));
+    }
+
+    if let Some(tx) = player_state.get_control_tx() {
+        tx.send(PlayerControlEvent::EnqueueNext {
+            path: path_buf.to_string_lossy().to_string(),
+        })
+        .map_err(|e| format!("Error enviando comando: {}", e))
+    } else {
+        Err("No hay reproductor activo".to_string())
+    }
+}
+
+/// Obtiene la configuración de crossfade guardada en settings.json
+#[tauri::command]
+pub fn get_crossfade_settings() -> Result<CrossfadeSettings, String> {
+    Ok(CrossfadeSettings::from(&AppConfig::load().audio))
+}
+
+/// Cambia la configuración de crossfade
+///
+/// AIDEV-NOTE: Se guarda en AudioConfig (settings.json) y, si hay un player
+/// activo, se aplica en caliente con `SetCrossfade`.
+#[tauri::command]
+pub fn set_crossfade(
+    enabled: bool,
+    seconds: f64,
+    curve: CrossfadeCurve,
+    player_state: State<'_, AudioPlayerState>,
+) -> Result<CrossfadeSettings, String> {
+    let settings = CrossfadeSettings {
+        enabled,
+        seconds,
+        curve,
+    }
+    .clamped();
+    log::info!("set_crossfade command: {:?}", settings);
+
+    let mut config = AppConfig::load();
+    config.audio.crossfade_enabled = settings.enabled;
+    config.audio.crossfade_seconds = settings.seconds;
+    config.audio.crossfade_curve = settings.curve;
+    config.save()?;
+
+    if let Some(tx) = player_state.get_control_tx() {
+        tx.send(PlayerControlEvent::SetCrossfade { settings })
+            .map_err(|e| format!("Error enviando comando: {}", e))?;
+    }
+
+    Ok(settings)
+}
+
+/// Obtiene la configuración de normalización guardada en settings.json
+#[tauri::command]
+pub fn get_normalization_settings() -> Result<NormalizationSettings, String> {
+    Ok(NormalizationSettings::from(&AppConfig::load().audio))
+}
+
+/// Cambia la normalización de loudness
+///
+/// AIDEV-NOTE: Se guarda en AudioConfig (settings.json) y se aplica en caliente a
+/// ambos reproductores (principal y pre-escucha) con `SetNormalization`.
+/// Los tracks sin análisis de loudness suenan sin ganancia.
+#[tauri::command]
+pub fn set_normalization(
+    enabled: bool,
+    target_lufs: f64,
+    player_state: State<'_, AudioPlayerState>,
+    preview_state: State<'_, PreviewPlayerState>,
+) -> Result<NormalizationSettings, String> {
+    let settings = NormalizationSettings {
+        enabled,
+        target_lufs,
+    }
+    .clamped();
+    log::info!("set_normalization command: {:?}", settings);
+
+    let mut config = AppConfig::load();
+    config.audio.normalization_enabled = settings.enabled;
+    config.audio.normalization_target_lufs = settings.target_lufs;
+    config.save()?;
+
+    if let Some(tx) = player_state.get_control_tx() {
+        tx.send(PlayerControlEvent::SetNormalization { settings })
+            .map_err(|e| format!("Error enviando comando: {}", e))?;
+    }
+    if preview_state.is_active() {
+        preview_state.send(PlayerControlEvent::SetNormalization { settings })?;
+    }
+
+    Ok(settings)
+}
+
+/// Obtiene la configuración de la cadena DSP (preamp, EQ, limitador)
+#[tauri::command]
+pub fn get_dsp_settings() -> Result<DspSettings, String> {
+    Ok(AppConfig::load().audio.dsp)
+}
+
+/// Cambia la cadena DSP del reproductor principal
+///
+/// AIDEV-NOTE: Se guarda en AudioConfig (settings.json) y se aplica en caliente con
+/// `SetDsp`. La pre-escucha no usa el EQ del usuario (solo el limitador de la
+/// normalización), así que no se le envía.
+#[tauri::command]
+pub fn set_dsp_settings(
+    settings: DspSettings,
+    player_state: State<'_, AudioPlayerState>,
+) -> Result<DspSettings, String> {
+    let settings = settings.clamped();
+    log::info!(
+        "set_dsp_settings command: enabled={}, preamp={:.1} dB",
+        settings.enabled,
+        settings.preamp_db
+    );
+
+    let mut config = AppConfig::load();
+    config.audio.dsp = settings.clone();
+    config.save()?;
+
+    send_dsp_settings(&player_state, settings.clone())?;
+    Ok(settings)
+}
+
+/// Lista los presets de EQ: primero los de fábrica y después los del usuario
+#[tauri::command]
+pub fn get_eq_presets() -> Result<Vec<EqPreset>, String> {
+    let mut presets = EqPreset::builtin_presets();
+    presets.extend(AppConfig::load().audio.eq_presets);
+    Ok(presets)
+}
+
+/// Guarda el EQ actual como preset del usuario (sobrescribe si ya existe)
+#[tauri::command]
+pub fn save_eq_preset(name: String) -> Result<EqPreset, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("El nombre del preset no puede estar vacío".to_string());
+    }
+    if is_builtin_preset(&name) {
+        return Err(format!("'{}' es un preset de fábrica", name));
+    }
+
+    let mut config = AppConfig::load();
+    let preset = EqPreset {
+        name: name.clone(),
+        preamp_db: config.audio.dsp.preamp_db,
+        bands: config.audio.dsp.bands.clone(),
+        builtin: false,
+    };
+
+    config.audio.eq_presets.retain(|p| p.name != name);
+    config.audio.eq_presets.push(preset.clone());
+    config.audio.dsp.preset = Some(name);
+    config.save()?;
+
+    Ok(preset)
+}
+
+/// Borra un preset del usuario
+#[tauri::command]
+pub fn delete_eq_preset(name: String) -> Result<(), String> {
+    if is_builtin_preset(&name) {
+        return Err(format!("'{}' es un preset de fábrica", name));
+    }
+
+    let mut config = AppConfig::load();
+    let before = config.audio.eq_presets.len();
+    config.audio.eq_presets.retain(|p| p.name != name);
+    if config.audio.eq_presets.len() == before {
+        return Err(format!("Preset no encontrado: {}", name));
+    }
+    if config.audio.dsp.preset.as_deref() == Some(name.as_str()) {
+        config.audio.dsp.preset = None;
+    }
+    config.save()
+}
+
+/// Aplica un preset (de fábrica o del usuario) y activa la cadena DSP
+#[tauri::command]
+pub fn apply_eq_preset(
+    name: String,
+    player_state: State<'_, AudioPlayerState>,
+) -> Result<DspSettings, String> {
+    let mut config = AppConfig::load();
+    let preset = EqPreset::builtin_presets()
+        .into_iter()
+        .chain(config.audio.eq_presets.iter().cloned())
+        .find(|p| p.name == name)
+        .ok_or_else(|| format!("Preset no encontrado: {}", name))?;
+    log::info!("apply_eq_preset command: {}", preset.name);
+
+    let settings = DspSettings {
+        enabled: true,
+        preamp_db: preset.preamp_db,
+        bands: preset.bands,
+        preset: Some(preset.name),
+        ..config.audio.dsp
+    }
+    .clamped();
+
+    config.audio.dsp = settings.clone();
+    config.save()?;
+
+    send_dsp_settings(&player_state, settings.clone())?;
+    Ok(settings)
+}
+
+fn is_builtin_preset(name: &str) -> bool {
+    EqPreset::builtin_presets().iter().any(|p| p.name == name)
+}
+
+fn send_dsp_settings(player_state: &AudioPlayerState, settings: DspSettings) -> Result<(), String> {
+    if let Some(tx) = player_state.get_control_tx() {
+        tx.send(PlayerControlEvent::SetDsp { settings })
+            .map_err(|e| format!("Error enviando comando: {}", e))?;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// COMANDOS TAURI - LOOPS
+// ============================================================================
+
+/// Activa un loop guardado en el reproductor principal
+///
+/// AIDEV-NOTE: Marca el loop como `is_active` (uno por pista) para que vuelva a
+/// sonar en bucle la próxima vez que se cargue el track. Si el track del loop
+/// está sonando, el decode thread empieza a repetirlo de inmediato.
+#[tauri::command]
+pub async fn activate_loop(
+    loop_id: String,
+    pool: State<'_, DbPool>,
+    player_state: State<'_, AudioPlayerState>,
+) -> Result<LoopRegion, String> {
+    log::info!("activate_loop command: {}", loop_id);
+
+    let pool = pool.inner().clone();
+    let (path, region) = tokio::task::spawn_blocking(move || {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        let (item, path) = loop_with_path(&conn, &loop_id)?;
+        let region = loop_region(item.loop_start, item.loop_end)?;
+        queries::set_active_loop(&conn, &item.track_id, Some(&loop_id))
+            .map_err(|e| format!("Error activando loop: {}", e))?;
+        Ok::<_, String>((path, region))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    if let Some(tx) = player_state.get_control_tx() {
+        tx.send(PlayerControlEvent::SetLoop { path, region })
+            .map_err(|e| format!("Error enviando comando: {}", e))?;
+    }
+
+    Ok(region)
+}
+
+/// Desactiva el loop de una pista y deja que la reproducción siga
+#[tauri::command]
+pub async fn deactivate_loop(
+    track_id: String,
+    pool: State<'_, DbPool>,
+    player_state: State<'_, AudioPlayerState>,
+) -> Result<(), String> {
+    log::info!("deactivate_loop command: {}", track_id);
+
+    let pool = pool.inner().clone();
+    let path = tokio::task::spawn_blocking(move || {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        queries::set_active_loop(&conn, &track_id, None)
+            .map_err(|e| format!("Error desactivando loop: {}", e))?;
+        queries::get_track(&conn, &track_id)
+            .map(|track| track.path)
+            .map_err(|e| format!("Error obteniendo pista: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    if let Some(tx) = player_state.get_control_tx() {
+        tx.send(PlayerControlEvent::ClearLoop { path })
+            .map_err(|e| format!("Error enviando comando: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Cambia los límites de un loop guardado; si está activo se aplica en vivo
+#[tauri::command]
+pub async fn resize_loop(
+    loop_id: String,
+    loop_start: f64,
+    loop_end: f64,
+    pool: State<'_, DbPool>,
+    player_state: State<'_, AudioPlayerState>,
+) -> Result<LoopRegion, String> {
+    log::info!(
+        "resize_loop command: {} ({:.3}s → {:.3}s)",
+        loop_id,
+        loop_start,
+        loop_end
+    );
+
+    let region = loop_region(loop_start, loop_end)?;
+
+    let pool = pool.inner().clone();
+    let (path, is_active) = tokio::task::spawn_blocking(move || {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        queries::update_loop(&conn, &loop_id, None, Some(loop_start), Some(loop_end), None)
+            .map_err(|e| format!("Error actualizando loop: {}", e))?;
+        let (item, path) = loop_with_path(&conn, &loop_id)?;
+        Ok::<_, String>((path, item.is_active))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    if is_active {
+        if let Some(tx) = player_state.get_control_tx() {
+            tx.send(PlayerControlEvent::SetLoop { path, region })
+                .map_err(|e| format!("Error enviando comando: {}", e))?;
+        }
+    }
+
+    Ok(region)
+}
+
+/// Loop guardado y ruta de su pista (el decode thread solo conoce rutas)
+fn loop_with_path(conn: &rusqlite::Connection, loop_id: &str) -> Result<(Loop, String), String> {
+    let item = queries::get_loop(conn, loop_id)
+        .map_err(|e| format!("Error obteniendo loop: {}", e))?
+        .ok_or_else(|| format!("Loop no encontrado: {}", loop_id))?;
+    let track = queries::get_track(conn, &item.track_id)
+        .map_err(|e| format!("Error obteniendo pista: {}", e))?;
+    Ok((item, track.path))
+}
+
+/// Región de un loop, rechazando las que el decode thread no puede repetir
+fn loop_region(start: f64, end: f64) -> Result<LoopRegion, String> {
+    let region = LoopRegion { start, end };
+    if !region.is_valid() {
+        return Err(format!(
+            "Loop inválido ({:.3}s → {:.3}s): mínimo {} ms",
+            start, end, LOOP_MIN_MS
+        ));
+    }
+    Ok(region)
+}
+
+// ============================================================================
+// COMANDOS TAURI - HOT CUES
+// ============================================================================
+
+/// Dispara un hot cue (1-8) del track que suena en el reproductor principal
+///
+/// AIDEV-NOTE: El decode thread resuelve la posición en `cue_points`. `trigger`
+/// por defecto es `jump` (saltar y reproducir); para el comportamiento CDJ el
+/// frontend envía `press` al pulsar y `release` al soltar el botón.
+#[tauri::command]
+pub fn jump_to_cue(
+    hotkey: u8,
+    trigger: Option<CueTrigger>,
+    player_state: State<'_, AudioPlayerState>,
+) -> Result<(), String> {
+    if !(1..=8).contains(&hotkey) {
+        return Err(format!("Hotkey debe estar entre 1 y 8: {}", hotkey));
+    }
+    let trigger = trigger.unwrap_or_default();
+    log::info!("jump_to_cue command: {} ({:?})", hotkey, trigger);
+
+    if let Some(tx) = player_state.get_control_tx() {
+        tx.send(PlayerControlEvent::JumpToCue { hotkey, trigger })
+            .map_err(|e| format!("Error enviando comando: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Obtiene el estado del pitch fader
+#[tauri::command]
+pub fn get_tempo_settings(
+    player_state: State<'_, AudioPlayerState>,
+) -> Result<TempoSettings, String> {
+    Ok(*player_state.tempo.lock().unwrap())
+}
+
+/// Mueve el pitch fader
+///
+/// AIDEV-NOTE: `percent` se limita al rango (±8/16/50%). El rango y el key lock se
+/// guardan en AudioConfig (settings.json); el porcentaje vuelve a 0 al reiniciar.
+/// Con key lock cambia solo el tempo (WSOLA); sin él, tempo y tono (modo vinilo).
+#[tauri::command]
+pub fn set_playback_tempo(
+    percent: f64,
+    range: TempoRange,
+    key_lock: bool,
+    player_state: State<'_, AudioPlayerState>,
+) -> Result<TempoSettings, String> {
+    let settings = TempoSettings {
+        percent,
+        range,
+        key_lock,
+    }
+    .clamped();
+    log::info!("set_playback_tempo command: {:?}", settings);
+
+    let previous = std::mem::replace(&mut *player_state.tempo.lock().unwrap(), settings);
+
+    if previous.range != settings.range || previous.key_lock != settings.key_lock {
+        let mut config = AppConfig::load();
+        config.audio.tempo_range = settings.range;
+        config.audio.key_lock = settings.key_lock;
+        config.save()?;
+    }
+
+    if let Some(tx) = player_state.get_control_tx() {
+        tx.send(PlayerControlEvent::SetKeyLock {
+            enabled: settings.key_lock,
+        })
+        .map_err(|e| format!("Error enviando comando: {}", e))?;
+        tx.send(PlayerControlEvent::SetTempo {
+            rate: settings.rate(),
+        })
+        .map_err(|e| format!("Error enviando comando: {}", e))?;
+    }
+
+    Ok(settings)
+}
+
 /// Cambia el volumen del reproductor
 #[tauri::command]
 pub fn set_playback_volume(
@@ -283,17 +925,171 @@ pub fn set_playback_volume(
 }
 
 /// Cambia el dispositivo de audio
+///
+/// AIDEV-NOTE: Se guarda en `AudioConfig.output_device` como dispositivo
+/// preferido: si se desconecta se usa el por defecto y se vuelve a él al
+/// reconectarlo (ver `spawn_device_monitor`).
 #[tauri::command]
 pub fn set_audio_device(
     device_name: Option<String>,
     player_state: State<'_, AudioPlayerState>,
 ) -> Result<(), String> {
+    let mut config = AppConfig::load();
+    config.audio.output_device = device_name
+        .clone()
+        .unwrap_or_else(|| DEFAULT_DEVICE.to_string());
+    config.save()?;
+
     if let Some(tx) = player_state.get_control_tx() {
         tx.send(PlayerControlEvent::ChangeAudioDevice { device_name })
-            .map_err(|e| format!("Error enviando comando: {}", e))
-    } else {
-        Err("No hay reproductor activo".to_string())
+            .map_err(|e| format!("Error enviando comando: {}", e))?;
     }
+    Ok(())
+}
+
+/// Arranca el monitor de hot-plug de dispositivos de salida
+///
+/// AIDEV-NOTE: Avisa al frontend (`audio:devices-changed`) y a los decode threads
+/// activos, que deciden si caer al dispositivo por defecto o volver al preferido.
+pub(crate) fn spawn_device_monitor(app_handle: AppHandle) -> Result<DeviceMonitor, String> {
+    DeviceMonitor::spawn(move |devices| {
+        emit_devices_changed(&app_handle, devices);
+
+        let main = app_handle.state::<AudioPlayerState>().get_control_tx();
+        let preview = app_handle.state::<PreviewPlayerState>().get_control_tx();
+        for tx in main.into_iter().chain(preview) {
+            let _ = tx.send(PlayerControlEvent::DevicesChanged {
+                devices: devices.to_vec(),
+            });
+        }
+    })
+    .map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// COMANDOS TAURI - PREVIEW (AURICULARES)
+// ============================================================================
+
+/// Reproduce un track en el dispositivo de pre-escucha
+///
+/// AIDEV-NOTE: El player de pre-escucha se crea lazy igual que el principal.
+/// `seek` permite empezar directamente en un cue point.
+#[tauri::command]
+pub async fn preview_track(
+    path: String,
+    seek: Option<f64>,
+    app_handle: AppHandle,
+    preview_state: State<'_, PreviewPlayerState>,
+) -> Result<(), String> {
+    log::info!("🎧 preview_track: {} (seek={:?})", path, seek);
+
+    let path_buf = PathBuf::from(&path);
+    if !path_buf.exists() {
+        return Err(format!("El archivo no existe: {:?}", path_buf));
+    }
+
+    let volume = *preview_state.volume.lock().unwrap();
+
+    if preview_state.is_active() {
+        return preview_state.send(PlayerControlEvent::StreamFile {
+            path: path_buf.to_string_lossy().to_string(),
+            seek,
+            volume,
+        });
+    }
+
+    let player = crate::audio::AudioPlayer::preview(app_handle).map_err(|e| e.to_string())?;
+
+    // El dispositivo se fija antes del primer StreamFile (el output aún no existe)
+    let device = AppConfig::load().audio.preview_device;
+    if device.is_none() {
+        log::warn!("⚠️ Sin dispositivo de pre-escucha configurado, usando el por defecto");
+    }
+    player
+        .set_audio_device(device)
+        .and_then(|_| player.set_volume(volume))
+        .map_err(|e| e.to_string())?;
+
+    preview_state.set_control_tx(player.get_control_tx());
+    player.play(&path_buf, seek).map_err(|e| e.to_string())?;
+
+    // El player se mantiene vivo mientras su decode thread corre; es el único
+    // de pre-escucha (los siguientes tracks se envían con StreamFile)
+    std::mem::forget(player);
+    Ok(())
+}
+
+/// Pausa la pre-escucha
+#[tauri::command]
+pub fn preview_pause(preview_state: State<'_, PreviewPlayerState>) -> Result<(), String> {
+    preview_state.send(PlayerControlEvent::Pause)
+}
+
+/// Reanuda la pre-escucha
+#[tauri::command]
+pub fn preview_resume(preview_state: State<'_, PreviewPlayerState>) -> Result<(), String> {
+    preview_state.send(PlayerControlEvent::Resume)
+}
+
+/// Detiene la pre-escucha y libera su dispositivo
+///
+/// AIDEV-NOTE: El decode thread sigue vivo (sin output) para el siguiente
+/// `preview_track`; soltar aquí el Sender dejaría un thread huérfano por cada
+/// stop/play porque el player olvidado conserva otro Sender.
+#[tauri::command]
+pub fn preview_stop(preview_state: State<'_, PreviewPlayerState>) -> Result<(), String> {
+    preview_state.send(PlayerControlEvent::Stop)
+}
+
+/// Salta a una posición de la pre-escucha
+#[tauri::command]
+pub fn preview_seek(
+    position: f64,
+    preview_state: State<'_, PreviewPlayerState>,
+) -> Result<(), String> {
+    preview_state.send(PlayerControlEvent::Seek { position })
+}
+
+/// Cambia el volumen de la pre-escucha (independiente del principal)
+#[tauri::command]
+pub fn set_preview_volume(
+    volume: f64,
+    preview_state: State<'_, PreviewPlayerState>,
+) -> Result<(), String> {
+    let volume = volume.clamp(0.0, 1.0);
+    *preview_state.volume.lock().unwrap() = volume;
+
+    if preview_state.is_active() {
+        preview_state.send(PlayerControlEvent::ChangeVolume { volume })?;
+    }
+    Ok(())
+}
+
+/// Obtiene el dispositivo de pre-escucha guardado
+#[tauri::command]
+pub fn get_preview_device() -> Result<Option<String>, String> {
+    Ok(AppConfig::load().audio.preview_device)
+}
+
+/// Cambia el dispositivo de pre-escucha
+///
+/// AIDEV-NOTE: Se guarda en AudioConfig y, si la pre-escucha está activa,
+/// se recrea su output en el nuevo dispositivo.
+#[tauri::command]
+pub fn set_preview_device(
+    device_name: Option<String>,
+    preview_state: State<'_, PreviewPlayerState>,
+) -> Result<(), String> {
+    log::info!("🎧 set_preview_device: {:?}", device_name);
+
+    let mut config = AppConfig::load();
+    config.audio.preview_device = device_name.clone();
+    config.save()?;
+
+    if preview_state.is_active() {
+        preview_state.send(PlayerControlEvent::ChangeAudioDevice { device_name })?;
+    }
+    Ok(())
 }
 
 // ============================================================================
@@ -314,17 +1110,20 @@ pub fn get_playback_state(
     })
 }
 
-/// Obtiene la posición actual de reproducción
-/// AIDEV-NOTE: Deprecado - usar eventos `audio:timestamp` en su lugar
+/// Obtiene la posición audible de reproducción
+///
+/// AIDEV-NOTE: Misma posición que `audio:timestamp` (compensada por el ring buffer
+/// y la latencia del dispositivo), útil para consultas puntuales entre eventos.
 #[tauri::command]
 pub fn get_playback_position(
-    _player_state: State<'_, AudioPlayerState>,
+    player_state: State<'_, AudioPlayerState>,
 ) -> Result<PlaybackPositionResponse, String> {
-    // No podemos obtener la posición sin referencia al player
-    // Usar eventos en su lugar
+    let handle = player_state.position.lock().unwrap().clone();
+    let (current_position, duration) =
+        handle.map_or((0.0, 0.0), |handle| (handle.position(), handle.duration()));
     Ok(PlaybackPositionResponse {
-        current_position: 0.0,
-        duration: 0.0,
+        current_position,
+        duration,
     })
 }
 
@@ -335,7 +1134,78 @@ pub fn get_playback_position(
 /// Lista los dispositivos de audio disponibles
 #[tauri::command]
 pub async fn get_audio_devices() -> Result<Vec<AudioDeviceInfo>, String> {
-    CpalAudioOutput::list_devices().map_err(|e| e.to_string())
+    list_output_devices().map_err(|e| e.to_string())
+}
+
+/// Obtiene la configuración de salida (buffer, sample rate, perfil de latencia)
+///
+/// AIDEV-NOTE: Es lo pedido; lo que el dispositivo acepta realmente se emite en
+/// `audio:output_config` / `preview:output_config` cada vez que se abre el output.
+#[tauri::command]
+pub fn get_output_settings() -> Result<OutputSettings, String> {
+    Ok(OutputSettings::from(&AppConfig::load().audio))
+}
+
+/// Cambia la configuración de salida
+///
+/// AIDEV-NOTE: Se guarda en AudioConfig (settings.json) y los reproductores activos
+/// recrean su output con `SetOutputSettings`, retomando desde la posición audible.
+#[tauri::command]
+pub fn set_output_settings(
+    settings: OutputSettings,
+    player_state: State<'_, AudioPlayerState>,
+    preview_state: State<'_, PreviewPlayerState>,
+) -> Result<OutputSettings, String> {
+    let settings = settings.clamped();
+    log::info!("set_output_settings command: {:?}", settings);
+
+    let mut config = AppConfig::load();
+    config.audio.buffer_size = settings.buffer_size;
+    config.audio.sample_rate = settings.sample_rate;
+    config.audio.latency_profile = settings.profile;
+    config.audio.bit_perfect = settings.bit_perfect;
+    config.save()?;
+
+    send_output_settings(&player_state, &preview_state, settings)?;
+    Ok(settings)
+}
+
+/// Refleja en settings.json un setting `audio.*` guardado con `update_setting`
+///
+/// AIDEV-NOTE: Los reproductores leen la salida de settings.json; si la clave
+/// afecta al output se aplica también en caliente.
+pub(crate) fn sync_output_setting(
+    app_handle: &AppHandle,
+    key: &str,
+    value: &str,
+) -> Result<(), String> {
+    let mut config = AppConfig::load();
+    if !config.audio.apply_setting(key, value) {
+        return Ok(());
+    }
+    config.save()?;
+
+    send_output_settings(
+        &app_handle.state::<AudioPlayerState>(),
+        &app_handle.state::<PreviewPlayerState>(),
+        OutputSettings::from(&config.audio),
+    )
+}
+
+/// Envía la configuración de salida a los reproductores activos
+fn send_output_settings(
+    player_state: &AudioPlayerState,
+    preview_state: &PreviewPlayerState,
+    settings: OutputSettings,
+) -> Result<(), String> {
+    if let Some(tx) = player_state.get_control_tx() {
+        tx.send(PlayerControlEvent::SetOutputSettings { settings })
+            .map_err(|e| format!("Error enviando comando: {}", e))?;
+    }
+    if preview_state.is_active() {
+        preview_state.send(PlayerControlEvent::SetOutputSettings { settings })?;
+    }
+    Ok(())
 }
 
 /// Decodifica metadatos de audio sin reproducir
@@ -474,6 +1344,19 @@ mod tests {
         assert_eq!(response.mime_type, "audio/mpeg");
     }
 
+    #[test]
+    fn test_preview_player_state() {
+        let state = PreviewPlayerState::new();
+        assert!(!state.is_active());
+        assert!(state.send(PlayerControlEvent::Pause).is_err());
+
+        let (tx, rx) = mpsc::channel();
+        state.set_control_tx(tx);
+        assert!(state.is_active());
+        state.send(PlayerControlEvent::Pause).unwrap();
+        assert!(matches!(rx.try_recv(), Ok(PlayerControlEvent::Pause)));
+    }
+
     #[test]
     fn test_audio_player_state() {
         let state = AudioPlayerState::new();
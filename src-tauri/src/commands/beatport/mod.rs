@@ -22,6 +22,7 @@ use std::sync::Arc;
 use tauri::{AppHandle, Emitter, State};
 use tokio::sync::Semaphore;
 
+use crate::audio::key::KeyNotation;
 use crate::commands::library::LibraryState;
 use crate::db::{queries, DbPool};
 use crate::library::beatport::{
@@ -64,8 +65,9 @@ pub async fn fix_tags(
     let client = Arc::new(
         BeatportClient::new().map_err(|e| format!("Error creando cliente Beatport: {}", e))?,
     );
-    let tagger = BeatportTagger::new(client);
     let pool_arc = Arc::new(pool.inner().clone());
+    let tagger =
+        BeatportTagger::new(client).with_key_notation(load_key_notation(pool_arc.clone()).await?);
 
     let mut results: Vec<FixTagsResult> = Vec::with_capacity(total);
 
@@ -504,7 +506,10 @@ pub async fn apply_selected_tags(
     let client = Arc::new(
         BeatportClient::new().map_err(|e| format!("Error creando cliente Beatport: {}", e))?,
     );
-    let tagger = Arc::new(BeatportTagger::new(client.clone()));
+    let tagger = Arc::new(
+        BeatportTagger::new(client.clone())
+            .with_key_notation(load_key_notation(pool_arc.clone()).await?),
+    );
 
     // OPTIMIZACIÓN 2: Concurrencia controlada (3 concurrent para API v4)
     let config = ConcurrencyConfig::for_api();
@@ -624,6 +629,16 @@ pub async fn apply_selected_tags(
 // FUNCIONES AUXILIARES
 // ============================================================================
 
+/// Lee la notación de tonalidad preferida para los tags que se escriben
+async fn load_key_notation(pool: Arc<DbPool>) -> Result<KeyNotation, String> {
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        queries::get_key_notation(&conn).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
 /// Actualiza el track en la base de datos con los nuevos tags
 async fn update_track_in_db(
     pool: Arc<DbPool>,
@@ -660,10 +675,16 @@ async fn update_track_in_db(
             params.push(Box::new(bpm));
         }
 
-        // Key: Siempre se actualiza si tiene valor
+        // Key: Siempre se actualiza si tiene valor (en la notación preferida)
         if let Some(ref key) = tags.key {
+            let (key, canonical) = queries::normalize_track_key(&conn, Some(key))
+                .map_err(|e| format!("Error normalizando tonalidad: {}", e))?;
             updates.push("key = ?".to_string());
-            params.push(Box::new(key.clone()));
+            params.push(Box::new(key));
+            updates.push("key_canonical = ?".to_string());
+            params.push(Box::new(canonical));
+            // Viene de los tags (Beatport): el análisis ya no la sustituye
+            updates.push("key_source = NULL".to_string());
         }
 
         // Genre: Siempre se actualiza si tiene valor (corrige géneros)
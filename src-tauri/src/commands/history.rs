@@ -0,0 +1,103 @@
+//! Comandos Tauri para el historial de reproducción
+//!
+//! AIDEV-NOTE: Las escuchas las registra el decode thread del reproductor
+//! principal (ver `audio::player::history`); aquí se consultan y se configura
+//! el umbral que decide cuándo cuenta una escucha.
+
+use tauri::State;
+
+use crate::audio::{PlayThreshold, PlayerControlEvent};
+use crate::commands::audio::AudioPlayerState;
+use crate::config::AppConfig;
+use crate::db::models::{PlayHistoryEntry, TrackPlayStats};
+use crate::db::queries;
+use crate::db::DbPool;
+
+/// Límite por defecto de las listas de historial
+const DEFAULT_HISTORY_LIMIT: usize = 50;
+
+/// Últimas escuchas, de la más reciente a la más antigua
+#[tauri::command]
+pub async fn get_recently_played(
+    limit: Option<usize>,
+    pool: State<'_, DbPool>,
+) -> Result<Vec<PlayHistoryEntry>, String> {
+    let pool = pool.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        queries::get_recently_played(&conn, limit.unwrap_or(DEFAULT_HISTORY_LIMIT))
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Tracks más escuchados entre `from` y `to` (días `YYYY-MM-DD`, ambos incluidos)
+#[tauri::command]
+pub async fn get_most_played(
+    from: Option<String>,
+    to: Option<String>,
+    limit: Option<usize>,
+    pool: State<'_, DbPool>,
+) -> Result<Vec<TrackPlayStats>, String> {
+    let pool = pool.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        queries::get_most_played(
+            &conn,
+            from.as_deref(),
+            to.as_deref(),
+            limit.unwrap_or(DEFAULT_HISTORY_LIMIT),
+        )
+        .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Historial completo de un track
+#[tauri::command]
+pub async fn get_track_history(
+    track_id: String,
+    pool: State<'_, DbPool>,
+) -> Result<Vec<PlayHistoryEntry>, String> {
+    let pool = pool.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        queries::get_track_history(&conn, &track_id).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Obtiene el umbral para contar una reproducción
+#[tauri::command]
+pub fn get_play_threshold() -> Result<PlayThreshold, String> {
+    Ok(PlayThreshold::from(&AppConfig::load().audio))
+}
+
+/// Cambia el umbral para contar una reproducción (porcentaje o segundos, lo que llegue antes)
+///
+/// AIDEV-NOTE: Se guarda en AudioConfig (settings.json) y se aplica en caliente al
+/// track que está sonando con `SetPlayThreshold`.
+#[tauri::command]
+pub fn set_play_threshold(
+    percent: f64,
+    seconds: f64,
+    player_state: State<'_, AudioPlayerState>,
+) -> Result<PlayThreshold, String> {
+    let threshold = PlayThreshold { percent, seconds }.clamped();
+    log::info!("set_play_threshold command: {:?}", threshold);
+
+    let mut config = AppConfig::load();
+    config.audio.play_count_percent = threshold.percent;
+    config.audio.play_count_seconds = threshold.seconds;
+    config.save()?;
+
+    if let Some(tx) = player_state.get_control_tx() {
+        tx.send(PlayerControlEvent::SetPlayThreshold { threshold })
+            .map_err(|e| format!("Error enviando comando: {}", e))?;
+    }
+
+    Ok(threshold)
+}
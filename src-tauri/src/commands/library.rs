@@ -280,9 +280,12 @@ pub async fn update_track_metadata(
                 Some(b) => Some(b),
                 None => track.bpm,
             },
+            // La tonalidad se escribe en la notación preferida, igual que en DB
             key: match &request.key {
                 Some(s) if s.is_empty() => None,
-                Some(s) => Some(s.clone()),
+                Some(s) => queries::normalize_track_key(&conn, Some(s))
+                    .map_err(|e| e.to_string())?
+                    .0,
                 None => track.key.clone(),
             },
             rating: match request.rating {
@@ -301,6 +304,7 @@ pub async fn update_track_metadata(
                 .unwrap_or("unknown")
                 .to_lowercase(),
             artwork: None,
+            replay_gain: None,
         };
 
         // Escribir tags al archivo físico
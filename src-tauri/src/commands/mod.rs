@@ -2,6 +2,8 @@ pub mod analysis;
 pub mod audio;
 pub mod beatport;
 pub mod conversion;
+pub mod history;
 pub mod library;
 pub mod playlists;
+pub mod queue;
 pub mod settings;
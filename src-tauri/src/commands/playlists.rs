@@ -339,6 +339,8 @@ mod tests {
             label: None,
             isrc: None,
             beatport_id: None,
+            key_canonical: None,
+            key_index: None,
         };
 
         let track_id = insert_track(&db.conn, &track).unwrap();
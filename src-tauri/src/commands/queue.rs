@@ -0,0 +1,304 @@
+//! Comandos Tauri para la cola de reproducción
+//!
+//! AIDEV-NOTE: La cola vive en `AudioPlayerState` y la comparte el decode thread,
+//! que la usa para precargar y avanzar al siguiente track. Cada mutación se persiste
+//! en SQLite (DbPool + spawn_blocking) y se notifica al decode thread con
+//! `QueueUpdated` para que descarte la precarga obsoleta.
+
+use std::collections::HashMap;
+
+use tauri::{AppHandle, State};
+
+use crate::audio::{PlayerControlEvent, QueueSnapshot, RepeatMode};
+use crate::commands::audio::{start_playback, AudioPlayerState};
+use crate::db::models::{QueueItem, SavedQueue};
+use crate::db::queries;
+use crate::db::DbPool;
+
+/// Obtiene el estado actual de la cola
+#[tauri::command]
+pub fn get_queue(player_state: State<'_, AudioPlayerState>) -> Result<QueueSnapshot, String> {
+    Ok(player_state.queue.lock().unwrap().snapshot())
+}
+
+/// Reemplaza la cola con los tracks indicados
+///
+/// Si se pasa `start_index`, empieza a reproducir ese track.
+#[tauri::command]
+pub async fn set_queue(
+    track_ids: Vec<String>,
+    start_index: Option<usize>,
+    app_handle: AppHandle,
+    player_state: State<'_, AudioPlayerState>,
+    pool: State<'_, DbPool>,
+) -> Result<QueueSnapshot, String> {
+    log::info!(
+        "📋 set_queue: {} tracks, start={:?}",
+        track_ids.len(),
+        start_index
+    );
+
+    let items = load_queue_items(&pool, track_ids).await?;
+    let start_item = player_state
+        .queue
+        .lock()
+        .unwrap()
+        .replace(items, start_index);
+
+    play_or_notify(start_item, app_handle, &player_state)?;
+    persist_and_snapshot(&pool, &player_state).await
+}
+
+/// Reproduce una playlist desde la posición indicada
+#[tauri::command]
+pub async fn play_playlist(
+    playlist_id: String,
+    start_index: usize,
+    app_handle: AppHandle,
+    player_state: State<'_, AudioPlayerState>,
+    pool: State<'_, DbPool>,
+) -> Result<QueueSnapshot, String> {
+    log::info!("📋 play_playlist: {} desde {}", playlist_id, start_index);
+
+    let pool_clone = pool.inner().clone();
+    let tracks = tokio::task::spawn_blocking(move || {
+        let conn = pool_clone.get().map_err(|e| e.to_string())?;
+        queries::get_playlist_tracks(&conn, &playlist_id).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    if start_index >= tracks.len() {
+        return Err(format!(
+            "Índice {} fuera de rango (playlist con {} tracks)",
+            start_index,
+            tracks.len()
+        ));
+    }
+    if tracks[start_index].id.is_none() {
+        return Err(format!(
+            "El track {} de la playlist no tiene id",
+            start_index
+        ));
+    }
+
+    // AIDEV-NOTE: Los tracks sin id no entran en la cola; el índice de inicio
+    // se cuenta en la playlist y hay que trasladarlo a la cola filtrada
+    let queue_start = tracks[..start_index]
+        .iter()
+        .filter(|track| track.id.is_some())
+        .count();
+    let items = tracks
+        .into_iter()
+        .filter_map(|track| {
+            track.id.map(|track_id| QueueItem {
+                track_id,
+                path: track.path,
+            })
+        })
+        .collect();
+
+    let start_item = player_state
+        .queue
+        .lock()
+        .unwrap()
+        .replace(items, Some(queue_start));
+
+    play_or_notify(start_item, app_handle, &player_state)?;
+    persist_and_snapshot(&pool, &player_state).await
+}
+
+/// Añade tracks al final de la cola
+#[tauri::command]
+pub async fn add_to_queue(
+    track_ids: Vec<String>,
+    player_state: State<'_, AudioPlayerState>,
+    pool: State<'_, DbPool>,
+) -> Result<QueueSnapshot, String> {
+    let items = load_queue_items(&pool, track_ids).await?;
+    player_state.queue.lock().unwrap().append(items);
+
+    notify_queue_updated(&player_state);
+    persist_and_snapshot(&pool, &player_state).await
+}
+
+/// Elimina un track de la cola (índice en el orden original)
+#[tauri::command]
+pub async fn remove_from_queue(
+    index: usize,
+    player_state: State<'_, AudioPlayerState>,
+    pool: State<'_, DbPool>,
+) -> Result<QueueSnapshot, String> {
+    let removed = player_state.queue.lock().unwrap().remove(index);
+    if removed.is_none() {
+        return Err(format!("Índice {} fuera de rango", index));
+    }
+
+    notify_queue_updated(&player_state);
+    persist_and_snapshot(&pool, &player_state).await
+}
+
+/// Vacía la cola
+#[tauri::command]
+pub async fn clear_queue(
+    player_state: State<'_, AudioPlayerState>,
+    pool: State<'_, DbPool>,
+) -> Result<QueueSnapshot, String> {
+    player_state.queue.lock().unwrap().clear();
+
+    notify_queue_updated(&player_state);
+    persist_and_snapshot(&pool, &player_state).await
+}
+
+/// Reproduce un track concreto de la cola (índice en el orden original)
+#[tauri::command]
+pub async fn play_queue_index(
+    index: usize,
+    app_handle: AppHandle,
+    player_state: State<'_, AudioPlayerState>,
+    pool: State<'_, DbPool>,
+) -> Result<QueueSnapshot, String> {
+    let item = player_state.queue.lock().unwrap().jump_to(index);
+    if item.is_none() {
+        return Err(format!("Índice {} fuera de rango", index));
+    }
+
+    play_or_notify(item, app_handle, &player_state)?;
+    persist_and_snapshot(&pool, &player_state).await
+}
+
+/// Salta al siguiente track de la cola
+#[tauri::command]
+pub async fn queue_next(
+    app_handle: AppHandle,
+    player_state: State<'_, AudioPlayerState>,
+    pool: State<'_, DbPool>,
+) -> Result<QueueSnapshot, String> {
+    let item = {
+        let mut queue = player_state.queue.lock().unwrap();
+        queue.set_active(true);
+        queue.advance(false)
+    };
+    if item.is_none() {
+        return Err("No hay más tracks en la cola".to_string());
+    }
+
+    play_or_notify(item, app_handle, &player_state)?;
+    persist_and_snapshot(&pool, &player_state).await
+}
+
+/// Vuelve al track anterior de la cola
+#[tauri::command]
+pub async fn queue_previous(
+    app_handle: AppHandle,
+    player_state: State<'_, AudioPlayerState>,
+    pool: State<'_, DbPool>,
+) -> Result<QueueSnapshot, String> {
+    let item = player_state.queue.lock().unwrap().previous();
+    if item.is_none() {
+        return Err("La cola está vacía".to_string());
+    }
+
+    play_or_notify(item, app_handle, &player_state)?;
+    persist_and_snapshot(&pool, &player_state).await
+}
+
+/// Activa/desactiva el modo shuffle
+#[tauri::command]
+pub async fn set_queue_shuffle(
+    enabled: bool,
+    player_state: State<'_, AudioPlayerState>,
+    pool: State<'_, DbPool>,
+) -> Result<QueueSnapshot, String> {
+    player_state.queue.lock().unwrap().set_shuffle(enabled);
+
+    notify_queue_updated(&player_state);
+    persist_and_snapshot(&pool, &player_state).await
+}
+
+/// Cambia el modo de repetición ("off", "one", "all")
+#[tauri::command]
+pub async fn set_queue_repeat(
+    mode: RepeatMode,
+    player_state: State<'_, AudioPlayerState>,
+    pool: State<'_, DbPool>,
+) -> Result<QueueSnapshot, String> {
+    player_state.queue.lock().unwrap().set_repeat(mode);
+
+    notify_queue_updated(&player_state);
+    persist_and_snapshot(&pool, &player_state).await
+}
+
+// ============================================================================
+// HELPERS
+// ============================================================================
+
+/// Obtiene path de cada track conservando el orden pedido
+async fn load_queue_items(pool: &DbPool, track_ids: Vec<String>) -> Result<Vec<QueueItem>, String> {
+    let pool = pool.clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        let tracks = queries::get_tracks_batch(&conn, &track_ids).map_err(|e| e.to_string())?;
+
+        // get_tracks_batch no garantiza el orden
+        let paths: HashMap<String, String> = tracks
+            .into_iter()
+            .filter_map(|track| track.id.map(|id| (id, track.path)))
+            .collect();
+
+        Ok(track_ids
+            .into_iter()
+            .filter_map(|track_id| {
+                paths.get(&track_id).map(|path| QueueItem {
+                    path: path.clone(),
+                    track_id,
+                })
+            })
+            .collect())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Reproduce el item si existe; si no, solo avisa al decode thread del cambio
+fn play_or_notify(
+    item: Option<QueueItem>,
+    app_handle: AppHandle,
+    player_state: &AudioPlayerState,
+) -> Result<(), String> {
+    match item {
+        Some(item) => start_playback(&item.path, app_handle, player_state),
+        None => {
+            notify_queue_updated(player_state);
+            Ok(())
+        }
+    }
+}
+
+/// Avisa al decode thread (si existe) de que la cola cambió
+fn notify_queue_updated(player_state: &AudioPlayerState) {
+    if let Some(tx) = player_state.get_control_tx() {
+        let _ = tx.send(PlayerControlEvent::QueueUpdated);
+    }
+}
+
+/// Guarda la cola en SQLite y retorna el estado para el frontend
+async fn persist_and_snapshot(
+    pool: &DbPool,
+    player_state: &AudioPlayerState,
+) -> Result<QueueSnapshot, String> {
+    let (saved, snapshot): (SavedQueue, QueueSnapshot) = {
+        let queue = player_state.queue.lock().unwrap();
+        (queue.to_saved(), queue.snapshot())
+    };
+
+    let pool = pool.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut conn = pool.get().map_err(|e| e.to_string())?;
+        queries::save_queue(&mut conn, &saved).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    Ok(snapshot)
+}
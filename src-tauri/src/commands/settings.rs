@@ -2,10 +2,12 @@
 //!
 //! AIDEV-NOTE: Migrado a DbPool + spawn_blocking para evitar bloquear el runtime de Tokio.
 
+use crate::audio::key::KeyNotation;
+use crate::commands::audio::sync_output_setting;
 use crate::db::models::Setting;
-use crate::db::queries::settings;
+use crate::db::queries::{self, settings};
 use crate::db::DbPool;
-use tauri::State;
+use tauri::{AppHandle, State};
 
 /// Obtiene el valor de una configuración específica
 #[tauri::command]
@@ -35,20 +37,36 @@ pub async fn get_all_settings(pool: State<'_, DbPool>) -> Result<Vec<Setting>, S
 }
 
 /// Actualiza o crea una configuración
+///
+/// AIDEV-NOTE: Los settings de salida (`audio.buffer_size`, `audio.sample_rate`,
+/// `audio.latency_profile`, `audio.bit_perfect`) se copian a settings.json, que
+/// es lo que leen los reproductores al abrir el output. `library.key_notation`
+/// pasa por `set_key_notation` para reescribir las tonalidades de las pistas.
 #[tauri::command]
 pub async fn update_setting(
+    app_handle: AppHandle,
     pool: State<'_, DbPool>,
     key: String,
     value: String,
     value_type: String,
 ) -> Result<(), String> {
     let pool = pool.inner().clone();
+    let (db_key, db_value) = (key.clone(), value.clone());
     tokio::task::spawn_blocking(move || {
         let conn = pool.get().map_err(|e| e.to_string())?;
-        settings::upsert_setting(&conn, &key, &value, &value_type).map_err(|e| e.to_string())
+        if db_key == queries::KEY_NOTATION_SETTING {
+            let notation = KeyNotation::parse(&db_value)
+                .ok_or_else(|| format!("Notación de tonalidad inválida: {}", db_value))?;
+            return queries::set_key_notation(&conn, notation)
+                .map(|_| ())
+                .map_err(|e| e.to_string());
+        }
+        settings::upsert_setting(&conn, &db_key, &db_value, &value_type).map_err(|e| e.to_string())
     })
     .await
-    .map_err(|e| format!("Task join error: {}", e))?
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    sync_output_setting(&app_handle, &key, &value)
 }
 
 /// Resetea todas las configuraciones a valores por defecto
@@ -57,7 +75,50 @@ pub async fn reset_settings(pool: State<'_, DbPool>) -> Result<(), String> {
     let pool = pool.inner().clone();
     tokio::task::spawn_blocking(move || {
         let conn = pool.get().map_err(|e| e.to_string())?;
-        settings::reset_all_settings(&conn).map_err(|e| e.to_string())
+        settings::reset_all_settings(&conn).map_err(|e| e.to_string())?;
+        // Las pistas vuelven también a la notación por defecto
+        queries::set_key_notation(&conn, KeyNotation::default())
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Obtiene la notación de tonalidad de la biblioteca (musical, camelot, openkey)
+#[tauri::command]
+pub async fn get_key_notation(pool: State<'_, DbPool>) -> Result<KeyNotation, String> {
+    let pool = pool.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        queries::get_key_notation(&conn).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Cambia la notación de tonalidad y reescribe `key` en todas las pistas
+///
+/// Los tags de los archivos no se tocan: la nueva notación se usa en las
+/// siguientes escrituras de TKEY.
+///
+/// # Returns
+/// Número de pistas actualizadas
+#[tauri::command]
+pub async fn set_key_notation(
+    pool: State<'_, DbPool>,
+    notation: KeyNotation,
+) -> Result<usize, String> {
+    let pool = pool.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        let updated = queries::set_key_notation(&conn, notation).map_err(|e| e.to_string())?;
+        log::info!(
+            "🎹 Notación de tonalidad: {} ({} pistas actualizadas)",
+            notation.as_str(),
+            updated
+        );
+        Ok(updated)
     })
     .await
     .map_err(|e| format!("Task join error: {}", e))?
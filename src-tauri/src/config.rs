@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
+use crate::audio::{CrossfadeCurve, DspSettings, EqPreset, LatencyProfile, TempoRange};
 use crate::utils::get_settings_path;
 
 /// Configuración principal de la aplicación
@@ -82,9 +83,65 @@ pub struct AudioConfig {
     #[serde(default = "default_sample_rate")]
     pub sample_rate: u32,
 
-    /// Tamaño de buffer
+    /// Tamaño de buffer (frames por callback del dispositivo)
     #[serde(default = "default_buffer_size")]
     pub buffer_size: u32,
+
+    /// Perfil de latencia: "low", "normal", "safe"
+    #[serde(default)]
+    pub latency_profile: LatencyProfile,
+
+    /// Abrir el dispositivo al sample rate del archivo si lo soporta (sin resampling)
+    #[serde(default = "default_bit_perfect")]
+    pub bit_perfect: bool,
+
+    /// Crossfade entre tracks consecutivos habilitado
+    #[serde(default)]
+    pub crossfade_enabled: bool,
+
+    /// Duración del crossfade (en segundos)
+    #[serde(default = "default_crossfade_seconds")]
+    pub crossfade_seconds: f64,
+
+    /// Curva del crossfade: "linear", "equalPower", "sCurve"
+    #[serde(default)]
+    pub crossfade_curve: CrossfadeCurve,
+
+    /// Rango del pitch fader: "8", "16", "50" (%)
+    #[serde(default)]
+    pub tempo_range: TempoRange,
+
+    /// Mantener el tono al cambiar el tempo
+    #[serde(default)]
+    pub key_lock: bool,
+
+    /// Dispositivo de pre-escucha (auriculares). `None` = dispositivo por defecto
+    #[serde(default)]
+    pub preview_device: Option<String>,
+
+    /// Normalizar el loudness de cada track durante la reproducción
+    #[serde(default)]
+    pub normalization_enabled: bool,
+
+    /// Loudness objetivo de la normalización (LUFS)
+    #[serde(default = "default_normalization_target_lufs")]
+    pub normalization_target_lufs: f64,
+
+    /// Cadena DSP del reproductor principal (preamp, EQ, limitador)
+    #[serde(default)]
+    pub dsp: DspSettings,
+
+    /// Presets de EQ guardados por el usuario (los de fábrica no se persisten)
+    #[serde(default)]
+    pub eq_presets: Vec<EqPreset>,
+
+    /// Porcentaje del track a escuchar para contar una reproducción
+    #[serde(default = "default_play_count_percent")]
+    pub play_count_percent: f64,
+
+    /// Segundos escuchados que cuentan como reproducción aunque no se llegue al porcentaje
+    #[serde(default = "default_play_count_seconds")]
+    pub play_count_seconds: f64,
 }
 
 impl Default for AudioConfig {
@@ -93,6 +150,20 @@ impl Default for AudioConfig {
             output_device: default_output_device(),
             sample_rate: default_sample_rate(),
             buffer_size: default_buffer_size(),
+            latency_profile: LatencyProfile::default(),
+            bit_perfect: default_bit_perfect(),
+            crossfade_enabled: false,
+            crossfade_seconds: default_crossfade_seconds(),
+            crossfade_curve: CrossfadeCurve::default(),
+            tempo_range: TempoRange::default(),
+            key_lock: false,
+            preview_device: None,
+            normalization_enabled: false,
+            normalization_target_lufs: default_normalization_target_lufs(),
+            dsp: DspSettings::default(),
+            eq_presets: Vec::new(),
+            play_count_percent: default_play_count_percent(),
+            play_count_seconds: default_play_count_seconds(),
         }
     }
 }
@@ -109,6 +180,26 @@ fn default_buffer_size() -> u32 {
     2048
 }
 
+fn default_bit_perfect() -> bool {
+    true
+}
+
+fn default_crossfade_seconds() -> f64 {
+    6.0
+}
+
+fn default_normalization_target_lufs() -> f64 {
+    -14.0
+}
+
+fn default_play_count_percent() -> f64 {
+    50.0
+}
+
+fn default_play_count_seconds() -> f64 {
+    240.0
+}
+
 /// Configuración de conversión de audio
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -154,6 +245,26 @@ fn default_preserve_structure() -> bool {
     true
 }
 
+impl AudioConfig {
+    /// Aplica un setting de la DB (`audio.*`) a la configuración
+    ///
+    /// AIDEV-NOTE: La tabla `settings` y settings.json guardan algunos valores
+    /// por duplicado; la salida de audio lee settings.json, así que los cambios
+    /// hechos con `update_setting` se reflejan aquí. Retorna `false` si la clave
+    /// no corresponde a la salida o el valor no es válido.
+    pub fn apply_setting(&mut self, key: &str, value: &str) -> bool {
+        match key {
+            "audio.sample_rate" => value.parse().map(|v| self.sample_rate = v).is_ok(),
+            "audio.buffer_size" => value.parse().map(|v| self.buffer_size = v).is_ok(),
+            "audio.bit_perfect" => value.parse().map(|v| self.bit_perfect = v).is_ok(),
+            "audio.latency_profile" => LatencyProfile::parse(value)
+                .map(|v| self.latency_profile = v)
+                .is_some(),
+            _ => false,
+        }
+    }
+}
+
 impl AppConfig {
     /// Carga la configuración desde el archivo settings.json
     ///
@@ -311,4 +422,101 @@ mod tests {
         assert_eq!(config.ui.theme, "dark");
         assert_eq!(config.audio.sample_rate, 44100);
     }
+
+    #[test]
+    fn test_crossfade_config() {
+        let config = AppConfig::default();
+        assert!(!config.audio.crossfade_enabled);
+        assert_eq!(config.audio.crossfade_seconds, 6.0);
+        assert_eq!(config.audio.crossfade_curve, CrossfadeCurve::EqualPower);
+
+        let json = r#"{"audio": {"crossfadeEnabled": true, "crossfadeCurve": "sCurve"}}"#;
+        let config: AppConfig = serde_json::from_str(json).unwrap();
+        assert!(config.audio.crossfade_enabled);
+        assert_eq!(config.audio.crossfade_seconds, 6.0);
+        assert_eq!(config.audio.crossfade_curve, CrossfadeCurve::SCurve);
+    }
+
+    #[test]
+    fn test_tempo_config() {
+        let config = AppConfig::default();
+        assert_eq!(config.audio.tempo_range, TempoRange::Eight);
+        assert!(!config.audio.key_lock);
+
+        let json = r#"{"audio": {"tempoRange": "16", "keyLock": true}}"#;
+        let config: AppConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.audio.tempo_range, TempoRange::Sixteen);
+        assert!(config.audio.key_lock);
+    }
+
+    #[test]
+    fn test_preview_device_config() {
+        assert_eq!(AppConfig::default().audio.preview_device, None);
+
+        let json = r#"{"audio": {"previewDevice": "USB Headphones"}}"#;
+        let config: AppConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            config.audio.preview_device.as_deref(),
+            Some("USB Headphones")
+        );
+    }
+
+    #[test]
+    fn test_normalization_config() {
+        let config = AppConfig::default();
+        assert!(!config.audio.normalization_enabled);
+        assert_eq!(config.audio.normalization_target_lufs, -14.0);
+
+        let json = r#"{"audio": {"normalizationEnabled": true, "normalizationTargetLufs": -18.0}}"#;
+        let config: AppConfig = serde_json::from_str(json).unwrap();
+        assert!(config.audio.normalization_enabled);
+        assert_eq!(config.audio.normalization_target_lufs, -18.0);
+    }
+
+    #[test]
+    fn test_dsp_config() {
+        let config = AppConfig::default();
+        assert!(!config.audio.dsp.enabled);
+        assert_eq!(config.audio.dsp.bands.len(), 10);
+        assert!(config.audio.eq_presets.is_empty());
+
+        let json = r#"{"audio": {"dsp": {"enabled": true, "preampDb": -3.0}}}"#;
+        let config: AppConfig = serde_json::from_str(json).unwrap();
+        assert!(config.audio.dsp.enabled);
+        assert_eq!(config.audio.dsp.preamp_db, -3.0);
+        assert!(config.audio.dsp.limiter_enabled);
+        assert_eq!(config.audio.dsp.bands.len(), 10);
+    }
+
+    #[test]
+    fn test_output_config() {
+        let config = AppConfig::default();
+        assert_eq!(config.audio.latency_profile, LatencyProfile::Normal);
+        assert!(config.audio.bit_perfect);
+
+        let json = r#"{"audio": {"latencyProfile": "safe", "bitPerfect": false}}"#;
+        let mut config: AppConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.audio.latency_profile, LatencyProfile::Safe);
+        assert!(!config.audio.bit_perfect);
+
+        assert!(config.audio.apply_setting("audio.buffer_size", "512"));
+        assert!(config.audio.apply_setting("audio.latency_profile", "low"));
+        assert!(!config.audio.apply_setting("audio.sample_rate", "rápido"));
+        assert!(!config.audio.apply_setting("ui.theme", "dark"));
+        assert_eq!(config.audio.buffer_size, 512);
+        assert_eq!(config.audio.latency_profile, LatencyProfile::Low);
+        assert_eq!(config.audio.sample_rate, 44100);
+    }
+
+    #[test]
+    fn test_play_count_config() {
+        let config = AppConfig::default();
+        assert_eq!(config.audio.play_count_percent, 50.0);
+        assert_eq!(config.audio.play_count_seconds, 240.0);
+
+        let json = r#"{"audio": {"playCountPercent": 75.0}}"#;
+        let config: AppConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.audio.play_count_percent, 75.0);
+        assert_eq!(config.audio.play_count_seconds, 240.0);
+    }
 }
@@ -4,7 +4,7 @@ mod runner;
  *
  * ## Estructura
  *
- * - **schema.rs**: Definiciones de esquema SQL (5 migraciones)
+ * - **schema.rs**: Definiciones de esquema SQL (17 migraciones)
  * - **runner.rs**: Ejecución de migraciones y control de versiones
  *
  * ## Versiones
@@ -14,6 +14,18 @@ mod runner;
  * - v3: Migración a UUIDs (TEXT en lugar de INTEGER)
  * - v4: Campos Beatport (label, isrc)
  * - v5: Campo beatport_id para tracking
+ * - v6: Cola de reproducción persistente (playback_queue)
+ * - v7: Análisis de loudness EBU R128 (loudness_analysis)
+ * - v8: Historial de reproducción (play_history)
+ * - v9: Formato y versión en la caché de waveforms (mono / bands)
+ * - v10: Caché de waveforms en binario cuantizado (JSON → u8/u16)
+ * - v11: Cola persistente de análisis en background (analysis_jobs)
+ * - v12: Tonalidad detectada por análisis (key_analysis)
+ * - v13: Tonalidad canónica en tracks (key_canonical)
+ * - v14: Beatgrids dinámicos con cambios de tempo (beatgrids.anchors)
+ * - v15: Downbeats y frases (downbeats, phrases)
+ * - v16: Origen de los cue points (cue_points.source)
+ * - v17: Origen de la tonalidad en tracks (key_source: tags / usuario / análisis)
  *
  * ## Uso
  *
@@ -8,9 +8,9 @@ use super::schema;
 use rusqlite::{Connection, Result};
 
 /// Versión actual del esquema
-/// AIDEV-NOTE: Versión 5 añade columna beatport_id para tracking de pistas fixeadas
+/// AIDEV-NOTE: Versión 17 añade el origen (tags/user/analysis) de la tonalidad
 #[allow(dead_code)]
-const CURRENT_VERSION: i32 = 5;
+const CURRENT_VERSION: i32 = 17;
 
 /// Ejecuta todas las migraciones pendientes
 pub fn run_migrations(conn: &Connection) -> Result<()> {
@@ -50,6 +50,66 @@ pub fn run_migrations(conn: &Connection) -> Result<()> {
         update_version(conn, 5)?;
     }
 
+    if current_version < 6 {
+        schema::migration_006_playback_queue(conn)?;
+        update_version(conn, 6)?;
+    }
+
+    if current_version < 7 {
+        schema::migration_007_loudness_analysis(conn)?;
+        update_version(conn, 7)?;
+    }
+
+    if current_version < 8 {
+        schema::migration_008_play_history(conn)?;
+        update_version(conn, 8)?;
+    }
+
+    if current_version < 9 {
+        schema::migration_009_waveform_format(conn)?;
+        update_version(conn, 9)?;
+    }
+
+    if current_version < 10 {
+        schema::migration_010_binary_waveforms(conn)?;
+        update_version(conn, 10)?;
+    }
+
+    if current_version < 11 {
+        schema::migration_011_analysis_jobs(conn)?;
+        update_version(conn, 11)?;
+    }
+
+    if current_version < 12 {
+        schema::migration_012_key_analysis(conn)?;
+        update_version(conn, 12)?;
+    }
+
+    if current_version < 13 {
+        schema::migration_013_key_canonical(conn)?;
+        update_version(conn, 13)?;
+    }
+
+    if current_version < 14 {
+        schema::migration_014_beatgrid_anchors(conn)?;
+        update_version(conn, 14)?;
+    }
+
+    if current_version < 15 {
+        schema::migration_015_phrases(conn)?;
+        update_version(conn, 15)?;
+    }
+
+    if current_version < 16 {
+        schema::migration_016_cue_source(conn)?;
+        update_version(conn, 16)?;
+    }
+
+    if current_version < 17 {
+        schema::migration_017_key_source(conn)?;
+        update_version(conn, 17)?;
+    }
+
     Ok(())
 }
 
@@ -78,7 +138,9 @@ fn update_version(conn: &Connection, version: i32) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::audio::key::MusicalKey;
     use crate::db::connection::Database;
+    use rusqlite::params;
 
     #[test]
     fn test_run_migrations() {
@@ -93,7 +155,7 @@ mod tests {
         run_migrations(&db.conn).unwrap();
 
         let version = get_current_version(&db.conn).unwrap();
-        assert_eq!(version, 5);
+        assert_eq!(version, 17);
     }
 
     #[test]
@@ -111,6 +173,14 @@ mod tests {
             "playlists",
             "playlist_tracks",
             "settings",
+            "playback_queue",
+            "playback_queue_state",
+            "loudness_analysis",
+            "play_history",
+            "analysis_jobs",
+            "key_analysis",
+            "downbeats",
+            "phrases",
         ];
 
         for table in tables {
@@ -162,4 +232,229 @@ mod tests {
 
         assert!(result.is_ok(), "Should succeed with valid track_id");
     }
+
+    #[test]
+    fn test_waveform_format_migration_keeps_cache() {
+        let db = Database::new_in_memory().unwrap();
+        db.conn
+            .execute(
+                "CREATE TABLE schema_version (version INTEGER PRIMARY KEY, applied_at TEXT NOT NULL)",
+                [],
+            )
+            .unwrap();
+        schema::migration_001_initial_schema(&db.conn).unwrap();
+        schema::migration_002_update_analysis_tables(&db.conn).unwrap();
+        schema::migration_003_uuid_migration(&db.conn).unwrap();
+        schema::migration_004_beatport_fields(&db.conn).unwrap();
+        schema::migration_005_beatport_id(&db.conn).unwrap();
+        schema::migration_006_playback_queue(&db.conn).unwrap();
+        schema::migration_007_loudness_analysis(&db.conn).unwrap();
+        schema::migration_008_play_history(&db.conn).unwrap();
+        update_version(&db.conn, 8).unwrap();
+
+        db.conn.execute(
+            "INSERT INTO tracks (id, path, title, artist, duration, bitrate, sample_rate, file_size, date_added, date_modified)
+             VALUES ('t1', 'test.mp3', 'Test', 'Artist', 180.0, 320, 44100, 8388608, '2024-01-01', '2024-01-01')",
+            [],
+        ).unwrap();
+        db.conn
+            .execute(
+                "INSERT INTO waveforms (id, track_id, data, resolution, date_generated)
+                 VALUES ('wf1', 't1', CAST('[0.5]' AS BLOB), 5, '2024-01-01')",
+                [],
+            )
+            .unwrap();
+
+        run_migrations(&db.conn).unwrap();
+
+        let (format, version): (String, i32) = db
+            .conn
+            .query_row(
+                "SELECT format, version FROM waveforms WHERE id = 'wf1'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(format, "mono");
+        assert_eq!(version, 1);
+
+        // Otro formato para el mismo track es otra fila; el mismo formato no
+        let insert = |id: &str, format: &str| {
+            db.conn.execute(
+                "INSERT INTO waveforms (id, track_id, format, data, resolution, date_generated)
+                 VALUES (?1, 't1', ?2, X'00', 1, '2024-01-01')",
+                [id, format],
+            )
+        };
+        assert!(insert("wf2", "bands").is_ok());
+        assert!(insert("wf3", "mono").is_err());
+    }
+
+    #[test]
+    fn test_binary_waveform_migration() {
+        let db = Database::new_in_memory().unwrap();
+        db.conn
+            .execute(
+                "CREATE TABLE schema_version (version INTEGER PRIMARY KEY, applied_at TEXT NOT NULL)",
+                [],
+            )
+            .unwrap();
+        run_migrations_until_9(&db.conn);
+
+        db.conn.execute(
+            "INSERT INTO tracks (id, path, title, artist, duration, bitrate, sample_rate, file_size, date_added, date_modified)
+             VALUES ('t1', 'test.mp3', 'Test', 'Artist', 180.0, 320, 44100, 8388608, '2024-01-01', '2024-01-01')",
+            [],
+        ).unwrap();
+        let peaks: Vec<f32> = (0..800).map(|i| (i as f32 * 0.1).sin().abs()).collect();
+        let json = serde_json::to_string(&peaks).unwrap();
+        crate::db::queries::save_waveform(&db.conn, "t1", "mono", 1, json.as_bytes()).unwrap();
+        crate::db::queries::save_waveform(&db.conn, "t1", "bands", 1, b"corrupt").unwrap();
+
+        run_migrations(&db.conn).unwrap();
+
+        let mono = crate::db::queries::get_waveform(&db.conn, "t1", "mono")
+            .unwrap()
+            .unwrap();
+        assert!(mono.data.len() * 4 < json.len());
+        let decoded = crate::audio::waveform::codec::WaveformBlob::decode(&mono.data).unwrap();
+        assert_eq!(decoded.series[0].len(), 800);
+        assert!((decoded.series[0][10] - peaks[10]).abs() < 1e-4);
+        // Lo que no se puede convertir se regenera
+        assert!(crate::db::queries::get_waveform(&db.conn, "t1", "bands")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_convert_legacy_waveforms() {
+        use crate::audio::waveform::codec::{PeakPrecision, WaveformBlob};
+        use crate::audio::waveform::WaveformPyramid;
+
+        // Mientras el codec siga en v1, escribe lo mismo que la migración
+        let blob = schema::convert_legacy_waveform("mono", b"[0.25,1.0]").unwrap();
+        let live = WaveformBlob {
+            duration: 0.0,
+            series: vec![vec![0.25, 1.0]],
+        };
+        assert_eq!(blob, live.encode(PeakPrecision::U16));
+
+        let json = br#"{"peaks":[0.5],"bands":[{"low":1.0,"mid":0.5,"high":0.25}]}"#;
+        let blob = schema::convert_legacy_waveform("bands", json).unwrap();
+        let decoded = WaveformBlob::decode(&blob).unwrap();
+        assert_eq!(decoded.series.len(), 4);
+        assert_eq!(decoded.series[1], vec![1.0]);
+
+        let json = br#"{"duration":10.0,"levels":[[0.5,1.0]]}"#;
+        let pyramid =
+            WaveformPyramid::decode(&schema::convert_legacy_waveform("pyramid", json).unwrap());
+        assert_eq!(pyramid.unwrap().duration, 10.0);
+
+        assert!(schema::convert_legacy_waveform("mono", b"not json").is_none());
+        assert!(schema::convert_legacy_waveform("unknown", b"[]").is_none());
+    }
+
+    #[test]
+    fn test_key_canonical_migration() {
+        let db = Database::new_in_memory().unwrap();
+        db.conn
+            .execute(
+                "CREATE TABLE schema_version (version INTEGER PRIMARY KEY, applied_at TEXT NOT NULL)",
+                [],
+            )
+            .unwrap();
+        run_migrations_until_9(&db.conn);
+        schema::migration_010_binary_waveforms(&db.conn).unwrap();
+        schema::migration_011_analysis_jobs(&db.conn).unwrap();
+        schema::migration_012_key_analysis(&db.conn).unwrap();
+        update_version(&db.conn, 12).unwrap();
+
+        for (id, key) in [("t1", "8A"), ("t2", "A minor"), ("t3", "1d"), ("t4", "???")] {
+            db.conn.execute(
+                "INSERT INTO tracks (id, path, title, artist, duration, bitrate, sample_rate, file_size, date_added, date_modified, key)
+                 VALUES (?1, ?1, 'Test', 'Artist', 180.0, 320, 44100, 8388608, '2024-01-01', '2024-01-01', ?2)",
+                [id, key],
+            ).unwrap();
+        }
+
+        run_migrations(&db.conn).unwrap();
+
+        let key_of = |id: &str| -> (String, Option<String>) {
+            db.conn
+                .query_row(
+                    "SELECT key, key_canonical FROM tracks WHERE id = ?1",
+                    [id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .unwrap()
+        };
+        for id in ["t1", "t2"] {
+            assert_eq!(key_of(id), ("Am".to_string(), Some("8A".to_string())));
+        }
+        assert_eq!(key_of("t3"), ("C".to_string(), Some("8B".to_string())));
+        // Lo que no se reconoce se conserva sin forma canónica
+        assert_eq!(key_of("t4"), ("???".to_string(), None));
+    }
+
+    #[test]
+    fn test_key_source_migration() {
+        let db = Database::new_in_memory().unwrap();
+        db.conn
+            .execute(
+                "CREATE TABLE schema_version (version INTEGER PRIMARY KEY, applied_at TEXT NOT NULL)",
+                [],
+            )
+            .unwrap();
+        run_migrations_until_9(&db.conn);
+        schema::migration_010_binary_waveforms(&db.conn).unwrap();
+        schema::migration_011_analysis_jobs(&db.conn).unwrap();
+        schema::migration_012_key_analysis(&db.conn).unwrap();
+        schema::migration_013_key_canonical(&db.conn).unwrap();
+        schema::migration_014_beatgrid_anchors(&db.conn).unwrap();
+        schema::migration_015_phrases(&db.conn).unwrap();
+        schema::migration_016_cue_source(&db.conn).unwrap();
+        update_version(&db.conn, 16).unwrap();
+
+        for (id, key, analyzed) in [("t1", "Am", "Am"), ("t2", "Fm", "G#m"), ("t3", "", "C")] {
+            db.conn.execute(
+                "INSERT INTO tracks (id, path, title, artist, duration, bitrate, sample_rate, file_size, date_added, date_modified, key, key_canonical)
+                 VALUES (?1, ?1, 'Test', 'Artist', 180.0, 320, 44100, 8388608, '2024-01-01', '2024-01-01', ?2, ?3)",
+                params![id, key, MusicalKey::parse(key).map(|k| k.canonical())],
+            ).unwrap();
+            db.conn
+                .execute(
+                    "INSERT INTO key_analysis (id, track_id, key, confidence, analyzed_at)
+                     VALUES (?1, ?1, ?2, 80.0, '2024-01-01')",
+                    [id, analyzed],
+                )
+                .unwrap();
+        }
+
+        run_migrations(&db.conn).unwrap();
+
+        let source_of = |id: &str| -> Option<String> {
+            db.conn
+                .query_row("SELECT key_source FROM tracks WHERE id = ?1", [id], |row| {
+                    row.get(0)
+                })
+                .unwrap()
+        };
+        assert_eq!(source_of("t1").as_deref(), Some("analysis"));
+        assert_eq!(source_of("t2"), None);
+        assert_eq!(source_of("t3"), None);
+    }
+
+    /// Aplica las migraciones 1-9 (esquema anterior a la caché binaria)
+    fn run_migrations_until_9(conn: &Connection) {
+        schema::migration_001_initial_schema(conn).unwrap();
+        schema::migration_002_update_analysis_tables(conn).unwrap();
+        schema::migration_003_uuid_migration(conn).unwrap();
+        schema::migration_004_beatport_fields(conn).unwrap();
+        schema::migration_005_beatport_id(conn).unwrap();
+        schema::migration_006_playback_queue(conn).unwrap();
+        schema::migration_007_loudness_analysis(conn).unwrap();
+        schema::migration_008_play_history(conn).unwrap();
+        schema::migration_009_waveform_format(conn).unwrap();
+        update_version(conn, 9).unwrap();
+    }
 }
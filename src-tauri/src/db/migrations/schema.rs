@@ -4,7 +4,14 @@
  * Contiene todas las funciones de migración que crean o modifican
  * el esquema de la base de datos.
  */
-use rusqlite::{Connection, Result};
+use std::io::Write;
+
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use rusqlite::{params, Connection, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::audio::key::{normalize_key, KeyNotation, MusicalKey};
 
 /// Migración 001: Esquema inicial
 pub(super) fn migration_001_initial_schema(conn: &Connection) -> Result<()> {
@@ -368,3 +375,443 @@ pub(super) fn migration_005_beatport_id(conn: &Connection) -> Result<()> {
 
     Ok(())
 }
+
+/// Migración 006: Cola de reproducción persistente
+///
+/// `playback_queue` guarda los tracks de la cola (orden original en `item_index`
+/// y orden de reproducción en `play_order`). `playback_queue_state` es una tabla
+/// de una sola fila con la posición actual, shuffle y modo de repetición.
+pub(super) fn migration_006_playback_queue(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS playback_queue (
+            item_index INTEGER PRIMARY KEY,
+            track_id TEXT NOT NULL,
+            play_order INTEGER NOT NULL,
+            FOREIGN KEY (track_id) REFERENCES tracks(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS playback_queue_state (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            current_item INTEGER,
+            shuffle INTEGER NOT NULL DEFAULT 0,
+            repeat_mode TEXT NOT NULL DEFAULT 'off',
+            updated_at TEXT NOT NULL
+        );
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migración 007: Análisis de loudness (EBU R128)
+///
+/// Un registro por track con integrated loudness, true peak y loudness range.
+/// `source` indica si viene del analizador ('analysis') o de tags ReplayGain
+/// importados ('tags'); en ese caso no hay loudness range.
+pub(super) fn migration_007_loudness_analysis(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS loudness_analysis (
+            id TEXT PRIMARY KEY,
+            track_id TEXT NOT NULL UNIQUE,
+            integrated_lufs REAL NOT NULL,
+            true_peak_dbtp REAL NOT NULL,
+            loudness_range REAL,
+            source TEXT NOT NULL DEFAULT 'analysis',
+            analyzed_at TEXT NOT NULL,
+            FOREIGN KEY (track_id) REFERENCES tracks(id) ON DELETE CASCADE
+        );
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migración 008: Historial de reproducción
+///
+/// Una fila por escucha que supera el umbral configurado (`seconds_played` es lo
+/// escuchado de verdad, sin contar seeks). `tracks.play_count` y `last_played`
+/// se actualizan en la misma transacción.
+pub(super) fn migration_008_play_history(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS play_history (
+            id TEXT PRIMARY KEY,
+            track_id TEXT NOT NULL,
+            played_at TEXT NOT NULL,
+            seconds_played REAL NOT NULL,
+            FOREIGN KEY (track_id) REFERENCES tracks(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_play_history_track ON play_history(track_id);
+        CREATE INDEX IF NOT EXISTS idx_play_history_played_at ON play_history(played_at);
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migración 009: Formato y versión en la caché de waveforms
+///
+/// Un track puede tener varias waveforms (una por `format`: 'mono', 'bands').
+/// `version` es la del algoritmo que la generó; al subirla se regenera. SQLite
+/// no permite cambiar el UNIQUE de `track_id`, así que se recrea la tabla y las
+/// filas existentes pasan a ser 'mono' v1.
+pub(super) fn migration_009_waveform_format(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE waveforms_new (
+            id TEXT PRIMARY KEY,
+            track_id TEXT NOT NULL,
+            format TEXT NOT NULL DEFAULT 'mono',
+            version INTEGER NOT NULL DEFAULT 1,
+            data BLOB NOT NULL,
+            resolution INTEGER NOT NULL,
+            date_generated TEXT NOT NULL,
+            UNIQUE (track_id, format),
+            FOREIGN KEY (track_id) REFERENCES tracks(id) ON DELETE CASCADE
+        );
+
+        INSERT INTO waveforms_new (id, track_id, format, version, data, resolution, date_generated)
+        SELECT id, track_id, 'mono', 1, data, resolution, date_generated FROM waveforms;
+
+        DROP TABLE waveforms;
+        ALTER TABLE waveforms_new RENAME TO waveforms;
+
+        CREATE INDEX IF NOT EXISTS idx_waveforms_track ON waveforms(track_id);
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migración 010: Caché de waveforms en binario cuantizado
+/// AIDEV-NOTE: Convierte las filas JSON a la versión 1 del formato de
+/// `audio::waveform::codec`, con su propia copia del encoder (el del codec
+/// puede cambiar; esta migración tiene que escribir siempre lo mismo). Las
+/// que no se pueden leer se borran (se regeneran al pedirlas). El archivo no
+/// encoge hasta un VACUUM (lo hace el GC de la caché).
+pub(super) fn migration_010_binary_waveforms(conn: &Connection) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+
+    let converted = {
+        let mut stmt = tx.prepare("SELECT id, format, data FROM waveforms")?;
+        let mut rows = stmt.query([])?;
+        let mut converted = Vec::new();
+        while let Some(row) = rows.next()? {
+            let id: String = row.get(0)?;
+            let format: String = row.get(1)?;
+            let blob = convert_legacy_waveform(&format, row.get_ref(2)?.as_bytes()?);
+            converted.push((id, blob));
+        }
+        converted
+    };
+
+    for (id, blob) in converted {
+        match blob {
+            Some(blob) => tx.execute(
+                "UPDATE waveforms SET data = ?1, resolution = ?2 WHERE id = ?3",
+                params![blob, blob.len() as i32, id],
+            )?,
+            None => tx.execute("DELETE FROM waveforms WHERE id = ?1", [id])?,
+        };
+    }
+
+    tx.commit()
+}
+
+/// Bandas de una fila JSON de waveform (formato "bands")
+#[derive(Deserialize)]
+struct LegacyBandPeak {
+    low: f32,
+    mid: f32,
+    high: f32,
+}
+
+#[derive(Deserialize)]
+struct LegacyBandsWaveform {
+    peaks: Vec<f32>,
+    #[serde(default)]
+    bands: Vec<LegacyBandPeak>,
+}
+
+/// Pirámide de detalle en JSON (formato "pyramid")
+#[derive(Deserialize)]
+struct LegacyPyramid {
+    duration: f64,
+    levels: Vec<Vec<f32>>,
+}
+
+/// Convierte una fila JSON de `waveforms` a un blob binario v1
+///
+/// `None` si el formato no se conoce o el JSON no se puede leer.
+pub(super) fn convert_legacy_waveform(format: &str, data: &[u8]) -> Option<Vec<u8>> {
+    match format {
+        "mono" => {
+            let peaks: Vec<f32> = serde_json::from_slice(data).ok()?;
+            Some(encode_waveform_blob_v1(
+                0.0,
+                &[peaks],
+                WaveformPrecisionV1::U16,
+            ))
+        }
+        "bands" => {
+            let waveform: LegacyBandsWaveform = serde_json::from_slice(data).ok()?;
+            let band = |f: fn(&LegacyBandPeak) -> f32| waveform.bands.iter().map(f).collect();
+            let series = [
+                waveform.peaks.clone(),
+                band(|b| b.low),
+                band(|b| b.mid),
+                band(|b| b.high),
+            ];
+            Some(encode_waveform_blob_v1(
+                0.0,
+                &series,
+                WaveformPrecisionV1::U16,
+            ))
+        }
+        "pyramid" => {
+            let pyramid: LegacyPyramid = serde_json::from_slice(data).ok()?;
+            Some(encode_waveform_blob_v1(
+                pyramid.duration,
+                &pyramid.levels,
+                WaveformPrecisionV1::U8,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Precisión de un blob v1 (mismo orden de variantes que `codec::PeakPrecision`)
+#[derive(Clone, Copy, Serialize)]
+enum WaveformPrecisionV1 {
+    U8,
+    U16,
+}
+
+#[derive(Serialize)]
+struct WaveformHeaderV1 {
+    version: u8,
+    precision: WaveformPrecisionV1,
+    compressed: bool,
+}
+
+#[derive(Serialize)]
+struct WaveformBodyV1 {
+    duration: f64,
+    series: Vec<Vec<u8>>,
+}
+
+/// Encoder congelado de la versión 1 del blob de waveform
+///
+/// `b"SYWF"`, cabecera bincode y cuerpo bincode con los peaks cuantizados,
+/// en deflate solo si así ocupa menos.
+fn encode_waveform_blob_v1(
+    duration: f64,
+    series: &[Vec<f32>],
+    precision: WaveformPrecisionV1,
+) -> Vec<u8> {
+    let quantize = |peaks: &Vec<f32>| -> Vec<u8> {
+        match precision {
+            WaveformPrecisionV1::U8 => peaks
+                .iter()
+                .map(|&peak| (peak.clamp(0.0, 1.0) * u8::MAX as f32).round() as u8)
+                .collect(),
+            WaveformPrecisionV1::U16 => peaks
+                .iter()
+                .flat_map(|&peak| {
+                    ((peak.clamp(0.0, 1.0) * u16::MAX as f32).round() as u16).to_le_bytes()
+                })
+                .collect(),
+        }
+    };
+    let body = WaveformBodyV1 {
+        duration,
+        series: series.iter().map(quantize).collect(),
+    };
+    let body = bincode::serialize(&body).unwrap_or_default();
+
+    let deflated = {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&body)
+            .ok()
+            .and_then(|_| encoder.finish().ok())
+            .filter(|deflated| deflated.len() < body.len())
+    };
+    let header = WaveformHeaderV1 {
+        version: 1,
+        precision,
+        compressed: deflated.is_some(),
+    };
+
+    let mut blob = b"SYWF".to_vec();
+    blob.extend(bincode::serialize(&header).unwrap_or_default());
+    blob.extend(deflated.unwrap_or(body));
+    blob
+}
+
+/// Migración 011: Cola persistente de análisis en background
+/// AIDEV-NOTE: Un job por track y tipo de análisis. Los terminados se borran
+/// (el resultado vive en su tabla); los fallidos se quedan con el error.
+/// `status`: 'pending' | 'running' | 'failed'.
+pub(super) fn migration_011_analysis_jobs(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS analysis_jobs (
+            id TEXT PRIMARY KEY,
+            track_id TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            priority INTEGER NOT NULL DEFAULT 0,
+            status TEXT NOT NULL DEFAULT 'pending',
+            attempts INTEGER NOT NULL DEFAULT 0,
+            error TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            UNIQUE (track_id, kind),
+            FOREIGN KEY (track_id) REFERENCES tracks(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_analysis_jobs_queue
+            ON analysis_jobs(status, priority DESC, created_at);
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migración 012: Tonalidad detectada por análisis
+/// AIDEV-NOTE: `tracks.key` sigue siendo la tonalidad que se muestra (tags o
+/// Beatport); esta tabla guarda el resultado del detector con su confidence.
+pub(super) fn migration_012_key_analysis(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS key_analysis (
+            id TEXT PRIMARY KEY,
+            track_id TEXT NOT NULL UNIQUE,
+            key TEXT NOT NULL,
+            confidence REAL NOT NULL,
+            analyzed_at TEXT NOT NULL,
+            FOREIGN KEY (track_id) REFERENCES tracks(id) ON DELETE CASCADE
+        );
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migración 013: Tonalidad canónica en tracks
+/// AIDEV-NOTE: `key_canonical` guarda la tonalidad en Camelot ("8A") para
+/// ordenar y filtrar; `key` pasa a estar en la notación preferida (musical
+/// por defecto). Las tonalidades que no se reconocen se dejan tal cual.
+pub(super) fn migration_013_key_canonical(conn: &Connection) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+
+    tx.execute_batch(
+        "
+        ALTER TABLE tracks ADD COLUMN key_canonical TEXT;
+        CREATE INDEX IF NOT EXISTS idx_tracks_key_canonical ON tracks(key_canonical);
+        ",
+    )?;
+
+    let keys: Vec<String> = {
+        let mut stmt =
+            tx.prepare("SELECT DISTINCT key FROM tracks WHERE key IS NOT NULL AND key != ''")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect::<Result<_>>()?
+    };
+
+    for value in keys {
+        if let (key, Some(canonical)) = normalize_key(&value, KeyNotation::default()) {
+            tx.execute(
+                "UPDATE tracks SET key = ?1, key_canonical = ?2 WHERE key = ?3",
+                params![key, canonical, value],
+            )?;
+        }
+    }
+
+    tx.commit()
+}
+
+/// Migración 014: Beatgrids dinámicos
+/// AIDEV-NOTE: `anchors` es un JSON con los cambios de tempo
+/// (`audio::beatgrid::BeatgridAnchor`); NULL = tempo constante (`bpm`/`offset`).
+pub(super) fn migration_014_beatgrid_anchors(conn: &Connection) -> Result<()> {
+    conn.execute_batch("ALTER TABLE beatgrids ADD COLUMN anchors TEXT;")?;
+
+    Ok(())
+}
+
+/// Migración 015: Downbeats y frases
+/// AIDEV-NOTE: `downbeats` guarda la fase del "1" de compás sobre el beatgrid
+/// (índice de beat) y `phrases` las secciones de 8/16/32 compases con su tipo
+/// (intro/verse/chorus/bridge/outro). Se recalculan juntas.
+pub(super) fn migration_015_phrases(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS downbeats (
+            id TEXT PRIMARY KEY,
+            track_id TEXT NOT NULL UNIQUE,
+            beat INTEGER NOT NULL,
+            position REAL NOT NULL,
+            beats_per_bar INTEGER NOT NULL,
+            analyzed_at TEXT NOT NULL,
+            FOREIGN KEY (track_id) REFERENCES tracks(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS phrases (
+            id TEXT PRIMARY KEY,
+            track_id TEXT NOT NULL,
+            start_bar INTEGER NOT NULL,
+            bars INTEGER NOT NULL,
+            start_time REAL NOT NULL,
+            end_time REAL NOT NULL,
+            kind TEXT NOT NULL,
+            energy REAL NOT NULL,
+            FOREIGN KEY (track_id) REFERENCES tracks(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_phrases_track ON phrases(track_id, start_bar);
+        ",
+    )?;
+
+    Ok(())
+}
+
+/// Migración 016: Origen de los cue points
+/// AIDEV-NOTE: `source` distingue los cues del usuario ('user') de los del
+/// análisis automático ('auto'); re-analizar solo sustituye los 'auto'.
+pub(super) fn migration_016_cue_source(conn: &Connection) -> Result<()> {
+    conn.execute_batch("ALTER TABLE cue_points ADD COLUMN source TEXT NOT NULL DEFAULT 'user';")?;
+
+    Ok(())
+}
+
+/// Migración 017: Origen de la tonalidad de las pistas
+/// AIDEV-NOTE: `key_source` es 'analysis' si la puso el detector, 'user' si se
+/// editó a mano y NULL si viene de los tags; el análisis solo pisa las suyas.
+/// Las pistas cuya tonalidad coincide con su análisis guardado se marcan como
+/// 'analysis' (era la regla con la que se sustituían hasta ahora).
+pub(super) fn migration_017_key_source(conn: &Connection) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+
+    tx.execute_batch("ALTER TABLE tracks ADD COLUMN key_source TEXT;")?;
+
+    let analyzed: Vec<(String, String)> = {
+        let mut stmt = tx.prepare("SELECT track_id, key FROM key_analysis")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<_>>()?
+    };
+
+    for (track_id, key) in analyzed {
+        if let Some(key) = MusicalKey::parse(&key) {
+            tx.execute(
+                "UPDATE tracks SET key_source = 'analysis' WHERE id = ?1 AND key_canonical = ?2",
+                params![track_id, key.canonical()],
+            )?;
+        }
+    }
+
+    tx.commit()
+}
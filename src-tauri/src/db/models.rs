@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::audio::beatgrid::BeatgridAnchor;
+
 /// Modelo de pista musical
 /// AIDEV-NOTE: Migrado de i64 a String (UUID v4) para mejor escalabilidad
 /// AIDEV-NOTE: v4 añade campos label e isrc para integración Beatport
@@ -32,20 +34,81 @@ pub struct Track {
     pub isrc: Option<String>,
     /// ID del track en Beatport (se establece cuando se aplica Fix Tags)
     pub beatport_id: Option<i64>,
+    /// Tonalidad en forma canónica (código Camelot, "8A"); None si `key` no se reconoce
+    pub key_canonical: Option<String>,
+    /// Posición en la rueda Camelot (0-23: 1A, 1B, 2A...) para ordenar por tonalidad.
+    /// Se calcula al leer a partir de `key_canonical`; no se guarda
+    #[serde(default)]
+    pub key_index: Option<u8>,
 }
 
 /// Modelo de waveform
-/// AIDEV-NOTE: data almacena peaks como Vec<f32> serializado a bincode
+/// AIDEV-NOTE: data almacena peaks cuantizados en binario (ver `audio::waveform::codec`);
+/// hasta v10 era JSON con floats
+/// AIDEV-NOTE: v9 añade format ('mono' | 'bands' | 'pyramid') y version del algoritmo;
+/// un track puede tener una fila por formato
 #[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Waveform {
     pub id: Option<String>,
     pub track_id: String,
+    pub format: String,
+    pub version: i32,
     pub data: Vec<u8>,
     pub resolution: i32,
     pub date_generated: String,
 }
 
+/// Ocupación de la caché de waveforms para un formato
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WaveformFormatStats {
+    pub format: String,
+    pub count: i64,
+    pub bytes: i64,
+}
+
+/// Ocupación de la caché de waveforms y de la base de datos
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WaveformCacheStats {
+    pub count: i64,
+    /// Bytes de los blobs de waveform
+    pub bytes: i64,
+    pub formats: Vec<WaveformFormatStats>,
+    /// Tamaño del archivo de la base de datos
+    pub database_bytes: i64,
+    /// Páginas libres que solo recupera un VACUUM
+    pub free_bytes: i64,
+}
+
+/// Job de la cola de análisis en background
+/// AIDEV-NOTE: v11 - tabla analysis_jobs; `path` y `duration` vienen de tracks
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysisJob {
+    pub id: String,
+    pub track_id: String,
+    pub path: String,
+    pub duration: f64,
+    /// Tipo de análisis ('beatgrid', 'loudness', 'waveform')
+    pub kind: String,
+    pub priority: i32,
+    /// 'pending' | 'running' | 'failed'
+    pub status: String,
+    pub attempts: i32,
+    pub error: Option<String>,
+}
+
+/// Jobs de la cola de análisis por estado
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysisQueueCounts {
+    pub pending: i64,
+    pub running: i64,
+    pub failed: i64,
+}
+
 /// Modelo de beatgrid
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -56,6 +119,57 @@ pub struct Beatgrid {
     pub offset: f64,             // Offset del primer beat en segundos
     pub confidence: Option<f64>, // Confidence score del análisis (0-100)
     pub analyzed_at: String,
+    pub anchors: Vec<BeatgridAnchor>, // Cambios de tempo (vacío = tempo constante)
+}
+
+/// Modelo de análisis de loudness (EBU R128)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Loudness {
+    pub id: Option<String>,
+    pub track_id: String,
+    pub integrated_lufs: f64,
+    pub true_peak_dbtp: f64,
+    pub loudness_range: Option<f64>, // None si viene de tags ReplayGain
+    pub source: String,              // 'analysis' | 'tags'
+    pub analyzed_at: String,
+}
+
+/// Modelo de tonalidad detectada por análisis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackKey {
+    pub id: Option<String>,
+    pub track_id: String,
+    pub key: String,     // Notación estándar: "Am", "F#", "Bbm"
+    pub confidence: f64, // Confidence score del análisis (0-100)
+    pub analyzed_at: String,
+}
+
+/// Modelo de downbeat (fase del "1" de compás sobre el beatgrid)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Downbeat {
+    pub id: Option<String>,
+    pub track_id: String,
+    pub beat: u32,          // Índice del primer beat que es "1" de compás
+    pub position: f64,      // Posición del primer downbeat en segundos
+    pub beats_per_bar: u32, // Beats por compás (4 en 4/4)
+    pub analyzed_at: String,
+}
+
+/// Modelo de frase (sección de 8/16/32 compases)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Phrase {
+    pub id: Option<String>,
+    pub track_id: String,
+    pub start_bar: u32,
+    pub bars: u32,
+    pub start_time: f64, // Inicio en segundos
+    pub end_time: f64,   // Fin en segundos
+    pub kind: String,    // "intro", "verse", "chorus", "bridge" o "outro"
+    pub energy: f64,     // Energía relativa a la frase más fuerte (0-1)
 }
 
 /// Modelo de cue point
@@ -69,6 +183,7 @@ pub struct CuePoint {
     pub color: String,
     pub cue_type: String,    // intro, outro, drop, break, custom
     pub hotkey: Option<i32>, // 1-8 para hot cues
+    pub source: String,      // user, auto
     pub created_at: String,
 }
 
@@ -108,6 +223,58 @@ pub struct PlaylistTrack {
     pub date_added: String,
 }
 
+/// Elemento de la cola de reproducción
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueItem {
+    pub track_id: String,
+    pub path: String,
+}
+
+/// Estado persistido de la cola de reproducción
+/// AIDEV-NOTE: v6 - tablas playback_queue y playback_queue_state
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedQueue {
+    /// Tracks en el orden en que se añadieron
+    pub items: Vec<QueueItem>,
+    /// Orden de reproducción (índices en `items`, permutado si shuffle)
+    pub order: Vec<usize>,
+    /// Posición actual dentro de `order`
+    pub current: Option<usize>,
+    pub shuffle: bool,
+    pub repeat_mode: String, // off, one, all
+}
+
+/// Escucha registrada en el historial de reproducción
+/// AIDEV-NOTE: v8 - tabla play_history (con título/artista del track para la UI)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayHistoryEntry {
+    pub id: String,
+    pub track_id: String,
+    pub path: String,
+    pub title: String,
+    pub artist: String,
+    pub played_at: String,
+    /// Segundos escuchados (sin contar seeks) hasta superar el umbral
+    pub seconds_played: f64,
+}
+
+/// Track con su número de escuchas en un periodo
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackPlayStats {
+    pub track_id: String,
+    pub path: String,
+    pub title: String,
+    pub artist: String,
+    /// Escuchas dentro del periodo pedido
+    pub plays: i64,
+    /// Última escucha dentro del periodo
+    pub last_played: String,
+}
+
 /// Modelo de configuración
 #[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -145,6 +312,8 @@ mod tests {
             label: None,
             isrc: None,
             beatport_id: None,
+            key_canonical: None,
+            key_index: None,
         };
 
         assert_eq!(track.title, "Test Track");
@@ -1,20 +1,39 @@
 /**
  * CRUD para beatgrids (análisis de tempo y grid)
  */
+use crate::audio::beatgrid::{BeatgridAnchor, TempoMap};
+use crate::audio::beatgrid_detector::BpmRange;
 use crate::db::models::Beatgrid;
+use crate::db::queries::settings::get_setting;
 use rusqlite::{params, Connection, OptionalExtension, Result};
 use uuid::Uuid;
 
+use super::phrases::{delete_phrase_analysis, shift_phrase_analysis};
+
+/// Claves de los settings con el rango de tempo preferido
+pub const BPM_MIN_SETTING: &str = "analysis.bpm_min";
+pub const BPM_MAX_SETTING: &str = "analysis.bpm_max";
+
 /// Inserta o actualiza beatgrid analizado para una pista
+///
+/// `anchors` vacío guarda un grid de tempo constante (`bpm` desde `offset`).
+///
+/// AIDEV-NOTE: El downbeat y las frases se calculan sobre el grid: un grid
+/// nuevo los invalida (se borran en la misma transacción y el job de frases
+/// los vuelve a calcular).
 pub fn upsert_beatgrid(
     conn: &Connection,
     track_id: &str,
     bpm: f64,
     offset: f64,
     confidence: Option<f64>,
+    anchors: &[BeatgridAnchor],
 ) -> Result<String> {
+    let anchors = anchors_to_json(anchors)?;
+    let tx = conn.unchecked_transaction()?;
+
     // Verificar si ya existe
-    let existing: Option<String> = conn
+    let existing: Option<String> = tx
         .query_row(
             "SELECT id FROM beatgrids WHERE track_id = ?1",
             [track_id],
@@ -22,30 +41,35 @@ pub fn upsert_beatgrid(
         )
         .optional()?;
 
-    if let Some(id) = existing {
+    let id = if let Some(id) = existing {
         // Actualizar existente
-        conn.execute(
-            "UPDATE beatgrids SET bpm = ?1, offset = ?2, confidence = ?3, analyzed_at = datetime('now')
-             WHERE id = ?4",
-            params![bpm, offset, confidence, &id],
+        tx.execute(
+            "UPDATE beatgrids SET bpm = ?1, offset = ?2, confidence = ?3, anchors = ?4,
+                 analyzed_at = datetime('now')
+             WHERE id = ?5",
+            params![bpm, offset, confidence, anchors, &id],
         )?;
-        Ok(id)
+        id
     } else {
         // Insertar nuevo
         let id = Uuid::new_v4().to_string();
-        conn.execute(
-            "INSERT INTO beatgrids (id, track_id, bpm, offset, confidence, analyzed_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, datetime('now'))",
-            params![&id, track_id, bpm, offset, confidence],
+        tx.execute(
+            "INSERT INTO beatgrids (id, track_id, bpm, offset, confidence, anchors, analyzed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, datetime('now'))",
+            params![&id, track_id, bpm, offset, confidence, anchors],
         )?;
-        Ok(id)
-    }
+        id
+    };
+
+    delete_phrase_analysis(&tx, track_id)?;
+    tx.commit()?;
+    Ok(id)
 }
 
 /// Obtiene beatgrid de una pista
 pub fn get_beatgrid(conn: &Connection, track_id: &str) -> Result<Option<Beatgrid>> {
     conn.query_row(
-        "SELECT id, track_id, bpm, offset, confidence, analyzed_at
+        "SELECT id, track_id, bpm, offset, confidence, analyzed_at, anchors
          FROM beatgrids
          WHERE track_id = ?1",
         [track_id],
@@ -57,6 +81,7 @@ pub fn get_beatgrid(conn: &Connection, track_id: &str) -> Result<Option<Beatgrid
                 offset: row.get(3)?,
                 confidence: row.get(4)?,
                 analyzed_at: row.get(5)?,
+                anchors: anchors_from_json(row.get(6)?)?,
             })
         },
     )
@@ -64,16 +89,83 @@ pub fn get_beatgrid(conn: &Connection, track_id: &str) -> Result<Option<Beatgrid
 }
 
 /// Actualiza solo el offset del beatgrid (ajuste fino manual)
+///
+/// En un grid dinámico se desplazan todas las anclas la misma distancia, y
+/// con ellas el downbeat y las frases (el índice de beat no cambia).
 pub fn update_beatgrid_offset(conn: &Connection, track_id: &str, offset: f64) -> Result<()> {
-    conn.execute(
-        "UPDATE beatgrids SET offset = ?1 WHERE track_id = ?2",
-        params![offset, track_id],
+    let Some(beatgrid) = get_beatgrid(conn, track_id)? else {
+        return Ok(());
+    };
+
+    let shift = offset - beatgrid.offset;
+    let anchors: Vec<BeatgridAnchor> = beatgrid
+        .anchors
+        .iter()
+        .map(|anchor| BeatgridAnchor {
+            position: anchor.position + shift,
+            ..*anchor
+        })
+        .collect();
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "UPDATE beatgrids SET offset = ?1, anchors = ?2 WHERE track_id = ?3",
+        params![offset, anchors_to_json(&anchors)?, track_id],
     )?;
-    Ok(())
+    shift_phrase_analysis(&tx, track_id, shift)?;
+    tx.commit()
 }
 
-/// Elimina beatgrid de una pista
+/// Elimina beatgrid de una pista (y el downbeat y las frases calculados sobre él)
 pub fn delete_beatgrid(conn: &Connection, track_id: &str) -> Result<()> {
-    conn.execute("DELETE FROM beatgrids WHERE track_id = ?1", [track_id])?;
-    Ok(())
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM beatgrids WHERE track_id = ?1", [track_id])?;
+    delete_phrase_analysis(&tx, track_id)?;
+    tx.commit()
+}
+
+/// Mapa de tempo del beatgrid de una pista
+pub fn get_tempo_map(conn: &Connection, track_id: &str) -> Result<Option<TempoMap>> {
+    Ok(get_beatgrid(conn, track_id)?
+        .map(|beatgrid| TempoMap::new(&beatgrid.anchors, beatgrid.bpm, beatgrid.offset)))
+}
+
+/// Beat (fraccionario) en el instante `time`; None si la pista no tiene beatgrid
+pub fn get_beat_at_time(conn: &Connection, track_id: &str, time: f64) -> Result<Option<f64>> {
+    Ok(get_tempo_map(conn, track_id)?.map(|map| map.beat_at(time)))
+}
+
+/// Instante (segundos) del beat `beat`; None si la pista no tiene beatgrid
+pub fn get_time_at_beat(conn: &Connection, track_id: &str, beat: f64) -> Result<Option<f64>> {
+    Ok(get_tempo_map(conn, track_id)?.map(|map| map.time_at(beat)))
+}
+
+/// Rango de tempo preferido para la detección (70-180 si no hay settings)
+pub fn get_bpm_range(conn: &Connection) -> Result<BpmRange> {
+    let value = |key: &str| -> Result<Option<f64>> {
+        Ok(get_setting(conn, key)?.and_then(|setting| setting.value.parse().ok()))
+    };
+    let default = BpmRange::default();
+    Ok(BpmRange::new(
+        value(BPM_MIN_SETTING)?.unwrap_or(default.min),
+        value(BPM_MAX_SETTING)?.unwrap_or(default.max),
+    ))
+}
+
+fn anchors_to_json(anchors: &[BeatgridAnchor]) -> Result<Option<String>> {
+    if anchors.is_empty() {
+        return Ok(None);
+    }
+    serde_json::to_string(anchors)
+        .map(Some)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+fn anchors_from_json(json: Option<String>) -> Result<Vec<BeatgridAnchor>> {
+    match json {
+        Some(json) => serde_json::from_str(&json).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(6, rusqlite::types::Type::Text, Box::new(e))
+        }),
+        None => Ok(Vec::new()),
+    }
 }
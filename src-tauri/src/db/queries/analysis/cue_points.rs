@@ -1,10 +1,18 @@
 /**
  * CRUD para cue points (puntos de marcación)
  */
+use crate::audio::cue_detector::AutoCue;
 use crate::db::models::CuePoint;
-use rusqlite::{params, Connection, Result};
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
 use uuid::Uuid;
 
+/// Máximo de cue points por pista
+const MAX_CUE_POINTS: i64 = 64;
+
+/// Distancia (segundos) por debajo de la cual un cue automático se descarta
+/// porque el usuario ya tiene uno ahí
+const USER_CUE_MARGIN: f64 = 0.5;
+
 /// Inserta nuevo cue point
 pub fn insert_cue_point(
     conn: &Connection,
@@ -22,7 +30,7 @@ pub fn insert_cue_point(
         |row| row.get(0),
     )?;
 
-    if count >= 64 {
+    if count >= MAX_CUE_POINTS {
         return Err(rusqlite::Error::InvalidParameterName(
             "Máximo 64 cue points por pista".to_string(),
         ));
@@ -51,28 +59,40 @@ pub fn insert_cue_point(
 /// Obtiene todos los cue points de una pista ordenados por posición
 pub fn get_cue_points(conn: &Connection, track_id: &str) -> Result<Vec<CuePoint>> {
     let mut stmt = conn.prepare(
-        "SELECT id, track_id, position, label, color, type, hotkey, created_at
+        "SELECT id, track_id, position, label, color, type, hotkey, source, created_at
          FROM cue_points
          WHERE track_id = ?1
          ORDER BY position ASC",
     )?;
 
-    let cue_points = stmt.query_map([track_id], |row| {
-        Ok(CuePoint {
-            id: row.get(0)?,
-            track_id: row.get(1)?,
-            position: row.get(2)?,
-            label: row.get(3)?,
-            color: row.get(4)?,
-            cue_type: row.get(5)?,
-            hotkey: row.get(6)?,
-            created_at: row.get(7)?,
-        })
-    })?;
+    let cue_points = stmt.query_map([track_id], row_to_cue_point)?;
 
     cue_points.collect()
 }
 
+/// Obtiene el hot cue (1-8) de la pista con esa ruta
+///
+/// AIDEV-NOTE: Lo usa el decode thread, que solo conoce la ruta del archivo.
+/// Si hay varios cues con el mismo hotkey gana el primero por posición.
+pub fn get_hot_cue_by_path(
+    conn: &Connection,
+    path: &str,
+    hotkey: i32,
+) -> Result<Option<CuePoint>> {
+    conn.query_row(
+        "SELECT c.id, c.track_id, c.position, c.label, c.color, c.type, c.hotkey, c.source,
+                c.created_at
+         FROM cue_points c
+         JOIN tracks t ON t.id = c.track_id
+         WHERE t.path = ?1 AND c.hotkey = ?2
+         ORDER BY c.position ASC
+         LIMIT 1",
+        params![path, hotkey],
+        row_to_cue_point,
+    )
+    .optional()
+}
+
 /// Actualiza cue point existente
 pub fn update_cue_point(
     conn: &Connection,
@@ -121,6 +141,10 @@ pub fn update_cue_point(
         return Ok(());
     }
 
+    // AIDEV-NOTE: Un cue automático editado pasa a ser del usuario, así
+    // re-analizar no deshace sus cambios
+    updates.push("source = 'user'");
+
     let query = format!("UPDATE cue_points SET {} WHERE id = ?", updates.join(", "));
     params_vec.push(Box::new(id.to_string()));
 
@@ -130,8 +154,77 @@ pub fn update_cue_point(
     Ok(())
 }
 
+/// Sustituye los cue points automáticos de una pista
+///
+/// Los cues del usuario no se tocan: los automáticos que caen a menos de
+/// `USER_CUE_MARGIN` de uno suyo se descartan, igual que los que superan el
+/// límite de la pista. Retorna cuántos se insertan.
+pub fn replace_auto_cue_points(
+    conn: &Connection,
+    track_id: &str,
+    cues: &[AutoCue],
+) -> Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+
+    tx.execute(
+        "DELETE FROM cue_points WHERE track_id = ?1 AND source = 'auto'",
+        [track_id],
+    )?;
+
+    let user_positions: Vec<f64> = {
+        let mut stmt = tx.prepare("SELECT position FROM cue_points WHERE track_id = ?1")?;
+        let positions = stmt.query_map([track_id], |row| row.get(0))?;
+        positions.collect::<Result<_>>()?
+    };
+
+    let mut inserted = 0;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO cue_points (id, track_id, position, label, color, type, hotkey, source, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, NULL, 'auto', datetime('now'))",
+        )?;
+        for cue in cues {
+            if user_positions.len() + inserted >= MAX_CUE_POINTS as usize {
+                break;
+            }
+            if user_positions
+                .iter()
+                .any(|position| (position - cue.position).abs() < USER_CUE_MARGIN)
+            {
+                continue;
+            }
+            stmt.execute(params![
+                Uuid::new_v4().to_string(),
+                track_id,
+                cue.position,
+                cue.kind.label(),
+                cue.kind.color(),
+                cue.kind.cue_type()
+            ])?;
+            inserted += 1;
+        }
+    }
+
+    tx.commit()?;
+    Ok(inserted)
+}
+
 /// Elimina cue point
 pub fn delete_cue_point(conn: &Connection, id: &str) -> Result<()> {
     conn.execute("DELETE FROM cue_points WHERE id = ?1", [id])?;
     Ok(())
 }
+
+fn row_to_cue_point(row: &Row) -> Result<CuePoint> {
+    Ok(CuePoint {
+        id: row.get(0)?,
+        track_id: row.get(1)?,
+        position: row.get(2)?,
+        label: row.get(3)?,
+        color: row.get(4)?,
+        cue_type: row.get(5)?,
+        hotkey: row.get(6)?,
+        source: row.get(7)?,
+        created_at: row.get(8)?,
+    })
+}
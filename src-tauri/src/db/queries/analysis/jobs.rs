@@ -0,0 +1,361 @@
+/**
+ * Cola persistente de análisis en background
+ *
+ * Los workers de `analysis::AnalysisQueue` toman jobs con
+ * `claim_next_analysis_job` (un solo UPDATE, así dos workers nunca toman el
+ * mismo) y al terminar los borran o los marcan como fallidos.
+ *
+ * Un job de frases no se toma mientras el beatgrid del mismo track siga
+ * pendiente o en curso: las frases se calculan sobre el grid guardado.
+ */
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
+use uuid::Uuid;
+
+use crate::db::models::{AnalysisJob, AnalysisQueueCounts};
+
+const JOB_COLUMNS: &str = "j.id, j.track_id, t.path, t.duration, j.kind, j.priority, j.status,
+     j.attempts, j.error";
+
+/// Condición SQL (sobre `tracks t`) de los tracks a los que les falta el análisis `kind`
+fn missing_analysis_condition(kind: &str) -> &'static str {
+    match kind {
+        "beatgrid" => "NOT EXISTS (SELECT 1 FROM beatgrids b WHERE b.track_id = t.id)",
+        // El loudness importado de tags no cuenta como analizado
+        "loudness" => {
+            "NOT EXISTS (SELECT 1 FROM loudness_analysis l
+                         WHERE l.track_id = t.id AND l.source = 'analysis')"
+        }
+        "key" => "NOT EXISTS (SELECT 1 FROM key_analysis k WHERE k.track_id = t.id)",
+        "phrases" => "NOT EXISTS (SELECT 1 FROM downbeats d WHERE d.track_id = t.id)",
+        "waveform" => {
+            "NOT EXISTS (SELECT 1 FROM waveforms w
+                         WHERE w.track_id = t.id AND w.format = 'mono')"
+        }
+        _ => "1",
+    }
+}
+
+/// Encola análisis de tracks
+///
+/// `track_ids` a `None` encola toda la biblioteca. Sin `force` solo se encolan
+/// los análisis que faltan. Un job ya encolado vuelve a 'pending' (si había
+/// fallado) y se queda con la prioridad más alta.
+///
+/// # Retorna
+/// Número de jobs encolados o actualizados
+pub fn enqueue_analysis_jobs(
+    conn: &Connection,
+    track_ids: Option<&[String]>,
+    kinds: &[&str],
+    priority: i32,
+    force: bool,
+) -> Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let mut enqueued = 0;
+
+    {
+        let mut insert = tx.prepare(
+            "INSERT INTO analysis_jobs
+                 (id, track_id, kind, priority, status, attempts, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, 'pending', 0, datetime('now'), datetime('now'))
+             ON CONFLICT(track_id, kind) DO UPDATE SET
+                 priority = MAX(priority, excluded.priority),
+                 status = CASE WHEN status = 'running' THEN status ELSE 'pending' END,
+                 error = NULL,
+                 updated_at = excluded.updated_at",
+        )?;
+
+        for kind in kinds {
+            let condition = if force {
+                "1"
+            } else {
+                missing_analysis_condition(kind)
+            };
+            let mut select = tx.prepare(&format!(
+                "SELECT t.id FROM tracks t WHERE (?1 IS NULL OR t.id = ?1) AND {}",
+                condition
+            ))?;
+
+            let candidates: Vec<String> = match track_ids {
+                None => select
+                    .query_map([None::<&str>], |row| row.get(0))?
+                    .collect::<Result<_>>()?,
+                Some(ids) => {
+                    let mut candidates = Vec::new();
+                    for id in ids {
+                        if let Some(id) = select.query_row([id], |row| row.get(0)).optional()? {
+                            candidates.push(id);
+                        }
+                    }
+                    candidates
+                }
+            };
+
+            for track_id in candidates {
+                enqueued += insert.execute(params![
+                    Uuid::new_v4().to_string(),
+                    track_id,
+                    kind,
+                    priority
+                ])?;
+            }
+        }
+    }
+
+    tx.commit()?;
+    Ok(enqueued)
+}
+
+/// Toma el job pendiente más prioritario y lo marca como 'running'
+pub fn claim_next_analysis_job(conn: &Connection) -> Result<Option<AnalysisJob>> {
+    let id: Option<String> = conn
+        .query_row(
+            "UPDATE analysis_jobs
+             SET status = 'running', attempts = attempts + 1, updated_at = datetime('now')
+             WHERE id = (
+                 SELECT j.id FROM analysis_jobs j
+                 WHERE j.status = 'pending'
+                   AND NOT (j.kind = 'phrases' AND EXISTS (
+                       SELECT 1 FROM analysis_jobs b
+                       WHERE b.track_id = j.track_id AND b.kind = 'beatgrid'
+                         AND b.status IN ('pending', 'running')
+                   ))
+                 ORDER BY j.priority DESC, j.created_at, j.rowid
+                 LIMIT 1
+             )
+             RETURNING id",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    match id {
+        Some(id) => get_analysis_job(conn, &id),
+        None => Ok(None),
+    }
+}
+
+/// Obtiene un job por id
+pub fn get_analysis_job(conn: &Connection, id: &str) -> Result<Option<AnalysisJob>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM analysis_jobs j JOIN tracks t ON t.id = j.track_id WHERE j.id = ?1",
+            JOB_COLUMNS
+        ),
+        [id],
+        row_to_job,
+    )
+    .optional()
+}
+
+/// Quita de la cola un job terminado
+pub fn complete_analysis_job(conn: &Connection, id: &str) -> Result<()> {
+    conn.execute("DELETE FROM analysis_jobs WHERE id = ?1", [id])?;
+    Ok(())
+}
+
+/// Marca un job como fallido (se queda en la cola hasta reintentarlo o limpiarla)
+pub fn fail_analysis_job(conn: &Connection, id: &str, error: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE analysis_jobs SET status = 'failed', error = ?1, updated_at = datetime('now')
+         WHERE id = ?2",
+        params![error, id],
+    )?;
+    Ok(())
+}
+
+/// Devuelve a 'pending' los jobs que quedaron 'running' (la app se cerró a medias)
+pub fn reset_running_analysis_jobs(conn: &Connection) -> Result<usize> {
+    conn.execute(
+        "UPDATE analysis_jobs SET status = 'pending' WHERE status = 'running'",
+        [],
+    )
+}
+
+/// Da prioridad `priority` a los jobs pendientes de `track_id`
+///
+/// Los que la tenían por una selección anterior vuelven a `base`.
+pub fn prioritize_analysis_track(
+    conn: &Connection,
+    track_id: &str,
+    priority: i32,
+    base: i32,
+) -> Result<usize> {
+    conn.execute(
+        "UPDATE analysis_jobs SET priority = ?3
+         WHERE status = 'pending' AND priority >= ?2 AND track_id <> ?1",
+        params![track_id, priority, base],
+    )?;
+    conn.execute(
+        "UPDATE analysis_jobs SET priority = ?2 WHERE status = 'pending' AND track_id = ?1",
+        params![track_id, priority],
+    )
+}
+
+/// Número de jobs por estado
+pub fn get_analysis_queue_counts(conn: &Connection) -> Result<AnalysisQueueCounts> {
+    let mut stmt = conn.prepare("SELECT status, COUNT(*) FROM analysis_jobs GROUP BY status")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+    })?;
+
+    let mut counts = AnalysisQueueCounts::default();
+    for row in rows {
+        let (status, count) = row?;
+        match status.as_str() {
+            "pending" => counts.pending = count,
+            "running" => counts.running = count,
+            "failed" => counts.failed = count,
+            _ => {}
+        }
+    }
+    Ok(counts)
+}
+
+/// Jobs fallidos, los más recientes primero
+pub fn get_failed_analysis_jobs(conn: &Connection) -> Result<Vec<AnalysisJob>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM analysis_jobs j JOIN tracks t ON t.id = j.track_id
+         WHERE j.status = 'failed' ORDER BY j.updated_at DESC, j.rowid DESC",
+        JOB_COLUMNS
+    ))?;
+    let jobs = stmt.query_map([], row_to_job)?;
+    jobs.collect()
+}
+
+/// Vacía la cola (pendientes y fallidos; los que están corriendo terminan)
+pub fn clear_analysis_jobs(conn: &Connection) -> Result<usize> {
+    conn.execute("DELETE FROM analysis_jobs WHERE status <> 'running'", [])
+}
+
+fn row_to_job(row: &Row) -> Result<AnalysisJob> {
+    Ok(AnalysisJob {
+        id: row.get(0)?,
+        track_id: row.get(1)?,
+        path: row.get(2)?,
+        duration: row.get(3)?,
+        kind: row.get(4)?,
+        priority: row.get(5)?,
+        status: row.get(6)?,
+        attempts: row.get(7)?,
+        error: row.get(8)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{migrations, queries, Database};
+
+    const KINDS: [&str; 3] = ["beatgrid", "loudness", "waveform"];
+
+    fn setup_db() -> Database {
+        let db = Database::new_in_memory().unwrap();
+        migrations::run_migrations(&db.conn).unwrap();
+        for id in ["t1", "t2", "t3"] {
+            db.conn
+                .execute(
+                    "INSERT INTO tracks (id, path, title, artist, duration, bitrate, sample_rate, file_size, date_added, date_modified)
+                     VALUES (?1, ?1 || '.mp3', 'Test', 'Artist', 180.0, 320, 44100, 8388608, '2024-01-01', '2024-01-01')",
+                    [id],
+                )
+                .unwrap();
+        }
+        db
+    }
+
+    #[test]
+    fn test_enqueue_only_missing() {
+        let db = setup_db();
+        queries::upsert_beatgrid(&db.conn, "t1", 128.0, 0.1, Some(0.9), &[]).unwrap();
+        // Loudness de tags: hay que analizarlo igualmente
+        queries::upsert_loudness(&db.conn, "t1", -14.0, -1.0, None, "tags").unwrap();
+
+        let enqueued = enqueue_analysis_jobs(&db.conn, None, &KINDS, 0, false).unwrap();
+        assert_eq!(enqueued, 8);
+        assert_eq!(get_analysis_queue_counts(&db.conn).unwrap().pending, 8);
+
+        // Volver a encolar no duplica
+        enqueue_analysis_jobs(&db.conn, None, &KINDS, 0, false).unwrap();
+        assert_eq!(get_analysis_queue_counts(&db.conn).unwrap().pending, 8);
+
+        let forced =
+            enqueue_analysis_jobs(&db.conn, Some(&["t1".to_string()]), &KINDS, 0, true).unwrap();
+        assert_eq!(forced, 3);
+        assert_eq!(get_analysis_queue_counts(&db.conn).unwrap().pending, 9);
+    }
+
+    #[test]
+    fn test_claim_by_priority() {
+        let db = setup_db();
+        let ids = ["t1".to_string(), "t2".to_string()];
+        enqueue_analysis_jobs(&db.conn, Some(&ids), &["beatgrid"], 0, false).unwrap();
+        enqueue_analysis_jobs(&db.conn, Some(&["t3".to_string()]), &["beatgrid"], 0, false)
+            .unwrap();
+        prioritize_analysis_track(&db.conn, "t3", 100, 0).unwrap();
+
+        let first = claim_next_analysis_job(&db.conn).unwrap().unwrap();
+        assert_eq!(first.track_id, "t3");
+        assert_eq!(first.status, "running");
+        assert_eq!(first.attempts, 1);
+        assert_eq!(first.path, "t3.mp3");
+
+        // Otra selección devuelve la anterior a la prioridad base
+        prioritize_analysis_track(&db.conn, "t2", 100, 0).unwrap();
+        let second = claim_next_analysis_job(&db.conn).unwrap().unwrap();
+        assert_eq!(second.track_id, "t2");
+        let third = claim_next_analysis_job(&db.conn).unwrap().unwrap();
+        assert_eq!(third.track_id, "t1");
+        assert!(claim_next_analysis_job(&db.conn).unwrap().is_none());
+
+        let counts = get_analysis_queue_counts(&db.conn).unwrap();
+        assert_eq!(counts.running, 3);
+        assert_eq!(counts.pending, 0);
+    }
+
+    #[test]
+    fn test_phrases_wait_for_beatgrid() {
+        let db = setup_db();
+        let t1 = ["t1".to_string()];
+        // El job de frases se encola antes, pero no puede ir antes que el grid
+        enqueue_analysis_jobs(&db.conn, Some(&t1), &["phrases"], 0, false).unwrap();
+        enqueue_analysis_jobs(&db.conn, Some(&t1), &["beatgrid"], 0, false).unwrap();
+
+        let beatgrid = claim_next_analysis_job(&db.conn).unwrap().unwrap();
+        assert_eq!(beatgrid.kind, "beatgrid");
+        // Mientras el grid está en curso, las frases siguen esperando
+        assert!(claim_next_analysis_job(&db.conn).unwrap().is_none());
+
+        complete_analysis_job(&db.conn, &beatgrid.id).unwrap();
+        let phrases = claim_next_analysis_job(&db.conn).unwrap().unwrap();
+        assert_eq!(phrases.kind, "phrases");
+    }
+
+    #[test]
+    fn test_complete_fail_and_recover() {
+        let db = setup_db();
+        enqueue_analysis_jobs(&db.conn, None, &["loudness"], 0, false).unwrap();
+        let done = claim_next_analysis_job(&db.conn).unwrap().unwrap();
+        let failed = claim_next_analysis_job(&db.conn).unwrap().unwrap();
+        claim_next_analysis_job(&db.conn).unwrap().unwrap();
+
+        complete_analysis_job(&db.conn, &done.id).unwrap();
+        fail_analysis_job(&db.conn, &failed.id, "Archivo no encontrado").unwrap();
+        // Arranque tras un cierre a medias
+        assert_eq!(reset_running_analysis_jobs(&db.conn).unwrap(), 1);
+
+        let counts = get_analysis_queue_counts(&db.conn).unwrap();
+        assert_eq!((counts.pending, counts.running, counts.failed), (1, 0, 1));
+        let failed_jobs = get_failed_analysis_jobs(&db.conn).unwrap();
+        assert_eq!(
+            failed_jobs[0].error.as_deref(),
+            Some("Archivo no encontrado")
+        );
+
+        // Re-encolar reintenta los fallidos
+        enqueue_analysis_jobs(&db.conn, None, &["loudness"], 0, false).unwrap();
+        assert_eq!(get_analysis_queue_counts(&db.conn).unwrap().pending, 3);
+
+        assert_eq!(clear_analysis_jobs(&db.conn).unwrap(), 3);
+    }
+}
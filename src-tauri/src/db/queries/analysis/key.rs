@@ -0,0 +1,74 @@
+/**
+ * CRUD para la tonalidad detectada por análisis
+ */
+use crate::db::models::TrackKey;
+use crate::db::queries::tracks::normalize_track_key;
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
+use uuid::Uuid;
+
+/// Confidence mínima (0-100) para que el análisis ponga la tonalidad de la pista
+pub const KEY_APPLY_MIN_CONFIDENCE: f64 = 50.0;
+
+/// Inserta o actualiza la tonalidad detectada de una pista
+///
+/// Con confidence suficiente se copia también a `tracks.key` si la pista no
+/// tiene tonalidad o la tenía de un análisis anterior. Nunca pisa la de los
+/// tags ni una editada a mano (`tracks.key_source`).
+pub fn upsert_key_analysis(
+    conn: &Connection,
+    track_id: &str,
+    key: &str,
+    confidence: f64,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO key_analysis (id, track_id, key, confidence, analyzed_at)
+         VALUES (?1, ?2, ?3, ?4, datetime('now'))
+         ON CONFLICT(track_id) DO UPDATE SET
+             key = excluded.key,
+             confidence = excluded.confidence,
+             analyzed_at = excluded.analyzed_at",
+        params![Uuid::new_v4().to_string(), track_id, key, confidence],
+    )?;
+
+    if confidence < KEY_APPLY_MIN_CONFIDENCE {
+        return Ok(());
+    }
+
+    let (key, canonical) = normalize_track_key(conn, Some(key))?;
+    conn.execute(
+        "UPDATE tracks SET key = ?2, key_canonical = ?3, key_source = 'analysis'
+         WHERE id = ?1
+           AND (key_source = 'analysis'
+                OR (key_source IS NULL AND (key IS NULL OR key = '')))",
+        params![track_id, key, canonical],
+    )?;
+    Ok(())
+}
+
+/// Obtiene la tonalidad detectada de una pista
+pub fn get_key_analysis(conn: &Connection, track_id: &str) -> Result<Option<TrackKey>> {
+    conn.query_row(
+        "SELECT id, track_id, key, confidence, analyzed_at
+         FROM key_analysis
+         WHERE track_id = ?1",
+        [track_id],
+        row_to_key,
+    )
+    .optional()
+}
+
+/// Elimina la tonalidad detectada de una pista
+pub fn delete_key_analysis(conn: &Connection, track_id: &str) -> Result<()> {
+    conn.execute("DELETE FROM key_analysis WHERE track_id = ?1", [track_id])?;
+    Ok(())
+}
+
+fn row_to_key(row: &Row) -> Result<TrackKey> {
+    Ok(TrackKey {
+        id: row.get(0)?,
+        track_id: row.get(1)?,
+        key: row.get(2)?,
+        confidence: row.get(3)?,
+        analyzed_at: row.get(4)?,
+    })
+}
@@ -2,7 +2,7 @@
  * CRUD para loops (bucles de reproducción)
  */
 use crate::db::models::Loop;
-use rusqlite::{params, Connection, Result};
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
 use uuid::Uuid;
 
 /// Inserta nuevo loop
@@ -41,21 +41,51 @@ pub fn get_loops(conn: &Connection, track_id: &str) -> Result<Vec<Loop>> {
          ORDER BY loop_start ASC",
     )?;
 
-    let loops = stmt.query_map([track_id], |row| {
-        Ok(Loop {
-            id: row.get(0)?,
-            track_id: row.get(1)?,
-            label: row.get(2)?,
-            loop_start: row.get(3)?,
-            loop_end: row.get(4)?,
-            is_active: row.get::<_, i32>(5)? == 1,
-            created_at: row.get(6)?,
-        })
-    })?;
+    let loops = stmt.query_map([track_id], row_to_loop)?;
 
     loops.collect()
 }
 
+/// Obtiene un loop por id
+pub fn get_loop(conn: &Connection, id: &str) -> Result<Option<Loop>> {
+    conn.query_row(
+        "SELECT id, track_id, label, loop_start, loop_end, is_active, created_at
+         FROM loops
+         WHERE id = ?1",
+        [id],
+        row_to_loop,
+    )
+    .optional()
+}
+
+/// Obtiene el loop activo de la pista con esa ruta, si lo hay
+///
+/// AIDEV-NOTE: Lo usa el decode thread, que solo conoce la ruta del archivo
+pub fn get_active_loop_by_path(conn: &Connection, path: &str) -> Result<Option<Loop>> {
+    conn.query_row(
+        "SELECT l.id, l.track_id, l.label, l.loop_start, l.loop_end, l.is_active, l.created_at
+         FROM loops l
+         JOIN tracks t ON t.id = l.track_id
+         WHERE t.path = ?1 AND l.is_active = 1
+         ORDER BY l.loop_start ASC
+         LIMIT 1",
+        [path],
+        row_to_loop,
+    )
+    .optional()
+}
+
+/// Marca un loop como activo y desactiva el resto de loops de la pista
+///
+/// `id = None` desactiva todos los loops de la pista.
+pub fn set_active_loop(conn: &Connection, track_id: &str, id: Option<&str>) -> Result<()> {
+    conn.execute(
+        "UPDATE loops SET is_active = CASE WHEN id = ?2 THEN 1 ELSE 0 END WHERE track_id = ?1",
+        params![track_id, id],
+    )?;
+    Ok(())
+}
+
 /// Actualiza loop existente
 pub fn update_loop(
     conn: &Connection,
@@ -124,3 +154,15 @@ pub fn delete_loop(conn: &Connection, id: &str) -> Result<()> {
     conn.execute("DELETE FROM loops WHERE id = ?1", [id])?;
     Ok(())
 }
+
+fn row_to_loop(row: &Row) -> Result<Loop> {
+    Ok(Loop {
+        id: row.get(0)?,
+        track_id: row.get(1)?,
+        label: row.get(2)?,
+        loop_start: row.get(3)?,
+        loop_end: row.get(4)?,
+        is_active: row.get::<_, i32>(5)? == 1,
+        created_at: row.get(6)?,
+    })
+}
@@ -0,0 +1,102 @@
+/**
+ * CRUD para análisis de loudness (EBU R128 / ReplayGain)
+ */
+use crate::db::models::Loudness;
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
+use uuid::Uuid;
+
+/// Inserta o actualiza el loudness de una pista
+///
+/// `source` es 'analysis' (analizador propio) o 'tags' (ReplayGain importado).
+pub fn upsert_loudness(
+    conn: &Connection,
+    track_id: &str,
+    integrated_lufs: f64,
+    true_peak_dbtp: f64,
+    loudness_range: Option<f64>,
+    source: &str,
+) -> Result<String> {
+    // Verificar si ya existe
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT id FROM loudness_analysis WHERE track_id = ?1",
+            [track_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    if let Some(id) = existing {
+        // Actualizar existente
+        conn.execute(
+            "UPDATE loudness_analysis SET integrated_lufs = ?1, true_peak_dbtp = ?2,
+                 loudness_range = ?3, source = ?4, analyzed_at = datetime('now')
+             WHERE id = ?5",
+            params![integrated_lufs, true_peak_dbtp, loudness_range, source, &id],
+        )?;
+        Ok(id)
+    } else {
+        // Insertar nuevo
+        let id = Uuid::new_v4().to_string();
+        conn.execute(
+            "INSERT INTO loudness_analysis
+                 (id, track_id, integrated_lufs, true_peak_dbtp, loudness_range, source, analyzed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, datetime('now'))",
+            params![
+                &id,
+                track_id,
+                integrated_lufs,
+                true_peak_dbtp,
+                loudness_range,
+                source
+            ],
+        )?;
+        Ok(id)
+    }
+}
+
+/// Obtiene el loudness de una pista
+pub fn get_loudness(conn: &Connection, track_id: &str) -> Result<Option<Loudness>> {
+    conn.query_row(
+        "SELECT id, track_id, integrated_lufs, true_peak_dbtp, loudness_range, source, analyzed_at
+         FROM loudness_analysis
+         WHERE track_id = ?1",
+        [track_id],
+        row_to_loudness,
+    )
+    .optional()
+}
+
+/// Obtiene el loudness de una pista por su ruta (usado por el decode thread)
+pub fn get_loudness_by_path(conn: &Connection, path: &str) -> Result<Option<Loudness>> {
+    conn.query_row(
+        "SELECT l.id, l.track_id, l.integrated_lufs, l.true_peak_dbtp, l.loudness_range,
+                l.source, l.analyzed_at
+         FROM loudness_analysis l
+         JOIN tracks t ON t.id = l.track_id
+         WHERE t.path = ?1",
+        [path],
+        row_to_loudness,
+    )
+    .optional()
+}
+
+/// Elimina el loudness de una pista
+pub fn delete_loudness(conn: &Connection, track_id: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM loudness_analysis WHERE track_id = ?1",
+        [track_id],
+    )?;
+    Ok(())
+}
+
+fn row_to_loudness(row: &Row) -> Result<Loudness> {
+    Ok(Loudness {
+        id: row.get(0)?,
+        track_id: row.get(1)?,
+        integrated_lufs: row.get(2)?,
+        true_peak_dbtp: row.get(3)?,
+        loudness_range: row.get(4)?,
+        source: row.get(5)?,
+        analyzed_at: row.get(6)?,
+    })
+}
@@ -7,6 +7,10 @@
  * - **beatgrids**: Análisis de tempo y beatgrid
  * - **cue_points**: Puntos de marcación en pistas
  * - **loops**: Bucles de reproducción
+ * - **loudness**: Análisis de loudness EBU R128
+ * - **key**: Tonalidad detectada por análisis
+ * - **phrases**: Downbeats y frases (intro/verse/chorus/bridge/outro)
+ * - **jobs**: Cola persistente de análisis en background
  *
  * ## Notas
  *
@@ -14,14 +18,37 @@
  */
 mod beatgrids;
 mod cue_points;
+mod jobs;
+mod key;
 mod loops;
+mod loudness;
+mod phrases;
 mod waveforms;
 
 // Re-exportar funciones públicas
-pub use beatgrids::{delete_beatgrid, get_beatgrid, update_beatgrid_offset, upsert_beatgrid};
-pub use cue_points::{delete_cue_point, get_cue_points, insert_cue_point, update_cue_point};
-pub use loops::{delete_loop, get_loops, insert_loop, update_loop};
-pub use waveforms::{get_waveform, save_waveform};
+pub use beatgrids::{
+    delete_beatgrid, get_beat_at_time, get_beatgrid, get_bpm_range, get_tempo_map,
+    get_time_at_beat, update_beatgrid_offset, upsert_beatgrid, BPM_MAX_SETTING, BPM_MIN_SETTING,
+};
+pub use cue_points::{
+    delete_cue_point, get_cue_points, get_hot_cue_by_path, insert_cue_point,
+    replace_auto_cue_points, update_cue_point,
+};
+pub use jobs::{
+    claim_next_analysis_job, clear_analysis_jobs, complete_analysis_job, enqueue_analysis_jobs,
+    fail_analysis_job, get_analysis_job, get_analysis_queue_counts, get_failed_analysis_jobs,
+    prioritize_analysis_track, reset_running_analysis_jobs,
+};
+pub use key::{
+    delete_key_analysis, get_key_analysis, upsert_key_analysis, KEY_APPLY_MIN_CONFIDENCE,
+};
+pub use loops::{
+    delete_loop, get_active_loop_by_path, get_loop, get_loops, insert_loop, set_active_loop,
+    update_loop,
+};
+pub use loudness::{delete_loudness, get_loudness, get_loudness_by_path, upsert_loudness};
+pub use phrases::{delete_phrase_analysis, get_downbeat, get_phrases, save_phrase_analysis};
+pub use waveforms::{gc_waveforms, get_waveform, get_waveform_cache_stats, save_waveform};
 
 #[cfg(test)]
 mod tests {
@@ -61,11 +88,13 @@ mod tests {
             label: None,
             isrc: None,
             beatport_id: None,
+            key_canonical: None,
+            key_index: None,
         };
         let track_id = tracks::insert_track(&db.conn, &track).unwrap();
 
         // Insertar beatgrid
-        let id = upsert_beatgrid(&db.conn, &track_id, 128.0, 0.5, Some(95.0)).unwrap();
+        let id = upsert_beatgrid(&db.conn, &track_id, 128.0, 0.5, Some(95.0), &[]).unwrap();
         assert!(!id.is_empty());
 
         // Obtener y verificar
@@ -73,6 +102,91 @@ mod tests {
         assert_eq!(beatgrid.bpm, 128.0);
         assert_eq!(beatgrid.offset, 0.5);
         assert_eq!(beatgrid.confidence, Some(95.0));
+        assert!(beatgrid.anchors.is_empty());
+    }
+
+    #[test]
+    fn test_dynamic_beatgrid() {
+        use crate::audio::beatgrid::BeatgridAnchor;
+
+        let db = setup_db();
+        let track = crate::db::models::Track {
+            id: None,
+            path: "/music/live.mp3".to_string(),
+            title: "Live".to_string(),
+            artist: "Artist".to_string(),
+            album: None,
+            genre: None,
+            year: None,
+            duration: 180.0,
+            bitrate: 320,
+            sample_rate: 44100,
+            file_size: 8388608,
+            bpm: None,
+            key: None,
+            rating: None,
+            play_count: 0,
+            last_played: None,
+            date_added: "2024-01-01".to_string(),
+            date_modified: "2024-01-01".to_string(),
+            label: None,
+            isrc: None,
+            beatport_id: None,
+            key_canonical: None,
+            key_index: None,
+        };
+        let track_id = tracks::insert_track(&db.conn, &track).unwrap();
+        assert!(get_beat_at_time(&db.conn, &track_id, 1.0).unwrap().is_none());
+
+        // 120 BPM durante 16 beats (8s) y luego 100 BPM
+        let anchors = [
+            BeatgridAnchor {
+                beat: 0,
+                position: 0.5,
+                bpm: 120.0,
+            },
+            BeatgridAnchor {
+                beat: 16,
+                position: 8.5,
+                bpm: 100.0,
+            },
+        ];
+        upsert_beatgrid(&db.conn, &track_id, 120.0, 0.5, Some(80.0), &anchors).unwrap();
+        assert_eq!(get_beatgrid(&db.conn, &track_id).unwrap().unwrap().anchors, anchors);
+
+        let beat = get_beat_at_time(&db.conn, &track_id, 11.5).unwrap().unwrap();
+        assert!((beat - 21.0).abs() < 1e-9);
+        let time = get_time_at_beat(&db.conn, &track_id, 8.0).unwrap().unwrap();
+        assert!((time - 4.5).abs() < 1e-9);
+
+        // El ajuste de offset mueve todo el grid
+        update_beatgrid_offset(&db.conn, &track_id, 0.6).unwrap();
+        let beatgrid = get_beatgrid(&db.conn, &track_id).unwrap().unwrap();
+        assert_eq!(beatgrid.offset, 0.6);
+        assert!((beatgrid.anchors[1].position - 8.6).abs() < 1e-9);
+
+        // Volver a un grid constante borra las anclas
+        upsert_beatgrid(&db.conn, &track_id, 124.0, 0.1, None, &[]).unwrap();
+        let time = get_time_at_beat(&db.conn, &track_id, 31.0).unwrap().unwrap();
+        assert!((time - (0.1 + 31.0 * 60.0 / 124.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_bpm_range_setting() {
+        use crate::db::queries::settings::upsert_setting;
+
+        let db = setup_db();
+        let range = get_bpm_range(&db.conn).unwrap();
+        assert_eq!((range.min, range.max), (70.0, 180.0));
+
+        upsert_setting(&db.conn, BPM_MIN_SETTING, "85", "number").unwrap();
+        upsert_setting(&db.conn, BPM_MAX_SETTING, "175", "number").unwrap();
+        let range = get_bpm_range(&db.conn).unwrap();
+        assert_eq!((range.min, range.max), (85.0, 175.0));
+
+        // Un valor no numérico usa el por defecto
+        upsert_setting(&db.conn, BPM_MIN_SETTING, "rápido", "number").unwrap();
+        assert_eq!(get_bpm_range(&db.conn).unwrap().min, 70.0);
     }
 
     #[test]
@@ -101,6 +215,8 @@ mod tests {
             label: None,
             isrc: None,
             beatport_id: None,
+            key_canonical: None,
+            key_index: None,
         };
         let track_id = tracks::insert_track(&db.conn, &track).unwrap();
 
@@ -121,4 +237,502 @@ mod tests {
         assert_eq!(cues.len(), 1);
         assert_eq!(cues[0].position, 30.0);
     }
+
+    #[test]
+    fn test_get_hot_cue_by_path() {
+        let db = setup_db();
+
+        let track = crate::db::models::Track {
+            id: None,
+            path: "/music/test.mp3".to_string(),
+            title: "Test".to_string(),
+            artist: "Artist".to_string(),
+            album: None,
+            genre: None,
+            year: None,
+            duration: 180.0,
+            bitrate: 320,
+            sample_rate: 44100,
+            file_size: 8388608,
+            bpm: None,
+            key: None,
+            rating: None,
+            play_count: 0,
+            last_played: None,
+            date_added: "2024-01-01".to_string(),
+            date_modified: "2024-01-01".to_string(),
+            label: None,
+            isrc: None,
+            beatport_id: None,
+            key_canonical: None,
+            key_index: None,
+        };
+        let track_id = tracks::insert_track(&db.conn, &track).unwrap();
+
+        let id = insert_cue_point(
+            &db.conn,
+            &track_id,
+            30.0,
+            "Intro",
+            "#FF0000",
+            "intro",
+            Some(1),
+        )
+        .unwrap();
+
+        let hot_cue = get_hot_cue_by_path(&db.conn, "/music/test.mp3", 1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(hot_cue.id.as_deref(), Some(id.as_str()));
+        assert!(get_hot_cue_by_path(&db.conn, "/music/test.mp3", 2)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_replace_auto_cue_points() {
+        use crate::audio::cue_detector::{AutoCue, AutoCueKind};
+
+        let db = setup_db();
+
+        let track = crate::db::models::Track {
+            id: None,
+            path: "/music/auto.mp3".to_string(),
+            title: "Auto".to_string(),
+            artist: "Artist".to_string(),
+            album: None,
+            genre: None,
+            year: None,
+            duration: 180.0,
+            bitrate: 320,
+            sample_rate: 44100,
+            file_size: 8388608,
+            bpm: None,
+            key: None,
+            rating: None,
+            play_count: 0,
+            last_played: None,
+            date_added: "2024-01-01".to_string(),
+            date_modified: "2024-01-01".to_string(),
+            label: None,
+            isrc: None,
+            beatport_id: None,
+            key_canonical: None,
+            key_index: None,
+        };
+        let track_id = tracks::insert_track(&db.conn, &track).unwrap();
+
+        insert_cue_point(&db.conn, &track_id, 32.2, "Mine", "#FFFFFF", "cue", Some(1)).unwrap();
+
+        let cues = [
+            AutoCue {
+                kind: AutoCueKind::Intro,
+                position: 0.5,
+            },
+            AutoCue {
+                kind: AutoCueKind::Main,
+                position: 32.0,
+            },
+            AutoCue {
+                kind: AutoCueKind::Outro,
+                position: 160.0,
+            },
+        ];
+        // El cue "Main" choca con el del usuario
+        assert_eq!(
+            replace_auto_cue_points(&db.conn, &track_id, &cues).unwrap(),
+            2
+        );
+        // Re-analizar no duplica ni toca los cues del usuario
+        assert_eq!(
+            replace_auto_cue_points(&db.conn, &track_id, &cues).unwrap(),
+            2
+        );
+
+        let stored = get_cue_points(&db.conn, &track_id).unwrap();
+        let summary: Vec<(&str, &str)> = stored
+            .iter()
+            .map(|c| (c.label.as_str(), c.source.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("Intro", "auto"), ("Mine", "user"), ("Outro", "auto")]
+        );
+        assert_eq!(stored[0].cue_type, "intro");
+        assert_eq!(stored[0].color, "#10b981");
+
+        // Un cue automático editado pasa a ser del usuario
+        let outro = stored[2].id.clone().unwrap();
+        update_cue_point(&db.conn, &outro, Some(161.0), None, None, None, None).unwrap();
+        replace_auto_cue_points(&db.conn, &track_id, &cues[..1]).unwrap();
+
+        let stored = get_cue_points(&db.conn, &track_id).unwrap();
+        assert_eq!(stored.len(), 3);
+        assert_eq!(stored[2].position, 161.0);
+        assert_eq!(stored[2].source, "user");
+    }
+
+    #[test]
+    fn test_active_loop() {
+        let db = setup_db();
+
+        let track = crate::db::models::Track {
+            id: None,
+            path: "/music/test.mp3".to_string(),
+            title: "Test".to_string(),
+            artist: "Artist".to_string(),
+            album: None,
+            genre: None,
+            year: None,
+            duration: 180.0,
+            bitrate: 320,
+            sample_rate: 44100,
+            file_size: 8388608,
+            bpm: None,
+            key: None,
+            rating: None,
+            play_count: 0,
+            last_played: None,
+            date_added: "2024-01-01".to_string(),
+            date_modified: "2024-01-01".to_string(),
+            label: None,
+            isrc: None,
+            beatport_id: None,
+            key_canonical: None,
+            key_index: None,
+        };
+        let track_id = tracks::insert_track(&db.conn, &track).unwrap();
+
+        let first = insert_loop(&db.conn, &track_id, "Intro", 8.0, 16.0).unwrap();
+        let second = insert_loop(&db.conn, &track_id, "Drop", 64.0, 72.0).unwrap();
+        assert!(get_active_loop_by_path(&db.conn, "/music/test.mp3")
+            .unwrap()
+            .is_none());
+
+        // Solo un loop activo por pista
+        set_active_loop(&db.conn, &track_id, Some(&first)).unwrap();
+        set_active_loop(&db.conn, &track_id, Some(&second)).unwrap();
+        let active = get_active_loop_by_path(&db.conn, "/music/test.mp3")
+            .unwrap()
+            .unwrap();
+        assert_eq!(active.id.as_deref(), Some(second.as_str()));
+        assert!(!get_loop(&db.conn, &first).unwrap().unwrap().is_active);
+
+        set_active_loop(&db.conn, &track_id, None).unwrap();
+        assert!(get_active_loop_by_path(&db.conn, "/music/test.mp3")
+            .unwrap()
+            .is_none());
+        assert!(get_loop(&db.conn, "missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_upsert_loudness() {
+        let db = setup_db();
+
+        let track = crate::db::models::Track {
+            id: None,
+            path: "/music/test.mp3".to_string(),
+            title: "Test".to_string(),
+            artist: "Artist".to_string(),
+            album: None,
+            genre: None,
+            year: None,
+            duration: 180.0,
+            bitrate: 320,
+            sample_rate: 44100,
+            file_size: 8388608,
+            bpm: None,
+            key: None,
+            rating: None,
+            play_count: 0,
+            last_played: None,
+            date_added: "2024-01-01".to_string(),
+            date_modified: "2024-01-01".to_string(),
+            label: None,
+            isrc: None,
+            beatport_id: None,
+            key_canonical: None,
+            key_index: None,
+        };
+        let track_id = tracks::insert_track(&db.conn, &track).unwrap();
+
+        let id = upsert_loudness(&db.conn, &track_id, -16.0, -2.0, None, "tags").unwrap();
+
+        // El análisis reemplaza a los tags importados
+        let same_id =
+            upsert_loudness(&db.conn, &track_id, -9.5, 0.3, Some(6.2), "analysis").unwrap();
+        assert_eq!(id, same_id);
+
+        let loudness = get_loudness(&db.conn, &track_id).unwrap().unwrap();
+        assert_eq!(loudness.integrated_lufs, -9.5);
+        assert_eq!(loudness.loudness_range, Some(6.2));
+        assert_eq!(loudness.source, "analysis");
+
+        let by_path = get_loudness_by_path(&db.conn, "/music/test.mp3")
+            .unwrap()
+            .unwrap();
+        assert_eq!(by_path.track_id, track_id);
+
+        delete_loudness(&db.conn, &track_id).unwrap();
+        assert!(get_loudness(&db.conn, &track_id).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_upsert_key_analysis() {
+        let db = setup_db();
+
+        let mut track = crate::db::models::Track {
+            id: None,
+            path: "/music/test.mp3".to_string(),
+            title: "Test".to_string(),
+            artist: "Artist".to_string(),
+            album: None,
+            genre: None,
+            year: None,
+            duration: 180.0,
+            bitrate: 320,
+            sample_rate: 44100,
+            file_size: 8388608,
+            bpm: None,
+            key: None,
+            rating: None,
+            play_count: 0,
+            last_played: None,
+            date_added: "2024-01-01".to_string(),
+            date_modified: "2024-01-01".to_string(),
+            label: None,
+            isrc: None,
+            beatport_id: None,
+            key_canonical: None,
+            key_index: None,
+        };
+        let untagged = tracks::insert_track(&db.conn, &track).unwrap();
+        track.path = "/music/tagged.mp3".to_string();
+        track.key = Some("Fm".to_string());
+        let tagged = tracks::insert_track(&db.conn, &track).unwrap();
+
+        upsert_key_analysis(&db.conn, &untagged, "Am", 40.0).unwrap();
+        upsert_key_analysis(&db.conn, &untagged, "C", 85.0).unwrap();
+        upsert_key_analysis(&db.conn, &tagged, "G#m", 70.0).unwrap();
+
+        let key = get_key_analysis(&db.conn, &untagged).unwrap().unwrap();
+        assert_eq!(key.key, "C");
+        assert_eq!(key.confidence, 85.0);
+
+        // tracks.key sigue al análisis, pero no pisa la tonalidad de los tags
+        assert_eq!(
+            tracks::get_track(&db.conn, &untagged).unwrap().key.as_deref(),
+            Some("C")
+        );
+        assert_eq!(
+            tracks::get_track(&db.conn, &tagged).unwrap().key.as_deref(),
+            Some("Fm")
+        );
+
+        delete_key_analysis(&db.conn, &untagged).unwrap();
+        assert!(get_key_analysis(&db.conn, &untagged).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_key_analysis_respects_confidence_and_manual_key() {
+        let db = setup_db();
+
+        let mut track = crate::db::models::Track {
+            id: None,
+            path: "/music/test.mp3".to_string(),
+            title: "Test".to_string(),
+            artist: "Artist".to_string(),
+            album: None,
+            genre: None,
+            year: None,
+            duration: 180.0,
+            bitrate: 320,
+            sample_rate: 44100,
+            file_size: 8388608,
+            bpm: None,
+            key: None,
+            rating: None,
+            play_count: 0,
+            last_played: None,
+            date_added: "2024-01-01".to_string(),
+            date_modified: "2024-01-01".to_string(),
+            label: None,
+            isrc: None,
+            beatport_id: None,
+            key_canonical: None,
+            key_index: None,
+        };
+        let doubtful = tracks::insert_track(&db.conn, &track).unwrap();
+        track.path = "/music/edited.mp3".to_string();
+        let edited = tracks::insert_track(&db.conn, &track).unwrap();
+        let key_of = |id: &str| tracks::get_track(&db.conn, id).unwrap().key;
+
+        // Sin confidence suficiente solo se guarda el análisis
+        upsert_key_analysis(&db.conn, &doubtful, "Am", KEY_APPLY_MIN_CONFIDENCE - 1.0).unwrap();
+        assert_eq!(key_of(&doubtful), None);
+        assert_eq!(
+            get_key_analysis(&db.conn, &doubtful).unwrap().unwrap().key,
+            "Am"
+        );
+
+        // Una tonalidad editada a mano no se pisa, aunque sea la del análisis
+        upsert_key_analysis(&db.conn, &edited, "Am", 90.0).unwrap();
+        assert_eq!(key_of(&edited).as_deref(), Some("Am"));
+        tracks::update_track_metadata(
+            &db.conn,
+            &edited,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some("Am"),
+            None,
+        )
+        .unwrap();
+        upsert_key_analysis(&db.conn, &edited, "C", 95.0).unwrap();
+        assert_eq!(key_of(&edited).as_deref(), Some("Am"));
+    }
+
+    #[test]
+    fn test_save_phrase_analysis() {
+        use crate::audio::phrase_detector::{PhraseAnalysis, PhraseKind, PhraseSection};
+
+        let db = setup_db();
+        let track = crate::db::models::Track {
+            id: None,
+            path: "/music/test.mp3".to_string(),
+            title: "Test".to_string(),
+            artist: "Artist".to_string(),
+            album: None,
+            genre: None,
+            year: None,
+            duration: 180.0,
+            bitrate: 320,
+            sample_rate: 44100,
+            file_size: 8388608,
+            bpm: None,
+            key: None,
+            rating: None,
+            play_count: 0,
+            last_played: None,
+            date_added: "2024-01-01".to_string(),
+            date_modified: "2024-01-01".to_string(),
+            label: None,
+            isrc: None,
+            beatport_id: None,
+            key_canonical: None,
+            key_index: None,
+        };
+        let track_id = tracks::insert_track(&db.conn, &track).unwrap();
+
+        let section = |start_bar: u32, bars: u32, kind: PhraseKind, energy: f64| PhraseSection {
+            start_bar,
+            bars,
+            start: 1.0 + start_bar as f64 * 2.0,
+            end: 1.0 + (start_bar + bars) as f64 * 2.0,
+            kind,
+            energy,
+        };
+        let mut analysis = PhraseAnalysis {
+            downbeat_beat: 1,
+            downbeat: 1.0,
+            beats_per_bar: 4,
+            phrases: vec![
+                section(16, 32, PhraseKind::Chorus, 1.0),
+                section(0, 16, PhraseKind::Intro, 0.4),
+            ],
+        };
+        save_phrase_analysis(&db.conn, &track_id, &analysis).unwrap();
+
+        let downbeat = get_downbeat(&db.conn, &track_id).unwrap().unwrap();
+        assert_eq!(downbeat.beat, 1);
+        assert_eq!(downbeat.beats_per_bar, 4);
+
+        // Ordenadas por compás
+        let phrases = get_phrases(&db.conn, &track_id).unwrap();
+        assert_eq!(phrases.len(), 2);
+        assert_eq!(phrases[0].kind, "intro");
+        assert_eq!(phrases[1].start_time, 33.0);
+
+        // Un nuevo análisis reemplaza las frases anteriores
+        analysis.downbeat_beat = 3;
+        analysis.phrases = vec![section(0, 8, PhraseKind::Outro, 0.2)];
+        save_phrase_analysis(&db.conn, &track_id, &analysis).unwrap();
+        assert_eq!(get_downbeat(&db.conn, &track_id).unwrap().unwrap().beat, 3);
+        assert_eq!(get_phrases(&db.conn, &track_id).unwrap().len(), 1);
+
+        delete_phrase_analysis(&db.conn, &track_id).unwrap();
+        assert!(get_downbeat(&db.conn, &track_id).unwrap().is_none());
+        assert!(get_phrases(&db.conn, &track_id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_beatgrid_changes_update_phrases() {
+        use crate::audio::phrase_detector::{PhraseAnalysis, PhraseKind, PhraseSection};
+
+        let db = setup_db();
+        let track = crate::db::models::Track {
+            id: None,
+            path: "/music/test.mp3".to_string(),
+            title: "Test".to_string(),
+            artist: "Artist".to_string(),
+            album: None,
+            genre: None,
+            year: None,
+            duration: 180.0,
+            bitrate: 320,
+            sample_rate: 44100,
+            file_size: 8388608,
+            bpm: None,
+            key: None,
+            rating: None,
+            play_count: 0,
+            last_played: None,
+            date_added: "2024-01-01".to_string(),
+            date_modified: "2024-01-01".to_string(),
+            label: None,
+            isrc: None,
+            beatport_id: None,
+            key_canonical: None,
+            key_index: None,
+        };
+        let track_id = tracks::insert_track(&db.conn, &track).unwrap();
+        let analysis = PhraseAnalysis {
+            downbeat_beat: 0,
+            downbeat: 0.5,
+            beats_per_bar: 4,
+            phrases: vec![PhraseSection {
+                start_bar: 0,
+                bars: 16,
+                start: 0.5,
+                end: 32.5,
+                kind: PhraseKind::Intro,
+                energy: 0.4,
+            }],
+        };
+        upsert_beatgrid(&db.conn, &track_id, 120.0, 0.5, Some(90.0), &[]).unwrap();
+        save_phrase_analysis(&db.conn, &track_id, &analysis).unwrap();
+
+        // Mover el offset desplaza el downbeat y las frases con el grid
+        update_beatgrid_offset(&db.conn, &track_id, 0.75).unwrap();
+        let downbeat = get_downbeat(&db.conn, &track_id).unwrap().unwrap();
+        assert!((downbeat.position - 0.75).abs() < 1e-9);
+        assert_eq!(downbeat.beat, 0);
+        let phrases = get_phrases(&db.conn, &track_id).unwrap();
+        assert!((phrases[0].start_time - 0.75).abs() < 1e-9);
+        assert!((phrases[0].end_time - 32.75).abs() < 1e-9);
+
+        // Un grid nuevo las invalida
+        upsert_beatgrid(&db.conn, &track_id, 124.0, 0.1, Some(90.0), &[]).unwrap();
+        assert!(get_downbeat(&db.conn, &track_id).unwrap().is_none());
+        assert!(get_phrases(&db.conn, &track_id).unwrap().is_empty());
+
+        // Y borrar el grid también
+        save_phrase_analysis(&db.conn, &track_id, &analysis).unwrap();
+        delete_beatgrid(&db.conn, &track_id).unwrap();
+        assert!(get_downbeat(&db.conn, &track_id).unwrap().is_none());
+        assert!(get_phrases(&db.conn, &track_id).unwrap().is_empty());
+    }
 }
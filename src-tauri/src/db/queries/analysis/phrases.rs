@@ -0,0 +1,125 @@
+/**
+ * CRUD para downbeats y frases (estructura de compases)
+ */
+use crate::audio::phrase_detector::PhraseAnalysis;
+use crate::db::models::{Downbeat, Phrase};
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use uuid::Uuid;
+
+/// Guarda el downbeat y las frases de una pista, reemplazando las anteriores
+pub fn save_phrase_analysis(
+    conn: &Connection,
+    track_id: &str,
+    analysis: &PhraseAnalysis,
+) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+
+    tx.execute(
+        "INSERT INTO downbeats (id, track_id, beat, position, beats_per_bar, analyzed_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, datetime('now'))
+         ON CONFLICT(track_id) DO UPDATE SET
+             beat = excluded.beat,
+             position = excluded.position,
+             beats_per_bar = excluded.beats_per_bar,
+             analyzed_at = excluded.analyzed_at",
+        params![
+            Uuid::new_v4().to_string(),
+            track_id,
+            analysis.downbeat_beat,
+            analysis.downbeat,
+            analysis.beats_per_bar
+        ],
+    )?;
+
+    tx.execute("DELETE FROM phrases WHERE track_id = ?1", [track_id])?;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO phrases (id, track_id, start_bar, bars, start_time, end_time, kind, energy)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        for phrase in &analysis.phrases {
+            stmt.execute(params![
+                Uuid::new_v4().to_string(),
+                track_id,
+                phrase.start_bar,
+                phrase.bars,
+                phrase.start,
+                phrase.end,
+                phrase.kind.as_str(),
+                phrase.energy
+            ])?;
+        }
+    }
+
+    tx.commit()
+}
+
+/// Obtiene el downbeat de una pista
+pub fn get_downbeat(conn: &Connection, track_id: &str) -> Result<Option<Downbeat>> {
+    conn.query_row(
+        "SELECT id, track_id, beat, position, beats_per_bar, analyzed_at
+         FROM downbeats
+         WHERE track_id = ?1",
+        [track_id],
+        |row| {
+            Ok(Downbeat {
+                id: row.get(0)?,
+                track_id: row.get(1)?,
+                beat: row.get(2)?,
+                position: row.get(3)?,
+                beats_per_bar: row.get(4)?,
+                analyzed_at: row.get(5)?,
+            })
+        },
+    )
+    .optional()
+}
+
+/// Obtiene las frases de una pista ordenadas por compás
+pub fn get_phrases(conn: &Connection, track_id: &str) -> Result<Vec<Phrase>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, track_id, start_bar, bars, start_time, end_time, kind, energy
+         FROM phrases
+         WHERE track_id = ?1
+         ORDER BY start_bar",
+    )?;
+
+    let phrases = stmt.query_map([track_id], |row| {
+        Ok(Phrase {
+            id: row.get(0)?,
+            track_id: row.get(1)?,
+            start_bar: row.get(2)?,
+            bars: row.get(3)?,
+            start_time: row.get(4)?,
+            end_time: row.get(5)?,
+            kind: row.get(6)?,
+            energy: row.get(7)?,
+        })
+    })?;
+
+    phrases.collect()
+}
+
+/// Elimina el downbeat y las frases de una pista
+pub fn delete_phrase_analysis(conn: &Connection, track_id: &str) -> Result<()> {
+    conn.execute("DELETE FROM phrases WHERE track_id = ?1", [track_id])?;
+    conn.execute("DELETE FROM downbeats WHERE track_id = ?1", [track_id])?;
+    Ok(())
+}
+
+/// Desplaza `shift` segundos el downbeat y las frases de una pista
+///
+/// Para cuando se mueve el offset del beatgrid: los beats y compases siguen
+/// siendo los mismos, solo cambia dónde caen.
+pub(super) fn shift_phrase_analysis(conn: &Connection, track_id: &str, shift: f64) -> Result<()> {
+    conn.execute(
+        "UPDATE downbeats SET position = position + ?1 WHERE track_id = ?2",
+        params![shift, track_id],
+    )?;
+    conn.execute(
+        "UPDATE phrases SET start_time = start_time + ?1, end_time = end_time + ?1
+         WHERE track_id = ?2",
+        params![shift, track_id],
+    )?;
+    Ok(())
+}
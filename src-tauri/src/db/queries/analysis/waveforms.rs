@@ -1,23 +1,28 @@
 /**
  * CRUD para waveforms (caché de visualización)
  */
-use rusqlite::{params, Connection, OptionalExtension, Result};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Result};
 use uuid::Uuid;
 
+use crate::db::models::{WaveformCacheStats, WaveformFormatStats};
+
 /// Guarda datos de waveform en cache
+///
+/// Una fila por track y `format`; `version` es la del algoritmo que la generó.
 pub fn save_waveform(
     conn: &Connection,
     track_id: &str,
-    data: &str, // JSON serializado de Vec<f32>
+    format: &str,
+    version: i32,
+    data: &[u8], // Blob binario de los peaks (ver `audio::waveform::codec`)
 ) -> Result<String> {
-    let data_bytes = data.as_bytes();
-    let resolution = data_bytes.len() as i32;
+    let resolution = data.len() as i32;
 
     // Verificar si ya existe
     let existing: Option<String> = conn
         .query_row(
-            "SELECT id FROM waveforms WHERE track_id = ?1",
-            [track_id],
+            "SELECT id FROM waveforms WHERE track_id = ?1 AND format = ?2",
+            [track_id, format],
             |row| row.get(0),
         )
         .optional()?;
@@ -25,39 +30,243 @@ pub fn save_waveform(
     if let Some(id) = existing {
         // Actualizar existente
         conn.execute(
-            "UPDATE waveforms SET data = ?1, resolution = ?2, date_generated = datetime('now') WHERE id = ?3",
-            params![data_bytes, resolution, &id],
+            "UPDATE waveforms SET data = ?1, resolution = ?2, version = ?3, date_generated = datetime('now') WHERE id = ?4",
+            params![data, resolution, version, &id],
         )?;
         Ok(id)
     } else {
         // Insertar nuevo
         let id = Uuid::new_v4().to_string();
         conn.execute(
-            "INSERT INTO waveforms (id, track_id, data, resolution, date_generated) 
-             VALUES (?1, ?2, ?3, ?4, datetime('now'))",
-            params![&id, track_id, data_bytes, resolution],
+            "INSERT INTO waveforms (id, track_id, format, version, data, resolution, date_generated)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, datetime('now'))",
+            params![&id, track_id, format, version, data, resolution],
         )?;
         Ok(id)
     }
 }
 
-/// Obtiene waveform de cache
+/// Obtiene waveform de cache en el formato pedido
 pub fn get_waveform(
     conn: &Connection,
     track_id: &str,
+    format: &str,
 ) -> Result<Option<crate::db::models::Waveform>> {
     conn.query_row(
-        "SELECT id, track_id, data, resolution, date_generated FROM waveforms WHERE track_id = ?1",
-        [track_id],
+        "SELECT id, track_id, format, version, data, resolution, date_generated
+         FROM waveforms WHERE track_id = ?1 AND format = ?2",
+        [track_id, format],
         |row| {
             Ok(crate::db::models::Waveform {
                 id: row.get(0)?,
                 track_id: row.get(1)?,
-                data: row.get(2)?,
-                resolution: row.get(3)?,
-                date_generated: row.get(4)?,
+                format: row.get(2)?,
+                version: row.get(3)?,
+                data: row.get(4)?,
+                resolution: row.get(5)?,
+                date_generated: row.get(6)?,
             })
         },
     )
     .optional()
 }
+
+/// Ocupación de la caché de waveforms por formato
+pub fn get_waveform_cache_stats(conn: &Connection) -> Result<WaveformCacheStats> {
+    let mut stmt = conn.prepare(
+        "SELECT format, COUNT(*), COALESCE(SUM(LENGTH(data)), 0)
+         FROM waveforms GROUP BY format ORDER BY format",
+    )?;
+    let formats = stmt
+        .query_map([], |row| {
+            Ok(WaveformFormatStats {
+                format: row.get(0)?,
+                count: row.get(1)?,
+                bytes: row.get(2)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+    let page_count: i64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+    let freelist_count: i64 = conn.query_row("PRAGMA freelist_count", [], |row| row.get(0))?;
+
+    Ok(WaveformCacheStats {
+        count: formats.iter().map(|f| f.count).sum(),
+        bytes: formats.iter().map(|f| f.bytes).sum(),
+        formats,
+        database_bytes: page_size * page_count,
+        free_bytes: page_size * freelist_count,
+    })
+}
+
+/// Limpia la caché de waveforms
+///
+/// Borra las filas de tracks que ya no existen y las de formatos o versiones
+/// distintos de `current` (formato, versión vigente). Con `max_bytes`, además
+/// borra las más antiguas hasta que los blobs quepan en ese tamaño.
+///
+/// # Retorna
+/// (track_id, formato) de las filas borradas
+pub fn gc_waveforms(
+    conn: &Connection,
+    current: &[(&str, i32)],
+    max_bytes: Option<i64>,
+) -> Result<Vec<(String, String)>> {
+    let mut removed = delete_returning(
+        conn,
+        "DELETE FROM waveforms WHERE track_id NOT IN (SELECT id FROM tracks)",
+        params![],
+    )?;
+
+    let placeholders = vec!["?"; current.len()].join(", ");
+    removed.extend(delete_returning(
+        conn,
+        &format!(
+            "DELETE FROM waveforms WHERE format NOT IN ({})",
+            placeholders
+        ),
+        params_from_iter(current.iter().map(|(format, _)| format)),
+    )?);
+    for (format, version) in current {
+        removed.extend(delete_returning(
+            conn,
+            "DELETE FROM waveforms WHERE format = ?1 AND version <> ?2",
+            params![format, version],
+        )?);
+    }
+
+    if let Some(max_bytes) = max_bytes {
+        // Las más recientes se quedan mientras quepan
+        let mut stmt = conn.prepare(
+            "SELECT id, LENGTH(data) FROM waveforms ORDER BY date_generated DESC, rowid DESC",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut total: i64 = 0;
+        for (id, bytes) in rows {
+            total = total.saturating_add(bytes);
+            if total > max_bytes {
+                removed.extend(delete_returning(
+                    conn,
+                    "DELETE FROM waveforms WHERE id = ?1",
+                    [&id],
+                )?);
+            }
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Ejecuta un DELETE y retorna (track_id, formato) de las filas borradas
+fn delete_returning<P: rusqlite::Params>(
+    conn: &Connection,
+    sql: &str,
+    params: P,
+) -> Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare(&format!("{} RETURNING track_id, format", sql))?;
+    let rows = stmt.query_map(params, |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{migrations, Database};
+
+    fn setup_db() -> Database {
+        let db = Database::new_in_memory().unwrap();
+        migrations::run_migrations(&db.conn).unwrap();
+        db.conn
+            .execute(
+                "INSERT INTO tracks (id, path, title, artist, duration, bitrate, sample_rate, file_size, date_added, date_modified)
+                 VALUES ('t1', 'test.mp3', 'Test', 'Artist', 180.0, 320, 44100, 8388608, '2024-01-01', '2024-01-01')",
+                [],
+            )
+            .unwrap();
+        db
+    }
+
+    #[test]
+    fn test_formats_cached_separately() {
+        let db = setup_db();
+        save_waveform(&db.conn, "t1", "mono", 1, b"[0.5]").unwrap();
+        save_waveform(&db.conn, "t1", "bands", 1, b"{\"peaks\":[0.5]}").unwrap();
+
+        let mono = get_waveform(&db.conn, "t1", "mono").unwrap().unwrap();
+        assert_eq!(mono.data, b"[0.5]");
+        let bands = get_waveform(&db.conn, "t1", "bands").unwrap().unwrap();
+        assert_eq!(bands.format, "bands");
+        assert!(get_waveform(&db.conn, "t1", "other").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_save_replaces_version() {
+        let db = setup_db();
+        let id = save_waveform(&db.conn, "t1", "bands", 1, b"[]").unwrap();
+        let updated = save_waveform(&db.conn, "t1", "bands", 2, b"[1.0]").unwrap();
+        assert_eq!(id, updated);
+
+        let waveform = get_waveform(&db.conn, "t1", "bands").unwrap().unwrap();
+        assert_eq!(waveform.version, 2);
+        assert_eq!(waveform.data, b"[1.0]");
+    }
+
+    #[test]
+    fn test_cache_stats() {
+        let db = setup_db();
+        save_waveform(&db.conn, "t1", "mono", 1, &[0u8; 100]).unwrap();
+        save_waveform(&db.conn, "t1", "pyramid", 1, &[0u8; 1000]).unwrap();
+
+        let stats = get_waveform_cache_stats(&db.conn).unwrap();
+        assert_eq!(stats.count, 2);
+        assert_eq!(stats.bytes, 1100);
+        assert_eq!(stats.formats[0].format, "mono");
+        assert_eq!(stats.formats[1].bytes, 1000);
+        assert!(stats.database_bytes > 0);
+    }
+
+    #[test]
+    fn test_gc_removes_stale_rows() {
+        let db = setup_db();
+        save_waveform(&db.conn, "t1", "mono", 1, &[0u8; 10]).unwrap();
+        save_waveform(&db.conn, "t1", "bands", 1, &[0u8; 10]).unwrap();
+        save_waveform(&db.conn, "t1", "legacy", 1, &[0u8; 10]).unwrap();
+
+        // `bands` ha subido de versión y `legacy` ya no existe
+        let removed = gc_waveforms(&db.conn, &[("mono", 1), ("bands", 2)], None).unwrap();
+        assert_eq!(removed.len(), 2);
+        assert!(removed.contains(&("t1".to_string(), "bands".to_string())));
+        assert!(get_waveform(&db.conn, "t1", "mono").unwrap().is_some());
+        assert!(get_waveform(&db.conn, "t1", "bands").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_gc_trims_oldest_to_budget() {
+        let db = setup_db();
+        save_waveform(&db.conn, "t1", "mono", 1, &[0u8; 100]).unwrap();
+        save_waveform(&db.conn, "t1", "pyramid", 1, &[0u8; 100]).unwrap();
+        db.conn
+            .execute(
+                "UPDATE waveforms SET date_generated = '2020-01-01 00:00:00' WHERE format = 'mono'",
+                [],
+            )
+            .unwrap();
+
+        let current = [("mono", 1), ("pyramid", 1)];
+        assert_eq!(
+            gc_waveforms(&db.conn, &current, Some(150)).unwrap(),
+            vec![("t1".to_string(), "mono".to_string())]
+        );
+        assert!(get_waveform(&db.conn, "t1", "mono").unwrap().is_none());
+        assert!(get_waveform(&db.conn, "t1", "pyramid").unwrap().is_some());
+        assert!(gc_waveforms(&db.conn, &current, Some(150))
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,259 @@
+//! Historial de reproducción y contadores de escuchas
+//!
+//! AIDEV-NOTE: El decode thread registra una escucha cuando el track supera el
+//! umbral configurado (ver `audio::player::history`). Cada registro incrementa
+//! `tracks.play_count` y actualiza `tracks.last_played` en la misma transacción.
+//! Las fechas son UTC en formato SQLite (`YYYY-MM-DD HH:MM:SS`).
+
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
+use uuid::Uuid;
+
+use crate::db::models::{PlayHistoryEntry, TrackPlayStats};
+
+/// Registra una escucha del track en `path`
+///
+/// # Retorna
+/// * `Ok(Some(id))` - Escucha registrada
+/// * `Ok(None)` - El archivo no está en la biblioteca
+pub fn record_play_by_path(
+    conn: &mut Connection,
+    path: &str,
+    seconds_played: f64,
+) -> Result<Option<String>> {
+    let tx = conn.transaction()?;
+
+    let track_id: Option<String> = tx
+        .query_row("SELECT id FROM tracks WHERE path = ?1", [path], |row| {
+            row.get(0)
+        })
+        .optional()?;
+    let Some(track_id) = track_id else {
+        return Ok(None);
+    };
+
+    let id = Uuid::new_v4().to_string();
+    let played_at: String = tx.query_row("SELECT datetime('now')", [], |row| row.get(0))?;
+    tx.execute(
+        "INSERT INTO play_history (id, track_id, played_at, seconds_played)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![&id, &track_id, &played_at, seconds_played],
+    )?;
+    tx.execute(
+        "UPDATE tracks SET play_count = COALESCE(play_count, 0) + 1, last_played = ?1
+         WHERE id = ?2",
+        params![&played_at, &track_id],
+    )?;
+
+    tx.commit()?;
+    Ok(Some(id))
+}
+
+/// Últimas escuchas, de la más reciente a la más antigua
+pub fn get_recently_played(conn: &Connection, limit: usize) -> Result<Vec<PlayHistoryEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT h.id, h.track_id, t.path, t.title, t.artist, h.played_at, h.seconds_played
+         FROM play_history h
+         JOIN tracks t ON t.id = h.track_id
+         ORDER BY h.played_at DESC, h.rowid DESC
+         LIMIT ?1",
+    )?;
+    let entries = stmt.query_map([limit as i64], row_to_entry)?;
+    entries.collect()
+}
+
+/// Historial completo de un track, de la escucha más reciente a la más antigua
+pub fn get_track_history(conn: &Connection, track_id: &str) -> Result<Vec<PlayHistoryEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT h.id, h.track_id, t.path, t.title, t.artist, h.played_at, h.seconds_played
+         FROM play_history h
+         JOIN tracks t ON t.id = h.track_id
+         WHERE h.track_id = ?1
+         ORDER BY h.played_at DESC, h.rowid DESC",
+    )?;
+    let entries = stmt.query_map([track_id], row_to_entry)?;
+    entries.collect()
+}
+
+/// Tracks más escuchados en un rango de fechas
+///
+/// `from` y `to` son días (`YYYY-MM-DD`) incluidos en el rango; `None` deja el
+/// extremo abierto. Empates por la escucha más reciente.
+pub fn get_most_played(
+    conn: &Connection,
+    from: Option<&str>,
+    to: Option<&str>,
+    limit: usize,
+) -> Result<Vec<TrackPlayStats>> {
+    let mut stmt = conn.prepare(
+        "SELECT h.track_id, t.path, t.title, t.artist, COUNT(*) AS plays,
+                MAX(h.played_at) AS last_played
+         FROM play_history h
+         JOIN tracks t ON t.id = h.track_id
+         WHERE (?1 IS NULL OR h.played_at >= date(?1))
+           AND (?2 IS NULL OR h.played_at < date(?2, '+1 day'))
+         GROUP BY h.track_id
+         ORDER BY plays DESC, last_played DESC
+         LIMIT ?3",
+    )?;
+
+    let stats = stmt.query_map(params![from, to, limit as i64], |row| {
+        Ok(TrackPlayStats {
+            track_id: row.get(0)?,
+            path: row.get(1)?,
+            title: row.get(2)?,
+            artist: row.get(3)?,
+            plays: row.get(4)?,
+            last_played: row.get(5)?,
+        })
+    })?;
+    stats.collect()
+}
+
+fn row_to_entry(row: &Row) -> Result<PlayHistoryEntry> {
+    Ok(PlayHistoryEntry {
+        id: row.get(0)?,
+        track_id: row.get(1)?,
+        path: row.get(2)?,
+        title: row.get(3)?,
+        artist: row.get(4)?,
+        played_at: row.get(5)?,
+        seconds_played: row.get(6)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{migrations, queries::tracks, Database};
+
+    fn setup_db() -> Database {
+        let db = Database::new_in_memory().unwrap();
+        migrations::run_migrations(&db.conn).unwrap();
+        db
+    }
+
+    fn insert_test_track(db: &Database, path: &str) -> String {
+        let track = crate::db::models::Track {
+            id: None,
+            path: path.to_string(),
+            title: "History Track".to_string(),
+            artist: "Artist".to_string(),
+            album: None,
+            genre: None,
+            year: None,
+            duration: 180.0,
+            bitrate: 320,
+            sample_rate: 44100,
+            file_size: 8388608,
+            bpm: None,
+            key: None,
+            rating: None,
+            play_count: 0,
+            last_played: None,
+            date_added: "2024-01-01".to_string(),
+            date_modified: "2024-01-01".to_string(),
+            label: None,
+            isrc: None,
+            beatport_id: None,
+            key_canonical: None,
+            key_index: None,
+        };
+        tracks::insert_track(&db.conn, &track).unwrap()
+    }
+
+    /// Inserta una escucha con fecha fija (para probar rangos)
+    fn insert_play_at(db: &Database, track_id: &str, played_at: &str) {
+        db.conn
+            .execute(
+                "INSERT INTO play_history (id, track_id, played_at, seconds_played)
+                 VALUES (?1, ?2, ?3, 90.0)",
+                params![Uuid::new_v4().to_string(), track_id, played_at],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_record_play_updates_track() {
+        let mut db = setup_db();
+        let track_id = insert_test_track(&db, "/music/h1.mp3");
+
+        assert!(record_play_by_path(&mut db.conn, "/music/h1.mp3", 95.0)
+            .unwrap()
+            .is_some());
+        assert!(record_play_by_path(&mut db.conn, "/music/h1.mp3", 120.0)
+            .unwrap()
+            .is_some());
+
+        let track = tracks::get_track(&db.conn, &track_id).unwrap();
+        assert_eq!(track.play_count, 2);
+
+        let history = get_track_history(&db.conn, &track_id).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(
+            track.last_played.as_deref(),
+            Some(history[0].played_at.as_str())
+        );
+        assert_eq!(history[0].seconds_played, 120.0);
+    }
+
+    #[test]
+    fn test_record_play_unknown_path() {
+        let mut db = setup_db();
+        assert_eq!(
+            record_play_by_path(&mut db.conn, "/music/missing.mp3", 60.0).unwrap(),
+            None
+        );
+        assert!(get_recently_played(&db.conn, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_recently_played_order_and_limit() {
+        let db = setup_db();
+        let a = insert_test_track(&db, "/music/a.mp3");
+        let b = insert_test_track(&db, "/music/b.mp3");
+        insert_play_at(&db, &a, "2024-03-01 10:00:00");
+        insert_play_at(&db, &b, "2024-03-02 10:00:00");
+        insert_play_at(&db, &a, "2024-03-03 10:00:00");
+
+        let recent = get_recently_played(&db.conn, 2).unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].track_id, a);
+        assert_eq!(recent[0].played_at, "2024-03-03 10:00:00");
+        assert_eq!(recent[1].track_id, b);
+    }
+
+    #[test]
+    fn test_most_played_in_range() {
+        let db = setup_db();
+        let a = insert_test_track(&db, "/music/a.mp3");
+        let b = insert_test_track(&db, "/music/b.mp3");
+        insert_play_at(&db, &a, "2024-03-01 10:00:00");
+        insert_play_at(&db, &b, "2024-03-02 10:00:00");
+        insert_play_at(&db, &b, "2024-03-02 23:59:59");
+        insert_play_at(&db, &a, "2024-04-15 10:00:00");
+        insert_play_at(&db, &a, "2024-04-16 10:00:00");
+
+        // Todo el historial: `a` tiene 3 escuchas
+        let all = get_most_played(&db.conn, None, None, 10).unwrap();
+        assert_eq!(all[0].track_id, a);
+        assert_eq!(all[0].plays, 3);
+        assert_eq!(all[0].last_played, "2024-04-16 10:00:00");
+
+        // Solo marzo (el día final es inclusivo)
+        let march = get_most_played(&db.conn, Some("2024-03-01"), Some("2024-03-02"), 10).unwrap();
+        assert_eq!(march.len(), 2);
+        assert_eq!(march[0].track_id, b);
+        assert_eq!(march[0].plays, 2);
+        assert_eq!(march[1].plays, 1);
+    }
+
+    #[test]
+    fn test_history_removed_with_track() {
+        let mut db = setup_db();
+        let track_id = insert_test_track(&db, "/music/gone.mp3");
+        record_play_by_path(&mut db.conn, "/music/gone.mp3", 60.0).unwrap();
+
+        tracks::delete_track(&db.conn, &track_id).unwrap();
+        assert!(get_recently_played(&db.conn, 10).unwrap().is_empty());
+    }
+}
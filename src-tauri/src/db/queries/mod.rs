@@ -1,5 +1,7 @@
 pub mod analysis;
+pub mod history;
 pub mod playlists;
+pub mod queue;
 pub mod settings;
 /// Módulo de queries separado por responsabilidades
 ///
@@ -9,6 +11,8 @@ pub mod tracks;
 
 // Re-exportar las funciones principales para compatibilidad
 pub use analysis::*;
+pub use history::*;
 pub use playlists::*;
+pub use queue::*;
 pub use settings::*;
 pub use tracks::*;
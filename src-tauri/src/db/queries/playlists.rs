@@ -1,4 +1,5 @@
 use crate::db::models::{Playlist, Track};
+use crate::db::queries::tracks::key_wheel_index;
 /// CRUD para playlists con UUIDs
 use rusqlite::{params, Connection, OptionalExtension, Result};
 use uuid::Uuid;
@@ -172,7 +173,7 @@ pub fn get_playlist_tracks(conn: &Connection, playlist_id: &str) -> Result<Vec<T
         "SELECT t.id, t.path, t.title, t.artist, t.album, t.genre, t.year,
                 t.duration, t.bitrate, t.sample_rate, t.file_size,
                 t.bpm, t.key, t.rating, t.play_count, t.last_played,
-                t.date_added, t.date_modified, t.label, t.isrc, t.beatport_id
+                t.date_added, t.date_modified, t.label, t.isrc, t.beatport_id, t.key_canonical
          FROM tracks t
          INNER JOIN playlist_tracks pt ON t.id = pt.track_id
          WHERE pt.playlist_id = ?1
@@ -202,6 +203,8 @@ pub fn get_playlist_tracks(conn: &Connection, playlist_id: &str) -> Result<Vec<T
             label: row.get(18)?,
             isrc: row.get(19)?,
             beatport_id: row.get(20)?,
+            key_canonical: row.get(21)?,
+            key_index: key_wheel_index(row.get::<_, Option<String>>(21)?.as_deref()),
         })
     })?;
 
@@ -273,6 +276,8 @@ mod tests {
             label: None,
             isrc: None,
             beatport_id: None,
+            key_canonical: None,
+            key_index: None,
         };
         let track_id = tracks::insert_track(&db.conn, &track).unwrap();
 
@@ -0,0 +1,211 @@
+use crate::db::models::{QueueItem, SavedQueue};
+/// Persistencia de la cola de reproducción
+/// AIDEV-NOTE: La cola se reescribe completa en cada guardado (cientos de filas como
+/// mucho). Los tracks borrados de la biblioteca desaparecen por ON DELETE CASCADE,
+/// así que al cargar se compactan los índices.
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use std::collections::HashMap;
+
+/// Guarda la cola completa reemplazando la anterior
+pub fn save_queue(conn: &mut Connection, queue: &SavedQueue) -> Result<()> {
+    let tx = conn.transaction()?;
+
+    tx.execute("DELETE FROM playback_queue", [])?;
+
+    // play_order de cada item = su posición dentro de `order`
+    let mut play_orders = vec![0usize; queue.items.len()];
+    for (position, &item_index) in queue.order.iter().enumerate() {
+        if let Some(slot) = play_orders.get_mut(item_index) {
+            *slot = position;
+        }
+    }
+
+    for (item_index, item) in queue.items.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO playback_queue (item_index, track_id, play_order)
+             VALUES (?1, ?2, ?3)",
+            params![
+                item_index as i64,
+                &item.track_id,
+                play_orders[item_index] as i64
+            ],
+        )?;
+    }
+
+    // Guardamos el item actual (no la posición) para sobrevivir a borrados
+    let current_item = queue
+        .current
+        .and_then(|position| queue.order.get(position))
+        .map(|&index| index as i64);
+
+    tx.execute(
+        "INSERT INTO playback_queue_state (id, current_item, shuffle, repeat_mode, updated_at)
+         VALUES (1, ?1, ?2, ?3, CURRENT_TIMESTAMP)
+         ON CONFLICT(id) DO UPDATE SET
+            current_item = excluded.current_item,
+            shuffle = excluded.shuffle,
+            repeat_mode = excluded.repeat_mode,
+            updated_at = excluded.updated_at",
+        params![current_item, queue.shuffle, &queue.repeat_mode],
+    )?;
+
+    tx.commit()
+}
+
+/// Carga la cola guardada
+///
+/// # Retorna
+/// * `Ok(None)` - Nunca se ha guardado una cola
+pub fn load_queue(conn: &Connection) -> Result<Option<SavedQueue>> {
+    let state: Option<(Option<i64>, bool, String)> = conn
+        .query_row(
+            "SELECT current_item, shuffle, repeat_mode FROM playback_queue_state WHERE id = 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?;
+
+    let Some((current_item, shuffle, repeat_mode)) = state else {
+        return Ok(None);
+    };
+
+    let mut stmt = conn.prepare(
+        "SELECT q.item_index, q.play_order, q.track_id, t.path
+         FROM playback_queue q
+         INNER JOIN tracks t ON t.id = q.track_id
+         ORDER BY q.item_index",
+    )?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                QueueItem {
+                    track_id: row.get(2)?,
+                    path: row.get(3)?,
+                },
+            ))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    // Compactar índices (puede haber huecos por tracks borrados)
+    let mut index_map = HashMap::new();
+    let mut items = Vec::with_capacity(rows.len());
+    let mut by_play_order = Vec::with_capacity(rows.len());
+    for (new_index, (old_index, play_order, item)) in rows.into_iter().enumerate() {
+        index_map.insert(old_index, new_index);
+        items.push(item);
+        by_play_order.push((play_order, new_index));
+    }
+    by_play_order.sort_unstable();
+    let order: Vec<usize> = by_play_order.into_iter().map(|(_, index)| index).collect();
+
+    let current = current_item
+        .and_then(|old_index| index_map.get(&old_index))
+        .and_then(|index| order.iter().position(|i| i == index));
+
+    Ok(Some(SavedQueue {
+        items,
+        order,
+        current,
+        shuffle,
+        repeat_mode,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{migrations, queries::tracks, Database};
+
+    fn setup_db() -> Database {
+        let db = Database::new_in_memory().unwrap();
+        migrations::run_migrations(&db.conn).unwrap();
+        db
+    }
+
+    fn insert_test_track(db: &Database, path: &str) -> QueueItem {
+        let track = crate::db::models::Track {
+            id: None,
+            path: path.to_string(),
+            title: "Queue Track".to_string(),
+            artist: "Artist".to_string(),
+            album: None,
+            genre: None,
+            year: None,
+            duration: 180.0,
+            bitrate: 320,
+            sample_rate: 44100,
+            file_size: 8388608,
+            bpm: None,
+            key: None,
+            rating: None,
+            play_count: 0,
+            last_played: None,
+            date_added: "2024-01-01".to_string(),
+            date_modified: "2024-01-01".to_string(),
+            label: None,
+            isrc: None,
+            beatport_id: None,
+            key_canonical: None,
+            key_index: None,
+        };
+        let track_id = tracks::insert_track(&db.conn, &track).unwrap();
+        QueueItem {
+            track_id,
+            path: path.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_load_queue_empty() {
+        let db = setup_db();
+        assert_eq!(load_queue(&db.conn).unwrap(), None);
+    }
+
+    #[test]
+    fn test_save_and_load_queue() {
+        let mut db = setup_db();
+        let items: Vec<QueueItem> = (0..3)
+            .map(|i| insert_test_track(&db, &format!("/music/q{}.mp3", i)))
+            .collect();
+
+        let queue = SavedQueue {
+            items: items.clone(),
+            order: vec![2, 0, 1],
+            current: Some(1),
+            shuffle: true,
+            repeat_mode: "all".to_string(),
+        };
+        save_queue(&mut db.conn, &queue).unwrap();
+
+        let loaded = load_queue(&db.conn).unwrap().unwrap();
+        assert_eq!(loaded, queue);
+    }
+
+    #[test]
+    fn test_load_queue_after_track_deleted() {
+        let mut db = setup_db();
+        let items: Vec<QueueItem> = (0..3)
+            .map(|i| insert_test_track(&db, &format!("/music/d{}.mp3", i)))
+            .collect();
+
+        let queue = SavedQueue {
+            items: items.clone(),
+            order: vec![0, 1, 2],
+            current: Some(2),
+            shuffle: false,
+            repeat_mode: "off".to_string(),
+        };
+        save_queue(&mut db.conn, &queue).unwrap();
+
+        tracks::delete_track(&db.conn, &items[0].track_id).unwrap();
+
+        let loaded = load_queue(&db.conn).unwrap().unwrap();
+        assert_eq!(loaded.items, vec![items[1].clone(), items[2].clone()]);
+        assert_eq!(loaded.order, vec![0, 1]);
+        // El item actual sigue siendo el mismo track
+        assert_eq!(loaded.current, Some(1));
+    }
+}
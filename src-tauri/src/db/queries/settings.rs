@@ -15,10 +15,16 @@ const DEFAULT_SETTINGS: &[(&str, &str, &str)] = &[
     ("audio.output_device", "default", "string"),
     ("audio.sample_rate", "44100", "number"),
     ("audio.buffer_size", "2048", "number"),
+    ("audio.latency_profile", "normal", "string"),
+    ("audio.bit_perfect", "true", "boolean"),
     // Library
     ("library.auto_scan_on_startup", "false", "boolean"),
     ("library.scan_interval_hours", "0", "number"),
     ("library.import_folder", "", "string"),
+    ("library.key_notation", "musical", "string"),
+    // Analysis
+    ("analysis.bpm_min", "70", "number"),
+    ("analysis.bpm_max", "180", "number"),
     // Conversion
     ("conversion.enabled", "false", "boolean"),
     ("conversion.auto_convert", "false", "boolean"),
@@ -6,6 +6,8 @@ use std::collections::HashSet;
 use std::path::Path;
 use walkdir::WalkDir;
 
+use super::keys::get_key_notation;
+use crate::audio::key::normalize_key;
 use crate::library::metadata::MetadataExtractor;
 use crate::utils::extract_date_from_path;
 
@@ -124,6 +126,7 @@ pub fn consolidate_library(
     // 10. Importar archivos nuevos
     // AIDEV-NOTE: Si falla la extracción de metadatos (ej: UTF-16 BOM corrupto),
     // igual importamos el archivo con metadatos básicos del nombre de archivo
+    let key_notation = get_key_notation(conn)?;
     let mut new_tracks_added = 0;
     let mut metadata_errors = 0;
     let mut insert_errors = 0;
@@ -204,6 +207,13 @@ pub fn consolidate_library(
                     )
                 }
             };
+        let (key, key_canonical) = match key {
+            Some(key) => {
+                let (key, canonical) = normalize_key(&key, key_notation);
+                (Some(key), canonical)
+            }
+            None => (None, None),
+        };
 
         // Insertar nuevo track (funciona tanto con metadata completa como fallback)
         // AIDEV-NOTE: date_added viene del path YYMM, date_modified es CURRENT_TIMESTAMP
@@ -212,8 +222,8 @@ pub fn consolidate_library(
                 id, path, title, artist, album, genre, year,
                 duration, bitrate, sample_rate, file_size,
                 bpm, key, rating, play_count, last_played,
-                date_added, date_modified
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, CURRENT_TIMESTAMP)",
+                date_added, date_modified, key_canonical
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, CURRENT_TIMESTAMP, ?18)",
             params![
                 id,
                 path_str.as_ref(),
@@ -232,6 +242,7 @@ pub fn consolidate_library(
                 0, // play_count inicial
                 None::<String>, // last_played
                 date_added, // extraído del path YYMM
+                key_canonical,
             ],
         );
 
@@ -0,0 +1,64 @@
+//! Notación de tonalidades de la biblioteca
+
+use rusqlite::{params, Connection, Result};
+
+use crate::audio::key::{normalize_key, KeyNotation, MusicalKey};
+use crate::db::queries::settings::{get_setting, upsert_setting};
+
+/// Clave del setting con la notación preferida
+pub const KEY_NOTATION_SETTING: &str = "library.key_notation";
+
+/// Obtiene la notación de tonalidad preferida (musical si no hay setting válido)
+pub fn get_key_notation(conn: &Connection) -> Result<KeyNotation> {
+    Ok(get_setting(conn, KEY_NOTATION_SETTING)?
+        .and_then(|setting| KeyNotation::parse(&setting.value))
+        .unwrap_or_default())
+}
+
+/// Cambia la notación preferida y reescribe `tracks.key` en ella
+///
+/// AIDEV-NOTE: Solo se tocan las pistas con `key_canonical`; las tonalidades
+/// que no se reconocieron se dejan como estaban.
+///
+/// # Retorna
+/// Número de pistas actualizadas
+pub fn set_key_notation(conn: &Connection, notation: KeyNotation) -> Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+    upsert_setting(&tx, KEY_NOTATION_SETTING, notation.as_str(), "string")?;
+
+    let mut updated = 0;
+    for key in MusicalKey::all() {
+        updated += tx.execute(
+            "UPDATE tracks SET key = ?1 WHERE key_canonical = ?2 AND key IS NOT ?1",
+            params![key.format(notation), key.canonical()],
+        )?;
+    }
+
+    tx.commit()?;
+    Ok(updated)
+}
+
+/// Normaliza una tonalidad a la notación preferida
+///
+/// Devuelve `(key, key_canonical)` listos para guardar en `tracks`.
+pub fn normalize_track_key(
+    conn: &Connection,
+    key: Option<&str>,
+) -> Result<(Option<String>, Option<String>)> {
+    match key.map(str::trim).filter(|k| !k.is_empty()) {
+        Some(key) => {
+            let (key, canonical) = normalize_key(key, get_key_notation(conn)?);
+            Ok((Some(key), canonical))
+        }
+        None => Ok((None, None)),
+    }
+}
+
+/// Posición de una tonalidad canónica en la rueda Camelot (`Track::key_index`)
+///
+/// AIDEV-NOTE: "10A" < "1A" como texto; la tabla ordena por este índice.
+pub fn key_wheel_index(key_canonical: Option<&str>) -> Option<u8> {
+    key_canonical
+        .and_then(MusicalKey::parse)
+        .map(|key| key.sort_index())
+}
@@ -3,6 +3,7 @@
 pub mod consolidate;
 pub mod create;
 pub mod delete;
+pub mod keys;
 pub mod read;
 pub mod search;
 pub mod update;
@@ -10,6 +11,9 @@ pub mod update;
 pub use consolidate::{consolidate_library, ConsolidateLibraryResult};
 pub use create::insert_track;
 pub use delete::{delete_track, reset_library, ResetLibraryResult};
+pub use keys::{
+    get_key_notation, key_wheel_index, normalize_track_key, set_key_notation, KEY_NOTATION_SETTING,
+};
 pub use read::{get_all_tracks, get_track, get_tracks_batch};
 pub use search::search_tracks;
 pub use update::{update_track, update_track_metadata};
@@ -17,6 +21,7 @@ pub use update::{update_track, update_track_metadata};
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::audio::key::KeyNotation;
     use crate::db::{models::Track, Database};
 
     fn setup_db() -> Database {
@@ -51,6 +56,8 @@ mod tests {
             label: None,
             isrc: None,
             beatport_id: None,
+            key_canonical: None,
+            key_index: None,
         };
 
         let id = insert_track(&db.conn, &track).unwrap();
@@ -87,6 +94,8 @@ mod tests {
             label: None,
             isrc: None,
             beatport_id: None,
+            key_canonical: None,
+            key_index: None,
         };
 
         insert_track(&db.conn, &track).unwrap();
@@ -122,6 +131,8 @@ mod tests {
             label: None,
             isrc: None,
             beatport_id: None,
+            key_canonical: None,
+            key_index: None,
         };
 
         let id = insert_track(&db.conn, &track).unwrap();
@@ -171,6 +182,8 @@ mod tests {
             label: None,
             isrc: None,
             beatport_id: None,
+            key_canonical: None,
+            key_index: None,
         };
 
         let id = insert_track(&db.conn, &track).unwrap();
@@ -208,6 +221,8 @@ mod tests {
                 label: None,
                 isrc: None,
                 beatport_id: None,
+                key_canonical: None,
+                key_index: None,
             };
             insert_track(&db.conn, &track).unwrap();
         }
@@ -224,4 +239,121 @@ mod tests {
         let tracks_after = get_all_tracks(&db.conn).unwrap();
         assert_eq!(tracks_after.len(), 0);
     }
+
+    #[test]
+    fn test_key_notation() {
+        let db = setup_db();
+
+        let mut track = Track {
+            id: None,
+            path: "/music/a.mp3".to_string(),
+            title: "A".to_string(),
+            artist: "Artist".to_string(),
+            album: None,
+            genre: None,
+            year: None,
+            duration: 180.0,
+            bitrate: 320,
+            sample_rate: 44100,
+            file_size: 8388608,
+            bpm: None,
+            key: Some("A minor".to_string()),
+            rating: None,
+            play_count: 0,
+            last_played: None,
+            date_added: "2024-01-01".to_string(),
+            date_modified: "2024-01-01".to_string(),
+            label: None,
+            isrc: None,
+            beatport_id: None,
+            key_canonical: None,
+            key_index: None,
+        };
+        let a_minor = insert_track(&db.conn, &track).unwrap();
+        track.path = "/music/b.mp3".to_string();
+        track.key = Some("Unknown".to_string());
+        let unknown = insert_track(&db.conn, &track).unwrap();
+
+        let stored = get_track(&db.conn, &a_minor).unwrap();
+        assert_eq!(stored.key.as_deref(), Some("Am"));
+        assert_eq!(stored.key_canonical.as_deref(), Some("8A"));
+        let found = search_tracks(&db.conn, "1m").unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id.as_deref(), Some(a_minor.as_str()));
+
+        // Cambiar la notación reescribe las tonalidades reconocidas
+        assert_eq!(set_key_notation(&db.conn, KeyNotation::OpenKey).unwrap(), 1);
+        assert_eq!(get_key_notation(&db.conn).unwrap(), KeyNotation::OpenKey);
+        assert_eq!(
+            get_track(&db.conn, &a_minor).unwrap().key.as_deref(),
+            Some("1m")
+        );
+        let stored = get_track(&db.conn, &unknown).unwrap();
+        assert_eq!(stored.key.as_deref(), Some("Unknown"));
+        assert_eq!(stored.key_canonical, None);
+
+        // Las escrituras nuevas usan la notación activa
+        update_track_metadata(
+            &db.conn,
+            &unknown,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some("F#m"),
+            None,
+        )
+        .unwrap();
+        let stored = get_track(&db.conn, &unknown).unwrap();
+        assert_eq!(stored.key.as_deref(), Some("4m"));
+        assert_eq!(stored.key_canonical.as_deref(), Some("11A"));
+    }
+
+    #[test]
+    fn test_key_index_sorts_by_wheel() {
+        let db = setup_db();
+
+        let mut track = Track {
+            id: None,
+            path: String::new(),
+            title: "T".to_string(),
+            artist: "Artist".to_string(),
+            album: None,
+            genre: None,
+            year: None,
+            duration: 180.0,
+            bitrate: 320,
+            sample_rate: 44100,
+            file_size: 8388608,
+            bpm: None,
+            key: None,
+            rating: None,
+            play_count: 0,
+            last_played: None,
+            date_added: "2024-01-01".to_string(),
+            date_modified: "2024-01-01".to_string(),
+            label: None,
+            isrc: None,
+            beatport_id: None,
+            key_canonical: None,
+            key_index: None,
+        };
+        for (i, key) in ["10A", "1A", "Am", "1B", "Unknown"].iter().enumerate() {
+            track.path = format!("/music/{i}.mp3");
+            track.key = Some(key.to_string());
+            insert_track(&db.conn, &track).unwrap();
+        }
+
+        // Como texto "10A" < "1A"; el índice sigue la rueda
+        let mut tracks = get_all_tracks(&db.conn).unwrap();
+        tracks.sort_by_key(|t| t.key_index.unwrap_or(u8::MAX));
+        let keys: Vec<_> = tracks
+            .iter()
+            .map(|t| t.key_canonical.as_deref().unwrap_or("-"))
+            .collect();
+        assert_eq!(keys, ["1A", "1B", "8A", "10A", "-"]);
+        assert!(tracks[4].key_index.is_none());
+    }
 }
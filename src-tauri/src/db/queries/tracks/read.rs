@@ -2,6 +2,7 @@
 
 use rusqlite::{Connection, Result};
 
+use super::keys::key_wheel_index;
 use crate::db::models::Track;
 
 /// Obtiene un track por su UUID
@@ -10,7 +11,7 @@ pub fn get_track(conn: &Connection, id: &str) -> Result<Track> {
         "SELECT id, path, title, artist, album, genre, year,
                 duration, bitrate, sample_rate, file_size,
                 bpm, key, rating, play_count, last_played,
-                date_added, date_modified, label, isrc, beatport_id
+                date_added, date_modified, label, isrc, beatport_id, key_canonical
          FROM tracks WHERE id = ?1",
         [id],
         |row| {
@@ -36,6 +37,8 @@ pub fn get_track(conn: &Connection, id: &str) -> Result<Track> {
                 label: row.get(18)?,
                 isrc: row.get(19)?,
                 beatport_id: row.get(20)?,
+                key_canonical: row.get(21)?,
+                key_index: key_wheel_index(row.get::<_, Option<String>>(21)?.as_deref()),
             })
         },
     )
@@ -47,7 +50,7 @@ pub fn get_all_tracks(conn: &Connection) -> Result<Vec<Track>> {
         "SELECT id, path, title, artist, album, genre, year,
                 duration, bitrate, sample_rate, file_size,
                 bpm, key, rating, play_count, last_played,
-                date_added, date_modified, label, isrc, beatport_id
+                date_added, date_modified, label, isrc, beatport_id, key_canonical
          FROM tracks ORDER BY date_added DESC",
     )?;
 
@@ -74,6 +77,8 @@ pub fn get_all_tracks(conn: &Connection) -> Result<Vec<Track>> {
             label: row.get(18)?,
             isrc: row.get(19)?,
             beatport_id: row.get(20)?,
+            key_canonical: row.get(21)?,
+            key_index: key_wheel_index(row.get::<_, Option<String>>(21)?.as_deref()),
         })
     })?;
 
@@ -117,7 +122,7 @@ pub fn get_tracks_batch(conn: &Connection, ids: &[String]) -> Result<Vec<Track>>
         "SELECT id, path, title, artist, album, genre, year,
                 duration, bitrate, sample_rate, file_size,
                 bpm, key, rating, play_count, last_played,
-                date_added, date_modified, label, isrc, beatport_id
+                date_added, date_modified, label, isrc, beatport_id, key_canonical
          FROM tracks WHERE id IN ({})",
         placeholders
     );
@@ -151,6 +156,8 @@ pub fn get_tracks_batch(conn: &Connection, ids: &[String]) -> Result<Vec<Track>>
             label: row.get(18)?,
             isrc: row.get(19)?,
             beatport_id: row.get(20)?,
+            key_canonical: row.get(21)?,
+            key_index: key_wheel_index(row.get::<_, Option<String>>(21)?.as_deref()),
         })
     })?;
 
@@ -188,7 +195,8 @@ mod tests {
                 date_modified TEXT NOT NULL,
                 label TEXT,
                 isrc TEXT,
-                beatport_id INTEGER
+                beatport_id INTEGER,
+                key_canonical TEXT
             )",
             [],
         )
@@ -1,23 +1,29 @@
 //! Operaciones de búsqueda de tracks
 
-use rusqlite::{Connection, Result};
+use rusqlite::{params, Connection, Result};
 
+use super::keys::key_wheel_index;
+use crate::audio::key::MusicalKey;
 use crate::db::models::Track;
 
 /// Busca tracks por título, artista o álbum
+///
+/// Si la búsqueda es una tonalidad (en cualquier notación) también devuelve
+/// las pistas en esa tonalidad.
 pub fn search_tracks(conn: &Connection, query: &str) -> Result<Vec<Track>> {
     let pattern = format!("%{}%", query);
+    let canonical = MusicalKey::parse(query).map(|key| key.canonical());
 
     let mut stmt = conn.prepare(
         "SELECT id, path, title, artist, album, genre, year, duration, bitrate,
                 sample_rate, file_size, bpm, key, rating, play_count, last_played,
-                date_added, date_modified, label, isrc, beatport_id
+                date_added, date_modified, label, isrc, beatport_id, key_canonical
          FROM tracks
-         WHERE title LIKE ?1 OR artist LIKE ?1 OR album LIKE ?1
+         WHERE title LIKE ?1 OR artist LIKE ?1 OR album LIKE ?1 OR key_canonical = ?2
          ORDER BY date_added DESC",
     )?;
 
-    let tracks = stmt.query_map([&pattern], |row| {
+    let tracks = stmt.query_map(params![pattern, canonical], |row| {
         Ok(Track {
             id: row.get(0)?,
             path: row.get(1)?,
@@ -40,6 +46,8 @@ pub fn search_tracks(conn: &Connection, query: &str) -> Result<Vec<Track>> {
             label: row.get(18)?,
             isrc: row.get(19)?,
             beatport_id: row.get(20)?,
+            key_canonical: row.get(21)?,
+            key_index: key_wheel_index(row.get::<_, Option<String>>(21)?.as_deref()),
         })
     })?;
 
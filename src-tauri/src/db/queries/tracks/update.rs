@@ -2,18 +2,24 @@
 
 use rusqlite::{params, Connection, Result};
 
+use super::keys::normalize_track_key;
 use crate::db::models::Track;
 
 /// Actualiza un track existente
+///
+/// Si la tonalidad cambia pasa a ser del usuario (el análisis ya no la pisa).
 pub fn update_track(conn: &Connection, track: &Track) -> Result<()> {
+    let (key, key_canonical) = normalize_track_key(conn, track.key.as_deref())?;
+
     conn.execute(
         "UPDATE tracks SET
             path = ?1, title = ?2, artist = ?3, album = ?4,
             genre = ?5, year = ?6, duration = ?7, bitrate = ?8,
             sample_rate = ?9, file_size = ?10, bpm = ?11, key = ?12,
             rating = ?13, play_count = ?14, last_played = ?15,
-            date_modified = ?16
-         WHERE id = ?17",
+            date_modified = ?16, key_canonical = ?17,
+            key_source = CASE WHEN key IS ?12 THEN key_source ELSE 'user' END
+         WHERE id = ?18",
         params![
             track.path,
             track.title,
@@ -26,11 +32,12 @@ pub fn update_track(conn: &Connection, track: &Track) -> Result<()> {
             track.sample_rate,
             track.file_size,
             track.bpm,
-            track.key,
+            key,
             track.rating,
             track.play_count,
             track.last_played,
             track.date_modified,
+            key_canonical,
             track.id,
         ],
     )?;
@@ -39,7 +46,8 @@ pub fn update_track(conn: &Connection, track: &Track) -> Result<()> {
 }
 
 /// Actualización parcial de metadatos de track
-/// AIDEV-NOTE: Agregado parámetro 'key' para soportar tonalidad musical
+/// AIDEV-NOTE: Agregado parámetro 'key' para soportar tonalidad musical; una
+/// tonalidad puesta aquí es del usuario y el análisis ya no la sustituye
 #[allow(clippy::too_many_arguments)]
 pub fn update_track_metadata(
     conn: &Connection,
@@ -91,8 +99,12 @@ pub fn update_track_metadata(
         params.push(Box::new(b));
     }
     if let Some(k) = key {
+        let (k, canonical) = normalize_track_key(conn, Some(k))?;
         updates.push("key = ?");
-        params.push(Box::new(k.to_string()));
+        params.push(Box::new(k));
+        updates.push("key_canonical = ?");
+        params.push(Box::new(canonical));
+        updates.push("key_source = 'user'");
     }
     if let Some(r) = rating {
         updates.push("rating = ?");
@@ -1,3 +1,4 @@
+pub mod analysis;
 pub mod audio;
 pub mod commands;
 pub mod config;
@@ -5,11 +6,13 @@ pub mod db;
 pub mod library;
 pub mod utils;
 
+use analysis::AnalysisQueue;
 use audio::WaveformState;
-use commands::audio::AudioPlayerState;
+use commands::audio::{AudioPlayerState, PreviewPlayerState};
 use commands::library::LibraryState;
 use db::{create_pool, DbPool};
 use std::sync::Arc;
+use tauri::Manager;
 use utils::paths::{ensure_app_dirs, get_db_path, get_log_path};
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
@@ -70,6 +73,20 @@ pub fn run() {
         }
     };
 
+    // AIDEV-NOTE: Restaurar la cola de reproducción de la sesión anterior
+    // (no arranca la reproducción, solo deja la cola lista)
+    let player_state = AudioPlayerState::new();
+    match db_pool.get().map_err(|e| e.to_string()).and_then(|conn| {
+        db::queries::load_queue(&conn).map_err(|e| e.to_string())
+    }) {
+        Ok(Some(saved)) => {
+            log::info!("📋 Cola restaurada: {} tracks", saved.items.len());
+            player_state.restore_queue(saved);
+        }
+        Ok(None) => {}
+        Err(e) => log::warn!("⚠️ No se pudo restaurar la cola: {}", e),
+    }
+
     // Inicializar estado de waveform
     let waveform_state = Arc::new(WaveformState::new());
 
@@ -96,10 +113,30 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
-        .manage(AudioPlayerState::new()) // AudioPlayer se inicializa lazy al primer play
+        .manage(player_state) // AudioPlayer se inicializa lazy al primer play
+        .manage(PreviewPlayerState::new()) // Pre-escucha en auriculares (lazy)
         .manage(LibraryState::new())
         .manage(waveform_state)
         .manage(db_pool) // AIDEV-NOTE: Pool unificado para todos los comandos de DB
+        .setup(|app| {
+            // Monitor de hot-plug: se guarda como estado para que viva con la app
+            match commands::audio::spawn_device_monitor(app.handle().clone()) {
+                Ok(monitor) => {
+                    app.manage(monitor);
+                }
+                Err(e) => log::warn!("⚠️ Monitor de dispositivos no disponible: {}", e),
+            }
+
+            // Cola de análisis en background (retoma lo pendiente de la sesión anterior)
+            let pool = app.state::<DbPool>().inner().clone();
+            match AnalysisQueue::start(app.handle().clone(), pool) {
+                Ok(queue) => {
+                    app.manage(Arc::new(queue));
+                }
+                Err(e) => log::warn!("⚠️ Cola de análisis no disponible: {}", e),
+            }
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             greet,
             // Audio commands
@@ -111,11 +148,41 @@ pub fn run() {
             commands::audio::get_playback_position,
             commands::audio::set_playback_volume,
             commands::audio::get_audio_devices,
+            commands::audio::get_output_settings,
+            commands::audio::set_output_settings,
             commands::audio::set_audio_device,
             commands::audio::seek_to_position,
+            commands::audio::enqueue_next_track,
+            commands::audio::get_crossfade_settings,
+            commands::audio::set_crossfade,
+            commands::audio::get_tempo_settings,
+            commands::audio::set_playback_tempo,
+            commands::audio::get_normalization_settings,
+            commands::audio::set_normalization,
+            commands::audio::get_dsp_settings,
+            commands::audio::set_dsp_settings,
+            commands::audio::get_eq_presets,
+            commands::audio::save_eq_preset,
+            commands::audio::delete_eq_preset,
+            commands::audio::apply_eq_preset,
+            commands::audio::activate_loop,
+            commands::audio::deactivate_loop,
+            commands::audio::resize_loop,
+            commands::audio::jump_to_cue,
+            commands::audio::preview_track,
+            commands::audio::preview_pause,
+            commands::audio::preview_resume,
+            commands::audio::preview_stop,
+            commands::audio::preview_seek,
+            commands::audio::set_preview_volume,
+            commands::audio::get_preview_device,
+            commands::audio::set_preview_device,
             commands::audio::get_waveform,
+            commands::audio::get_waveform_range,
             commands::audio::cancel_waveform,
             commands::audio::clear_waveform_cache,
+            commands::audio::get_waveform_cache_stats,
+            commands::audio::gc_waveform_cache,
             commands::audio::decode_audio_metadata,
             commands::audio::read_audio_file,
             commands::audio::allow_asset_file,
@@ -145,24 +212,60 @@ pub fn run() {
             commands::playlists::remove_track_from_playlist,
             commands::playlists::reorder_playlist_tracks,
             commands::playlists::get_playlist_tracks_cmd,
+            // History commands
+            commands::history::get_recently_played,
+            commands::history::get_most_played,
+            commands::history::get_track_history,
+            commands::history::get_play_threshold,
+            commands::history::set_play_threshold,
+            // Queue commands
+            commands::queue::get_queue,
+            commands::queue::set_queue,
+            commands::queue::play_playlist,
+            commands::queue::add_to_queue,
+            commands::queue::remove_from_queue,
+            commands::queue::clear_queue,
+            commands::queue::play_queue_index,
+            commands::queue::queue_next,
+            commands::queue::queue_previous,
+            commands::queue::set_queue_shuffle,
+            commands::queue::set_queue_repeat,
             // Analysis commands
             commands::analysis::analyze_beatgrid,
             commands::analysis::get_beatgrid,
+            commands::analysis::get_beat_positions,
             commands::analysis::update_beatgrid_offset,
             commands::analysis::delete_beatgrid,
+            commands::analysis::analyze_loudness,
+            commands::analysis::get_loudness,
+            commands::analysis::analyze_key,
+            commands::analysis::get_key_analysis,
+            commands::analysis::analyze_phrases,
+            commands::analysis::get_phrases,
             commands::analysis::create_cue_point,
             commands::analysis::get_cue_points,
+            commands::analysis::analyze_cues,
             commands::analysis::update_cue_point,
             commands::analysis::delete_cue_point,
             commands::analysis::create_loop,
             commands::analysis::get_loops,
             commands::analysis::update_loop,
             commands::analysis::delete_loop,
+            commands::analysis::analyze_library,
+            commands::analysis::enqueue_analysis,
+            commands::analysis::prioritize_analysis,
+            commands::analysis::pause_analysis,
+            commands::analysis::resume_analysis,
+            commands::analysis::get_analysis_status,
+            commands::analysis::get_failed_analysis_jobs,
+            commands::analysis::clear_analysis_queue,
             // Settings commands
             commands::settings::get_setting,
             commands::settings::get_all_settings,
             commands::settings::update_setting,
             commands::settings::reset_settings,
+            commands::settings::get_key_notation,
+            commands::settings::set_key_notation,
             // Conversion commands
             commands::conversion::convert_track_to_mp3,
             commands::conversion::batch_convert_to_mp3,
@@ -6,6 +6,8 @@
 use std::path::Path;
 use std::sync::Arc;
 
+use crate::audio::key::{normalize_key, KeyNotation};
+
 use super::super::client::BeatportClient;
 use super::super::error::BeatportError;
 use super::super::models::{BeatportTags, BeatportTrack, FixTagsResult};
@@ -15,20 +17,35 @@ use super::writer::{write_artwork_only, write_tags};
 /// Tagger que aplica metadatos de Beatport a archivos locales
 pub struct BeatportTagger {
     pub(super) client: Arc<BeatportClient>,
+    /// Notación en la que se escribe la tonalidad (TKEY)
+    pub(super) key_notation: KeyNotation,
 }
 
 impl BeatportTagger {
     /// Crea una nueva instancia del tagger
     pub fn new(client: Arc<BeatportClient>) -> Self {
-        Self { client }
+        Self {
+            client,
+            key_notation: KeyNotation::default(),
+        }
     }
 
     /// Crea un tagger con un cliente nuevo
     pub fn with_new_client() -> Result<Self, BeatportError> {
         let client = BeatportClient::new()?;
-        Ok(Self {
-            client: Arc::new(client),
-        })
+        Ok(Self::new(Arc::new(client)))
+    }
+
+    /// Usa la notación de tonalidad indicada al escribir tags
+    pub fn with_key_notation(mut self, notation: KeyNotation) -> Self {
+        self.key_notation = notation;
+        self
+    }
+
+    /// Pasa la tonalidad de Beatport ("A Minor") a la notación configurada
+    fn apply_key_notation(&self, mut tags: BeatportTags) -> BeatportTags {
+        tags.key = tags.key.map(|key| normalize_key(&key, self.key_notation).0);
+        tags
     }
 
     /// Arregla los tags de un track individual
@@ -83,8 +100,13 @@ impl BeatportTagger {
         }
 
         // 4. Aplicar lógica de merge
-        let merged_tags =
-            merge_tags(&beatport_tags, current_bpm, current_genre, current_album, current_year);
+        let merged_tags = self.apply_key_notation(merge_tags(
+            &beatport_tags,
+            current_bpm,
+            current_genre,
+            current_album,
+            current_year,
+        ));
 
         // 5. Escribir tags al archivo
         match write_tags(file_path, &merged_tags) {
@@ -199,13 +221,13 @@ impl BeatportTagger {
         }
 
         // 3. Aplicar lógica de merge (respeta BPM local si existe)
-        let merged_tags = merge_tags(
+        let merged_tags = self.apply_key_notation(merge_tags(
             &beatport_tags,
             current_bpm,
             None, // genre - siempre aplicamos de Beatport
             None, // album - siempre aplicamos de Beatport
             None, // year - siempre aplicamos de Beatport
-        );
+        ));
 
         // 4. Escribir tags al archivo
         match write_tags(file_path, &merged_tags) {
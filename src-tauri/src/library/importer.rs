@@ -1,12 +1,14 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 
 use super::error::Result;
-use super::metadata::MetadataExtractor;
+use super::metadata::{MetadataExtractor, TrackMetadata};
 use super::scanner::LibraryScanner;
+use crate::analysis::{AnalysisKind, AnalysisQueue, PRIORITY_NORMAL};
 use crate::db::models::Track;
 use crate::db::{queries, DbPool};
 
@@ -124,6 +126,7 @@ impl LibraryImporter {
         // Fase 2: Importar archivos
         let mut imported = 0;
         let mut failed = 0;
+        let mut imported_ids = Vec::new();
         let mut last_progress_time = Instant::now();
 
         for (idx, file_path) in audio_files.iter().enumerate() {
@@ -134,11 +137,16 @@ impl LibraryImporter {
                     match self.metadata_to_track(&metadata, file_path) {
                         Ok(track) => {
                             // Insertar en DB
-                            if let Err(e) = queries::insert_track(&conn, &track) {
-                                log::error!("Error insertando pista {}: {}", track.path, e);
-                                failed += 1;
-                            } else {
-                                imported += 1;
+                            match queries::insert_track(&conn, &track) {
+                                Ok(track_id) => {
+                                    imported += 1;
+                                    Self::import_replaygain(&conn, &track_id, &metadata);
+                                    imported_ids.push(track_id);
+                                }
+                                Err(e) => {
+                                    log::error!("Error insertando pista {}: {}", track.path, e);
+                                    failed += 1;
+                                }
                             }
                         }
                         Err(_) => {
@@ -178,10 +186,44 @@ impl LibraryImporter {
         };
 
         self.emit_complete(&app_handle, result.clone());
+        Self::enqueue_analysis(&app_handle, &imported_ids);
 
         Ok(result)
     }
 
+    /// Encola el análisis en background de las pistas importadas
+    fn enqueue_analysis(app_handle: &AppHandle, track_ids: &[String]) {
+        if track_ids.is_empty() {
+            return;
+        }
+        let Some(queue) = app_handle.try_state::<Arc<AnalysisQueue>>() else {
+            return;
+        };
+        if let Err(e) = queue.enqueue(Some(track_ids), &AnalysisKind::ALL, PRIORITY_NORMAL, false) {
+            log::warn!("⚠️ Error encolando análisis de pistas importadas: {}", e);
+        }
+    }
+
+    /// Guarda el ReplayGain de los tags como loudness (source 'tags')
+    ///
+    /// AIDEV-NOTE: Permite normalizar pistas aún no analizadas; un análisis
+    /// posterior con `analyze_loudness` reemplaza estos valores.
+    fn import_replaygain(conn: &rusqlite::Connection, track_id: &str, metadata: &TrackMetadata) {
+        let Some(rg) = metadata.replay_gain else {
+            return;
+        };
+        if let Err(e) = queries::upsert_loudness(
+            conn,
+            track_id,
+            rg.integrated_lufs(),
+            rg.peak_db(),
+            None,
+            "tags",
+        ) {
+            log::warn!("⚠️ Error guardando ReplayGain de {}: {}", metadata.path, e);
+        }
+    }
+
     /// Convierte metadatos extraídos a modelo Track
     fn metadata_to_track(
         &self,
@@ -227,6 +269,8 @@ impl LibraryImporter {
             label: None,        // Se obtendrá de Beatport
             isrc: None,         // Se obtendrá de Beatport
             beatport_id: None,  // Se establecerá al fixear con Beatport
+            key_canonical: None, // Se calcula al insertar
+            key_index: None,
         })
     }
 
@@ -338,6 +382,7 @@ mod tests {
             channels: 2,
             format: "wav".to_string(),
             artwork: None,
+            replay_gain: None,
         };
 
         let result = importer.metadata_to_track(&metadata, &file_path);
@@ -391,6 +436,7 @@ mod tests {
             channels: 2,
             format: "wav".to_string(),
             artwork: None,
+            replay_gain: None,
         };
 
         let result = importer.metadata_to_track(&metadata, &file_path);
@@ -427,6 +473,7 @@ mod tests {
             channels: 2,
             format: "wav".to_string(),
             artwork: None,
+            replay_gain: None,
         };
 
         let result = importer.metadata_to_track(&metadata, &file_path);
@@ -1,6 +1,7 @@
 use super::super::error::{LibraryError, Result};
 use super::helpers::*;
 use super::models::TrackMetadata;
+use super::replaygain::{get_itunnorm_from_mp3_file, get_replaygain};
 use lofty::config::ParseOptions;
 use lofty::file::{AudioFile, TaggedFileExt};
 use lofty::probe::Probe;
@@ -99,6 +100,13 @@ impl MetadataExtractor {
             )
         };
 
+        // ReplayGain de los tags (iTunNORM de ID3 aunque no haya tag primario)
+        let replay_gain = match tag {
+            Some(tag) => get_replaygain(path, tag),
+            None if format == "mp3" => get_itunnorm_from_mp3_file(path),
+            None => None,
+        };
+
         // Fallback: Si lofty no pudo extraer BPM y es MP3, intentar con id3 crate
         if bpm.is_none() && format == "mp3" {
             bpm = get_bpm_from_mp3_file(path);
@@ -121,6 +129,7 @@ impl MetadataExtractor {
             channels,
             format,
             artwork: None, // TODO: Extraer artwork en futuro
+            replay_gain,
         })
     }
 
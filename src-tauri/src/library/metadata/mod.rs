@@ -7,6 +7,7 @@ pub mod artwork;
 pub mod extractor;
 pub mod helpers;
 pub mod models;
+pub mod replaygain;
 pub mod writer;
 
 #[cfg(test)]
@@ -16,4 +17,5 @@ mod tests;
 pub use artwork::{extract_artwork, picture_to_data_uri};
 pub use extractor::MetadataExtractor;
 pub use models::TrackMetadata;
-pub use writer::{write_metadata, write_rating_to_mp3_file};
+pub use replaygain::{write_replaygain, ReplayGain, REPLAYGAIN_REFERENCE_LUFS};
+pub use writer::{write_key, write_metadata, write_rating_to_mp3_file};
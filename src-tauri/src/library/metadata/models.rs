@@ -52,4 +52,8 @@ pub struct TrackMetadata {
 
     /// Artwork (imagen de portada) en base64
     pub artwork: Option<String>,
+
+    /// Ganancia ReplayGain (REPLAYGAIN_TRACK_* o iTunNORM)
+    #[serde(default)]
+    pub replay_gain: Option<super::replaygain::ReplayGain>,
 }
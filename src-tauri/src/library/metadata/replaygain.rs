@@ -0,0 +1,260 @@
+use super::super::error::{LibraryError, Result};
+use id3::TagLike;
+use lofty::config::{ParseOptions, WriteOptions};
+use lofty::file::{TaggedFile, TaggedFileExt};
+use lofty::probe::Probe;
+use lofty::tag::{ItemKey, Tag};
+use std::path::Path;
+
+/// Nivel de referencia de ReplayGain 2.0 (LUFS)
+pub const REPLAYGAIN_REFERENCE_LUFS: f64 = -18.0;
+
+/// Descripción del frame COMM que usa iTunes para Sound Check
+const ITUNNORM_DESCRIPTION: &str = "iTunNORM";
+
+/// Clave freeform de iTunNORM en archivos MP4
+const ITUNNORM_MP4_KEY: &str = "----:com.apple.iTunes:iTunNORM";
+
+/// Ganancia de track estilo ReplayGain
+///
+/// AIDEV-NOTE: Se lee de REPLAYGAIN_TRACK_GAIN/PEAK (todos los formatos vía lofty)
+/// o, si no existen, del iTunNORM de iTunes (COMM en ID3, freeform en MP4).
+/// Convierte en ambos sentidos con el loudness EBU R128 usando la referencia de
+/// ReplayGain 2.0 (-18 LUFS).
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayGain {
+    /// Ganancia a aplicar (dB)
+    pub track_gain_db: f64,
+    /// Pico del track (lineal, 1.0 = 0 dBFS)
+    pub track_peak: Option<f64>,
+}
+
+impl ReplayGain {
+    /// Construye la ganancia a partir de un análisis EBU R128
+    pub fn from_loudness(integrated_lufs: f64, true_peak_dbtp: f64) -> Self {
+        Self {
+            track_gain_db: REPLAYGAIN_REFERENCE_LUFS - integrated_lufs,
+            track_peak: Some(10f64.powf(true_peak_dbtp / 20.0)),
+        }
+    }
+
+    /// Integrated loudness equivalente (LUFS)
+    pub fn integrated_lufs(&self) -> f64 {
+        REPLAYGAIN_REFERENCE_LUFS - self.track_gain_db
+    }
+
+    /// Pico en dB (o 0 dBTP si el tag no lo incluye)
+    pub fn peak_db(&self) -> f64 {
+        self.track_peak
+            .filter(|&p| p > 0.0)
+            .map(|p| 20.0 * p.log10())
+            .unwrap_or(0.0)
+    }
+
+    /// Parsea un valor de REPLAYGAIN_TRACK_GAIN ("-6.52 dB")
+    fn parse_gain(value: &str) -> Option<f64> {
+        let number = value.trim().trim_end_matches("dB").trim_end_matches("db");
+        number.trim().parse::<f64>().ok().filter(|g| g.is_finite())
+    }
+
+    /// Parsea un valor de iTunNORM (10 valores hexadecimales)
+    ///
+    /// Los dos primeros son la ganancia por canal en escala 1/1000 W
+    /// (`gain = -10 * log10(valor / 1000)`); el 7º y 8º el pico por canal (0-32768).
+    fn parse_itunnorm(value: &str) -> Option<Self> {
+        let values: Vec<u32> = value
+            .split_whitespace()
+            .map(|v| u32::from_str_radix(v, 16))
+            .collect::<std::result::Result<_, _>>()
+            .ok()?;
+        if values.len() < 10 {
+            return None;
+        }
+
+        let level = values[0].max(values[1]);
+        if level == 0 {
+            return None;
+        }
+        let track_gain_db = -10.0 * (level as f64 / 1000.0).log10();
+
+        let peak = values[6].max(values[7]);
+        let track_peak = (peak > 0).then(|| peak as f64 / 32768.0);
+
+        Some(Self {
+            track_gain_db,
+            track_peak,
+        })
+    }
+
+    /// Genera el valor iTunNORM equivalente
+    fn to_itunnorm(self) -> String {
+        let factor = 10f64.powf(-self.track_gain_db / 10.0);
+        let level_1000 = (1000.0 * factor).round().clamp(1.0, 65534.0) as u32;
+        let level_2500 = (2500.0 * factor).round().clamp(1.0, 65534.0) as u32;
+        let peak = (self.track_peak.unwrap_or(1.0) * 32768.0)
+            .round()
+            .clamp(0.0, 32768.0) as u32;
+
+        [
+            level_1000, level_1000, level_2500, level_2500, 0, 0, peak, peak, 0, 0,
+        ]
+        .iter()
+        .map(|v| format!(" {:08X}", v))
+        .collect()
+    }
+}
+
+/// Extrae la ganancia ReplayGain del tag (o iTunNORM como fallback)
+pub fn get_replaygain(path: &Path, tag: &Tag) -> Option<ReplayGain> {
+    if let Some(track_gain_db) = tag
+        .get_string(&ItemKey::ReplayGainTrackGain)
+        .and_then(ReplayGain::parse_gain)
+    {
+        let track_peak = tag
+            .get_string(&ItemKey::ReplayGainTrackPeak)
+            .and_then(|p| p.trim().parse::<f64>().ok())
+            .filter(|p| *p > 0.0);
+        return Some(ReplayGain {
+            track_gain_db,
+            track_peak,
+        });
+    }
+
+    if let Some(rg) = tag
+        .get_string(&ItemKey::Unknown(ITUNNORM_MP4_KEY.to_string()))
+        .and_then(ReplayGain::parse_itunnorm)
+    {
+        return Some(rg);
+    }
+
+    get_itunnorm_from_mp3_file(path)
+}
+
+/// Extrae iTunNORM del frame COMM de un MP3 usando id3 crate
+///
+/// AIDEV-NOTE: lofty solo expone el primer COMM como comentario genérico,
+/// así que buscamos el de descripción "iTunNORM" directamente.
+pub fn get_itunnorm_from_mp3_file(path: &Path) -> Option<ReplayGain> {
+    let tag = id3::Tag::read_from_path(path).ok()?;
+
+    tag.comments()
+        .find(|comment| comment.description == ITUNNORM_DESCRIPTION)
+        .and_then(|comment| ReplayGain::parse_itunnorm(&comment.text))
+}
+
+/// Escribe REPLAYGAIN_TRACK_GAIN/PEAK y, en MP3, el iTunNORM equivalente
+///
+/// # Arguments
+/// * `path` - Ruta al archivo de audio
+/// * `replay_gain` - Ganancia y pico del track
+pub fn write_replaygain(path: &Path, replay_gain: &ReplayGain) -> Result<()> {
+    let parse_options = ParseOptions::new().read_properties(false);
+
+    let mut tagged_file: TaggedFile = Probe::open(path)
+        .map_err(|e: lofty::error::LoftyError| {
+            LibraryError::MetadataExtractionFailed(e.to_string())
+        })?
+        .options(parse_options)
+        .read()
+        .map_err(|e: lofty::error::LoftyError| {
+            LibraryError::MetadataExtractionFailed(e.to_string())
+        })?;
+
+    let tag = match tagged_file.primary_tag_mut() {
+        Some(t) => t,
+        None => {
+            let tag_type = super::helpers::get_preferred_tag_type(tagged_file.file_type());
+            tagged_file.insert_tag(Tag::new(tag_type));
+            tagged_file.primary_tag_mut().unwrap()
+        }
+    };
+
+    tag.insert_text(
+        ItemKey::ReplayGainTrackGain,
+        format!("{:.2} dB", replay_gain.track_gain_db),
+    );
+    if let Some(peak) = replay_gain.track_peak {
+        tag.insert_text(ItemKey::ReplayGainTrackPeak, format!("{:.6}", peak));
+    }
+
+    tagged_file
+        .save_to_path(path, WriteOptions::default())
+        .map_err(|e: lofty::error::LoftyError| {
+            LibraryError::MetadataExtractionFailed(format!("Failed to write ReplayGain: {}", e))
+        })?;
+
+    // AIDEV-NOTE: Igual que POPM, iTunNORM se escribe con id3 crate DESPUÉS de lofty
+    if path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("mp3"))
+    {
+        write_itunnorm_to_mp3_file(path, replay_gain)?;
+    }
+
+    Ok(())
+}
+
+/// Escribe el frame COMM "iTunNORM" en un MP3
+fn write_itunnorm_to_mp3_file(path: &Path, replay_gain: &ReplayGain) -> Result<()> {
+    let mut tag = id3::Tag::read_from_path(path).unwrap_or_else(|_| id3::Tag::new());
+
+    tag.remove_comment(Some(ITUNNORM_DESCRIPTION), None);
+    tag.add_frame(id3::frame::Comment {
+        lang: "eng".to_string(),
+        description: ITUNNORM_DESCRIPTION.to_string(),
+        text: replay_gain.to_itunnorm(),
+    });
+
+    tag.write_to_path(path, id3::Version::Id3v24).map_err(|e| {
+        LibraryError::MetadataExtractionFailed(format!(
+            "Failed to write iTunNORM to {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gain() {
+        assert_eq!(ReplayGain::parse_gain("-6.52 dB"), Some(-6.52));
+        assert_eq!(ReplayGain::parse_gain("+1.20 dB"), Some(1.2));
+        assert_eq!(ReplayGain::parse_gain("3.1"), Some(3.1));
+        assert_eq!(ReplayGain::parse_gain("loud"), None);
+    }
+
+    #[test]
+    fn test_loudness_roundtrip() {
+        let rg = ReplayGain::from_loudness(-9.0, -0.5);
+        assert!((rg.track_gain_db - -9.0).abs() < 1e-9);
+        assert!((rg.integrated_lufs() - -9.0).abs() < 1e-9);
+        assert!((rg.peak_db() - -0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_itunnorm_roundtrip() {
+        let rg = ReplayGain {
+            track_gain_db: -7.25,
+            track_peak: Some(0.98),
+        };
+        let norm = rg.to_itunnorm();
+        assert_eq!(norm.split_whitespace().count(), 10);
+
+        let parsed = ReplayGain::parse_itunnorm(&norm).unwrap();
+        assert!((parsed.track_gain_db - -7.25).abs() < 0.01);
+        assert!((parsed.track_peak.unwrap() - 0.98).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_parse_itunnorm_invalid() {
+        assert!(ReplayGain::parse_itunnorm("00000A2C 00000A2C").is_none());
+        assert!(ReplayGain::parse_itunnorm("not hex at all x y z w v u t").is_none());
+    }
+}
@@ -92,6 +92,44 @@ pub fn write_metadata(path: &Path, metadata: &TrackMetadata) -> Result<()> {
     Ok(())
 }
 
+/// Escribe solo la tonalidad (TKEY en ID3v2, INITIALKEY en Vorbis/APE)
+///
+/// # Arguments
+/// * `path` - Ruta al archivo de audio
+/// * `key` - Tonalidad en notación estándar ("Am", "F#")
+pub fn write_key(path: &Path, key: &str) -> Result<()> {
+    let parse_options = ParseOptions::new().read_properties(false);
+
+    let mut tagged_file: TaggedFile = Probe::open(path)
+        .map_err(|e: lofty::error::LoftyError| {
+            LibraryError::MetadataExtractionFailed(e.to_string())
+        })?
+        .options(parse_options)
+        .read()
+        .map_err(|e: lofty::error::LoftyError| {
+            LibraryError::MetadataExtractionFailed(e.to_string())
+        })?;
+
+    let tag = match tagged_file.primary_tag_mut() {
+        Some(t) => t,
+        None => {
+            let tag_type = get_preferred_tag_type(tagged_file.file_type());
+            tagged_file.insert_tag(Tag::new(tag_type));
+            tagged_file.primary_tag_mut().unwrap()
+        }
+    };
+
+    tag.insert_text(ItemKey::InitialKey, key.to_string());
+
+    tagged_file
+        .save_to_path(path, WriteOptions::default())
+        .map_err(|e: lofty::error::LoftyError| {
+            LibraryError::MetadataExtractionFailed(format!("Failed to write key: {}", e))
+        })?;
+
+    Ok(())
+}
+
 /// Escribe rating al tag ID3v2 usando frame POPM con id3 crate
 /// Replica el comportamiento de UpdateTrackRating en TypeScript
 ///
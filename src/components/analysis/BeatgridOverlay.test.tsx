@@ -123,6 +123,32 @@ describe('BeatgridOverlay', () => {
     expect(svg).toHaveClass('custom-class');
   });
 
+  it('debería usar las posiciones del backend si las hay', () => {
+    // Grid dinámico: 120 BPM y luego 60 BPM desde el segundo 2
+    const beats = [0, 0.5, 1, 1.5, 2, 3, 4, 5];
+    const { container } = render(
+      <BeatgridOverlay {...defaultProps} duration={8} width={800} beats={beats} />
+    );
+
+    const lines = container.querySelectorAll('line');
+    expect(lines.length).toBe(beats.length);
+    expect(parseFloat(lines[5].getAttribute('x1')!)).toBeCloseTo(300, 5);
+    expect(lines[4].getAttribute('stroke-width')).toBe('2');
+  });
+
+  it('debería numerar los beats desde el offset con posiciones del backend', () => {
+    const beats = [0.25, 0.75, 1.25, 1.75, 2.25];
+    const { container } = render(
+      <BeatgridOverlay {...defaultProps} offset={1.25} beats={beats} showBeatNumbers />
+    );
+
+    const lines = container.querySelectorAll('line');
+    // Beats -1, 0, 1, 2, 3: el downbeat es el del offset
+    expect(lines[2].getAttribute('stroke-width')).toBe('2');
+    expect(lines[0].getAttribute('stroke-width')).toBe('1');
+    expect(container.querySelector('text')?.textContent).toBe('1');
+  });
+
   it('debería calcular posiciones correctas para diferentes BPMs', () => {
     const { container: bpm60 } = render(
       <BeatgridOverlay {...defaultProps} bpm={60} duration={60} />
@@ -1,6 +1,7 @@
 /**
  * Overlay de beatgrid sobre waveform
- * Muestra grid vertical de beats basado en BPM y offset
+ * Muestra grid vertical de beats: las posiciones del backend si las hay
+ * (siguen los cambios de tempo) o extrapoladas desde BPM y offset
  */
 
 import { useMemo } from 'react';
@@ -13,6 +14,8 @@ export interface BeatgridOverlayProps {
   bpm: number;
   /** Offset del primer beat en segundos */
   offset: number;
+  /** Posiciones de los beats en segundos (`get_beat_positions`) */
+  beats?: number[];
   /** Ancho del contenedor en pixels */
   width: number;
   /** Alto del contenedor en pixels */
@@ -55,10 +58,35 @@ const calculateBeatPositions = (
   return beats;
 };
 
+/**
+ * Convierte las posiciones del backend en posiciones del timeline
+ *
+ * AIDEV-NOTE: El grid empieza en 0 aunque el offset sea posterior; el beat 1
+ * es el del offset para que los downbeats coincidan con el cálculo por BPM
+ */
+const mapBeatPositions = (
+  beatTimes: number[],
+  duration: number,
+  offset: number,
+  width: number
+): Array<{ position: number; beatNumber: number }> => {
+  if (duration <= 0 || width <= 0) {
+    return [];
+  }
+
+  const visible = beatTimes.filter((time) => time >= 0 && time < duration);
+  const beforeOffset = visible.filter((time) => time < offset - 1e-3).length;
+  return visible.map((time, index) => ({
+    position: (time / duration) * width,
+    beatNumber: index - beforeOffset + 1,
+  }));
+};
+
 export const BeatgridOverlay: React.FC<BeatgridOverlayProps> = ({
   duration,
   bpm,
   offset,
+  beats: beatTimes,
   width,
   height,
   showBeatNumbers = false,
@@ -66,8 +94,11 @@ export const BeatgridOverlay: React.FC<BeatgridOverlayProps> = ({
   className,
 }) => {
   const beats = useMemo(
-    () => calculateBeatPositions(duration, bpm, offset, width),
-    [duration, bpm, offset, width]
+    () =>
+      beatTimes
+        ? mapBeatPositions(beatTimes, duration, offset, width)
+        : calculateBeatPositions(duration, bpm, offset, width),
+    [beatTimes, duration, bpm, offset, width]
   );
 
   if (beats.length === 0) {
@@ -88,7 +119,7 @@ export const BeatgridOverlay: React.FC<BeatgridOverlayProps> = ({
     >
       {beats.map(({ position, beatNumber }) => {
         // Destacar cada 4 beats (downbeat)
-        const isDownbeat = beatNumber % 4 === 1;
+        const isDownbeat = (((beatNumber - 1) % 4) + 4) % 4 === 0;
         const strokeWidth = isDownbeat ? 2 : 1;
         const opacity = isDownbeat ? lineOpacity * 1.5 : lineOpacity;
 
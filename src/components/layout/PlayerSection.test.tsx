@@ -136,6 +136,12 @@ describe("PlayerSection", () => {
       isSuccess: true,
     } as any);
 
+    vi.mocked(useAnalysisHook.useGetBeatPositions).mockReturnValue({
+      data: undefined,
+      isLoading: false,
+      isSuccess: true,
+    } as any);
+
     vi.mocked(useAnalysisHook.useAnalyzeBeatgrid).mockReturnValue({
       mutateAsync: mockAnalyzeBeatgrid,
       isPending: false,
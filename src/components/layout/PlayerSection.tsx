@@ -16,6 +16,7 @@ import { useAudioPlayer } from "../../hooks/useAudioPlayer";
 import { useArtwork } from "../../hooks/useArtwork";
 import {
   useGetBeatgrid,
+  useGetBeatPositions,
   useAnalyzeBeatgrid,
 } from "../../hooks/useAnalysis";
 import { WaveformCanvas } from "../WaveformCanvas";
@@ -65,6 +66,10 @@ export const PlayerSection = ({ track, tracks = [], onTrackChange }: PlayerSecti
 
   // Analysis hooks - solo cargar si hay track
   const { data: beatgrid } = useGetBeatgrid(trackId);
+  const { data: beatPositions } = useGetBeatPositions(
+    trackId,
+    track?.duration ?? 0,
+  );
   const analyzeBeatgrid = useAnalyzeBeatgrid();
 
   // Actualizar dimensiones del waveform
@@ -326,6 +331,7 @@ export const PlayerSection = ({ track, tracks = [], onTrackChange }: PlayerSecti
             duration={track?.duration ?? 0}
             bpm={beatgrid.bpm}
             offset={beatgrid.offset}
+            beats={beatPositions}
             width={waveformDimensions.width}
             height={waveformDimensions.height}
             confidence={beatgrid.confidence}
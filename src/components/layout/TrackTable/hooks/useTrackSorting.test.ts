@@ -0,0 +1,36 @@
+/**
+ * Tests para useTrackSorting hook
+ * Ordenación de la tabla de tracks por columna
+ */
+
+import { describe, it, expect } from 'vitest';
+import { renderHook } from '@testing-library/react';
+import { useTrackSorting } from './useTrackSorting';
+import type { Track } from '../../../../types/library';
+
+const track = (key?: string, keyIndex?: number): Track => ({
+  path: `/music/${key ?? 'none'}.mp3`,
+  title: key ?? 'none',
+  artist: 'Artist',
+  duration: 180,
+  key,
+  keyIndex,
+  dateAdded: '2024-01-01',
+  bitrate: 320,
+  sampleRate: 44100,
+  fileSize: 0,
+  playCount: 0,
+  dateModified: '2024-01-01',
+});
+
+describe('useTrackSorting', () => {
+  it('debe ordenar por tonalidad según la rueda Camelot', () => {
+    const tracks = [track('10A', 18), track(), track('1A', 0), track('8A', 14)];
+
+    const { result } = renderHook(() =>
+      useTrackSorting({ tracks, sortColumn: 'key', sortDirection: 'asc' })
+    );
+
+    expect(result.current.map((t) => t.key)).toEqual(['1A', '8A', '10A', undefined]);
+  });
+});
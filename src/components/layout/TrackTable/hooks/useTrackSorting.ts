@@ -71,8 +71,10 @@ export const useTrackSorting = ({ tracks, sortColumn, sortDirection }: UseTrackS
           bValue = (b.genre ?? '').toLowerCase();
           break;
         case 'key':
-          aValue = (a.key ?? '').toLowerCase();
-          bValue = (b.key ?? '').toLowerCase();
+          // AIDEV-NOTE: Orden de la rueda Camelot ("10A" va después de "1A");
+          // las pistas sin tonalidad reconocida van al final
+          aValue = a.keyIndex ?? Number.MAX_SAFE_INTEGER;
+          bValue = b.keyIndex ?? Number.MAX_SAFE_INTEGER;
           break;
       }
 
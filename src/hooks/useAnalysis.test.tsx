@@ -14,6 +14,7 @@ import React from 'react';
 import {
   useAnalyzeBeatgrid,
   useGetBeatgrid,
+  useGetBeatPositions,
   useUpdateBeatgridOffset,
   useDeleteBeatgrid,
   useCreateCuePoint,
@@ -244,6 +245,42 @@ describe('Beatgrid Hooks', () => {
     });
   });
 
+  describe('useGetBeatPositions', () => {
+    it('debería pedir los beats de toda la pista', async () => {
+      mockInvoke.mockResolvedValueOnce([0.1, 0.57, 1.04]);
+
+      const { result } = renderHook(() => useGetBeatPositions('track-123', 180), {
+        wrapper: createQueryWrapper(),
+      });
+
+      await waitFor(() => {
+        expect(result.current.isSuccess).toBe(true);
+      });
+
+      expect(mockInvoke).toHaveBeenCalledWith('get_beat_positions', {
+        trackId: 'track-123',
+        start: 0,
+        end: 180,
+      });
+      expect(result.current.data).toEqual([0.1, 0.57, 1.04]);
+    });
+
+    it('no debería ejecutar query sin duración', () => {
+      const { result } = renderHook(() => useGetBeatPositions('track-123', 0), {
+        wrapper: createQueryWrapper(),
+      });
+
+      expect(result.current.isFetching).toBe(false);
+      expect(mockInvoke).not.toHaveBeenCalled();
+    });
+
+    it('debería invalidarse junto con el beatgrid', () => {
+      expect(analysisKeys.beatPositions('track-123').slice(0, 3)).toEqual(
+        analysisKeys.beatgrid('track-123')
+      );
+    });
+  });
+
   describe('useUpdateBeatgridOffset', () => {
     it('debería actualizar offset correctamente', async () => {
       mockInvoke.mockResolvedValueOnce(undefined);
@@ -29,6 +29,8 @@ export const analysisKeys = {
   all: ['analysis'] as const,
   beatgrids: () => [...analysisKeys.all, 'beatgrids'] as const,
   beatgrid: (trackId: string) => [...analysisKeys.beatgrids(), trackId] as const,
+  // AIDEV-NOTE: Cuelga del beatgrid: invalidarlo también recalcula los beats
+  beatPositions: (trackId: string) => [...analysisKeys.beatgrid(trackId), 'positions'] as const,
   cuePoints: () => [...analysisKeys.all, 'cuePoints'] as const,
   cuePointsForTrack: (trackId: string) => [...analysisKeys.cuePoints(), trackId] as const,
   loops: () => [...analysisKeys.all, 'loops'] as const,
@@ -73,6 +75,26 @@ export const useGetBeatgrid = (trackId: string) => {
   });
 };
 
+/**
+ * Obtiene las posiciones (segundos) de todos los beats de una pista
+ *
+ * Siguen los cambios de tempo del grid; vacío si no hay beatgrid
+ */
+export const useGetBeatPositions = (trackId: string, duration: number) => {
+  return useQuery({
+    queryKey: analysisKeys.beatPositions(trackId),
+    queryFn: async () => {
+      return await invoke<number[]>('get_beat_positions', {
+        trackId,
+        start: 0,
+        end: duration,
+      });
+    },
+    enabled: trackId.length > 0 && duration > 0,
+    staleTime: 5 * 60 * 1000,
+  });
+};
+
 /**
  * Actualiza offset del beatgrid (ajuste manual)
  */
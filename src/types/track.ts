@@ -34,6 +34,8 @@ export interface TrackDisplay extends TrackIdentity {
   duration: number;
   bpm?: number;
   key?: string;
+  /** Posición en la rueda Camelot (1A, 1B, 2A...) para ordenar por tonalidad */
+  keyIndex?: number;
   rating?: number;
   genre?: string;
   year?: number;
@@ -170,6 +172,7 @@ export function createTrackDisplay(track: Track): TrackDisplay {
     duration: track.duration,
     bpm: track.bpm,
     key: track.key,
+    keyIndex: track.keyIndex,
     rating: track.rating,
     genre: track.genre,
     year: track.year,